@@ -0,0 +1,185 @@
+/*
+Benchmark of the Dense and Cell List Cell Migration Simulations
+
+Runs physics::dynamic_f32::run with neighbor_search DENSE and CELL_LIST on the same cells
+and measures the run time. The dense simulation allocates (cell_num x cell_num) matrices,
+so it is only run up to dense_limit cells.
+
+
+Generates these files
+
+./dynamic_cell_num.csv           Contains the number of neurons and glial cells
+./dynamic_dense_run_time.csv     Contains the run time of the dense simulation, 0 when skipped
+./dynamic_cell_list_run_time.csv Contains the run time of the cell list simulation
+./dynamic_max_diff.csv           Contains the largest position difference between the two
+                                 simulations, 0 when the dense simulation is skipped
+
+*/
+
+extern crate arrayfire;
+extern crate raybnn;
+
+use std::time::Instant;
+
+use raybnn::physics::neighbor::neighbor_search_type;
+
+// Use CUDA GPU and GPU device 0
+const BACK_END: arrayfire::Backend = arrayfire::Backend::CUDA;
+const DEVICE: i32 = 0;
+
+#[allow(unused_must_use)]
+fn main() {
+    // Use CUDA GPU and GPU device 0
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+
+    //List of Neuron Sizes to sweep through
+
+    let active_size_list = vec![500, 1000, 2000, 4000, 8000, 16000, 32000, 64000, 128000];
+
+    //Largest number of cells for the dense simulation
+    let dense_limit: u64 = 20000;
+
+    //Create Initial Neural Network
+
+    let neuron_size: u64 = 51000;
+    let input_size: u64 = 4;
+    let output_size: u64 = 3;
+    let proc_num: u64 = 3;
+    let active_size: u64 = 500;
+    let space_dims: u64 = 3;
+    let sim_steps: u64 = 10;
+    let batch_size: u64 = 105;
+    let neuron_rad = 0.1; //Cell Radius
+
+    //Parameters to initialize neural network
+    let mut netdata: raybnn::neural::network_f32::network_metadata_type =
+        raybnn::neural::network_f32::create_nullnetdata();
+    netdata.neuron_size = neuron_size;
+    netdata.input_size = input_size;
+    netdata.output_size = output_size;
+    netdata.proc_num = proc_num;
+    netdata.active_size = active_size;
+    netdata.space_dims = space_dims;
+    netdata.step_num = sim_steps;
+    netdata.batch_size = batch_size;
+    netdata.del_unused_neuron = true;
+
+    netdata.time_step = 0.1;
+    netdata.nratio = 0.5;
+    netdata.neuron_std = 0.001;
+    netdata.neuron_rad = neuron_rad;
+    netdata.init_prob = 0.01;
+    netdata.center_const = 0.005;
+    netdata.spring_const = 0.01;
+    netdata.repel_const = 0.01;
+
+    //Placeholder dimension of network
+    let temp_dims = arrayfire::Dim4::new(&[4, 1, 1, 1]);
+
+    let mut cell_num = Vec::new();
+    let mut dense_run_time = Vec::new();
+    let mut cell_list_run_time = Vec::new();
+    let mut max_diff = Vec::new();
+
+    //Loop Through Different Neural Network Sizes
+    arrayfire::sync(DEVICE);
+    for new_active_size in active_size_list.clone() {
+        netdata.active_size = new_active_size;
+
+        //Same cell density for every size, as in network_builder_type
+        let mut sphere_rad = ((2 * new_active_size) as f32) / 0.28;
+        sphere_rad = (sphere_rad / ((4.0 / 3.0) * std::f32::consts::PI)).cbrt();
+        netdata.sphere_rad = sphere_rad;
+        netdata.con_rad = (sphere_rad / (proc_num as f32)) * 1.4;
+
+        let mut glia_pos = arrayfire::constant::<f32>(0.0, temp_dims);
+        let mut neuron_pos = arrayfire::constant::<f32>(0.0, temp_dims);
+
+        //Create Neural Network Sphere
+        raybnn::physics::initial_f32::sphere_cell_collision_minibatch(
+            &netdata,
+            &mut glia_pos,
+            &mut neuron_pos,
+        );
+
+        let total_num = neuron_pos.dims()[0] + glia_pos.dims()[0];
+        cell_num.push(total_num as f64);
+
+        //Cell list simulation
+        let mut glia_pos_cell_list = glia_pos.clone();
+        let mut neuron_pos_cell_list = neuron_pos.clone();
+        netdata.neighbor_search = neighbor_search_type::CELL_LIST;
+
+        arrayfire::sync(DEVICE);
+        let start = Instant::now();
+
+        raybnn::physics::dynamic_f32::run(
+            &netdata,
+            &mut glia_pos_cell_list,
+            &mut neuron_pos_cell_list,
+        );
+
+        arrayfire::sync(DEVICE);
+        let duration = start.elapsed();
+        cell_list_run_time.push(duration.as_secs_f64());
+
+        if total_num > dense_limit {
+            dense_run_time.push(0.0);
+            max_diff.push(0.0);
+
+            println!(
+                "cells {} dense skipped, cell list {:?}",
+                total_num, duration
+            );
+            continue;
+        }
+
+        //Dense simulation
+        let mut glia_pos_dense = glia_pos.clone();
+        let mut neuron_pos_dense = neuron_pos.clone();
+        netdata.neighbor_search = neighbor_search_type::DENSE;
+
+        arrayfire::sync(DEVICE);
+        let start = Instant::now();
+
+        raybnn::physics::dynamic_f32::run(&netdata, &mut glia_pos_dense, &mut neuron_pos_dense);
+
+        arrayfire::sync(DEVICE);
+        let dense_duration = start.elapsed();
+        dense_run_time.push(dense_duration.as_secs_f64());
+
+        //Compare the final positions
+        let neuron_diff = arrayfire::abs(&(neuron_pos_dense - neuron_pos_cell_list));
+        let glia_diff = arrayfire::abs(&(glia_pos_dense - glia_pos_cell_list));
+        let (neuron_max, _) = arrayfire::max_all::<f32>(&neuron_diff);
+        let (glia_max, _) = arrayfire::max_all::<f32>(&glia_diff);
+        max_diff.push(neuron_max.max(glia_max) as f64);
+
+        println!(
+            "cells {} dense {:?}, cell list {:?}, max difference {}",
+            total_num,
+            dense_duration,
+            duration,
+            neuron_max.max(glia_max)
+        );
+    }
+
+    //Save Number of Cells
+    raybnn::export::dataloader_f64::write_vec_cpu_to_csv("./dynamic_cell_num.csv", &cell_num);
+
+    //Save Dense Runtime
+    raybnn::export::dataloader_f64::write_vec_cpu_to_csv(
+        "./dynamic_dense_run_time.csv",
+        &dense_run_time,
+    );
+
+    //Save Cell List Runtime
+    raybnn::export::dataloader_f64::write_vec_cpu_to_csv(
+        "./dynamic_cell_list_run_time.csv",
+        &cell_list_run_time,
+    );
+
+    //Save Position Difference
+    raybnn::export::dataloader_f64::write_vec_cpu_to_csv("./dynamic_max_diff.csv", &max_diff);
+}
@@ -74,6 +74,8 @@ fn main() {
             center_const: 0.005,
             spring_const: 0.01,
             repel_const: 0.01,
+
+            neighbor_search: raybnn::physics::neighbor::neighbor_search_type::DENSE,
         };
 
     //Placeholder dimension of network
@@ -60,6 +60,8 @@ fn main() {
             center_const: 0.005,
             spring_const: 0.01,
             repel_const: 0.01,
+
+            neighbor_search: raybnn::physics::neighbor::neighbor_search_type::DENSE,
         };
 
     let temp_dims = arrayfire::Dim4::new(&[4, 1, 1, 1]);
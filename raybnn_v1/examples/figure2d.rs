@@ -19,6 +19,7 @@ extern crate raybnn;
 
 use std::time::{Duration, Instant};
 
+use raybnn::physics::raytrace_f32::raytrace_accel_type;
 use raybnn::physics::raytrace_f32::raytrace_option_type;
 
 const BACK_END: arrayfire::Backend = arrayfire::Backend::CUDA;
@@ -210,6 +211,7 @@ fn run_RT3(RT3_rad: f32) {
             input_connection_num: con_num_list[ii].clone(),
             ray_neuron_intersect: true,
             ray_glia_intersect: true,
+            accel: raytrace_accel_type::BRUTE_FORCE,
         };
 
         //Raytrace the neurons
@@ -438,6 +440,7 @@ fn main() {
             input_connection_num: con_num_list[ii].clone(),
             ray_neuron_intersect: true,
             ray_glia_intersect: true,
+            accel: raytrace_accel_type::BRUTE_FORCE,
         };
 
         //Raytrace the neurons
@@ -21,6 +21,7 @@ extern crate raybnn;
 
 use std::time::{Duration, Instant};
 
+use raybnn::physics::raytrace_f32::raytrace_accel_type;
 use raybnn::physics::raytrace_f32::raytrace_option_type;
 
 const BACK_END: arrayfire::Backend = arrayfire::Backend::CUDA;
@@ -217,6 +218,7 @@ fn main() {
             input_connection_num: 100000,
             ray_neuron_intersect: true,
             ray_glia_intersect: true,
+            accel: raytrace_accel_type::BRUTE_FORCE,
         };
 
         // Raytrace neural connections
@@ -161,6 +161,14 @@ fn main() {
 
         exit_counter_threshold: exit_counter_threshold,
         shuffle_counter_threshold: shuffle_counter_threshold,
+        optimizer: raybnn::optimal::optimizer::create_optimizer_options(
+            raybnn::optimal::optimizer::optimizer_kind_type::ADAM,
+        ),
+        lr_schedule: raybnn::optimal::scheduler::lr_schedule_type::CONSTANT,
+        regularization: raybnn::optimal::regularization::create_regularization_options(),
+        normalization: raybnn::neural::normalization::create_norm_options(
+            raybnn::neural::normalization::norm_kind_type::NONE,
+        ),
     };
 
     let mut alpha_max_vec = vec![max_alpha; 1000];
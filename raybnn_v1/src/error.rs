@@ -0,0 +1,124 @@
+use std::fmt;
+
+/*
+Errors returned by the Result versions of the file loaders and savers (the try_* functions)
+and by the network builders
+
+Io:                  Reading or writing file failed
+Parse:               Value on line of file could not be parsed. line starts from 1
+ShapeMismatch:       Number of values does not match the requested dimensions
+InvalidNetwork:      Network file or network settings are not consistent
+InvalidDataset:      Dataset or cross-validation settings are not consistent
+InvalidCheckpoint:   Binary checkpoint has a bad header, unsupported version or missing section
+ChecksumMismatch:    Data of a binary checkpoint section does not match its stored checksum
+InvalidTrainState:   Training settings are not valid, or saved optimizer or scheduler state
+                     does not match them
+
+*/
+
+#[derive(Debug)]
+pub enum Error {
+    Io {
+        file: String,
+        source: std::io::Error,
+    },
+    Parse {
+        file: String,
+        line: u64,
+        value: String,
+    },
+    ShapeMismatch {
+        file: String,
+        expected: u64,
+        found: u64,
+    },
+    InvalidNetwork(String),
+    InvalidDataset(String),
+    InvalidCheckpoint {
+        file: String,
+        reason: String,
+    },
+    ChecksumMismatch {
+        file: String,
+        section: String,
+    },
+    InvalidTrainState(String),
+}
+
+impl Error {
+    pub fn io(file: &str, source: std::io::Error) -> Error {
+        Error::Io {
+            file: file.to_string(),
+            source: source,
+        }
+    }
+
+    // Adds the file and line to a Parse error from a string parser, which does not know them
+    pub fn at(self, file: &str, line: u64) -> Error {
+        match self {
+            Error::Parse { value, .. } => Error::Parse {
+                file: file.to_string(),
+                line: line,
+                value: value,
+            },
+            Error::ShapeMismatch {
+                expected, found, ..
+            } => Error::ShapeMismatch {
+                file: file.to_string(),
+                expected: expected,
+                found: found,
+            },
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io { file, source } => write!(f, "io error on {}: {}", file, source),
+            Error::Parse { file, line, value } => {
+                write!(
+                    f,
+                    "could not parse {:?} on line {} of {}",
+                    value, line, file
+                )
+            }
+            Error::ShapeMismatch {
+                file,
+                expected,
+                found,
+            } => write!(
+                f,
+                "shape mismatch in {}: expected {} values, found {}",
+                file, expected, found
+            ),
+            Error::InvalidNetwork(msg) => write!(f, "invalid network: {}", msg),
+            Error::InvalidDataset(msg) => write!(f, "invalid dataset: {}", msg),
+            Error::InvalidCheckpoint { file, reason } => {
+                write!(f, "invalid checkpoint {}: {}", file, reason)
+            }
+            Error::ChecksumMismatch { file, section } => {
+                write!(f, "checksum mismatch in section {} of {}", section, file)
+            }
+            Error::InvalidTrainState(msg) => write!(f, "invalid trainer state: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn parse_value<T: std::str::FromStr>(value: &str) -> Result<T, Error> {
+    value.parse::<T>().map_err(|_| Error::Parse {
+        file: String::new(),
+        line: 0,
+        value: value.to_string(),
+    })
+}
@@ -0,0 +1,1146 @@
+extern crate arrayfire;
+
+use std::collections::HashMap;
+
+use std::fs::File;
+use std::io::Read;
+use std::io::Write;
+
+use std::fs;
+
+use crate::neural::network::network_metadata_type;
+use crate::neural::network::neural_network_type;
+
+use crate::neural::normalization::create_norm_options;
+use crate::neural::normalization::create_norm_state;
+use crate::neural::normalization::norm_kind_type;
+use crate::neural::normalization::norm_state_type;
+
+use crate::optimal::optimizer::optimizer_kind_type;
+
+use crate::physics::neighbor::neighbor_search_type;
+
+use crate::export::dataloader::try_load_network2;
+
+use crate::precision::float_trait::raybnn_float_trait;
+
+use crate::error::Error;
+
+/*
+Versioned binary checkpoint of neural_network_type
+
+All integers are little endian
+
+Header
+magic:               8 bytes "RAYBNNCK"
+version:             u32, CHECKPOINT_VERSION
+dtype:               u8 length + name of the floating point type when saved ("f32" or "f64")
+section_num:         u32, number of sections
+
+Section
+name:                u16 length + name
+elem_type:           u8, one of SECTION_F32, SECTION_F64, SECTION_I32, SECTION_U64
+dims:                4 x u64, arrayfire dimensions
+byte_len:            u64, number of data bytes
+data:                byte_len bytes
+checksum:            u32, CRC-32 of data
+
+Sections
+WRowIdxCSR, WColIdx, neuron_idx:               i32
+W, H, A, B, C, D, E, glia_pos, neuron_pos:     dtype
+netdata_u64:         neuron_size, input_size, output_size, proc_num, active_size,
+                     space_dims, step_num, batch_size, del_unused_neuron
+netdata_float:       time_step, nratio, neuron_std, sphere_rad, neuron_rad, con_rad, init_prob,
+                     add_neuron_rate, del_neuron_rate, center_const, spring_const, repel_const
+norm_kind:           u64, normalization kind (0 NONE, 1 LAYER_NORM, 2 BATCH_NORM)
+neighbor_search:     u64, cell migration neighbor search (0 DENSE, 1 CELL_LIST)
+norm_float:          momentum, epsilon
+norm_running_mean, norm_running_var:           dtype, batch norm running statistics
+
+Sections are looked up by name, so later versions can add sections without
+breaking older readers. Every floating point section has the dtype of the header. A
+checkpoint saved as f32 can be loaded as f64 and the other way around.
+
+*/
+
+pub const CHECKPOINT_MAGIC: [u8; 8] = *b"RAYBNNCK";
+pub const CHECKPOINT_VERSION: u32 = 1;
+
+const SECTION_F32: u8 = 0;
+const SECTION_F64: u8 = 1;
+const SECTION_I32: u8 = 2;
+const SECTION_U64: u8 = 3;
+
+const PARAM_SECTIONS: [&str; 7] = ["W", "H", "A", "B", "C", "D", "E"];
+
+struct section_type {
+    elem_type: u8,
+    dims: [u64; 4],
+    data: Vec<u8>,
+}
+
+// CRC-32 (IEEE 802.3, reflected polynomial 0xEDB88320)
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xFFFFFFFF;
+    for byte in data {
+        crc = crc ^ (*byte as u32);
+        for _ in 0..8 {
+            let mask = (!(crc & 1)).wrapping_add(1);
+            crc = (crc >> 1) ^ (0xEDB88320 & mask);
+        }
+    }
+    !crc
+}
+
+fn write_section(
+    out: &mut Vec<u8>,
+    name: &str,
+    elem_type: u8,
+    dims: arrayfire::Dim4,
+    data: Vec<u8>,
+) {
+    out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    out.extend_from_slice(name.as_bytes());
+    out.push(elem_type);
+    for i in 0..4 {
+        out.extend_from_slice(&dims[i].to_le_bytes());
+    }
+    out.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    out.extend_from_slice(&data);
+    out.extend_from_slice(&crc32(&data).to_le_bytes());
+}
+
+fn float_section_data<T: raybnn_float_trait>(arr: &arrayfire::Array<T>) -> (u8, Vec<u8>) {
+    let mut vecT = vec![T::default(); arr.elements()];
+    arr.host(&mut vecT);
+
+    float_vec_data(&vecT)
+}
+
+fn float_vec_data<T: raybnn_float_trait>(vecT: &Vec<T>) -> (u8, Vec<u8>) {
+    let mut data: Vec<u8> = Vec::new();
+    if T::DTYPE_NAME == "f32" {
+        for value in vecT {
+            data.extend_from_slice(&value.to_f32().unwrap().to_le_bytes());
+        }
+        (SECTION_F32, data)
+    } else {
+        for value in vecT {
+            data.extend_from_slice(&value.to_f64().unwrap().to_le_bytes());
+        }
+        (SECTION_F64, data)
+    }
+}
+
+fn i32_section_data(arr: &arrayfire::Array<i32>) -> Vec<u8> {
+    let mut veci32 = vec![i32::default(); arr.elements()];
+    arr.host(&mut veci32);
+
+    let mut data: Vec<u8> = Vec::new();
+    for value in veci32 {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data
+}
+
+// Converts the neural network into checkpoint sections
+fn network_sections<T: raybnn_float_trait>(
+    neural_network: &neural_network_type<T>,
+) -> Result<Vec<(String, u8, arrayfire::Dim4, Vec<u8>)>, Error> {
+    let netdata = &neural_network.netdata;
+
+    let WValuesdims0 = neural_network.WColIdx.dims()[0];
+    let network_paramsdims0 = neural_network.network_params.dims()[0];
+
+    if (network_paramsdims0 < WValuesdims0) || (((network_paramsdims0 - WValuesdims0) % 6) != 0) {
+        return Err(Error::InvalidNetwork(format!(
+            "network_params has {} values, which is not WColIdx ({}) plus 6 equal UAF/bias vectors",
+            network_paramsdims0, WValuesdims0
+        )));
+    }
+
+    let Hdims0 = (network_paramsdims0 - WValuesdims0) / 6;
+
+    let mut sections: Vec<(String, u8, arrayfire::Dim4, Vec<u8>)> = Vec::new();
+
+    sections.push((
+        "WRowIdxCSR".to_string(),
+        SECTION_I32,
+        neural_network.WRowIdxCSR.dims(),
+        i32_section_data(&neural_network.WRowIdxCSR),
+    ));
+    sections.push((
+        "WColIdx".to_string(),
+        SECTION_I32,
+        neural_network.WColIdx.dims(),
+        i32_section_data(&neural_network.WColIdx),
+    ));
+
+    let mut start: i64 = 0;
+    for i in 0..PARAM_SECTIONS.len() {
+        let size = if i == 0 { WValuesdims0 } else { Hdims0 };
+        if size == 0 {
+            let (elem_type, _) = float_vec_data::<T>(&Vec::new());
+            sections.push((
+                PARAM_SECTIONS[i].to_string(),
+                elem_type,
+                arrayfire::Dim4::new(&[0, 1, 1, 1]),
+                Vec::new(),
+            ));
+            continue;
+        }
+
+        let end = start + (size as i64) - 1;
+        let seqs = [arrayfire::Seq::new(start as i32, end as i32, 1i32)];
+        let param = arrayfire::index(&neural_network.network_params, &seqs);
+
+        let (elem_type, data) = float_section_data(&param);
+        sections.push((PARAM_SECTIONS[i].to_string(), elem_type, param.dims(), data));
+
+        start = end + 1;
+    }
+
+    let (elem_type, data) = float_section_data(&neural_network.glia_pos);
+    sections.push((
+        "glia_pos".to_string(),
+        elem_type,
+        neural_network.glia_pos.dims(),
+        data,
+    ));
+
+    let (elem_type, data) = float_section_data(&neural_network.neuron_pos);
+    sections.push((
+        "neuron_pos".to_string(),
+        elem_type,
+        neural_network.neuron_pos.dims(),
+        data,
+    ));
+
+    sections.push((
+        "neuron_idx".to_string(),
+        SECTION_I32,
+        neural_network.neuron_idx.dims(),
+        i32_section_data(&neural_network.neuron_idx),
+    ));
+
+    let netarr0: Vec<u64> = vec![
+        netdata.neuron_size,
+        netdata.input_size,
+        netdata.output_size,
+        netdata.proc_num,
+        netdata.active_size,
+        netdata.space_dims,
+        netdata.step_num,
+        netdata.batch_size,
+        netdata.del_unused_neuron as u64,
+    ];
+    let mut data: Vec<u8> = Vec::new();
+    for value in netarr0.clone() {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    sections.push((
+        "netdata_u64".to_string(),
+        SECTION_U64,
+        arrayfire::Dim4::new(&[netarr0.len() as u64, 1, 1, 1]),
+        data,
+    ));
+
+    let netarr1: Vec<T> = vec![
+        netdata.time_step,
+        netdata.nratio,
+        netdata.neuron_std,
+        netdata.sphere_rad,
+        netdata.neuron_rad,
+        netdata.con_rad,
+        netdata.init_prob,
+        netdata.add_neuron_rate,
+        netdata.del_neuron_rate,
+        netdata.center_const,
+        netdata.spring_const,
+        netdata.repel_const,
+    ];
+    let (elem_type, data) = float_vec_data(&netarr1);
+    sections.push((
+        "netdata_float".to_string(),
+        elem_type,
+        arrayfire::Dim4::new(&[netarr1.len() as u64, 1, 1, 1]),
+        data,
+    ));
+
+    let norm = &neural_network.norm;
+    sections.push(u64_vec_section(
+        "norm_kind",
+        &vec![norm.options.kind as u64],
+    ));
+    sections.push(float_vec_section(
+        "norm_float",
+        &vec![norm.options.momentum, norm.options.epsilon],
+    ));
+    sections.push(float_array_section("norm_running_mean", &norm.running_mean));
+    sections.push(float_array_section("norm_running_var", &norm.running_var));
+
+    sections.push(u64_vec_section(
+        "neighbor_search",
+        &vec![netdata.neighbor_search as u64],
+    ));
+
+    Ok(sections)
+}
+
+// Reads the normalization state. Checkpoints written before normalization have none
+fn norm_from_sections<T: raybnn_float_trait>(
+    filename: &str,
+    sections: &HashMap<String, section_type>,
+) -> Result<norm_state_type<T>, Error> {
+    if !sections.contains_key("norm_kind") {
+        return Ok(create_norm_state(create_norm_options(norm_kind_type::NONE)));
+    }
+
+    let norm_kind = section_to_u64_vec(filename, sections, "norm_kind")?;
+    let (norm_float, _) = section_to_float_vec::<T>(filename, sections, "norm_float")?;
+
+    if (norm_kind.len() != 1) || (norm_float.len() != 2) {
+        return Err(Error::InvalidCheckpoint {
+            file: filename.to_string(),
+            reason: format!(
+                "normalization has {} integer and {} floating point values, expected 1 and 2",
+                norm_kind.len(),
+                norm_float.len()
+            ),
+        });
+    }
+
+    let kind = match norm_kind[0] {
+        0 => norm_kind_type::NONE,
+        1 => norm_kind_type::LAYER_NORM,
+        2 => norm_kind_type::BATCH_NORM,
+        _ => {
+            return Err(Error::InvalidCheckpoint {
+                file: filename.to_string(),
+                reason: format!("unknown normalization kind {}", norm_kind[0]),
+            });
+        }
+    };
+
+    let mut options = create_norm_options::<T>(kind);
+    options.momentum = norm_float[0];
+    options.epsilon = norm_float[1];
+
+    let mut norm = create_norm_state(options);
+    norm.running_mean = section_to_float_array::<T>(filename, sections, "norm_running_mean")?;
+    norm.running_var = section_to_float_array::<T>(filename, sections, "norm_running_var")?;
+
+    Ok(norm)
+}
+
+// Reads the neighbor search. Checkpoints written before the cell list have none
+fn neighbor_search_from_sections(
+    filename: &str,
+    sections: &HashMap<String, section_type>,
+) -> Result<neighbor_search_type, Error> {
+    if !sections.contains_key("neighbor_search") {
+        return Ok(neighbor_search_type::DENSE);
+    }
+
+    let neighbor_search = section_to_u64_vec(filename, sections, "neighbor_search")?;
+
+    match neighbor_search.as_slice() {
+        [0] => Ok(neighbor_search_type::DENSE),
+        [1] => Ok(neighbor_search_type::CELL_LIST),
+        _ => Err(Error::InvalidCheckpoint {
+            file: filename.to_string(),
+            reason: format!("unknown neighbor search {:?}", neighbor_search),
+        }),
+    }
+}
+
+fn write_checkpoint<T: raybnn_float_trait>(
+    filename: &str,
+    sections: Vec<(String, u8, arrayfire::Dim4, Vec<u8>)>,
+) -> Result<(), Error> {
+    let mut out: Vec<u8> = Vec::new();
+    out.extend_from_slice(&CHECKPOINT_MAGIC);
+    out.extend_from_slice(&CHECKPOINT_VERSION.to_le_bytes());
+    out.push(T::DTYPE_NAME.len() as u8);
+    out.extend_from_slice(T::DTYPE_NAME.as_bytes());
+    out.extend_from_slice(&(sections.len() as u32).to_le_bytes());
+
+    for (name, elem_type, dims, data) in sections {
+        write_section(&mut out, &name, elem_type, dims, data);
+    }
+
+    let mut file0 = File::create(filename).map_err(|e| Error::io(filename, e))?;
+    file0.write_all(&out).map_err(|e| Error::io(filename, e))?;
+
+    Ok(())
+}
+
+/*
+Saves the neural network as a binary checkpoint
+
+Inputs
+filename:            Checkpoint file
+neural_network:      Neural network
+
+*/
+pub fn try_save_checkpoint<T: raybnn_float_trait>(
+    filename: &str,
+    neural_network: &neural_network_type<T>,
+) -> Result<(), Error> {
+    let sections = network_sections(neural_network)?;
+
+    write_checkpoint::<T>(filename, sections)
+}
+
+pub fn save_checkpoint<T: raybnn_float_trait>(
+    filename: &str,
+    neural_network: &neural_network_type<T>,
+) {
+    try_save_checkpoint(filename, neural_network).unwrap_or_else(|e| panic!("{}", e))
+}
+
+fn take<'a>(
+    filename: &str,
+    bytes: &'a [u8],
+    offset: &mut usize,
+    size: usize,
+) -> Result<&'a [u8], Error> {
+    if (bytes.len() < size) || ((bytes.len() - size) < *offset) {
+        return Err(Error::InvalidCheckpoint {
+            file: filename.to_string(),
+            reason: format!("file is truncated at byte {}", *offset),
+        });
+    }
+
+    let out = &bytes[*offset..(*offset + size)];
+    *offset = *offset + size;
+    Ok(out)
+}
+
+fn take_u8(filename: &str, bytes: &[u8], offset: &mut usize) -> Result<u8, Error> {
+    Ok(take(filename, bytes, offset, 1)?[0])
+}
+
+fn take_u16(filename: &str, bytes: &[u8], offset: &mut usize) -> Result<u16, Error> {
+    let mut buf = [0u8; 2];
+    buf.copy_from_slice(take(filename, bytes, offset, 2)?);
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn take_u32(filename: &str, bytes: &[u8], offset: &mut usize) -> Result<u32, Error> {
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(take(filename, bytes, offset, 4)?);
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn take_u64(filename: &str, bytes: &[u8], offset: &mut usize) -> Result<u64, Error> {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(take(filename, bytes, offset, 8)?);
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn take_str(
+    filename: &str,
+    bytes: &[u8],
+    offset: &mut usize,
+    size: usize,
+) -> Result<String, Error> {
+    let raw = take(filename, bytes, offset, size)?;
+    String::from_utf8(raw.to_vec()).map_err(|_| Error::InvalidCheckpoint {
+        file: filename.to_string(),
+        reason: format!("name at byte {} is not valid UTF-8", *offset - size),
+    })
+}
+
+// Reads the header and every section of a version 1 checkpoint
+fn read_sections_v1(
+    filename: &str,
+    bytes: &[u8],
+    offset: &mut usize,
+) -> Result<HashMap<String, section_type>, Error> {
+    let mut sections: HashMap<String, section_type> = HashMap::new();
+
+    let section_num = take_u32(filename, bytes, offset)?;
+    for _ in 0..section_num {
+        let name_len = take_u16(filename, bytes, offset)? as usize;
+        let name = take_str(filename, bytes, offset, name_len)?;
+        let elem_type = take_u8(filename, bytes, offset)?;
+
+        let mut dims = [0u64; 4];
+        for i in 0..4 {
+            dims[i] = take_u64(filename, bytes, offset)?;
+        }
+
+        let byte_len = take_u64(filename, bytes, offset)? as usize;
+        let data = take(filename, bytes, offset, byte_len)?.to_vec();
+        let checksum = take_u32(filename, bytes, offset)?;
+
+        if crc32(&data) != checksum {
+            return Err(Error::ChecksumMismatch {
+                file: filename.to_string(),
+                section: name,
+            });
+        }
+
+        sections.insert(
+            name,
+            section_type {
+                elem_type: elem_type,
+                dims: dims,
+                data: data,
+            },
+        );
+    }
+
+    Ok(sections)
+}
+
+fn get_section<'a>(
+    filename: &str,
+    sections: &'a HashMap<String, section_type>,
+    name: &str,
+    expected_types: &[u8],
+) -> Result<&'a section_type, Error> {
+    let section = sections.get(name).ok_or_else(|| Error::InvalidCheckpoint {
+        file: filename.to_string(),
+        reason: format!("missing section {}", name),
+    })?;
+
+    if !expected_types.contains(&section.elem_type) {
+        return Err(Error::InvalidCheckpoint {
+            file: filename.to_string(),
+            reason: format!("section {} has element type {}", name, section.elem_type),
+        });
+    }
+
+    let elem_size: u64 = match section.elem_type {
+        SECTION_F32 | SECTION_I32 => 4,
+        _ => 8,
+    };
+    let elements = section
+        .dims
+        .iter()
+        .try_fold(1u64, |acc, d| acc.checked_mul(*d));
+    let byte_len = elements.and_then(|e| e.checked_mul(elem_size));
+    let (elements, byte_len) = match (elements, byte_len) {
+        (Some(elements), Some(byte_len)) => (elements, byte_len),
+        _ => {
+            return Err(Error::InvalidCheckpoint {
+                file: filename.to_string(),
+                reason: format!(
+                    "section {} has too large dimensions {:?}",
+                    name, section.dims
+                ),
+            });
+        }
+    };
+    if byte_len != (section.data.len() as u64) {
+        return Err(Error::ShapeMismatch {
+            file: filename.to_string(),
+            expected: elements,
+            found: (section.data.len() as u64) / elem_size,
+        });
+    }
+
+    Ok(section)
+}
+
+fn section_to_float_vec<T: raybnn_float_trait>(
+    filename: &str,
+    sections: &HashMap<String, section_type>,
+    name: &str,
+) -> Result<(Vec<T>, [u64; 4]), Error> {
+    let section = get_section(filename, sections, name, &[SECTION_F32, SECTION_F64])?;
+
+    let mut vecT: Vec<T> = Vec::new();
+    if section.elem_type == SECTION_F32 {
+        for chunk in section.data.chunks_exact(4) {
+            let mut buf = [0u8; 4];
+            buf.copy_from_slice(chunk);
+            vecT.push(T::from_f64(f32::from_le_bytes(buf) as f64));
+        }
+    } else {
+        for chunk in section.data.chunks_exact(8) {
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(chunk);
+            vecT.push(T::from_f64(f64::from_le_bytes(buf)));
+        }
+    }
+
+    Ok((vecT, section.dims))
+}
+
+fn section_to_float_array<T: raybnn_float_trait>(
+    filename: &str,
+    sections: &HashMap<String, section_type>,
+    name: &str,
+) -> Result<arrayfire::Array<T>, Error> {
+    let (vecT, dims) = section_to_float_vec::<T>(filename, sections, name)?;
+
+    Ok(arrayfire::Array::new(&vecT, arrayfire::Dim4::new(&dims)))
+}
+
+fn section_to_i32_array(
+    filename: &str,
+    sections: &HashMap<String, section_type>,
+    name: &str,
+) -> Result<arrayfire::Array<i32>, Error> {
+    let section = get_section(filename, sections, name, &[SECTION_I32])?;
+
+    let mut veci32: Vec<i32> = Vec::new();
+    for chunk in section.data.chunks_exact(4) {
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(chunk);
+        veci32.push(i32::from_le_bytes(buf));
+    }
+
+    Ok(arrayfire::Array::new(
+        &veci32,
+        arrayfire::Dim4::new(&section.dims),
+    ))
+}
+
+fn section_to_u64_vec(
+    filename: &str,
+    sections: &HashMap<String, section_type>,
+    name: &str,
+) -> Result<Vec<u64>, Error> {
+    let section = get_section(filename, sections, name, &[SECTION_U64])?;
+
+    let mut vecu64: Vec<u64> = Vec::new();
+    for chunk in section.data.chunks_exact(8) {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(chunk);
+        vecu64.push(u64::from_le_bytes(buf));
+    }
+
+    Ok(vecu64)
+}
+
+// Reads the header and all sections of a checkpoint that is loaded as T
+fn read_checkpoint<T: raybnn_float_trait>(
+    filename: &str,
+) -> Result<HashMap<String, section_type>, Error> {
+    let bytes = fs::read(filename).map_err(|e| Error::io(filename, e))?;
+
+    let mut offset: usize = 0;
+    let magic = take(filename, &bytes, &mut offset, CHECKPOINT_MAGIC.len())?;
+    if magic != CHECKPOINT_MAGIC {
+        return Err(Error::InvalidCheckpoint {
+            file: filename.to_string(),
+            reason: "missing RAYBNNCK header".to_string(),
+        });
+    }
+
+    let version = take_u32(filename, &bytes, &mut offset)?;
+
+    let dtype_len = take_u8(filename, &bytes, &mut offset)? as usize;
+    let dtype = take_str(filename, &bytes, &mut offset, dtype_len)?;
+    let dtype_section = match dtype.as_str() {
+        "f32" => SECTION_F32,
+        "f64" => SECTION_F64,
+        _ => {
+            return Err(Error::InvalidCheckpoint {
+                file: filename.to_string(),
+                reason: format!("unknown dtype {:?}", dtype),
+            });
+        }
+    };
+
+    let sections = match version {
+        1 => read_sections_v1(filename, &bytes, &mut offset)?,
+        _ => {
+            return Err(Error::InvalidCheckpoint {
+                file: filename.to_string(),
+                reason: format!(
+                    "format version {} is newer than supported version {}",
+                    version, CHECKPOINT_VERSION
+                ),
+            });
+        }
+    };
+
+    //Every floating point section is saved with the dtype of the header
+    for (name, section) in sections.iter() {
+        let is_float = (section.elem_type == SECTION_F32) || (section.elem_type == SECTION_F64);
+        if is_float && (section.elem_type != dtype_section) {
+            return Err(Error::InvalidCheckpoint {
+                file: filename.to_string(),
+                reason: format!(
+                    "section {} has element type {}, but the dtype is {}",
+                    name, section.elem_type, dtype
+                ),
+            });
+        }
+    }
+
+    if dtype != T::DTYPE_NAME {
+        tracing::debug!(
+            filename,
+            dtype = dtype.as_str(),
+            to = T::DTYPE_NAME,
+            "converting checkpoint precision"
+        );
+    }
+
+    Ok(sections)
+}
+
+// Rebuilds the neural network from checkpoint sections
+fn network_from_sections<T: raybnn_float_trait>(
+    filename: &str,
+    sections: &HashMap<String, section_type>,
+) -> Result<neural_network_type<T>, Error> {
+    let netarr0 = section_to_u64_vec(filename, sections, "netdata_u64")?;
+    let (netarr1, _) = section_to_float_vec::<T>(filename, sections, "netdata_float")?;
+
+    if (netarr0.len() != 9) || (netarr1.len() != 12) {
+        return Err(Error::InvalidCheckpoint {
+            file: filename.to_string(),
+            reason: format!(
+                "netdata has {} integer and {} floating point values, expected 9 and 12",
+                netarr0.len(),
+                netarr1.len()
+            ),
+        });
+    }
+
+    let netdata: network_metadata_type<T> = network_metadata_type {
+        neuron_size: netarr0[0],
+        input_size: netarr0[1],
+        output_size: netarr0[2],
+        proc_num: netarr0[3],
+        active_size: netarr0[4],
+        space_dims: netarr0[5],
+        step_num: netarr0[6],
+        batch_size: netarr0[7],
+        del_unused_neuron: (netarr0[8] == 1),
+
+        time_step: netarr1[0],
+        nratio: netarr1[1],
+        neuron_std: netarr1[2],
+        sphere_rad: netarr1[3],
+        neuron_rad: netarr1[4],
+        con_rad: netarr1[5],
+        init_prob: netarr1[6],
+        add_neuron_rate: netarr1[7],
+        del_neuron_rate: netarr1[8],
+        center_const: netarr1[9],
+        spring_const: netarr1[10],
+        repel_const: netarr1[11],
+
+        neighbor_search: neighbor_search_from_sections(filename, sections)?,
+    };
+
+    let WRowIdxCSR = section_to_i32_array(filename, sections, "WRowIdxCSR")?;
+    let WColIdx = section_to_i32_array(filename, sections, "WColIdx")?;
+    let neuron_idx = section_to_i32_array(filename, sections, "neuron_idx")?;
+
+    let glia_pos = section_to_float_array::<T>(filename, sections, "glia_pos")?;
+    let neuron_pos = section_to_float_array::<T>(filename, sections, "neuron_pos")?;
+
+    let mut params: Vec<T> = Vec::new();
+    for name in PARAM_SECTIONS {
+        let (vecT, _) = section_to_float_vec::<T>(filename, sections, name)?;
+
+        if (name == "W") && ((vecT.len() as u64) != WColIdx.elements() as u64) {
+            return Err(Error::InvalidNetwork(format!(
+                "{} has {} weights but {} column indexes",
+                filename,
+                vecT.len(),
+                WColIdx.elements()
+            )));
+        }
+
+        params.extend(vecT);
+    }
+
+    let network_params_dims = arrayfire::Dim4::new(&[params.len() as u64, 1, 1, 1]);
+    let network_params = arrayfire::Array::new(&params, network_params_dims);
+
+    let norm = norm_from_sections::<T>(filename, sections)?;
+
+    let neural_network: neural_network_type<T> = neural_network_type {
+        netdata: netdata,
+        WRowIdxCSR: WRowIdxCSR,
+        WColIdx: WColIdx,
+        network_params: network_params,
+        glia_pos: glia_pos,
+        neuron_pos: neuron_pos,
+        neuron_idx: neuron_idx,
+        norm: norm,
+    };
+
+    Ok(neural_network)
+}
+
+/*
+Loads a neural network from a binary checkpoint. The checkpoint may have been saved
+with either floating point type
+
+Inputs
+filename:            Checkpoint file
+
+Outputs:
+neural_network:      Neural network
+
+*/
+pub fn try_load_checkpoint<T: raybnn_float_trait>(
+    filename: &str,
+) -> Result<neural_network_type<T>, Error> {
+    let sections = read_checkpoint::<T>(filename)?;
+
+    network_from_sections(filename, &sections)
+}
+
+pub fn load_checkpoint<T: raybnn_float_trait>(filename: &str) -> neural_network_type<T> {
+    try_load_checkpoint(filename).unwrap_or_else(|e| panic!("{}", e))
+}
+
+// Checks whether the file starts with the checkpoint magic header
+pub fn is_checkpoint(filename: &str) -> bool {
+    let mut magic = [0u8; 8];
+    match File::open(filename) {
+        Ok(mut file0) => match file0.read_exact(&mut magic) {
+            Ok(_) => magic == CHECKPOINT_MAGIC,
+            Err(_) => false,
+        },
+        Err(_) => false,
+    }
+}
+
+/*
+Loads a neural network from either a binary checkpoint or a text network file
+written by save_network/save_network2
+
+Inputs
+filename:            Checkpoint or text network file
+
+Outputs:
+neural_network:      Neural network
+
+*/
+pub fn try_load_any_network<T: raybnn_float_trait>(
+    filename: &str,
+) -> Result<neural_network_type<T>, Error> {
+    if is_checkpoint(filename) {
+        try_load_checkpoint(filename)
+    } else {
+        try_load_network2(filename)
+    }
+}
+
+pub fn load_any_network<T: raybnn_float_trait>(filename: &str) -> neural_network_type<T> {
+    try_load_any_network(filename).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/*
+Converts a text network file written by save_network/save_network2 into a binary checkpoint
+
+Inputs
+text_filename:       Text network file
+checkpoint_filename: Binary checkpoint file to write
+
+*/
+pub fn try_migrate_text_network<T: raybnn_float_trait>(
+    text_filename: &str,
+    checkpoint_filename: &str,
+) -> Result<(), Error> {
+    let neural_network: neural_network_type<T> = try_load_network2(text_filename)?;
+
+    try_save_checkpoint(checkpoint_filename, &neural_network)
+}
+
+pub fn migrate_text_network<T: raybnn_float_trait>(text_filename: &str, checkpoint_filename: &str) {
+    try_migrate_text_network::<T>(text_filename, checkpoint_filename)
+        .unwrap_or_else(|e| panic!("{}", e))
+}
+
+/*
+Trainer state saved next to the neural network so that training can resume where it stopped
+
+epoch:               Next epoch to run
+seed:                Seed of the random numbers of the training loop. The dropout mask of
+                     epoch i uses stream_raybnn_seed(seed, i), so resumed training draws
+                     the same masks as training without a break
+alpha:               Current learning rate
+loss_val:            Training loss of the current batch
+first_loss:          Training loss before the first epoch
+mean_loss:           Moving average of the training loss
+min_loss:            Minimum of mean_loss, used for plateau detection
+loss_counter:        Number of epochs since mean_loss last improved
+global_alpha_max:    Minimum of alpha_max_vec
+cur_alpha_max:       Maximum learning rate of the current epoch
+crossval_mean_loss:  Last validation loss
+optimizer_kind:      Optimizer that optimizer_state belongs to
+optimizer_state:     State of the optimizer_trait, for example the Adam moments
+scheduler_state:     State of the lr_scheduler_trait, for example the plateau counter and
+                     the COSINE_ANNEALING counter kept by neural_controller_type before
+grad:                Current descent direction
+alpha_max_vec:       Maximum learning rate schedule
+loss_vec:            Training loss history
+crossval_vec:        Validation loss history
+alpha_history_vec:   Learning rate history
+idx_history_vec:     Epoch of each entry of alpha_history_vec
+
+*/
+#[derive(Clone)]
+pub struct train_state_type<T: raybnn_float_trait> {
+    pub epoch: u64,
+    pub seed: u64,
+
+    pub alpha: T,
+    pub loss_val: T,
+    pub first_loss: T,
+    pub mean_loss: T,
+    pub min_loss: T,
+    pub loss_counter: u64,
+    pub global_alpha_max: T,
+    pub cur_alpha_max: T,
+    pub crossval_mean_loss: T,
+
+    pub optimizer_kind: optimizer_kind_type,
+    pub optimizer_state: Vec<arrayfire::Array<T>>,
+    pub scheduler_state: Vec<T>,
+    pub grad: arrayfire::Array<T>,
+
+    pub alpha_max_vec: Vec<T>,
+    pub loss_vec: Vec<T>,
+    pub crossval_vec: Vec<T>,
+    pub alpha_history_vec: Vec<T>,
+    pub idx_history_vec: Vec<u64>,
+}
+
+fn u64_vec_section(name: &str, vecu64: &Vec<u64>) -> (String, u8, arrayfire::Dim4, Vec<u8>) {
+    let mut data: Vec<u8> = Vec::new();
+    for value in vecu64 {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    (
+        name.to_string(),
+        SECTION_U64,
+        arrayfire::Dim4::new(&[vecu64.len() as u64, 1, 1, 1]),
+        data,
+    )
+}
+
+fn float_vec_section<T: raybnn_float_trait>(
+    name: &str,
+    vecT: &Vec<T>,
+) -> (String, u8, arrayfire::Dim4, Vec<u8>) {
+    let (elem_type, data) = float_vec_data(vecT);
+
+    (
+        name.to_string(),
+        elem_type,
+        arrayfire::Dim4::new(&[vecT.len() as u64, 1, 1, 1]),
+        data,
+    )
+}
+
+fn float_array_section<T: raybnn_float_trait>(
+    name: &str,
+    arr: &arrayfire::Array<T>,
+) -> (String, u8, arrayfire::Dim4, Vec<u8>) {
+    let (elem_type, data) = float_section_data(arr);
+
+    (name.to_string(), elem_type, arr.dims(), data)
+}
+
+/*
+Saves the neural network and the trainer state as a binary checkpoint
+
+Inputs
+filename:            Checkpoint file
+neural_network:      Neural network
+train_state:         Trainer state
+
+*/
+pub fn try_save_train_checkpoint<T: raybnn_float_trait>(
+    filename: &str,
+    neural_network: &neural_network_type<T>,
+    train_state: &train_state_type<T>,
+) -> Result<(), Error> {
+    let mut sections = network_sections(neural_network)?;
+
+    sections.push(u64_vec_section(
+        "train_u64",
+        &vec![
+            train_state.epoch,
+            train_state.seed,
+            train_state.loss_counter,
+            train_state.optimizer_state.len() as u64,
+            train_state.optimizer_kind as u64,
+        ],
+    ));
+    sections.push(float_vec_section(
+        "train_float",
+        &vec![
+            train_state.alpha,
+            train_state.loss_val,
+            train_state.first_loss,
+            train_state.mean_loss,
+            train_state.min_loss,
+            train_state.global_alpha_max,
+            train_state.cur_alpha_max,
+            train_state.crossval_mean_loss,
+        ],
+    ));
+
+    for k in 0..train_state.optimizer_state.len() {
+        sections.push(float_array_section(
+            &format!("train_optimizer_{}", k),
+            &train_state.optimizer_state[k],
+        ));
+    }
+    sections.push(float_vec_section(
+        "train_scheduler",
+        &train_state.scheduler_state,
+    ));
+    sections.push(float_array_section("train_grad", &train_state.grad));
+
+    sections.push(float_vec_section(
+        "train_alpha_max_vec",
+        &train_state.alpha_max_vec,
+    ));
+    sections.push(float_vec_section("train_loss_vec", &train_state.loss_vec));
+    sections.push(float_vec_section(
+        "train_crossval_vec",
+        &train_state.crossval_vec,
+    ));
+    sections.push(float_vec_section(
+        "train_alpha_history_vec",
+        &train_state.alpha_history_vec,
+    ));
+    sections.push(u64_vec_section(
+        "train_idx_history_vec",
+        &train_state.idx_history_vec,
+    ));
+
+    write_checkpoint::<T>(filename, sections)
+}
+
+pub fn save_train_checkpoint<T: raybnn_float_trait>(
+    filename: &str,
+    neural_network: &neural_network_type<T>,
+    train_state: &train_state_type<T>,
+) {
+    try_save_train_checkpoint(filename, neural_network, train_state)
+        .unwrap_or_else(|e| panic!("{}", e))
+}
+
+/*
+Loads the neural network and the trainer state from a binary checkpoint
+written by save_train_checkpoint
+
+Inputs
+filename:            Checkpoint file
+
+Outputs:
+neural_network:      Neural network
+train_state:         Trainer state
+
+*/
+pub fn try_load_train_checkpoint<T: raybnn_float_trait>(
+    filename: &str,
+) -> Result<(neural_network_type<T>, train_state_type<T>), Error> {
+    let sections = read_checkpoint::<T>(filename)?;
+
+    let neural_network = network_from_sections::<T>(filename, &sections)?;
+
+    let train_u64 = section_to_u64_vec(filename, &sections, "train_u64")?;
+    let (train_float, _) = section_to_float_vec::<T>(filename, &sections, "train_float")?;
+
+    if (train_u64.len() != 5) || (train_float.len() != 8) {
+        return Err(Error::InvalidCheckpoint {
+            file: filename.to_string(),
+            reason: format!(
+                "trainer state has {} integer and {} floating point values, expected 5 and 8",
+                train_u64.len(),
+                train_float.len()
+            ),
+        });
+    }
+
+    let optimizer_kind = match train_u64[4] {
+        0 => optimizer_kind_type::SGD,
+        1 => optimizer_kind_type::NESTEROV,
+        2 => optimizer_kind_type::ADAM,
+        3 => optimizer_kind_type::ADAMW,
+        4 => optimizer_kind_type::RMSPROP,
+        5 => optimizer_kind_type::ADAGRAD,
+        6 => optimizer_kind_type::LION,
+        _ => {
+            return Err(Error::InvalidCheckpoint {
+                file: filename.to_string(),
+                reason: format!("unknown optimizer kind {}", train_u64[4]),
+            })
+        }
+    };
+
+    let grad = section_to_float_array::<T>(filename, &sections, "train_grad")?;
+
+    let mut optimizer_state: Vec<arrayfire::Array<T>> = Vec::new();
+    for k in 0..train_u64[3] {
+        let name = format!("train_optimizer_{}", k);
+        optimizer_state.push(section_to_float_array::<T>(filename, &sections, &name)?);
+    }
+
+    //The size of the optimizer state is checked by optimizer_trait::set_state
+    let param_size = neural_network.network_params.elements();
+    if grad.elements() != param_size {
+        return Err(Error::ShapeMismatch {
+            file: filename.to_string(),
+            expected: param_size as u64,
+            found: grad.elements() as u64,
+        });
+    }
+
+    //Checkpoints written before learning rate schedulers have no scheduler state
+    let mut scheduler_state: Vec<T> = Vec::new();
+    if sections.contains_key("train_scheduler") {
+        (scheduler_state, _) = section_to_float_vec::<T>(filename, &sections, "train_scheduler")?;
+    }
+
+    let (alpha_max_vec, _) = section_to_float_vec::<T>(filename, &sections, "train_alpha_max_vec")?;
+    let (loss_vec, _) = section_to_float_vec::<T>(filename, &sections, "train_loss_vec")?;
+    let (crossval_vec, _) = section_to_float_vec::<T>(filename, &sections, "train_crossval_vec")?;
+    let (alpha_history_vec, _) =
+        section_to_float_vec::<T>(filename, &sections, "train_alpha_history_vec")?;
+    let idx_history_vec = section_to_u64_vec(filename, &sections, "train_idx_history_vec")?;
+
+    let train_state: train_state_type<T> = train_state_type {
+        epoch: train_u64[0],
+        seed: train_u64[1],
+        loss_counter: train_u64[2],
+
+        alpha: train_float[0],
+        loss_val: train_float[1],
+        first_loss: train_float[2],
+        mean_loss: train_float[3],
+        min_loss: train_float[4],
+        global_alpha_max: train_float[5],
+        cur_alpha_max: train_float[6],
+        crossval_mean_loss: train_float[7],
+
+        optimizer_kind: optimizer_kind,
+        optimizer_state: optimizer_state,
+        scheduler_state: scheduler_state,
+        grad: grad,
+
+        alpha_max_vec: alpha_max_vec,
+        loss_vec: loss_vec,
+        crossval_vec: crossval_vec,
+        alpha_history_vec: alpha_history_vec,
+        idx_history_vec: idx_history_vec,
+    };
+
+    Ok((neural_network, train_state))
+}
+
+pub fn load_train_checkpoint<T: raybnn_float_trait>(
+    filename: &str,
+) -> (neural_network_type<T>, train_state_type<T>) {
+    try_load_train_checkpoint(filename).unwrap_or_else(|e| panic!("{}", e))
+}
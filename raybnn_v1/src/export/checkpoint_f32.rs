@@ -0,0 +1,45 @@
+// f32 instantiation of the generic crate::export::checkpoint module
+pub use crate::export::checkpoint::*;
+
+use crate::neural::network_f32::neural_network_type;
+
+pub type train_state_type = crate::export::checkpoint::train_state_type<f32>;
+
+// Functions whose precision only appears in the return type are pinned here
+
+pub fn try_load_checkpoint(filename: &str) -> Result<neural_network_type, crate::error::Error> {
+    crate::export::checkpoint::try_load_checkpoint::<f32>(filename)
+}
+
+pub fn load_checkpoint(filename: &str) -> neural_network_type {
+    crate::export::checkpoint::load_checkpoint::<f32>(filename)
+}
+
+pub fn try_load_any_network(filename: &str) -> Result<neural_network_type, crate::error::Error> {
+    crate::export::checkpoint::try_load_any_network::<f32>(filename)
+}
+
+pub fn load_any_network(filename: &str) -> neural_network_type {
+    crate::export::checkpoint::load_any_network::<f32>(filename)
+}
+
+pub fn try_migrate_text_network(
+    text_filename: &str,
+    checkpoint_filename: &str,
+) -> Result<(), crate::error::Error> {
+    crate::export::checkpoint::try_migrate_text_network::<f32>(text_filename, checkpoint_filename)
+}
+
+pub fn migrate_text_network(text_filename: &str, checkpoint_filename: &str) {
+    crate::export::checkpoint::migrate_text_network::<f32>(text_filename, checkpoint_filename)
+}
+
+pub fn try_load_train_checkpoint(
+    filename: &str,
+) -> Result<(neural_network_type, train_state_type), crate::error::Error> {
+    crate::export::checkpoint::try_load_train_checkpoint::<f32>(filename)
+}
+
+pub fn load_train_checkpoint(filename: &str) -> (neural_network_type, train_state_type) {
+    crate::export::checkpoint::load_train_checkpoint::<f32>(filename)
+}
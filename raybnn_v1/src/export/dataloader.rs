@@ -0,0 +1,1452 @@
+extern crate arrayfire;
+use crate::neural::network::network_metadata_type;
+use nohash_hasher;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use serde::{Deserialize, Serialize};
+
+use rayon::array;
+use rayon::prelude::*;
+
+use std::fs::File;
+use std::io::Write;
+
+use std::fs;
+
+use crate::export::dataloader_i32::try_str_to_vec as try_str_to_vec_i32;
+use crate::export::dataloader_i32::vec_to_str as vec_to_str_i32;
+
+use crate::export::dataloader_u64::try_str_to_vec_cpu as try_str_to_vec_cpu_u64;
+use crate::export::dataloader_u64::vec_cpu_to_str as vec_cpu_to_str_u64;
+
+use crate::neural::network::neural_network_type;
+
+use crate::neural::network::create_nullnetdata;
+
+use crate::neural::normalization::create_norm_options;
+use crate::neural::normalization::create_norm_state;
+use crate::neural::normalization::norm_kind_type;
+
+use crate::physics::neighbor::neighbor_search_type;
+
+use crate::precision::float_trait::raybnn_float_trait;
+
+use crate::error::parse_value;
+use crate::error::Error;
+
+use std::io::{self, prelude::*, BufReader};
+
+// Number of lines in a network file written by save_network
+const NETWORK_FILE_LINES: usize = 14;
+
+pub fn vec_to_str<T: raybnn_float_trait>(invec: &arrayfire::Array<T>) -> String {
+    let mut vec0 = vec![T::default(); invec.elements()];
+    invec.host(&mut vec0);
+    let mut s0 = format!("{:?}", vec0);
+    s0 = s0.replace("[", "");
+    s0 = s0.replace("]", "");
+    s0 = s0.replace(" ", "");
+
+    s0
+}
+
+pub fn vec_cpu_to_str<T: raybnn_float_trait>(invec: &Vec<T>) -> String {
+    let mut s0 = format!("{:?}", invec.clone());
+    s0 = s0.replace("[", "");
+    s0 = s0.replace("]", "");
+    s0 = s0.replace(" ", "");
+
+    s0
+}
+
+pub fn try_str_to_vec_cpu<T: raybnn_float_trait>(instr: &str) -> Result<Vec<T>, Error> {
+    let mut vecT: Vec<T> = Vec::new();
+
+    let mut newline = instr.replace("\n", "");
+    newline = newline.replace(" ", "");
+
+    if newline.len() > 0 {
+        let strvec: Vec<&str> = newline.split(",").collect();
+        let ssize: u64 = strvec.len() as u64;
+
+        for i in 0u64..ssize {
+            let value: T = parse_value::<T>(strvec[i as usize])?;
+            vecT.push(value);
+        }
+    }
+
+    Ok(vecT)
+}
+
+pub fn str_to_vec_cpu<T: raybnn_float_trait>(instr: &str) -> Vec<T> {
+    try_str_to_vec_cpu(instr).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_str_to_vec<T: raybnn_float_trait>(instr: &str) -> Result<arrayfire::Array<T>, Error> {
+    let temp_dims = arrayfire::Dim4::new(&[1, 1, 1, 1]);
+    let mut outarr = arrayfire::constant::<T>(T::zero(), temp_dims);
+
+    let mut newline = instr.replace("\n", "");
+    newline = newline.replace(" ", "");
+
+    let strvec: Vec<&str> = newline.split(",").collect();
+    let ssize: u64 = strvec.len() as u64;
+
+    if (ssize > 1) {
+        let mut vecT: Vec<T> = Vec::new();
+        for i in 0u64..ssize {
+            let value: T = parse_value::<T>(strvec[i as usize])?;
+            vecT.push(value);
+        }
+
+        let new_dims = arrayfire::Dim4::new(&[ssize, 1, 1, 1]);
+        outarr = arrayfire::Array::new(&vecT, new_dims);
+    }
+
+    Ok(outarr)
+}
+
+pub fn str_to_vec<T: raybnn_float_trait>(instr: &str) -> arrayfire::Array<T> {
+    try_str_to_vec(instr).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_str_to_matrix<T: raybnn_float_trait>(
+    instr: &str,
+    dims: arrayfire::Dim4,
+) -> Result<arrayfire::Array<T>, Error> {
+    let temp_dims = arrayfire::Dim4::new(&[1, 1, 1, 1]);
+    let mut outarr = arrayfire::constant::<T>(T::zero(), temp_dims);
+
+    let mut newline = instr.replace("\n", "");
+    newline = newline.replace(" ", "");
+
+    let strvec: Vec<&str> = newline.split(",").collect();
+    let ssize: u64 = strvec.len() as u64;
+
+    if (ssize > 1) {
+        let mut vecT: Vec<T> = Vec::new();
+        for i in 0u64..ssize {
+            let value: T = parse_value::<T>(strvec[i as usize])?;
+            vecT.push(value);
+        }
+
+        if ssize != dims.elements() {
+            return Err(Error::ShapeMismatch {
+                file: String::new(),
+                expected: dims.elements(),
+                found: ssize,
+            });
+        }
+
+        outarr = arrayfire::Array::new(&vecT, dims);
+    }
+
+    Ok(outarr)
+}
+
+pub fn str_to_matrix<T: raybnn_float_trait>(
+    instr: &str,
+    dims: arrayfire::Dim4,
+) -> arrayfire::Array<T> {
+    try_str_to_matrix(instr, dims).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_extract_file_info2<T: raybnn_float_trait>(filepath: &str) -> Result<Vec<T>, Error> {
+    let targetstr = filepath.clone().replace(".csv", "");
+
+    let strsplit: Vec<&str> = targetstr.split('_').collect();
+
+    let mut outdata: Vec<T> = Vec::new();
+
+    let mut idx = 0;
+
+    let mut parse_state = 0;
+    for tmp in strsplit {
+        if tmp.contains("cube") && (parse_state == 0) {
+            parse_state = 1;
+        }
+
+        if (parse_state == 1) {
+            if idx == 1 {
+                //println!("data {}",tmp);
+                let elem = parse_value::<T>(tmp).map_err(|e| e.at(filepath, 0))?;
+                outdata.push(elem);
+                break;
+            }
+
+            idx = idx + 1;
+        }
+    }
+
+    Ok(outdata)
+}
+
+pub fn extract_file_info2<T: raybnn_float_trait>(filepath: &str) -> Vec<T> {
+    try_extract_file_info2(filepath).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_file_to_vec_cpu<T: raybnn_float_trait>(filename: &str) -> Result<Vec<T>, Error> {
+    let contents = fs::read_to_string(filename).map_err(|e| Error::io(filename, e))?;
+
+    let lines: Vec<&str> = contents.split('\n').collect();
+
+    let rows: Vec<Vec<T>> = lines
+        .par_iter()
+        .enumerate()
+        .map(|(i, line)| try_str_to_vec_cpu(line).map_err(|e| e.at(filename, (i + 1) as u64)))
+        .collect::<Result<Vec<Vec<T>>, Error>>()?;
+
+    Ok(rows.into_iter().flatten().collect())
+}
+
+pub fn file_to_vec_cpu<T: raybnn_float_trait>(filename: &str) -> Vec<T> {
+    try_file_to_vec_cpu(filename).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_file_to_matrix<T: raybnn_float_trait>(
+    filename: &str,
+    dims: arrayfire::Dim4,
+) -> Result<arrayfire::Array<T>, Error> {
+    let arr: Vec<T> = try_file_to_vec_cpu(filename)?;
+
+    let dims2 = arrayfire::Dim4::new(&[dims[1], dims[0], 1, 1]);
+    if (arr.len() as u64) != dims2.elements() {
+        return Err(Error::ShapeMismatch {
+            file: filename.to_string(),
+            expected: dims2.elements(),
+            found: arr.len() as u64,
+        });
+    }
+    let mut outarr = arrayfire::Array::new(&arr, dims2);
+
+    Ok(arrayfire::transpose(&outarr, false))
+}
+
+pub fn file_to_matrix<T: raybnn_float_trait>(
+    filename: &str,
+    dims: arrayfire::Dim4,
+) -> arrayfire::Array<T> {
+    try_file_to_matrix(filename, dims).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_file_to_hash_cpu<T: raybnn_float_trait>(
+    filename: &str,
+    sample_size: u64,
+    batch_size: u64,
+) -> Result<nohash_hasher::IntMap<u64, Vec<T>>, Error> {
+    let arr: Vec<T> = try_file_to_vec_cpu(filename)?;
+
+    Ok(vec_cpu_to_hash(&arr, sample_size, batch_size))
+}
+
+pub fn file_to_hash_cpu<T: raybnn_float_trait>(
+    filename: &str,
+    sample_size: u64,
+    batch_size: u64,
+) -> nohash_hasher::IntMap<u64, Vec<T>> {
+    try_file_to_hash_cpu(filename, sample_size, batch_size).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn compute_stats<T: raybnn_float_trait>(
+    input_size: u64,
+    dataset: &nohash_hasher::IntMap<u64, Vec<T>>,
+
+    mean: &mut arrayfire::Array<T>,
+    stdev: &mut arrayfire::Array<T>,
+) {
+    let two = T::from_f64(2.0);
+
+    let mut tempvec = dataset[&0].clone();
+
+    let mut veclen = tempvec.len() as u64;
+    let mut formatarr = arrayfire::Array::new(
+        &tempvec,
+        arrayfire::Dim4::new(&[input_size, veclen / input_size, 1, 1]),
+    );
+
+    *mean = arrayfire::mean(&formatarr, 1);
+    *stdev = arrayfire::stdev_v2(&formatarr, arrayfire::VarianceBias::SAMPLE, 1);
+
+    for (key, value) in dataset {
+        tempvec = value.clone();
+
+        veclen = tempvec.len() as u64;
+        formatarr = arrayfire::Array::new(
+            &tempvec,
+            arrayfire::Dim4::new(&[input_size, veclen / input_size, 1, 1]),
+        );
+
+        let tempmean = arrayfire::mean(&formatarr, 1);
+        let tempstdev = arrayfire::stdev_v2(&formatarr, arrayfire::VarianceBias::SAMPLE, 1);
+
+        *mean = (mean.clone() + tempmean) / two;
+        *stdev = (stdev.clone() + tempstdev) / two;
+    }
+
+    let eps = T::from_f64(0.001);
+
+    //  (eps > stdev )
+    let CMP1 = arrayfire::gt(&eps, stdev, false);
+
+    let selidx = arrayfire::locate(&CMP1);
+
+    if selidx.dims()[0] > 0 {
+        let mut ones = arrayfire::constant::<T>(T::one(), selidx.dims());
+
+        let mut idxrs2 = arrayfire::Indexer::default();
+        idxrs2.set_index(&selidx, 0, None);
+        arrayfire::assign_gen(stdev, &idxrs2, &ones);
+    }
+}
+
+pub fn normalize_dataset<T: raybnn_float_trait>(
+    input_size: u64,
+
+    mean: &arrayfire::Array<T>,
+    stdev: &arrayfire::Array<T>,
+
+    dataset: &nohash_hasher::IntMap<u64, Vec<T>>,
+) -> nohash_hasher::IntMap<u64, Vec<T>> {
+    let mut tempdata = dataset.clone();
+
+    for (key, value) in dataset {
+        let tempvec = value.clone();
+
+        let veclen = tempvec.len() as u64;
+        let mut formatarr = arrayfire::Array::new(
+            &tempvec,
+            arrayfire::Dim4::new(&[input_size, veclen / input_size, 1, 1]),
+        );
+
+        //formatarr = (formatarr - mean)/stdev;
+        formatarr = arrayfire::sub(&formatarr, mean, true);
+        formatarr = arrayfire::div(&formatarr, stdev, true);
+
+        let mut tempvec = vec![T::default(); formatarr.elements()];
+        formatarr.host(&mut tempvec);
+
+        tempdata.insert(key.clone(), tempvec);
+    }
+
+    tempdata
+}
+
+pub fn try_largefile_to_hash_cpu<T: raybnn_float_trait>(
+    filename: &str,
+) -> Result<nohash_hasher::IntMap<u64, Vec<T>>, Error> {
+    let mut lookup: nohash_hasher::IntMap<u64, Vec<T>> = nohash_hasher::IntMap::default();
+
+    let file = File::open(filename).map_err(|e| Error::io(filename, e))?;
+    let reader = BufReader::new(file);
+
+    let mut idx = 0;
+    for line in reader.lines() {
+        let tempstr = line.map_err(|e| Error::io(filename, e))?;
+        let outvec = try_str_to_vec_cpu(&tempstr).map_err(|e| e.at(filename, idx + 1))?;
+        lookup.insert(idx, outvec);
+        idx = idx + 1;
+    }
+
+    Ok(lookup)
+}
+
+pub fn largefile_to_hash_cpu<T: raybnn_float_trait>(
+    filename: &str,
+) -> nohash_hasher::IntMap<u64, Vec<T>> {
+    try_largefile_to_hash_cpu(filename).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn shuffle_hash_cpu<T: raybnn_float_trait>(
+    dataX: &mut nohash_hasher::IntMap<u64, Vec<T>>,
+    dataY: &mut nohash_hasher::IntMap<u64, Vec<T>>,
+) {
+    let totalsize = dataX.keys().len() as u64;
+
+    let randarr_dims = arrayfire::Dim4::new(&[totalsize, 1, 1, 1]);
+
+    let randarr = arrayfire::randu::<f32>(randarr_dims);
+
+    let (_, idx) = arrayfire::sort_index(&randarr, 0, false);
+
+    let mut idxvec = vec![u32::default(); idx.elements()];
+    idx.host(&mut idxvec);
+
+    let mut newX: nohash_hasher::IntMap<u64, Vec<T>> = nohash_hasher::IntMap::default();
+    let mut newY: nohash_hasher::IntMap<u64, Vec<T>> = nohash_hasher::IntMap::default();
+
+    let mut newidx: u64 = 0;
+    for i in 0..totalsize {
+        newidx = idxvec[i as usize] as u64;
+
+        newX.insert(newidx, (*dataX)[&i].clone());
+        newY.insert(newidx, (*dataY)[&i].clone());
+    }
+
+    *dataX = newX;
+    *dataY = newY;
+}
+
+pub fn format_vec_to_matrix<T: raybnn_float_trait>(
+    input: &Vec<T>,
+    dims: arrayfire::Dim4,
+) -> arrayfire::Array<T> {
+    let dims2 = arrayfire::Dim4::new(&[dims[1], dims[0], 1, 1]);
+    let outarr = arrayfire::Array::new(input, dims2);
+
+    arrayfire::transpose(&outarr, false)
+}
+
+pub fn try_file_to_hash_matrix<T: raybnn_float_trait>(
+    filename: &str,
+    sample_size: u64,
+    batch_size: u64,
+    dims: arrayfire::Dim4,
+) -> Result<nohash_hasher::IntMap<u64, arrayfire::Array<T>>, Error> {
+    let mut lookup2: nohash_hasher::IntMap<u64, arrayfire::Array<T>> =
+        nohash_hasher::IntMap::default();
+
+    let lookup: nohash_hasher::IntMap<u64, Vec<T>> =
+        try_file_to_hash_cpu(filename, sample_size, batch_size)?;
+
+    let item_num = lookup.len() as u64;
+
+    let temp_dims = arrayfire::Dim4::new(&[1, 1, 1, 1]);
+
+    let mut temparr = arrayfire::constant::<T>(T::zero(), temp_dims);
+    for i in 0..item_num {
+        if (lookup[&i].len() as u64) != dims.elements() {
+            return Err(Error::ShapeMismatch {
+                file: filename.to_string(),
+                expected: dims.elements(),
+                found: lookup[&i].len() as u64,
+            });
+        }
+
+        temparr = arrayfire::Array::new(&lookup[&i], dims);
+
+        lookup2.insert(i, temparr);
+    }
+
+    Ok(lookup2)
+}
+
+pub fn file_to_hash_matrix<T: raybnn_float_trait>(
+    filename: &str,
+    sample_size: u64,
+    batch_size: u64,
+    dims: arrayfire::Dim4,
+) -> nohash_hasher::IntMap<u64, arrayfire::Array<T>> {
+    try_file_to_hash_matrix(filename, sample_size, batch_size, dims)
+        .unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_write_arr_to_csv<T: raybnn_float_trait>(
+    filename: &str,
+    arr: &arrayfire::Array<T>,
+) -> Result<(), Error> {
+    let mut wtr0: Vec<String> = Vec::new();
+
+    let item_num = arr.dims()[0] as i64;
+
+    for i in 0..item_num {
+        let cur_item = arrayfire::row(arr, i);
+        let s0 = vec_to_str(&cur_item);
+        wtr0.push(s0);
+    }
+
+    let mut file0 = File::create(filename).map_err(|e| Error::io(filename, e))?;
+    writeln!(file0, "{}", wtr0.join("\n")).map_err(|e| Error::io(filename, e))?;
+
+    Ok(())
+}
+
+pub fn write_arr_to_csv<T: raybnn_float_trait>(filename: &str, arr: &arrayfire::Array<T>) {
+    try_write_arr_to_csv(filename, arr).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_write_vec_cpu_to_csv<T: raybnn_float_trait>(
+    filename: &str,
+    invec: &Vec<T>,
+) -> Result<(), Error> {
+    let mut wtr0 = vec_cpu_to_str(invec);
+
+    //wtr0 = wtr0.replace(",", "\n");
+
+    let mut file0 = File::create(filename).map_err(|e| Error::io(filename, e))?;
+    writeln!(file0, "{}", wtr0).map_err(|e| Error::io(filename, e))?;
+
+    Ok(())
+}
+
+pub fn write_vec_cpu_to_csv<T: raybnn_float_trait>(filename: &str, invec: &Vec<T>) {
+    try_write_vec_cpu_to_csv(filename, invec).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_hash_batch_to_files<T: raybnn_float_trait>(
+    filename: &str,
+    hash_map: &nohash_hasher::IntMap<u64, Vec<T>>,
+) -> Result<(), Error> {
+    for batch_idx in 0..(hash_map.len() as u64) {
+        let cur_batch = hash_map[&batch_idx].clone();
+
+        let tmpfilename = format!("{}_batch_idx_{}.yhat", filename, batch_idx);
+
+        try_write_vec_cpu_to_csv(&tmpfilename, &cur_batch)?;
+    }
+
+    Ok(())
+}
+
+pub fn hash_batch_to_files<T: raybnn_float_trait>(
+    filename: &str,
+    hash_map: &nohash_hasher::IntMap<u64, Vec<T>>,
+) {
+    try_hash_batch_to_files(filename, hash_map).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_save_network<T: raybnn_float_trait>(
+    filename: &str,
+    netdata: &network_metadata_type<T>,
+    WValues: &arrayfire::Array<T>,
+    WRowIdxCSR: &arrayfire::Array<i32>,
+    WColIdx: &arrayfire::Array<i32>,
+    H: &arrayfire::Array<T>,
+    A: &arrayfire::Array<T>,
+    B: &arrayfire::Array<T>,
+    C: &arrayfire::Array<T>,
+    D: &arrayfire::Array<T>,
+    E: &arrayfire::Array<T>,
+    glia_pos: &arrayfire::Array<T>,
+    neuron_pos: &arrayfire::Array<T>,
+    neuron_idx: &arrayfire::Array<i32>,
+) -> Result<(), Error> {
+    tracing::debug!(
+        filename,
+        connection_num = WColIdx.dims()[0],
+        "saving network"
+    );
+
+    let neuron_size: u64 = netdata.neuron_size.clone();
+    let input_size: u64 = netdata.input_size.clone();
+    let output_size: u64 = netdata.output_size.clone();
+    let proc_num: u64 = netdata.proc_num.clone();
+    let active_size: u64 = netdata.active_size.clone();
+    let space_dims: u64 = netdata.space_dims.clone();
+    let step_num: u64 = netdata.step_num.clone();
+    let batch_size: u64 = netdata.batch_size.clone();
+
+    let del_unused_neuron: bool = netdata.del_unused_neuron.clone();
+
+    let time_step: T = netdata.time_step.clone();
+    let nratio: T = netdata.nratio.clone();
+    let neuron_std: T = netdata.neuron_std.clone();
+    let sphere_rad: T = netdata.sphere_rad.clone();
+    let neuron_rad: T = netdata.neuron_rad.clone();
+    let con_rad: T = netdata.con_rad.clone();
+    let init_prob: T = netdata.init_prob.clone();
+    let add_neuron_rate: T = netdata.add_neuron_rate.clone();
+    let del_neuron_rate: T = netdata.del_neuron_rate.clone();
+    let center_const: T = netdata.center_const.clone();
+    let spring_const: T = netdata.spring_const.clone();
+    let repel_const: T = netdata.repel_const.clone();
+
+    let mut wtr0: Vec<String> = Vec::new();
+
+    let s0 = vec_to_str(WValues);
+    wtr0.push(s0);
+
+    let s0 = vec_to_str_i32(WRowIdxCSR);
+    wtr0.push(s0);
+
+    let s0 = vec_to_str_i32(WColIdx);
+    wtr0.push(s0);
+
+    let s0 = vec_to_str(H);
+    wtr0.push(s0);
+
+    let s0 = vec_to_str(A);
+    wtr0.push(s0);
+
+    let s0 = vec_to_str(B);
+    wtr0.push(s0);
+
+    let s0 = vec_to_str(C);
+    wtr0.push(s0);
+
+    let s0 = vec_to_str(D);
+    wtr0.push(s0);
+
+    let s0 = vec_to_str(E);
+    wtr0.push(s0);
+
+    let s0 = vec_to_str(glia_pos);
+    wtr0.push(s0);
+
+    let s0 = vec_to_str(neuron_pos);
+    wtr0.push(s0);
+
+    let s0 = vec_to_str_i32(neuron_idx);
+    wtr0.push(s0);
+
+    let netarr0: Vec<u64> = vec![
+        neuron_size,
+        input_size,
+        output_size,
+        proc_num,
+        active_size,
+        space_dims,
+        step_num,
+        batch_size,
+        del_unused_neuron as u64,
+        netdata.neighbor_search as u64,
+    ];
+
+    let s0 = vec_cpu_to_str_u64(&netarr0);
+    wtr0.push(s0);
+
+    let netarr1: Vec<T> = vec![
+        time_step,
+        nratio,
+        neuron_std,
+        sphere_rad,
+        neuron_rad,
+        con_rad,
+        init_prob,
+        add_neuron_rate,
+        del_neuron_rate,
+        center_const,
+        spring_const,
+        repel_const,
+    ];
+
+    let s0 = vec_cpu_to_str(&netarr1);
+    wtr0.push(s0);
+
+    let mut file0 = File::create(filename).map_err(|e| Error::io(filename, e))?;
+    writeln!(file0, "{}", wtr0.join("\n")).map_err(|e| Error::io(filename, e))?;
+
+    Ok(())
+}
+
+pub fn save_network<T: raybnn_float_trait>(
+    filename: &str,
+    netdata: &network_metadata_type<T>,
+    WValues: &arrayfire::Array<T>,
+    WRowIdxCSR: &arrayfire::Array<i32>,
+    WColIdx: &arrayfire::Array<i32>,
+    H: &arrayfire::Array<T>,
+    A: &arrayfire::Array<T>,
+    B: &arrayfire::Array<T>,
+    C: &arrayfire::Array<T>,
+    D: &arrayfire::Array<T>,
+    E: &arrayfire::Array<T>,
+    glia_pos: &arrayfire::Array<T>,
+    neuron_pos: &arrayfire::Array<T>,
+    neuron_idx: &arrayfire::Array<i32>,
+) {
+    try_save_network(
+        filename, netdata, WValues, WRowIdxCSR, WColIdx, H, A, B, C, D, E, glia_pos, neuron_pos,
+        neuron_idx,
+    )
+    .unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_save_network2<T: raybnn_float_trait>(
+    filename: &str,
+    neural_network: &neural_network_type<T>,
+) -> Result<(), Error> {
+    tracing::debug!(
+        filename,
+        connection_num = neural_network.WColIdx.dims()[0],
+        "saving network"
+    );
+
+    let WValuesdims0 = neural_network.WColIdx.dims()[0];
+
+    let network_paramsdims0 = neural_network.network_params.dims()[0];
+
+    if (network_paramsdims0 < WValuesdims0) || (((network_paramsdims0 - WValuesdims0) % 6) != 0) {
+        return Err(Error::InvalidNetwork(format!(
+            "network_params has {} values, which is not WColIdx ({}) plus 6 equal UAF/bias vectors",
+            network_paramsdims0, WValuesdims0
+        )));
+    }
+
+    let Hdims0 = (network_paramsdims0 - WValuesdims0) / 6;
+
+    let Wstart = 0;
+    let Wend = (WValuesdims0 as i64) - 1;
+
+    let Hstart = Wend + 1;
+    let Hend = Hstart + (Hdims0 as i64) - 1;
+
+    let Astart = Hend + 1;
+    let Aend = Astart + (Hdims0 as i64) - 1;
+
+    let Bstart = Aend + 1;
+    let Bend = Bstart + (Hdims0 as i64) - 1;
+
+    let Cstart = Bend + 1;
+    let Cend = Cstart + (Hdims0 as i64) - 1;
+
+    let Dstart = Cend + 1;
+    let Dend = Dstart + (Hdims0 as i64) - 1;
+
+    let Estart = Dend + 1;
+    let Eend = Estart + (Hdims0 as i64) - 1;
+
+    let Wseqs = [arrayfire::Seq::new(Wstart as i32, Wend as i32, 1i32)];
+    let Hseqs = [arrayfire::Seq::new(Hstart as i32, Hend as i32, 1i32)];
+    let Aseqs = [arrayfire::Seq::new(Astart as i32, Aend as i32, 1i32)];
+    let Bseqs = [arrayfire::Seq::new(Bstart as i32, Bend as i32, 1i32)];
+    let Cseqs = [arrayfire::Seq::new(Cstart as i32, Cend as i32, 1i32)];
+    let Dseqs = [arrayfire::Seq::new(Dstart as i32, Dend as i32, 1i32)];
+    let Eseqs = [arrayfire::Seq::new(Estart as i32, Eend as i32, 1i32)];
+
+    let WValues = arrayfire::index(&neural_network.network_params, &Wseqs);
+    let H = arrayfire::index(&neural_network.network_params, &Hseqs);
+    let A = arrayfire::index(&neural_network.network_params, &Aseqs);
+    let B = arrayfire::index(&neural_network.network_params, &Bseqs);
+    let C = arrayfire::index(&neural_network.network_params, &Cseqs);
+    let D = arrayfire::index(&neural_network.network_params, &Dseqs);
+    let E = arrayfire::index(&neural_network.network_params, &Eseqs);
+
+    try_save_network(
+        filename,
+        &neural_network.netdata,
+        &WValues,
+        &neural_network.WRowIdxCSR,
+        &neural_network.WColIdx,
+        &H,
+        &A,
+        &B,
+        &C,
+        &D,
+        &E,
+        &neural_network.glia_pos,
+        &neural_network.neuron_pos,
+        &neural_network.neuron_idx,
+    )
+}
+
+pub fn save_network2<T: raybnn_float_trait>(
+    filename: &str,
+    neural_network: &neural_network_type<T>,
+) {
+    try_save_network2(filename, neural_network).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/*
+Parses the network metadata stored in lines 13 and 14 of a network file, counting from 1
+
+Inputs
+filename:            Network file, only used for error messages
+netarr0:             Integer metadata (line 13). The 10th value, the neighbor search, is
+                     missing in files saved before it was added
+netarr1:             Floating point metadata (line 14)
+
+Outputs:
+netdata:             Network metadata
+
+*/
+fn netdata_from_vec<T: raybnn_float_trait>(
+    filename: &str,
+    netarr0: &Vec<u64>,
+    netarr1: &Vec<T>,
+) -> Result<network_metadata_type<T>, Error> {
+    if netarr0.len() < 9 {
+        return Err(Error::InvalidNetwork(format!(
+            "{} has {} integer metadata values on line 13, expected 9",
+            filename,
+            netarr0.len()
+        )));
+    }
+    if netarr1.len() < 12 {
+        return Err(Error::InvalidNetwork(format!(
+            "{} has {} floating point metadata values on line 14, expected 12",
+            filename,
+            netarr1.len()
+        )));
+    }
+
+    let neuron_size: u64 = netarr0[0];
+    let input_size: u64 = netarr0[1];
+    let output_size: u64 = netarr0[2];
+    let proc_num: u64 = netarr0[3];
+    let active_size: u64 = netarr0[4];
+    let space_dims: u64 = netarr0[5];
+    let step_num: u64 = netarr0[6];
+    let batch_size: u64 = netarr0[7];
+
+    let del_unused_neuron: bool = (netarr0[8] == 1);
+
+    let neighbor_search = match netarr0.get(9) {
+        None | Some(0) => neighbor_search_type::DENSE,
+        Some(1) => neighbor_search_type::CELL_LIST,
+        Some(value) => {
+            return Err(Error::InvalidNetwork(format!(
+                "{} has unknown neighbor search {}",
+                filename, value
+            )));
+        }
+    };
+
+    if space_dims == 0 {
+        return Err(Error::InvalidNetwork(format!(
+            "{} has space_dims = 0",
+            filename
+        )));
+    }
+
+    let time_step: T = netarr1[0];
+    let nratio: T = netarr1[1];
+    let neuron_std: T = netarr1[2];
+    let sphere_rad: T = netarr1[3];
+    let neuron_rad: T = netarr1[4];
+    let con_rad: T = netarr1[5];
+    let init_prob: T = netarr1[6];
+    let add_neuron_rate: T = netarr1[7];
+    let del_neuron_rate: T = netarr1[8];
+    let center_const: T = netarr1[9];
+    let spring_const: T = netarr1[10];
+    let repel_const: T = netarr1[11];
+
+    let newnetdata = network_metadata_type {
+        neuron_size: neuron_size,
+        input_size: input_size,
+        output_size: output_size,
+        proc_num: proc_num,
+        active_size: active_size,
+        space_dims: space_dims,
+        step_num: step_num,
+        batch_size: batch_size,
+        del_unused_neuron: del_unused_neuron,
+
+        time_step: time_step,
+        nratio: nratio,
+        neuron_std: neuron_std,
+        sphere_rad: sphere_rad,
+        neuron_rad: neuron_rad,
+        con_rad: con_rad,
+        init_prob: init_prob,
+        add_neuron_rate: add_neuron_rate,
+        del_neuron_rate: del_neuron_rate,
+        center_const: center_const,
+        spring_const: spring_const,
+        repel_const: repel_const,
+
+        neighbor_search: neighbor_search,
+    };
+
+    Ok(newnetdata)
+}
+
+// Reshapes a flattened position vector into (N, space_dims)
+fn reshape_pos<T: raybnn_float_trait>(
+    filename: &str,
+    space_dims: u64,
+    pos: &mut arrayfire::Array<T>,
+) -> Result<(), Error> {
+    let elements = pos.dims()[0];
+    if (elements % space_dims) != 0 {
+        return Err(Error::ShapeMismatch {
+            file: filename.to_string(),
+            expected: ((elements / space_dims) + 1) * space_dims,
+            found: elements,
+        });
+    }
+
+    let p_dims = arrayfire::Dim4::new(&[(elements / space_dims) as u64, space_dims, 1, 1]);
+    *pos = arrayfire::moddims(pos, p_dims);
+
+    Ok(())
+}
+
+pub fn try_load_network<T: raybnn_float_trait>(
+    filename: &str,
+    netdata: &mut network_metadata_type<T>,
+    WValues: &mut arrayfire::Array<T>,
+    WRowIdxCSR: &mut arrayfire::Array<i32>,
+    WColIdx: &mut arrayfire::Array<i32>,
+    H: &mut arrayfire::Array<T>,
+    A: &mut arrayfire::Array<T>,
+    B: &mut arrayfire::Array<T>,
+    C: &mut arrayfire::Array<T>,
+    D: &mut arrayfire::Array<T>,
+    E: &mut arrayfire::Array<T>,
+    glia_pos: &mut arrayfire::Array<T>,
+    neuron_pos: &mut arrayfire::Array<T>,
+    neuron_idx: &mut arrayfire::Array<i32>,
+) -> Result<(), Error> {
+    tracing::debug!(filename, "loading network");
+
+    let mut netarr0: Vec<u64> = Vec::new();
+    let mut netarr1: Vec<T> = Vec::new();
+
+    let contents = fs::read_to_string(filename).map_err(|e| Error::io(filename, e))?;
+    let lines: Vec<&str> = contents.split("\n").collect();
+
+    if lines.len() < NETWORK_FILE_LINES {
+        return Err(Error::InvalidNetwork(format!(
+            "{} has {} lines, expected {}",
+            filename,
+            lines.len(),
+            NETWORK_FILE_LINES
+        )));
+    }
+
+    let mut i = 0;
+    for line in lines {
+        let lineno = (i + 1) as u64;
+        match i {
+            0 => *WValues = try_str_to_vec(&line).map_err(|e| e.at(filename, lineno))?,
+            1 => *WRowIdxCSR = try_str_to_vec_i32(&line).map_err(|e| e.at(filename, lineno))?,
+            2 => *WColIdx = try_str_to_vec_i32(&line).map_err(|e| e.at(filename, lineno))?,
+            3 => *H = try_str_to_vec(&line).map_err(|e| e.at(filename, lineno))?,
+            4 => *A = try_str_to_vec(&line).map_err(|e| e.at(filename, lineno))?,
+            5 => *B = try_str_to_vec(&line).map_err(|e| e.at(filename, lineno))?,
+            6 => *C = try_str_to_vec(&line).map_err(|e| e.at(filename, lineno))?,
+
+            7 => *D = try_str_to_vec(&line).map_err(|e| e.at(filename, lineno))?,
+
+            8 => *E = try_str_to_vec(&line).map_err(|e| e.at(filename, lineno))?,
+
+            9 => *glia_pos = try_str_to_vec(&line).map_err(|e| e.at(filename, lineno))?,
+
+            10 => *neuron_pos = try_str_to_vec(&line).map_err(|e| e.at(filename, lineno))?,
+
+            11 => *neuron_idx = try_str_to_vec_i32(&line).map_err(|e| e.at(filename, lineno))?,
+
+            12 => netarr0 = try_str_to_vec_cpu_u64(&line).map_err(|e| e.at(filename, lineno))?,
+
+            13 => netarr1 = try_str_to_vec_cpu(&line).map_err(|e| e.at(filename, lineno))?,
+
+            _ => {}
+        }
+
+        i = i + 1;
+    }
+
+    let newnetdata = netdata_from_vec(filename, &netarr0, &netarr1)?;
+
+    let space_dims = newnetdata.space_dims;
+
+    *netdata = newnetdata;
+
+    reshape_pos(filename, space_dims, glia_pos)?;
+
+    reshape_pos(filename, space_dims, neuron_pos)?;
+
+    Ok(())
+}
+
+pub fn load_network<T: raybnn_float_trait>(
+    filename: &str,
+    netdata: &mut network_metadata_type<T>,
+    WValues: &mut arrayfire::Array<T>,
+    WRowIdxCSR: &mut arrayfire::Array<i32>,
+    WColIdx: &mut arrayfire::Array<i32>,
+    H: &mut arrayfire::Array<T>,
+    A: &mut arrayfire::Array<T>,
+    B: &mut arrayfire::Array<T>,
+    C: &mut arrayfire::Array<T>,
+    D: &mut arrayfire::Array<T>,
+    E: &mut arrayfire::Array<T>,
+    glia_pos: &mut arrayfire::Array<T>,
+    neuron_pos: &mut arrayfire::Array<T>,
+    neuron_idx: &mut arrayfire::Array<i32>,
+) {
+    try_load_network(
+        filename, netdata, WValues, WRowIdxCSR, WColIdx, H, A, B, C, D, E, glia_pos, neuron_pos,
+        neuron_idx,
+    )
+    .unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_load_network2<T: raybnn_float_trait>(
+    filename: &str,
+) -> Result<neural_network_type<T>, Error> {
+    tracing::debug!(filename, "loading network");
+
+    let temp_dims = arrayfire::Dim4::new(&[4, 1, 1, 1]);
+
+    let mut glia_pos = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut neuron_pos = arrayfire::constant::<T>(T::zero(), temp_dims);
+
+    let mut H = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut A = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut B = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut C = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut D = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut E = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut neuron_idx = arrayfire::constant::<i32>(0, temp_dims);
+
+    let mut WValues = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut WRowIdxCSR = arrayfire::constant::<i32>(0, temp_dims);
+    let mut WColIdx = arrayfire::constant::<i32>(0, temp_dims);
+
+    let mut netdata = create_nullnetdata::<T>();
+
+    try_load_network(
+        filename,
+        &mut netdata,
+        &mut WValues,
+        &mut WRowIdxCSR,
+        &mut WColIdx,
+        &mut H,
+        &mut A,
+        &mut B,
+        &mut C,
+        &mut D,
+        &mut E,
+        &mut glia_pos,
+        &mut neuron_pos,
+        &mut neuron_idx,
+    )?;
+
+    if WValues.dims()[0] != WColIdx.dims()[0] {
+        return Err(Error::InvalidNetwork(format!(
+            "{} has {} weights but {} column indexes",
+            filename,
+            WValues.dims()[0],
+            WColIdx.dims()[0]
+        )));
+    }
+
+    let total_param_size = WValues.dims()[0]
+        + H.dims()[0]
+        + A.dims()[0]
+        + B.dims()[0]
+        + C.dims()[0]
+        + D.dims()[0]
+        + E.dims()[0];
+    let network_params_dims = arrayfire::Dim4::new(&[total_param_size, 1, 1, 1]);
+
+    let Wstart = 0;
+    let Wend = (WValues.dims()[0] as i64) - 1;
+
+    let Hstart = Wend + 1;
+    let Hend = Hstart + (H.dims()[0] as i64) - 1;
+
+    let Astart = Hend + 1;
+    let Aend = Astart + (A.dims()[0] as i64) - 1;
+
+    let Bstart = Aend + 1;
+    let Bend = Bstart + (B.dims()[0] as i64) - 1;
+
+    let Cstart = Bend + 1;
+    let Cend = Cstart + (C.dims()[0] as i64) - 1;
+
+    let Dstart = Cend + 1;
+    let Dend = Dstart + (D.dims()[0] as i64) - 1;
+
+    let Estart = Dend + 1;
+    let Eend = Estart + (E.dims()[0] as i64) - 1;
+
+    let Wseqs = [arrayfire::Seq::new(Wstart as i32, Wend as i32, 1i32)];
+    let Hseqs = [arrayfire::Seq::new(Hstart as i32, Hend as i32, 1i32)];
+    let Aseqs = [arrayfire::Seq::new(Astart as i32, Aend as i32, 1i32)];
+    let Bseqs = [arrayfire::Seq::new(Bstart as i32, Bend as i32, 1i32)];
+    let Cseqs = [arrayfire::Seq::new(Cstart as i32, Cend as i32, 1i32)];
+    let Dseqs = [arrayfire::Seq::new(Dstart as i32, Dend as i32, 1i32)];
+    let Eseqs = [arrayfire::Seq::new(Estart as i32, Eend as i32, 1i32)];
+
+    let mut network_params = arrayfire::constant::<T>(T::zero(), network_params_dims);
+    arrayfire::assign_seq(&mut network_params, &Wseqs, &WValues);
+    arrayfire::assign_seq(&mut network_params, &Hseqs, &H);
+    arrayfire::assign_seq(&mut network_params, &Aseqs, &A);
+    arrayfire::assign_seq(&mut network_params, &Bseqs, &B);
+    arrayfire::assign_seq(&mut network_params, &Cseqs, &C);
+    arrayfire::assign_seq(&mut network_params, &Dseqs, &D);
+    arrayfire::assign_seq(&mut network_params, &Eseqs, &E);
+
+    let mut neural_network: neural_network_type<T> = neural_network_type {
+        netdata: netdata,
+        WRowIdxCSR: WRowIdxCSR,
+        WColIdx: WColIdx,
+        network_params: network_params,
+        glia_pos: glia_pos,
+        neuron_pos: neuron_pos,
+        neuron_idx: neuron_idx,
+        norm: create_norm_state(create_norm_options(norm_kind_type::NONE)),
+    };
+
+    Ok(neural_network)
+}
+
+pub fn load_network2<T: raybnn_float_trait>(filename: &str) -> neural_network_type<T> {
+    try_load_network2(filename).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_load_network_structure<T: raybnn_float_trait>(
+    filename: &str,
+
+    glia_pos: &mut arrayfire::Array<T>,
+    neuron_pos: &mut arrayfire::Array<T>,
+    neuron_idx: &mut arrayfire::Array<i32>,
+) -> Result<(), Error> {
+    tracing::debug!(filename, "loading network structure");
+
+    let mut netarr0: Vec<u64> = Vec::new();
+    let mut netarr1: Vec<T> = Vec::new();
+
+    let contents = fs::read_to_string(filename).map_err(|e| Error::io(filename, e))?;
+    let lines: Vec<&str> = contents.split("\n").collect();
+
+    if lines.len() < NETWORK_FILE_LINES {
+        return Err(Error::InvalidNetwork(format!(
+            "{} has {} lines, expected {}",
+            filename,
+            lines.len(),
+            NETWORK_FILE_LINES
+        )));
+    }
+
+    let mut i = 0;
+    for line in lines {
+        let lineno = (i + 1) as u64;
+        match i {
+            9 => *glia_pos = try_str_to_vec(&line).map_err(|e| e.at(filename, lineno))?,
+
+            10 => *neuron_pos = try_str_to_vec(&line).map_err(|e| e.at(filename, lineno))?,
+
+            11 => *neuron_idx = try_str_to_vec_i32(&line).map_err(|e| e.at(filename, lineno))?,
+
+            12 => netarr0 = try_str_to_vec_cpu_u64(&line).map_err(|e| e.at(filename, lineno))?,
+
+            13 => netarr1 = try_str_to_vec_cpu(&line).map_err(|e| e.at(filename, lineno))?,
+
+            _ => {}
+        }
+
+        i = i + 1;
+    }
+
+    let newnetdata = netdata_from_vec(filename, &netarr0, &netarr1)?;
+
+    reshape_pos(filename, newnetdata.space_dims, glia_pos)?;
+
+    reshape_pos(filename, newnetdata.space_dims, neuron_pos)?;
+
+    Ok(())
+}
+
+pub fn load_network_structure<T: raybnn_float_trait>(
+    filename: &str,
+
+    glia_pos: &mut arrayfire::Array<T>,
+    neuron_pos: &mut arrayfire::Array<T>,
+    neuron_idx: &mut arrayfire::Array<i32>,
+) {
+    try_load_network_structure(filename, glia_pos, neuron_pos, neuron_idx)
+        .unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn nfold<T: raybnn_float_trait>(
+    data_x: &Vec<T>,
+    data_y: &Vec<T>,
+    n: usize,
+) -> Vec<(Vec<T>, Vec<T>, Vec<T>, Vec<T>)> {
+    let fold_size_x = data_x.len() / n;
+    let fold_size_y = data_y.len() / n;
+    let mut folds = Vec::new();
+
+    for i in 0..n {
+        let test_x_start = i * fold_size_x;
+        let test_x_end = if i == n - 1 {
+            data_x.len()
+        } else {
+            (i + 1) * fold_size_x
+        };
+        let test_y_start = i * fold_size_y;
+        let test_y_end = if i == n - 1 {
+            data_y.len()
+        } else {
+            (i + 1) * fold_size_y
+        };
+
+        let test_x = data_x[test_x_start..test_x_end].to_vec();
+        let test_y = data_y[test_y_start..test_y_end].to_vec();
+
+        let mut train_x = Vec::new();
+        let mut train_y = Vec::new();
+
+        if test_x_start > 0 {
+            train_x.extend_from_slice(&data_x[0..test_x_start]);
+            train_y.extend_from_slice(&data_y[0..test_y_start]);
+        }
+
+        if test_x_end < data_x.len() {
+            train_x.extend_from_slice(&data_x[test_x_end..]);
+            train_y.extend_from_slice(&data_y[test_y_end..]);
+        }
+
+        folds.push((train_x, train_y, test_x, test_y));
+    }
+
+    folds
+}
+
+pub fn vec_cpu_to_hash<T: raybnn_float_trait>(
+    arr: &Vec<T>,
+    input_size: u64,
+    batch_size: u64,
+) -> nohash_hasher::IntMap<u64, Vec<T>> {
+    let arr_size = arr.len() as u64;
+    let item_num = (arr_size / (input_size * batch_size));
+
+    let mut lookup: nohash_hasher::IntMap<u64, Vec<T>> = nohash_hasher::IntMap::default();
+    let mut start: usize = 0;
+    let mut end: usize = 0;
+    for i in 0..item_num {
+        start = (i * (input_size * batch_size)) as usize;
+        end = ((i + 1) * (input_size * batch_size)) as usize;
+        lookup.insert(i, (&arr[start..end]).to_vec());
+    }
+
+    lookup
+}
+
+/*
+Fold assignment of k-fold cross-validation
+
+CONTIGUOUS:          Folds of consecutive samples, the same folds as nfold
+SHUFFLED:            Folds of randomly ordered samples
+STRATIFIED:          Every fold has about the same fraction of each label
+GROUP:               All samples of a group, e.g. one EEG subject, are in the same fold.
+                     groups has one entry per sample
+
+*/
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum fold_split_type {
+    CONTIGUOUS,
+    SHUFFLED,
+    STRATIFIED,
+    GROUP { groups: Vec<u64> },
+}
+
+/*
+Test samples of every fold of k-fold cross-validation
+
+Inputs
+labels:              Label of every sample. The number of samples is labels.len()
+fold_num:            Number of folds
+split:               Fold assignment
+seed:                Seed of the random order of SHUFFLED and STRATIFIED
+
+Outputs:
+fold_idx:            Sorted indices of the test samples of every fold. The training samples
+                     of a fold are all other samples
+
+*/
+pub fn try_kfold_idx(
+    labels: &[u64],
+    fold_num: u64,
+    split: &fold_split_type,
+    seed: u64,
+) -> Result<Vec<Vec<u64>>, Error> {
+    let sample_num = labels.len() as u64;
+    if (fold_num < 2) || (fold_num > sample_num) {
+        return Err(Error::InvalidDataset(format!(
+            "fold_num {} must be between 2 and the number of samples {}",
+            fold_num, sample_num
+        )));
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut fold_idx: Vec<Vec<u64>> = vec![Vec::new(); fold_num as usize];
+
+    match split {
+        fold_split_type::CONTIGUOUS | fold_split_type::SHUFFLED => {
+            let mut order: Vec<u64> = (0..sample_num).collect();
+            if *split == fold_split_type::SHUFFLED {
+                order.shuffle(&mut rng);
+            }
+
+            let fold_size = sample_num / fold_num;
+            for (i, idx) in order.iter().enumerate() {
+                let fold = ((i as u64) / fold_size).min(fold_num - 1);
+                fold_idx[fold as usize].push(*idx);
+            }
+        }
+        fold_split_type::STRATIFIED => {
+            let mut label_idx: BTreeMap<u64, Vec<u64>> = BTreeMap::new();
+            for (idx, label) in labels.iter().enumerate() {
+                label_idx.entry(*label).or_default().push(idx as u64);
+            }
+
+            //Deal the samples of every label to the folds in turn
+            let mut fold = 0;
+            for idx_vec in label_idx.values_mut() {
+                idx_vec.shuffle(&mut rng);
+                for idx in idx_vec.iter() {
+                    fold_idx[fold].push(*idx);
+                    fold = (fold + 1) % (fold_num as usize);
+                }
+            }
+        }
+        fold_split_type::GROUP { groups } => {
+            if groups.len() != labels.len() {
+                return Err(Error::InvalidDataset(format!(
+                    "{} groups for {} samples",
+                    groups.len(),
+                    labels.len()
+                )));
+            }
+
+            let mut group_idx: BTreeMap<u64, Vec<u64>> = BTreeMap::new();
+            for (idx, group) in groups.iter().enumerate() {
+                group_idx.entry(*group).or_default().push(idx as u64);
+            }
+            if (group_idx.len() as u64) < fold_num {
+                return Err(Error::InvalidDataset(format!(
+                    "{} groups can not fill {} folds",
+                    group_idx.len(),
+                    fold_num
+                )));
+            }
+
+            //Largest group first into the smallest fold
+            let mut group_vec: Vec<Vec<u64>> = group_idx.into_values().collect();
+            group_vec.sort_by(|a, b| b.len().cmp(&a.len()));
+            for idx_vec in group_vec {
+                let fold = (0..fold_idx.len())
+                    .min_by_key(|f| fold_idx[*f].len())
+                    .unwrap_or(0);
+                fold_idx[fold].extend(idx_vec);
+            }
+        }
+    }
+
+    for idx_vec in fold_idx.iter_mut() {
+        idx_vec.sort();
+    }
+
+    Ok(fold_idx)
+}
+
+pub fn kfold_idx(
+    labels: &[u64],
+    fold_num: u64,
+    split: &fold_split_type,
+    seed: u64,
+) -> Vec<Vec<u64>> {
+    try_kfold_idx(labels, fold_num, split, seed).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/*
+Splits a dataset of batches into samples
+
+Inputs
+data:                Batches with dimensions (feature_size, batch_size, traj_size), like the
+                     outputs of file_to_hash_cpu
+feature_size:        Number of input or output features
+batch_size:          Number of samples of a batch
+
+Outputs:
+samples:             Samples in batch order, dimensions (feature_size, traj_size)
+
+*/
+pub fn hash_to_samples<T: raybnn_float_trait>(
+    data: &nohash_hasher::IntMap<u64, Vec<T>>,
+    feature_size: u64,
+    batch_size: u64,
+) -> Vec<Vec<T>> {
+    let feature_size = feature_size as usize;
+    let batch_size = batch_size as usize;
+
+    let mut keys: Vec<u64> = data.keys().cloned().collect();
+    keys.sort();
+
+    let mut samples = Vec::new();
+    for key in keys {
+        let batch = &data[&key];
+        let traj_size = batch.len() / (feature_size * batch_size);
+
+        for b in 0..batch_size {
+            let mut sample = Vec::with_capacity(feature_size * traj_size);
+            for t in 0..traj_size {
+                let start = (b + t * batch_size) * feature_size;
+                sample.extend_from_slice(&batch[start..start + feature_size]);
+            }
+            samples.push(sample);
+        }
+    }
+
+    samples
+}
+
+/*
+Batches the samples at idx. The last batch is dropped when it has fewer than batch_size samples
+
+Outputs:
+data:                Batches with dimensions (feature_size, batch_size, traj_size), keys from 0
+
+*/
+pub fn samples_to_hash<T: raybnn_float_trait>(
+    samples: &[Vec<T>],
+    idx: &[u64],
+    feature_size: u64,
+    batch_size: u64,
+) -> nohash_hasher::IntMap<u64, Vec<T>> {
+    let feature_size = feature_size as usize;
+    let batch_size = batch_size as usize;
+
+    let mut data: nohash_hasher::IntMap<u64, Vec<T>> = nohash_hasher::IntMap::default();
+    for (i, batch_idx) in idx.chunks_exact(batch_size).enumerate() {
+        let traj_size = samples[batch_idx[0] as usize].len() / feature_size;
+
+        let mut batch = vec![T::zero(); feature_size * batch_size * traj_size];
+        for (b, sample_idx) in batch_idx.iter().enumerate() {
+            let sample = &samples[*sample_idx as usize];
+            for t in 0..traj_size {
+                let start = (b + t * batch_size) * feature_size;
+                batch[start..start + feature_size]
+                    .copy_from_slice(&sample[t * feature_size..(t + 1) * feature_size]);
+            }
+        }
+
+        data.insert(i as u64, batch);
+    }
+
+    data
+}
+
+/*
+Label of every output sample for STRATIFIED folds
+
+With one output feature the label is 1 above 0.5 and 0 otherwise. With more output features
+the label is the feature with the largest sum over the trajectory, i.e. the one-hot class
+
+*/
+pub fn samples_to_labels<T: raybnn_float_trait>(samples: &[Vec<T>], output_size: u64) -> Vec<u64> {
+    let output_size = output_size as usize;
+
+    samples
+        .iter()
+        .map(|sample| {
+            let mut total = vec![T::zero(); output_size];
+            for (k, value) in sample.iter().enumerate() {
+                total[k % output_size] = total[k % output_size] + *value;
+            }
+
+            if output_size == 1 {
+                let traj_size = T::from_f64((sample.len().max(1)) as f64);
+                return ((total[0] / traj_size) > T::from_f64(0.5)) as u64;
+            }
+
+            let mut label = 0;
+            for k in 1..output_size {
+                if total[k] > total[label] {
+                    label = k;
+                }
+            }
+            label as u64
+        })
+        .collect()
+}
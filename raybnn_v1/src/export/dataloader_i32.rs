@@ -5,7 +5,10 @@ use std::io::Write;
 
 use std::fs;
 
-pub fn str_to_vec(instr: &str) -> arrayfire::Array<i32> {
+use crate::error::parse_value;
+use crate::error::Error;
+
+pub fn try_str_to_vec(instr: &str) -> Result<arrayfire::Array<i32>, Error> {
     let temp_dims = arrayfire::Dim4::new(&[1, 1, 1, 1]);
     let mut outarr = arrayfire::constant::<i32>(0, temp_dims);
 
@@ -18,7 +21,7 @@ pub fn str_to_vec(instr: &str) -> arrayfire::Array<i32> {
     if (ssize > 1) {
         let mut veci32: Vec<i32> = Vec::new();
         for i in 0u64..ssize {
-            let value: i32 = strvec[i as usize].parse::<i32>().unwrap();
+            let value: i32 = parse_value::<i32>(strvec[i as usize])?;
             veci32.push(value);
         }
 
@@ -26,21 +29,37 @@ pub fn str_to_vec(instr: &str) -> arrayfire::Array<i32> {
         outarr = arrayfire::Array::new(&veci32, new_dims);
     }
 
-    outarr
+    Ok(outarr)
 }
 
-pub fn file_to_matrix(filename: &str, dims: arrayfire::Dim4) -> arrayfire::Array<i32> {
+pub fn str_to_vec(instr: &str) -> arrayfire::Array<i32> {
+    try_str_to_vec(instr).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_file_to_matrix(
+    filename: &str,
+    dims: arrayfire::Dim4,
+) -> Result<arrayfire::Array<i32>, Error> {
     let mut outarr = arrayfire::constant::<i32>(0, dims);
     let row_num: i64 = dims[0] as i64;
 
-    let contents = fs::read_to_string(filename).expect("error");
+    let contents = fs::read_to_string(filename).map_err(|e| Error::io(filename, e))?;
     let mut lines = contents.split("\n");
     for i in 0i64..row_num {
-        let row = str_to_vec(lines.next().unwrap());
+        let line = lines.next().ok_or(Error::ShapeMismatch {
+            file: filename.to_string(),
+            expected: row_num as u64,
+            found: i as u64,
+        })?;
+        let row = try_str_to_vec(line).map_err(|e| e.at(filename, (i + 1) as u64))?;
         arrayfire::set_row(&mut outarr, &row, i);
     }
 
-    outarr
+    Ok(outarr)
+}
+
+pub fn file_to_matrix(filename: &str, dims: arrayfire::Dim4) -> arrayfire::Array<i32> {
+    try_file_to_matrix(filename, dims).unwrap_or_else(|e| panic!("{}", e))
 }
 
 pub fn vec_to_str(invec: &arrayfire::Array<i32>) -> String {
@@ -63,7 +82,7 @@ pub fn vec_cpu_to_str(invec: &Vec<i32>) -> String {
     s0
 }
 
-pub fn write_arr_to_csv(filename: &str, arr: &arrayfire::Array<i32>) {
+pub fn try_write_arr_to_csv(filename: &str, arr: &arrayfire::Array<i32>) -> Result<(), Error> {
     let mut wtr0: Vec<String> = Vec::new();
 
     let item_num = arr.dims()[0] as i64;
@@ -74,6 +93,12 @@ pub fn write_arr_to_csv(filename: &str, arr: &arrayfire::Array<i32>) {
         wtr0.push(s0);
     }
 
-    let mut file0 = File::create(filename).unwrap();
-    writeln!(file0, "{}", wtr0.join("\n"));
+    let mut file0 = File::create(filename).map_err(|e| Error::io(filename, e))?;
+    writeln!(file0, "{}", wtr0.join("\n")).map_err(|e| Error::io(filename, e))?;
+
+    Ok(())
+}
+
+pub fn write_arr_to_csv(filename: &str, arr: &arrayfire::Array<i32>) {
+    try_write_arr_to_csv(filename, arr).unwrap_or_else(|e| panic!("{}", e))
 }
@@ -5,39 +5,46 @@ use std::fs;
 use rayon::array;
 use rayon::prelude::*;
 
-pub fn find_model_paths(dir_path: &str) -> Vec<String> {
+use crate::error::parse_value;
+use crate::error::Error;
+
+fn try_find_paths(dir_path: &str, pattern: &str) -> Result<Vec<String>, Error> {
     let mut models: Vec<String> = Vec::new();
 
-    let paths = fs::read_dir(dir_path).unwrap();
+    let paths = fs::read_dir(dir_path).map_err(|e| Error::io(dir_path, e))?;
 
     for path in paths {
-        let pathstr = path.unwrap().path().to_str().unwrap().to_string();
+        let pathstr = path
+            .map_err(|e| Error::io(dir_path, e))?
+            .path()
+            .to_string_lossy()
+            .to_string();
 
-        if pathstr.contains("active_size") && pathstr.contains(".csv") {
+        if pathstr.contains(pattern) && pathstr.contains(".csv") {
             models.push(pathstr);
         }
     }
 
-    models
+    Ok(models)
 }
 
-pub fn find_cube_paths(dir_path: &str) -> Vec<String> {
-    let mut models: Vec<String> = Vec::new();
-
-    let paths = fs::read_dir(dir_path).unwrap();
+pub fn try_find_model_paths(dir_path: &str) -> Result<Vec<String>, Error> {
+    try_find_paths(dir_path, "active_size")
+}
 
-    for path in paths {
-        let pathstr = path.unwrap().path().to_str().unwrap().to_string();
+pub fn find_model_paths(dir_path: &str) -> Vec<String> {
+    try_find_model_paths(dir_path).unwrap_or_else(|e| panic!("{}", e))
+}
 
-        if pathstr.contains("cube") && pathstr.contains(".csv") {
-            models.push(pathstr);
-        }
-    }
+pub fn try_find_cube_paths(dir_path: &str) -> Result<Vec<String>, Error> {
+    try_find_paths(dir_path, "cube")
+}
 
-    models
+pub fn find_cube_paths(dir_path: &str) -> Vec<String> {
+    try_find_cube_paths(dir_path).unwrap_or_else(|e| panic!("{}", e))
 }
 
-pub fn extract_file_info(filepath: &str) -> Vec<u64> {
+pub fn try_extract_file_info(filepath: &str) -> Result<Vec<u64>, Error> {
     let targetstr = filepath.clone().replace(".csv", "");
 
     let strsplit: Vec<&str> = targetstr.split('_').collect();
@@ -55,7 +62,7 @@ pub fn extract_file_info(filepath: &str) -> Vec<u64> {
         if (parse_state == 1) {
             if (idx % 3) == 2 {
                 //println!("data {}",tmp);
-                let elem = tmp.clone().parse::<u64>().unwrap();
+                let elem = parse_value::<u64>(tmp).map_err(|e| e.at(filepath, 0))?;
                 outdata.push(elem);
             }
 
@@ -63,10 +70,14 @@ pub fn extract_file_info(filepath: &str) -> Vec<u64> {
         }
     }
 
-    outdata
+    Ok(outdata)
 }
 
-pub fn str_to_vec(instr: &str) -> arrayfire::Array<u64> {
+pub fn extract_file_info(filepath: &str) -> Vec<u64> {
+    try_extract_file_info(filepath).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_str_to_vec(instr: &str) -> Result<arrayfire::Array<u64>, Error> {
     let temp_dims = arrayfire::Dim4::new(&[1, 1, 1, 1]);
     let mut outarr = arrayfire::constant::<u64>(0, temp_dims);
 
@@ -79,7 +90,7 @@ pub fn str_to_vec(instr: &str) -> arrayfire::Array<u64> {
     if (ssize > 1) {
         let mut vecu64: Vec<u64> = Vec::new();
         for i in 0u64..ssize {
-            let value: u64 = strvec[i as usize].parse::<u64>().unwrap();
+            let value: u64 = parse_value::<u64>(strvec[i as usize])?;
             vecu64.push(value);
         }
 
@@ -87,21 +98,37 @@ pub fn str_to_vec(instr: &str) -> arrayfire::Array<u64> {
         outarr = arrayfire::Array::new(&vecu64, new_dims);
     }
 
-    outarr
+    Ok(outarr)
 }
 
-pub fn file_to_matrix(filename: &str, dims: arrayfire::Dim4) -> arrayfire::Array<u64> {
+pub fn str_to_vec(instr: &str) -> arrayfire::Array<u64> {
+    try_str_to_vec(instr).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_file_to_matrix(
+    filename: &str,
+    dims: arrayfire::Dim4,
+) -> Result<arrayfire::Array<u64>, Error> {
     let mut outarr = arrayfire::constant::<u64>(0, dims);
     let row_num: i64 = dims[0] as i64;
 
-    let contents = fs::read_to_string(filename).expect("error");
+    let contents = fs::read_to_string(filename).map_err(|e| Error::io(filename, e))?;
     let mut lines = contents.split("\n");
     for i in 0i64..row_num {
-        let row = str_to_vec(lines.next().unwrap());
+        let line = lines.next().ok_or(Error::ShapeMismatch {
+            file: filename.to_string(),
+            expected: row_num as u64,
+            found: i as u64,
+        })?;
+        let row = try_str_to_vec(line).map_err(|e| e.at(filename, (i + 1) as u64))?;
         arrayfire::set_row(&mut outarr, &row, i);
     }
 
-    outarr
+    Ok(outarr)
+}
+
+pub fn file_to_matrix(filename: &str, dims: arrayfire::Dim4) -> arrayfire::Array<u64> {
+    try_file_to_matrix(filename, dims).unwrap_or_else(|e| panic!("{}", e))
 }
 
 pub fn vec_to_str(invec: &arrayfire::Array<u64>) -> String {
@@ -124,7 +151,7 @@ pub fn vec_cpu_to_str(invec: &Vec<u64>) -> String {
     s0
 }
 
-pub fn str_to_vec_cpu(instr: &str) -> Vec<u64> {
+pub fn try_str_to_vec_cpu(instr: &str) -> Result<Vec<u64>, Error> {
     let mut newline = instr.replace("\n", "");
     newline = newline.replace(" ", "");
 
@@ -134,20 +161,32 @@ pub fn str_to_vec_cpu(instr: &str) -> Vec<u64> {
     let mut vecu64: Vec<u64> = Vec::new();
     if (ssize > 1) {
         for i in 0u64..ssize {
-            let value: u64 = strvec[i as usize].parse::<u64>().unwrap();
+            let value: u64 = parse_value::<u64>(strvec[i as usize])?;
             vecu64.push(value);
         }
     }
 
-    vecu64
+    Ok(vecu64)
 }
 
-pub fn file_to_vec_cpu(filename: &str) -> Vec<u64> {
-    let contents = fs::read_to_string(filename).expect("error");
+pub fn str_to_vec_cpu(instr: &str) -> Vec<u64> {
+    try_str_to_vec_cpu(instr).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_file_to_vec_cpu(filename: &str) -> Result<Vec<u64>, Error> {
+    let contents = fs::read_to_string(filename).map_err(|e| Error::io(filename, e))?;
+
+    let lines: Vec<&str> = contents.split('\n').collect();
 
-    contents
-        .par_split('\n')
-        .map(str_to_vec_cpu)
-        .flatten_iter()
-        .collect()
+    let rows: Vec<Vec<u64>> = lines
+        .par_iter()
+        .enumerate()
+        .map(|(i, line)| try_str_to_vec_cpu(line).map_err(|e| e.at(filename, (i + 1) as u64)))
+        .collect::<Result<Vec<Vec<u64>>, Error>>()?;
+
+    Ok(rows.into_iter().flatten().collect())
+}
+
+pub fn file_to_vec_cpu(filename: &str) -> Vec<u64> {
+    try_file_to_vec_cpu(filename).unwrap_or_else(|e| panic!("{}", e))
 }
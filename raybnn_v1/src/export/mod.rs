@@ -1,3 +1,9 @@
+pub mod dataloader;
+
+pub mod checkpoint;
+pub mod checkpoint_f32;
+pub mod checkpoint_f64;
+
 pub mod dataloader_f32;
 pub mod dataloader_f64;
 
@@ -7,9 +13,12 @@ pub mod dataloader_u64;
 pub mod round_f32;
 pub mod round_f64;
 
+pub mod rand;
 pub mod rand_f32;
 pub mod rand_f64;
 pub mod rand_u64;
+pub mod rng;
+pub mod trace;
 
 pub mod numpy_f32;
 pub mod numpy_f64;
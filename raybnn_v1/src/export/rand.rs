@@ -0,0 +1,12 @@
+extern crate arrayfire;
+
+use rand::Rng;
+
+use crate::export::rng::with_raybnn_rng;
+
+use crate::precision::float_trait::raybnn_float_trait;
+
+// Uniform random number in [0, 1) from the random number context (crate::export::rng)
+pub fn single_random_uniform<T: raybnn_float_trait>() -> T {
+    T::from_f64(with_raybnn_rng(|rng| rng.gen_range(0.0..1.0)))
+}
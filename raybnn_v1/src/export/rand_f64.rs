@@ -1,12 +1,7 @@
-extern crate arrayfire;
+// f64 instantiation of the generic crate::export::rand module
 
-pub fn single_random_uniform() -> f64 {
-    let single_rand_dims = arrayfire::Dim4::new(&[1, 1, 1, 1]);
-
-    let singlerand = arrayfire::randu::<f64>(single_rand_dims);
+// Functions whose precision only appears in the return type are pinned here
 
-    let mut singlerand_cpu: [f64; 1] = [0.0];
-    singlerand.host(&mut singlerand_cpu);
-
-    return singlerand_cpu[0];
+pub fn single_random_uniform() -> f64 {
+    crate::export::rand::single_random_uniform::<f64>()
 }
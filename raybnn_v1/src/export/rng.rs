@@ -0,0 +1,148 @@
+extern crate arrayfire;
+
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+
+/*
+Random number context of the crate
+
+The construction, ray tracing, physics, evolution and training code draws host random numbers
+(neuron selection, connection rewiring, evolution moves) from the installed raybnn_rng_type
+and device random numbers (cell positions, ray directions, weights, dropout) from the arrayfire
+random engine. Installing a context with seed_raybnn_rng seeds both, so identical seeds give
+identical networks and training curves on the CPU backend.
+
+Without an installed context the host random numbers are seeded from the operating system.
+
+The context and the arrayfire random engine are shared by the whole process. Seeded runs that
+may execute concurrently, such as tests, hold a scope_raybnn_rng guard for the whole run, so
+their random numbers do not interleave.
+
+Example
+seed_raybnn_rng(42);
+let arch_search = network_builder_type::new(input_size, output_size, "/tmp/").build();
+
+Example of a scoped run
+let _scope = scope_raybnn_rng(42);
+let arch_search = network_builder_type::new(input_size, output_size, "/tmp/").build();
+train_network(..., &mut arch_search, ...);
+
+*/
+
+pub struct raybnn_rng_type {
+    pub seed: u64,
+    pub host: StdRng,
+}
+
+static RAYBNN_RNG: Mutex<Option<raybnn_rng_type>> = Mutex::new(None);
+
+// Held by a raybnn_rng_scope_type, one seeded scope runs at a time
+static RAYBNN_RNG_SCOPE: Mutex<()> = Mutex::new(());
+
+// Exclusive seeded context, restores the previous context when dropped
+pub struct raybnn_rng_scope_type {
+    previous: Option<raybnn_rng_type>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Drop for raybnn_rng_scope_type {
+    fn drop(&mut self) {
+        take_raybnn_rng();
+        if let Some(previous) = self.previous.take() {
+            set_raybnn_rng(previous);
+        }
+    }
+}
+
+// Offset between the streams of a parallel loop
+const STREAM_STRIDE: u64 = 0x9E3779B97F4A7C15;
+
+pub fn create_raybnn_rng(seed: u64) -> raybnn_rng_type {
+    raybnn_rng_type {
+        seed: seed,
+        host: StdRng::seed_from_u64(seed),
+    }
+}
+
+// Installs rng as the context of the crate and seeds the arrayfire random engine with its seed
+pub fn set_raybnn_rng(rng: raybnn_rng_type) {
+    arrayfire::set_seed(rng.seed);
+
+    let mut guard = RAYBNN_RNG.lock().unwrap_or_else(|e| e.into_inner());
+    *guard = Some(rng);
+}
+
+pub fn seed_raybnn_rng(seed: u64) {
+    set_raybnn_rng(create_raybnn_rng(seed));
+}
+
+/*
+Installs a context seeded with seed until the returned guard is dropped. Waits until every
+other scope is dropped, so the host and arrayfire random numbers of the scope are not drawn by
+other threads. Calls of seed_raybnn_rng inside the scope, for example by the builder, reseed
+the context of the scope
+
+Inputs
+seed:                Seed of the host generator and the arrayfire random engine
+
+Outputs:
+scope:               Guard of the context
+
+*/
+pub fn scope_raybnn_rng(seed: u64) -> raybnn_rng_scope_type {
+    let lock = RAYBNN_RNG_SCOPE.lock().unwrap_or_else(|e| e.into_inner());
+
+    let previous = take_raybnn_rng();
+    seed_raybnn_rng(seed);
+
+    raybnn_rng_scope_type {
+        previous: previous,
+        _lock: lock,
+    }
+}
+
+// Removes the installed context. Later host random numbers are seeded from the operating system
+pub fn take_raybnn_rng() -> Option<raybnn_rng_type> {
+    let mut guard = RAYBNN_RNG.lock().unwrap_or_else(|e| e.into_inner());
+    guard.take()
+}
+
+// Runs f with the host generator of the installed context
+pub fn with_raybnn_rng<R>(f: impl FnOnce(&mut StdRng) -> R) -> R {
+    let mut guard = RAYBNN_RNG.lock().unwrap_or_else(|e| e.into_inner());
+
+    match guard.as_mut() {
+        Some(rng) => f(&mut rng.host),
+        None => f(&mut StdRng::from_entropy()),
+    }
+}
+
+// Independent host generator drawn from the context, for loops that draw many numbers
+pub fn fork_raybnn_rng() -> StdRng {
+    StdRng::seed_from_u64(with_raybnn_rng(|rng| rng.gen::<u64>()))
+}
+
+/*
+Host generator of one task of a parallel loop. Draw base once before the loop with
+with_raybnn_rng, then every task gets the same numbers on any number of threads
+
+Inputs
+base:                Seed of the loop
+stream:              Index of the task
+
+Outputs:
+rng:                 Host generator of the task
+
+*/
+pub fn stream_raybnn_rng(base: u64, stream: u64) -> StdRng {
+    StdRng::seed_from_u64(stream_raybnn_seed(base, stream))
+}
+
+// Seed of stream of base, for arrayfire random engines of a loop
+pub fn stream_raybnn_seed(base: u64, stream: u64) -> u64 {
+    base ^ stream.wrapping_mul(STREAM_STRIDE)
+}
@@ -0,0 +1,48 @@
+use std::time::Instant;
+
+/*
+Structured logging of the crate
+
+Diagnostic output goes through the tracing facade instead of stdout. Nothing is printed until
+the application installs a subscriber, which can filter by module and level and time every phase.
+
+Phase spans
+cell_migration:      physics::dynamic::run
+ray_tracing:         physics::raytrace::RT1_random_rays, RT2_directly_connected and
+                     RT3_distance_limited_directly_connected
+network_update:      physics::update::add_neuron_to_existing, add_neuron_to_existing2,
+                     add_neuron_to_existing3 and reduce_network_size,
+                     optimal::genetic::crossover_network
+training_epoch:      interface::autotrain::train_network_with_callbacks
+evolution_step:      optimal::evolution::evolve_network
+architecture_search: interface::automatic::architecture_search
+
+Every phase ends with a "phase finished" event inside its span with the field elapsed_ms
+
+Example
+tracing_subscriber::fmt()
+    .with_env_filter("raybnn=info")
+    .init();
+
+*/
+
+// Entered phase span. Logs elapsed_ms and leaves the span when dropped
+pub struct phase_timer_type {
+    start: Instant,
+    _span: tracing::span::EnteredSpan,
+}
+
+pub fn enter_phase(span: tracing::Span) -> phase_timer_type {
+    phase_timer_type {
+        start: Instant::now(),
+        _span: span.entered(),
+    }
+}
+
+impl Drop for phase_timer_type {
+    fn drop(&mut self) {
+        let elapsed_ms = self.start.elapsed().as_secs_f64() * 1000.0;
+
+        tracing::info!(elapsed_ms, "phase finished");
+    }
+}
@@ -1,9 +1,11 @@
+pub mod large_sparse;
 pub mod large_sparse_f32;
 pub mod large_sparse_f64;
 
 pub mod large_sparse_i32;
 pub mod large_sparse_u64;
 
+pub mod adjacency;
 pub mod adjacency_f32;
 pub mod adjacency_f64;
 
@@ -11,5 +13,6 @@ pub mod tree_i32;
 pub mod tree_u32;
 pub mod tree_u64;
 
+pub mod path;
 pub mod path_f32;
 pub mod path_f64;
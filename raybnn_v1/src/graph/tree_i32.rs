@@ -119,13 +119,14 @@ pub fn check_connected(
 
         if detect_out_idx.dims()[0] < out_num {
             connected = false;
-            println!("Not Connected");
-        } else {
-            println!("Connected");
         }
 
-        arrayfire::print_gen("input_idx".to_string(), &input_idx, Some(6));
-        arrayfire::print_gen("con_out_idx".to_string(), &con_out_idx, Some(6));
+        tracing::debug!(
+            input = i,
+            connected_out_num = con_out_idx.dims()[0],
+            out_num,
+            "checked connection to the output neurons"
+        );
     }
 
     connected
@@ -0,0 +1,1624 @@
+extern crate arrayfire;
+
+use crate::precision::float_trait::raybnn_float_trait;
+
+use nohash_hasher;
+use std::collections::HashMap;
+
+use num::Float;
+
+use crate::neural::network::network_metadata_type;
+use crate::neural::network::neural_network_type;
+
+use crate::export::dataloader_u64::find_cube_paths;
+
+use crate::export::dataloader::extract_file_info2;
+
+use crate::export::dataloader::load_network_structure;
+
+use crate::graph::large_sparse_i32::CSR_to_COO;
+
+use crate::interface::autotransfer::shuffle_weights;
+
+use crate::interface::automatic::arch_search_type;
+use crate::interface::automatic::network_info_seed_type;
+use crate::interface::automatic::set_network_seed;
+
+use crate::export::rng::stream_raybnn_seed;
+
+use crate::export::trace::enter_phase;
+use crate::export::trace::phase_timer_type;
+
+use crate::export::checkpoint::train_state_type;
+use crate::export::checkpoint::try_load_train_checkpoint;
+use crate::export::checkpoint::try_save_train_checkpoint;
+
+use crate::error::Error;
+
+use crate::graph::path::find_path_backward_group2;
+
+use crate::neural::network::state_space_forward_batch_norm;
+
+use crate::neural::network::state_space_backward_group2_norm;
+
+use crate::neural::normalization::create_norm_state;
+use crate::neural::normalization::norm_options_type;
+use crate::neural::normalization::norm_state_type;
+
+use crate::optimal::optimizer::create_network_param_groups;
+use crate::optimal::optimizer::create_optimizer;
+use crate::optimal::optimizer::optimizer_options_type;
+use crate::optimal::optimizer::optimizer_trait;
+
+use crate::optimal::regularization::add_regularization_grad;
+use crate::optimal::regularization::apply_drop_mask;
+use crate::optimal::regularization::apply_max_norm;
+use crate::optimal::regularization::check_regularization_options;
+use crate::optimal::regularization::create_drop_mask;
+use crate::optimal::regularization::regularization_options_type;
+use crate::optimal::regularization::regularization_penalty;
+
+use crate::optimal::scheduler::create_lr_scheduler;
+use crate::optimal::scheduler::lr_schedule_type;
+use crate::optimal::scheduler::lr_scheduler_trait;
+
+use crate::optimal::control::statespace_BTLS;
+
+use serde::{Deserialize, Serialize};
+
+use crate::interface::autotest::validate_network;
+
+use crate::interface::callback::callback_action_type;
+use crate::interface::callback::create_train_progress;
+use crate::interface::callback::dispatch_callbacks;
+use crate::interface::callback::print_callback_type;
+use crate::interface::callback::train_callback_trait;
+
+use crate::physics::update::add_neuron_to_existing2;
+
+use crate::physics::update::reduce_network_size;
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum stop_strategy_type {
+    STOP_AT_EPOCH,
+    STOP_AT_TRAIN_LOSS,
+    CROSSVAL_STOPPING,
+    NONE,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum lr_strategy_type {
+    COSINE_ANNEALING,
+    SHUFFLE_CONNECTIONS,
+    NONE,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum lr_strategy2_type {
+    BTLS_ALPHA,
+    MAX_ALPHA,
+}
+
+#[derive(PartialEq, Eq, Serialize, Deserialize)]
+pub enum loss_status_type {
+    LOSS_OVERFLOW,
+    LOSS_PLATEAU,
+    NO_CONVERGENCE,
+    PREDETERMINED_STOP,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: raybnn_float_trait",
+    deserialize = "T: raybnn_float_trait"
+))]
+pub struct train_network_options_type<T: raybnn_float_trait> {
+    pub stop_strategy: stop_strategy_type,
+    pub lr_strategy: lr_strategy_type,
+    pub lr_strategy2: lr_strategy2_type,
+
+    pub max_epoch: u64,
+    pub stop_epoch: u64,
+    pub stop_train_loss: T,
+
+    pub exit_counter_threshold: u64,
+    pub shuffle_counter_threshold: u64,
+
+    pub optimizer: optimizer_options_type<T>,
+    pub lr_schedule: lr_schedule_type<T>,
+    pub regularization: regularization_options_type<T>,
+    pub normalization: norm_options_type<T>,
+}
+
+const LR_BUFFER: usize = 20;
+const LARGE_POS_NUM_u64: u64 = 1000000000;
+
+pub fn loss_wrapper<T: raybnn_float_trait>(
+    netdata: &network_metadata_type<T>,
+    X: &arrayfire::Array<T>,
+
+    WRowIdxCSR: &arrayfire::Array<i32>,
+    WColIdx: &arrayfire::Array<i32>,
+
+    Wseqs: &[arrayfire::Seq<i32>; 1],
+    Hseqs: &[arrayfire::Seq<i32>; 1],
+    Aseqs: &[arrayfire::Seq<i32>; 1],
+    Bseqs: &[arrayfire::Seq<i32>; 1],
+    Cseqs: &[arrayfire::Seq<i32>; 1],
+    Dseqs: &[arrayfire::Seq<i32>; 1],
+    Eseqs: &[arrayfire::Seq<i32>; 1],
+    network_params: &arrayfire::Array<T>,
+    norm: &mut norm_state_type<T>,
+
+    idxsel: &arrayfire::Array<i32>,
+    Y: &arrayfire::Array<T>,
+    eval_metric: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> T + Copy,
+
+    Z: &mut arrayfire::Array<T>,
+    Q: &mut arrayfire::Array<T>,
+    loss_output: &mut T,
+) {
+    state_space_forward_batch_norm(
+        netdata,
+        X,
+        WRowIdxCSR,
+        WColIdx,
+        Wseqs,
+        Hseqs,
+        Aseqs,
+        Bseqs,
+        Cseqs,
+        Dseqs,
+        Eseqs,
+        network_params,
+        norm,
+        Z,
+        Q,
+    );
+
+    let batch_size: u64 = netdata.batch_size.clone();
+    let proc_num: u64 = netdata.proc_num.clone();
+    let Qslices: u64 = Q.dims()[2];
+
+    //Get Yhat
+    let mut idxrs = arrayfire::Indexer::default();
+    let seq1 = arrayfire::Seq::new(0.0f32, (batch_size - 1) as f32, 1.0);
+    let seq2 = arrayfire::Seq::new((proc_num - 1) as f32, (Qslices - 1) as f32, 1.0);
+    idxrs.set_index(idxsel, 0, None);
+    idxrs.set_index(&seq1, 1, None);
+    idxrs.set_index(&seq2, 2, None);
+    let Yhat = arrayfire::index_gen(&Q, idxrs);
+    *loss_output = eval_metric(&Yhat, Y);
+}
+
+/*
+Turns the gradient of the loss into the direction of the next training step
+
+The gradient of the dropped weights is zeroed, the gradient of the regularization penalty is
+added and the negative gradient is transformed by the optimizer, e.g. sign(c) for LION
+
+Inputs
+network_params:      Parameters of the network, without the dropout mask
+drop_mask:           Dropout and dropconnect mask used by the forward and backward pass
+regularization:      Regularization settings
+Wseqs:               Indexes of the weights in network_params
+Aseqs, Bseqs, Cseqs, Dseqs, Eseqs: Indexes of the neuron parameters in network_params
+optimizer:           Optimizer of the training, its state is updated with the gradient
+
+Outputs:
+grad:                Gradient of the loss, replaced by the direction of the next step
+
+*/
+
+fn step_direction<T: raybnn_float_trait>(
+    network_params: &arrayfire::Array<T>,
+    drop_mask: &Option<arrayfire::Array<T>>,
+    regularization: &regularization_options_type<T>,
+
+    Wseqs: &[arrayfire::Seq<i32>; 1],
+    Aseqs: &[arrayfire::Seq<i32>; 1],
+    Bseqs: &[arrayfire::Seq<i32>; 1],
+    Cseqs: &[arrayfire::Seq<i32>; 1],
+    Dseqs: &[arrayfire::Seq<i32>; 1],
+    Eseqs: &[arrayfire::Seq<i32>; 1],
+
+    optimizer: &mut Box<dyn optimizer_trait<T>>,
+    grad: &mut arrayfire::Array<T>,
+) {
+    if let Some(mask) = drop_mask {
+        apply_drop_mask(mask, Wseqs, grad);
+    }
+
+    add_regularization_grad(
+        network_params,
+        regularization,
+        Wseqs,
+        Aseqs,
+        Bseqs,
+        Cseqs,
+        Dseqs,
+        Eseqs,
+        grad,
+    );
+
+    *grad = -grad.clone();
+
+    optimizer.direction(network_params, grad);
+}
+
+// Train one neural network using ADAM and BTLS
+
+//           Input arguments
+// traindata_X: Training input array to the neural network
+// 				traindata_X.dims()[0]  input feature size
+// 				traindata_X.dims()[1]  batch size
+// 				traindata_X.dims()[2]  sequence/traj size
+// traindata_Y: Training ground truth output array
+// 				traindata_Y.dims()[0]  output feature size
+// 				traindata_Y.dims()[1]  batch size
+// 				traindata_Y.dims()[2]  sequence/traj size
+// eval_metric: Function with inputs X and Yhat that gives evaluation metrics
+// eval_metric_grad: Gradient of eval_metric
+
+//          Output argumenets
+// stop_strategy:  How to train the network: Early stopping, cosine annealing, none
+// alpha_max_vec: Vector of maximum learning rates for each epoch
+// loss_vec: Training loss of the neural network
+// neural_network:  Entire neural network archtecture
+// loss_status: Loss convergence based on training loss
+pub fn train_network<T: raybnn_float_trait>(
+    traindata_X: &nohash_hasher::IntMap<u64, Vec<T>>,
+    traindata_Y: &nohash_hasher::IntMap<u64, Vec<T>>,
+
+    validationdata_X: &nohash_hasher::IntMap<u64, Vec<T>>,
+    validationdata_Y: &nohash_hasher::IntMap<u64, Vec<T>>,
+
+    eval_metric: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> T + Copy,
+    eval_metric_grad: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> arrayfire::Array<T> + Copy,
+
+    train_network_options: train_network_options_type<T>,
+
+    alpha_max_vec: &mut Vec<T>,
+    loss_vec: &mut Vec<T>,
+    crossval_vec: &mut Vec<T>,
+    arch_search: &mut arch_search_type<T>,
+    loss_status: &mut loss_status_type,
+) {
+    train_network_checkpoint(
+        traindata_X,
+        traindata_Y,
+        validationdata_X,
+        validationdata_Y,
+        eval_metric,
+        eval_metric_grad,
+        train_network_options,
+        alpha_max_vec,
+        loss_vec,
+        crossval_vec,
+        arch_search,
+        loss_status,
+        "",
+        0,
+        None,
+    );
+}
+
+// Same as train_network, but saves the trainer state every checkpoint_interval epochs
+// and can continue from a saved trainer state
+
+//           Extra input arguments
+// checkpoint_file: Binary checkpoint written with save_train_checkpoint
+// checkpoint_interval: Number of epochs between checkpoints. 0 disables checkpoints
+// resume_state: Trainer state to continue from. None starts a new training run
+pub fn train_network_checkpoint<T: raybnn_float_trait>(
+    traindata_X: &nohash_hasher::IntMap<u64, Vec<T>>,
+    traindata_Y: &nohash_hasher::IntMap<u64, Vec<T>>,
+
+    validationdata_X: &nohash_hasher::IntMap<u64, Vec<T>>,
+    validationdata_Y: &nohash_hasher::IntMap<u64, Vec<T>>,
+
+    eval_metric: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> T + Copy,
+    eval_metric_grad: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> arrayfire::Array<T> + Copy,
+
+    train_network_options: train_network_options_type<T>,
+
+    alpha_max_vec: &mut Vec<T>,
+    loss_vec: &mut Vec<T>,
+    crossval_vec: &mut Vec<T>,
+    arch_search: &mut arch_search_type<T>,
+    loss_status: &mut loss_status_type,
+
+    checkpoint_file: &str,
+    checkpoint_interval: u64,
+    resume_state: Option<train_state_type<T>>,
+) {
+    let mut print_callback = print_callback_type {};
+
+    train_network_with_callbacks(
+        traindata_X,
+        traindata_Y,
+        validationdata_X,
+        validationdata_Y,
+        eval_metric,
+        eval_metric_grad,
+        train_network_options,
+        alpha_max_vec,
+        loss_vec,
+        crossval_vec,
+        arch_search,
+        loss_status,
+        checkpoint_file,
+        checkpoint_interval,
+        resume_state,
+        &mut [&mut print_callback],
+    );
+}
+
+// Same as train_network_checkpoint, with the training progress reported to callbacks
+// (crate::interface::callback) instead of printed. Returns the first error of any callback
+
+//           Extra input arguments
+// callbacks: Called at the start and end of every epoch, after every batch, after every
+//            validation and after every checkpoint. Any callback can stop training
+pub fn try_train_network_with_callbacks<T: raybnn_float_trait>(
+    traindata_X: &nohash_hasher::IntMap<u64, Vec<T>>,
+    traindata_Y: &nohash_hasher::IntMap<u64, Vec<T>>,
+
+    validationdata_X: &nohash_hasher::IntMap<u64, Vec<T>>,
+    validationdata_Y: &nohash_hasher::IntMap<u64, Vec<T>>,
+
+    eval_metric: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> T + Copy,
+    eval_metric_grad: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> arrayfire::Array<T> + Copy,
+
+    train_network_options: train_network_options_type<T>,
+
+    alpha_max_vec: &mut Vec<T>,
+    loss_vec: &mut Vec<T>,
+    crossval_vec: &mut Vec<T>,
+    arch_search: &mut arch_search_type<T>,
+    loss_status: &mut loss_status_type,
+
+    checkpoint_file: &str,
+    checkpoint_interval: u64,
+    resume_state: Option<train_state_type<T>>,
+
+    callbacks: &mut [&mut dyn train_callback_trait<T>],
+) -> Result<(), Error> {
+    let result = train_epochs(
+        traindata_X,
+        traindata_Y,
+        validationdata_X,
+        validationdata_Y,
+        eval_metric,
+        eval_metric_grad,
+        train_network_options,
+        alpha_max_vec,
+        loss_vec,
+        crossval_vec,
+        arch_search,
+        loss_status,
+        checkpoint_file,
+        checkpoint_interval,
+        resume_state,
+        callbacks,
+    );
+
+    //Batch norm of the returned network uses the running statistics
+    (*arch_search).neural_network.norm.training = false;
+
+    result
+}
+
+fn train_epochs<T: raybnn_float_trait>(
+    traindata_X: &nohash_hasher::IntMap<u64, Vec<T>>,
+    traindata_Y: &nohash_hasher::IntMap<u64, Vec<T>>,
+
+    validationdata_X: &nohash_hasher::IntMap<u64, Vec<T>>,
+    validationdata_Y: &nohash_hasher::IntMap<u64, Vec<T>>,
+
+    eval_metric: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> T + Copy,
+    eval_metric_grad: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> arrayfire::Array<T> + Copy,
+
+    train_network_options: train_network_options_type<T>,
+
+    alpha_max_vec: &mut Vec<T>,
+    loss_vec: &mut Vec<T>,
+    crossval_vec: &mut Vec<T>,
+    arch_search: &mut arch_search_type<T>,
+    loss_status: &mut loss_status_type,
+
+    checkpoint_file: &str,
+    checkpoint_interval: u64,
+    resume_state: Option<train_state_type<T>>,
+
+    callbacks: &mut [&mut dyn train_callback_trait<T>],
+) -> Result<(), Error> {
+    let LARGE_POS_NUM = T::from_f64(1.0e9);
+    let LR_MAX = T::one();
+    *loss_status = loss_status_type::NO_CONVERGENCE;
+
+    let stop_strategy = train_network_options.stop_strategy;
+    let lr_strategy = train_network_options.lr_strategy;
+    let lr_strategy2 = train_network_options.lr_strategy2;
+    let regularization = train_network_options.regularization;
+    check_regularization_options(&regularization)?;
+
+    //The normalization statistics are kept when the normalization kind does not change
+    if (*arch_search).neural_network.norm.options.kind != train_network_options.normalization.kind {
+        (*arch_search).neural_network.norm = create_norm_state(train_network_options.normalization);
+    }
+    (*arch_search).neural_network.norm.options = train_network_options.normalization;
+    (*arch_search).neural_network.norm.training = true;
+
+    let max_epoch = train_network_options.max_epoch;
+
+    let stop_epoch = train_network_options.stop_epoch;
+    let stop_train_loss = train_network_options.stop_train_loss;
+
+    let exit_counter_threshold = train_network_options.exit_counter_threshold;
+    let shuffle_counter_threshold = train_network_options.shuffle_counter_threshold;
+
+    let neuron_size: u64 = (*arch_search).neural_network.netdata.neuron_size.clone();
+    let input_size: u64 = (*arch_search).neural_network.netdata.input_size.clone();
+    let output_size: u64 = (*arch_search).neural_network.netdata.output_size.clone();
+    let proc_num: u64 = (*arch_search).neural_network.netdata.proc_num.clone();
+    let mut active_size: u64 = (*arch_search).neural_network.netdata.active_size.clone();
+    let space_dims: u64 = (*arch_search).neural_network.netdata.space_dims.clone();
+    let step_num: u64 = (*arch_search).neural_network.netdata.step_num.clone();
+
+    let del_unused_neuron: bool = (*arch_search)
+        .neural_network
+        .netdata
+        .del_unused_neuron
+        .clone();
+
+    let time_step: T = (*arch_search).neural_network.netdata.time_step.clone();
+    let nratio: T = (*arch_search).neural_network.netdata.nratio.clone();
+    let neuron_std: T = (*arch_search).neural_network.netdata.neuron_std.clone();
+    let sphere_rad: T = (*arch_search).neural_network.netdata.sphere_rad.clone();
+    let neuron_rad: T = (*arch_search).neural_network.netdata.neuron_rad.clone();
+    let con_rad: T = (*arch_search).neural_network.netdata.con_rad.clone();
+    let center_const: T = (*arch_search).neural_network.netdata.center_const.clone();
+    let spring_const: T = (*arch_search).neural_network.netdata.spring_const.clone();
+    let repel_const: T = (*arch_search).neural_network.netdata.repel_const.clone();
+
+    let batch_size: u64 = (*arch_search).neural_network.netdata.batch_size.clone();
+
+    let traj_size: u64 = (traindata_X[&0].len() as u64) / (input_size * batch_size);
+
+    let traj_steps = traj_size + proc_num - 1;
+
+    let Z_dims = arrayfire::Dim4::new(&[neuron_size, batch_size, traj_steps, 1]);
+    let mut Z = arrayfire::constant::<T>(T::zero(), Z_dims);
+    let mut Q = arrayfire::constant::<T>(T::zero(), Z_dims);
+
+    let mut alpha: T = T::from_f64(0.0001);
+
+    active_size = (*arch_search).neural_network.neuron_idx.dims()[0];
+    let idxsel = arrayfire::rows(
+        &((*arch_search).neural_network.neuron_idx),
+        (active_size - output_size) as i64,
+        (active_size - 1) as i64,
+    );
+    let Qslices: u64 = Q.dims()[2];
+
+    let mut WRowIdxCOO = CSR_to_COO(&((*arch_search).neural_network.WRowIdxCSR));
+
+    let mut total_param_size = (*arch_search).neural_network.network_params.dims()[0];
+    let mut mt_dims = arrayfire::Dim4::new(&[total_param_size, 1, 1, 1]);
+    let mut grad = arrayfire::constant::<T>(T::zero(), mt_dims);
+
+    let mut idxsel_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+    let mut valsel_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+
+    let mut cvec_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+    let mut dXsel_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+
+    let mut nrows_out: nohash_hasher::IntMap<i64, u64> = nohash_hasher::IntMap::default();
+    let mut sparseval_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+    let mut sparsecol_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+    let mut sparserow_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+
+    let mut Wseqs = [arrayfire::Seq::default()];
+    let mut Hseqs = [arrayfire::Seq::default()];
+    let mut Aseqs = [arrayfire::Seq::default()];
+    let mut Bseqs = [arrayfire::Seq::default()];
+    let mut Cseqs = [arrayfire::Seq::default()];
+    let mut Dseqs = [arrayfire::Seq::default()];
+    let mut Eseqs = [arrayfire::Seq::default()];
+
+    let mut Hidxsel_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+    let mut Aidxsel_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+    let mut Bidxsel_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+    let mut Cidxsel_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+    let mut Didxsel_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+    let mut Eidxsel_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+    let mut combidxsel_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+
+    let mut dAseqs_out: nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]> =
+        nohash_hasher::IntMap::default();
+    let mut dBseqs_out: nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]> =
+        nohash_hasher::IntMap::default();
+    let mut dCseqs_out: nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]> =
+        nohash_hasher::IntMap::default();
+    let mut dDseqs_out: nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]> =
+        nohash_hasher::IntMap::default();
+    let mut dEseqs_out: nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]> =
+        nohash_hasher::IntMap::default();
+
+    find_path_backward_group2(
+        &((*arch_search).neural_network.netdata),
+        traj_steps,
+        traj_size,
+        &WRowIdxCOO,
+        &((*arch_search).neural_network.WColIdx),
+        &((*arch_search).neural_network.neuron_idx),
+        (*arch_search).neural_network.WColIdx.dims()[0],
+        neuron_size,
+        neuron_size,
+        neuron_size,
+        neuron_size,
+        neuron_size,
+        neuron_size,
+        &mut idxsel_out,
+        &mut valsel_out,
+        &mut cvec_out,
+        &mut dXsel_out,
+        &mut nrows_out,
+        &mut sparseval_out,
+        &mut sparserow_out,
+        &mut sparsecol_out,
+        &mut Hidxsel_out,
+        &mut Aidxsel_out,
+        &mut Bidxsel_out,
+        &mut Cidxsel_out,
+        &mut Didxsel_out,
+        &mut Eidxsel_out,
+        &mut combidxsel_out,
+        &mut dAseqs_out,
+        &mut dBseqs_out,
+        &mut dCseqs_out,
+        &mut dDseqs_out,
+        &mut dEseqs_out,
+        &mut Wseqs,
+        &mut Hseqs,
+        &mut Aseqs,
+        &mut Bseqs,
+        &mut Cseqs,
+        &mut Dseqs,
+        &mut Eseqs,
+    );
+
+    let mut optimizer = create_optimizer(
+        &train_network_options.optimizer,
+        create_network_param_groups(
+            total_param_size,
+            &train_network_options.optimizer,
+            &Hseqs,
+            &Aseqs,
+            &Bseqs,
+            &Cseqs,
+            &Dseqs,
+            &Eseqs,
+        ),
+    );
+
+    //COSINE_ANNEALING is a schedule on top of lr_schedule
+    let mut lr_scheduler = match lr_strategy {
+        lr_strategy_type::COSINE_ANNEALING => {
+            create_lr_scheduler(&lr_schedule_type::COMPOSE(vec![
+                train_network_options.lr_schedule.clone(),
+                lr_schedule_type::COSINE_ANNEALING {
+                    period_epoch: shuffle_counter_threshold,
+                },
+            ]))
+        }
+        _ => create_lr_scheduler(&train_network_options.lr_schedule),
+    };
+
+    let temp_dims = arrayfire::Dim4::new(&[1, 1, 1, 1]);
+
+    let train_X_dims = arrayfire::Dim4::new(&[input_size, batch_size, traj_size, 1]);
+    let mut X = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let Y_dims = arrayfire::Dim4::new(&[output_size, batch_size, traj_size, 1]);
+    let mut Y = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut batch_idx = 0;
+
+    // epoch_num = number of batches
+    let epoch_num = traindata_X.len() as u64;
+
+    let mut loss_val = LARGE_POS_NUM;
+    let mut first_loss = LARGE_POS_NUM;
+
+    let mut global_alpha_max = LR_MAX;
+
+    let mut alpha_history_vec: Vec<T> = Vec::new();
+    let mut idx_history_vec: Vec<u64> = Vec::new();
+
+    let mut alpha_idx = 0;
+    let mut cur_alpha_max = LR_MAX;
+
+    let mut mean_loss = LARGE_POS_NUM;
+    let mut min_loss = LARGE_POS_NUM;
+    let mut loss_counter = 0;
+
+    let mut eval_metric_out = Vec::new();
+    let mut Yhat_out = nohash_hasher::IntMap::default();
+
+    let mut avgelem = LARGE_POS_NUM;
+    let mut crossval_mean_loss = LARGE_POS_NUM;
+
+    let mut start_epoch: u64 = 0;
+
+    //Seed of the random numbers of the training loop, saved in the trainer state
+    let mut train_seed = arrayfire::get_seed();
+
+    match resume_state {
+        Some(train_state) => {
+            if train_state.optimizer_kind != optimizer.kind() {
+                return Err(Error::InvalidCheckpoint {
+                    file: checkpoint_file.to_string(),
+                    reason: "optimizer kind does not match train_network_options".to_string(),
+                });
+            }
+
+            start_epoch = train_state.epoch;
+            train_seed = train_state.seed;
+
+            alpha = train_state.alpha;
+            loss_val = train_state.loss_val;
+            first_loss = train_state.first_loss;
+            mean_loss = train_state.mean_loss;
+            min_loss = train_state.min_loss;
+            loss_counter = train_state.loss_counter;
+            global_alpha_max = train_state.global_alpha_max;
+            cur_alpha_max = train_state.cur_alpha_max;
+            crossval_mean_loss = train_state.crossval_mean_loss;
+
+            optimizer.set_state(train_state.optimizer_state)?;
+            if train_state.scheduler_state.len() > 0 {
+                lr_scheduler.set_state(&train_state.scheduler_state)?;
+            }
+            grad = train_state.grad;
+
+            *alpha_max_vec = train_state.alpha_max_vec;
+            *loss_vec = train_state.loss_vec;
+            *crossval_vec = train_state.crossval_vec;
+            alpha_history_vec = train_state.alpha_history_vec;
+            idx_history_vec = train_state.idx_history_vec;
+        }
+        None => {
+            X = arrayfire::Array::new(&traindata_X[&batch_idx], train_X_dims);
+
+            Y = arrayfire::Array::new(&traindata_Y[&batch_idx], Y_dims);
+
+            //Dropout and dropconnect of the gradient of the first step
+            let drop_mask = create_drop_mask(
+                &regularization,
+                &((*arch_search).neural_network.WColIdx),
+                stream_raybnn_seed(train_seed, start_epoch),
+            );
+            let mut drop_params = (*arch_search).neural_network.network_params.clone();
+            if let Some(mask) = &drop_mask {
+                apply_drop_mask(mask, &Wseqs, &mut drop_params);
+            }
+
+            loss_wrapper(
+                &((*arch_search).neural_network.netdata),
+                &X,
+                &((*arch_search).neural_network.WRowIdxCSR),
+                &((*arch_search).neural_network.WColIdx),
+                &Wseqs,
+                &Hseqs,
+                &Aseqs,
+                &Bseqs,
+                &Cseqs,
+                &Dseqs,
+                &Eseqs,
+                &drop_params,
+                &mut ((*arch_search).neural_network.norm),
+                &idxsel,
+                &Y,
+                eval_metric,
+                &mut Z,
+                &mut Q,
+                &mut loss_val,
+            );
+            //The reported loss includes the regularization penalty
+            loss_val = loss_val
+                + regularization_penalty(
+                    &((*arch_search).neural_network.network_params),
+                    &regularization,
+                    &Wseqs,
+                    &Aseqs,
+                    &Bseqs,
+                    &Cseqs,
+                    &Dseqs,
+                    &Eseqs,
+                );
+
+            first_loss = loss_val.clone();
+
+            state_space_backward_group2_norm(
+                &((*arch_search).neural_network.netdata),
+                &X,
+                &((*arch_search).neural_network.WRowIdxCSR),
+                &((*arch_search).neural_network.WColIdx),
+                &Wseqs,
+                &Hseqs,
+                &Aseqs,
+                &Bseqs,
+                &Cseqs,
+                &Dseqs,
+                &Eseqs,
+                &drop_params,
+                &Z,
+                &Q,
+                &Y,
+                eval_metric_grad,
+                &((*arch_search).neural_network.neuron_idx),
+                &idxsel_out,
+                &valsel_out,
+                &cvec_out,
+                &dXsel_out,
+                &nrows_out,
+                &sparseval_out,
+                &sparserow_out,
+                &sparsecol_out,
+                &Hidxsel_out,
+                &Aidxsel_out,
+                &Bidxsel_out,
+                &Cidxsel_out,
+                &Didxsel_out,
+                &Eidxsel_out,
+                &combidxsel_out,
+                &dAseqs_out,
+                &dBseqs_out,
+                &dCseqs_out,
+                &dDseqs_out,
+                &dEseqs_out,
+                &((*arch_search).neural_network.norm),
+                &mut grad,
+            );
+
+            step_direction(
+                &((*arch_search).neural_network.network_params),
+                &drop_mask,
+                &regularization,
+                &Wseqs,
+                &Aseqs,
+                &Bseqs,
+                &Cseqs,
+                &Dseqs,
+                &Eseqs,
+                &mut optimizer,
+                &mut grad,
+            );
+
+            *loss_vec = Vec::new();
+            *crossval_vec = Vec::new();
+
+            if alpha_max_vec.len() < LR_BUFFER {
+                for k in 0..LR_BUFFER {
+                    alpha_max_vec.push(LR_MAX);
+                }
+            } else {
+                let mut minelem = alpha_max_vec.iter().fold(T::infinity(), |a, &b| a.min(b));
+
+                if minelem.is_infinite() || minelem.is_nan() {
+                    minelem = LR_MAX;
+                }
+
+                global_alpha_max = minelem;
+            }
+
+            cur_alpha_max = alpha_max_vec[0];
+
+            mean_loss = first_loss.clone();
+            min_loss = first_loss.clone();
+
+            //TRAINING REGIMENT
+            match stop_strategy {
+                stop_strategy_type::STOP_AT_EPOCH => (),
+                stop_strategy_type::STOP_AT_TRAIN_LOSS => (),
+                stop_strategy_type::CROSSVAL_STOPPING => {
+                    eval_metric_out = Vec::new();
+                    Yhat_out = nohash_hasher::IntMap::default();
+
+                    validate_network(
+                        validationdata_X,
+                        validationdata_Y,
+                        eval_metric,
+                        arch_search,
+                        &mut Yhat_out,
+                        &mut eval_metric_out,
+                    );
+
+                    avgelem = eval_metric_out.iter().cloned().sum::<T>()
+                        / T::from_f64(eval_metric_out.len() as f64);
+
+                    crossval_mean_loss = avgelem;
+
+                    crossval_vec.push(crossval_mean_loss);
+                }
+                stop_strategy_type::NONE => (),
+            }
+        }
+    }
+
+    arrayfire::device_gc();
+
+    let mut epoch_phase: Option<phase_timer_type> = None;
+
+    // main loop here!
+    for i in start_epoch..max_epoch {
+        //SAVE TRAINER STATE
+        if (checkpoint_interval > 0) && (i > start_epoch) && ((i % checkpoint_interval) == 0) {
+            let train_state: train_state_type<T> = train_state_type {
+                epoch: i.clone(),
+                seed: train_seed,
+
+                alpha: alpha.clone(),
+                loss_val: loss_val.clone(),
+                first_loss: first_loss.clone(),
+                mean_loss: mean_loss.clone(),
+                min_loss: min_loss.clone(),
+                loss_counter: loss_counter.clone(),
+                global_alpha_max: global_alpha_max.clone(),
+                cur_alpha_max: cur_alpha_max.clone(),
+                crossval_mean_loss: crossval_mean_loss.clone(),
+
+                optimizer_kind: optimizer.kind(),
+                optimizer_state: optimizer.state(),
+                scheduler_state: lr_scheduler.state(),
+                grad: grad.clone(),
+
+                alpha_max_vec: alpha_max_vec.clone(),
+                loss_vec: loss_vec.clone(),
+                crossval_vec: crossval_vec.clone(),
+                alpha_history_vec: alpha_history_vec.clone(),
+                idx_history_vec: idx_history_vec.clone(),
+            };
+
+            try_save_train_checkpoint(
+                checkpoint_file,
+                &((*arch_search).neural_network),
+                &train_state,
+            )?;
+
+            let progress = create_train_progress(
+                i,
+                epoch_num,
+                max_epoch,
+                loss_val,
+                mean_loss,
+                crossval_mean_loss,
+                &eval_metric_out,
+                alpha,
+                &((*arch_search).neural_network),
+            );
+            for callback in callbacks.iter_mut() {
+                callback.on_checkpoint(&progress, checkpoint_file)?;
+            }
+        }
+
+        if ((i % epoch_num) == 0) || (i == start_epoch) {
+            let progress = create_train_progress(
+                i,
+                epoch_num,
+                max_epoch,
+                loss_val,
+                mean_loss,
+                crossval_mean_loss,
+                &eval_metric_out,
+                alpha,
+                &((*arch_search).neural_network),
+            );
+            for callback in callbacks.iter_mut() {
+                callback.on_epoch_start(&progress)?;
+            }
+
+            //Leave the span of the previous epoch before entering the next
+            drop(epoch_phase.take());
+            epoch_phase = Some(enter_phase(tracing::info_span!(
+                "training_epoch",
+                epoch = progress.epoch,
+                batch_num = epoch_num,
+                active_size = progress.active_size,
+                connection_num = progress.connection_num
+            )));
+        }
+
+        batch_idx = i % epoch_num;
+        //X is our traininig data feature
+        //in each epoch we choose one batch of training data
+        X = arrayfire::Array::new(&traindata_X[&batch_idx], train_X_dims);
+
+        Y = arrayfire::Array::new(&traindata_Y[&batch_idx], Y_dims);
+
+        alpha = cur_alpha_max.clone();
+
+        match lr_strategy2 {
+            lr_strategy2_type::BTLS_ALPHA => {
+                statespace_BTLS(
+                    T::from_f64(1.5) * first_loss,
+                    alpha.clone() / T::from_f64(10000.0),
+                    &((*arch_search).neural_network.network_params),
+                    &grad,
+                    T::from_f64(0.5),
+                    T::from_f64(0.1),
+                    &((*arch_search).neural_network.netdata),
+                    &X,
+                    &((*arch_search).neural_network.WRowIdxCSR),
+                    &((*arch_search).neural_network.WColIdx),
+                    &Wseqs,
+                    &Hseqs,
+                    &Aseqs,
+                    &Bseqs,
+                    &Cseqs,
+                    &Dseqs,
+                    &Eseqs,
+                    &idxsel,
+                    &Y,
+                    eval_metric,
+                    eval_metric_grad,
+                    &((*arch_search).neural_network.neuron_idx),
+                    &idxsel_out,
+                    &valsel_out,
+                    &cvec_out,
+                    &dXsel_out,
+                    &nrows_out,
+                    &sparseval_out,
+                    &sparserow_out,
+                    &sparsecol_out,
+                    &Hidxsel_out,
+                    &Aidxsel_out,
+                    &Bidxsel_out,
+                    &Cidxsel_out,
+                    &Didxsel_out,
+                    &Eidxsel_out,
+                    &combidxsel_out,
+                    &dAseqs_out,
+                    &dBseqs_out,
+                    &dCseqs_out,
+                    &dDseqs_out,
+                    &dEseqs_out,
+                    &((*arch_search).neural_network.norm),
+                    &mut Z,
+                    &mut Q,
+                    &mut alpha,
+                    &mut loss_val,
+                );
+                //Penalty at the point found by the line search
+                loss_val = loss_val
+                    + regularization_penalty(
+                        &((*arch_search).neural_network.network_params.clone()
+                            + grad.clone() * alpha),
+                        &regularization,
+                        &Wseqs,
+                        &Aseqs,
+                        &Bseqs,
+                        &Cseqs,
+                        &Dseqs,
+                        &Eseqs,
+                    );
+            }
+            lr_strategy2_type::MAX_ALPHA => (),
+        };
+
+        //GET CURRENT MAX LR
+        alpha_idx = (i / epoch_num) as usize;
+
+        if (alpha_idx + 5) >= alpha_max_vec.len() {
+            let mut minelem = alpha_max_vec.iter().fold(T::infinity(), |a, &b| a.min(b));
+
+            if minelem.is_infinite() || minelem.is_nan() {
+                minelem = LR_MAX;
+            }
+
+            global_alpha_max = minelem;
+
+            for k in 0..((alpha_idx + 10) - alpha_max_vec.len()) {
+                alpha_max_vec.push(global_alpha_max);
+            }
+        }
+
+        //OVERFLOW LOSS
+        if (loss_val > T::from_f64(1.5) * first_loss)
+            || (loss_val.is_nan())
+            || (loss_val.is_infinite())
+        {
+            let mut elem_num = 3;
+            let mut history_len = alpha_history_vec.len();
+
+            if history_len == 0 {
+                loss_vec.push(first_loss.clone());
+                alpha_history_vec.push(alpha_max_vec[0].clone());
+                idx_history_vec.push(0);
+                history_len = 1;
+            }
+
+            if elem_num > history_len {
+                elem_num = history_len;
+            }
+
+            let lastelems = &alpha_history_vec[history_len - elem_num..history_len];
+
+            let lastelems2 = &idx_history_vec[history_len - elem_num..history_len];
+
+            let lastelems_arr = arrayfire::Array::new(
+                &lastelems,
+                arrayfire::Dim4::new(&[lastelems.len() as u64, 1, 1, 1]),
+            );
+            let (_, max_idx) = arrayfire::imax(&lastelems_arr, 0);
+
+            let mut max_idx_cpu = vec![u32::default(); max_idx.elements()];
+            max_idx.host(&mut max_idx_cpu);
+
+            let maxelem = lastelems[max_idx_cpu[0] as usize];
+
+            let maxindex = lastelems2[max_idx_cpu[0] as usize];
+
+            alpha_idx = (maxindex / epoch_num) as usize;
+
+            let newalpha =
+                (T::from_f64(0.8) * alpha_max_vec[alpha_idx]).min(maxelem * T::from_f64(0.8));
+
+            for zz in alpha_idx..alpha_max_vec.len() {
+                alpha_max_vec[zz] = newalpha;
+            }
+
+            *loss_status = loss_status_type::LOSS_OVERFLOW;
+            return Ok(());
+        }
+
+        //LOSS PLATAUE
+        mean_loss = T::from_f64(0.9) * mean_loss + T::from_f64(0.1) * loss_val;
+
+        if (mean_loss * T::from_f64(1.05) < min_loss) {
+            min_loss = mean_loss;
+
+            loss_counter = 0;
+        } else {
+            loss_counter = loss_counter + 1;
+        }
+
+        //STOPPING POINT
+        match stop_strategy {
+            stop_strategy_type::STOP_AT_EPOCH => {
+                if (i == stop_epoch) {
+                    *loss_status = loss_status_type::PREDETERMINED_STOP;
+                    return Ok(());
+                }
+            }
+            stop_strategy_type::STOP_AT_TRAIN_LOSS => {
+                if (mean_loss < stop_train_loss) {
+                    *loss_status = loss_status_type::PREDETERMINED_STOP;
+                    return Ok(());
+                }
+            }
+            stop_strategy_type::CROSSVAL_STOPPING => {
+                if ((i % epoch_num) == 0) && (i > 0) {
+                    eval_metric_out = Vec::new();
+                    Yhat_out = nohash_hasher::IntMap::default();
+
+                    validate_network(
+                        validationdata_X,
+                        validationdata_Y,
+                        eval_metric,
+                        arch_search,
+                        &mut Yhat_out,
+                        &mut eval_metric_out,
+                    );
+
+                    avgelem = eval_metric_out.iter().cloned().sum::<T>()
+                        / T::from_f64(eval_metric_out.len() as f64);
+
+                    crossval_mean_loss = avgelem;
+
+                    crossval_vec.push(crossval_mean_loss);
+
+                    let progress = create_train_progress(
+                        i,
+                        epoch_num,
+                        max_epoch,
+                        loss_val,
+                        mean_loss,
+                        crossval_mean_loss,
+                        &eval_metric_out,
+                        alpha,
+                        &((*arch_search).neural_network),
+                    );
+                    let action = dispatch_callbacks(callbacks, |callback| {
+                        callback.on_validation(&progress)
+                    })?;
+                    if action == callback_action_type::STOP {
+                        *loss_status = loss_status_type::PREDETERMINED_STOP;
+                        return Ok(());
+                    }
+                }
+            }
+            stop_strategy_type::NONE => (),
+        }
+
+        //EXIT WHEN ANNEALING NOT WORKING
+        if loss_counter >= exit_counter_threshold {
+            *loss_status = loss_status_type::LOSS_PLATEAU;
+            return Ok(());
+        }
+
+        cur_alpha_max = alpha_max_vec[alpha_idx];
+        if alpha > cur_alpha_max {
+            alpha = cur_alpha_max;
+        }
+
+        //Learning rate schedule
+        alpha = alpha * lr_scheduler.factor(i, mean_loss);
+
+        loss_vec.push(loss_val);
+        alpha_history_vec.push(alpha);
+        idx_history_vec.push(i.clone());
+
+        optimizer.apply(
+            alpha,
+            &mut ((*arch_search).neural_network.network_params),
+            &grad,
+        );
+
+        apply_max_norm(
+            regularization.max_norm,
+            &WRowIdxCOO,
+            &Wseqs,
+            &mut ((*arch_search).neural_network.network_params),
+        );
+
+        //Dropout and dropconnect on the weights used by the forward and backward pass, seeded
+        //by the step that uses the gradient
+        let mut drop_mask = create_drop_mask(
+            &regularization,
+            &((*arch_search).neural_network.WColIdx),
+            stream_raybnn_seed(train_seed, i + 1),
+        );
+        let mut drop_params = (*arch_search).neural_network.network_params.clone();
+        if let Some(mask) = &drop_mask {
+            apply_drop_mask(mask, &Wseqs, &mut drop_params);
+        }
+
+        loss_wrapper(
+            &((*arch_search).neural_network.netdata),
+            //here X is one batch of training data
+            &X,
+            &((*arch_search).neural_network.WRowIdxCSR),
+            &((*arch_search).neural_network.WColIdx),
+            &Wseqs,
+            &Hseqs,
+            &Aseqs,
+            &Bseqs,
+            &Cseqs,
+            &Dseqs,
+            &Eseqs,
+            &drop_params,
+            &mut ((*arch_search).neural_network.norm),
+            &idxsel,
+            &Y,
+            eval_metric,
+            &mut Z,
+            &mut Q,
+            //loss_val is not loss of whole dataset, but the loss of one batch
+            &mut loss_val,
+        );
+        loss_val = loss_val
+            + regularization_penalty(
+                &((*arch_search).neural_network.network_params),
+                &regularization,
+                &Wseqs,
+                &Aseqs,
+                &Bseqs,
+                &Cseqs,
+                &Dseqs,
+                &Eseqs,
+            );
+
+        //Reset connections
+        match lr_strategy {
+            lr_strategy_type::COSINE_ANNEALING => (),
+            lr_strategy_type::SHUFFLE_CONNECTIONS => {
+                if (loss_counter == shuffle_counter_threshold) {
+                    /*
+                    let connection_num = WRowIdxCOO.dims()[0];
+
+
+                    let network_info = network_info_seed_type {
+                        i: i.clone(),
+                        proc_num: proc_num.clone(),
+                        connection_num: connection_num.clone(),
+                        active_size: active_size.clone(),
+                    };
+
+                    set_network_seed(&network_info);
+
+
+
+
+                    println!("shuffle {}", i);
+
+
+
+
+                    //Find all cube paths
+                    let cube_path_vec = find_cube_paths(&arch_search.evolution_info.dir_path  );
+
+                    let cube_path = cube_path_vec[0].clone();
+
+                    let cube_radius= extract_file_info2(&cube_path)[0];
+
+                    //Load cube pos
+                    let temp_dims = arrayfire::Dim4::new(&[1,1,1,1]);
+                    let mut cube_glia_pos = arrayfire::constant::<T>(T::zero(),temp_dims);
+                    let mut cube_neuron_pos = arrayfire::constant::<T>(T::zero(),temp_dims);
+                    let mut cube_neuron_idx = arrayfire::constant::<i32>(0,temp_dims);
+
+                    load_network_structure(
+                        &cube_path,
+
+                        &mut cube_glia_pos,
+                        &mut cube_neuron_pos,
+                        &mut cube_neuron_idx
+                    );
+                    drop(cube_glia_pos);
+                    drop(cube_neuron_idx);
+
+
+                    //Add new neurons
+                    add_neuron_to_existing2(
+                        &cube_neuron_pos,
+                        cube_radius,
+
+                        active_size+2,
+
+                        arch_search,
+                    );
+                    (*arch_search).neural_network.netdata.active_size = (*arch_search).neural_network.neuron_idx.dims()[0];
+
+
+                    WRowIdxCOO = CSR_to_COO(&((*arch_search).neural_network.WRowIdxCSR));
+
+
+
+
+
+
+
+
+
+
+
+                    //Make the neural network smaller
+
+                    reduce_network_size(
+                        active_size,
+
+                        arch_search,
+                    );
+                    (*arch_search).neural_network.netdata.active_size = (*arch_search).neural_network.neuron_idx.dims()[0];
+
+
+                    WRowIdxCOO = CSR_to_COO(&((*arch_search).neural_network.WRowIdxCSR));
+
+                    */
+
+                    shuffle_weights(i, arch_search);
+
+                    (*arch_search).neural_network.netdata.active_size =
+                        (*arch_search).neural_network.neuron_idx.dims()[0];
+                    active_size = (*arch_search).neural_network.netdata.active_size;
+                    WRowIdxCOO = CSR_to_COO(&((*arch_search).neural_network.WRowIdxCSR));
+
+                    total_param_size = (*arch_search).neural_network.network_params.dims()[0];
+                    mt_dims = arrayfire::Dim4::new(&[total_param_size, 1, 1, 1]);
+                    grad = arrayfire::constant::<T>(T::zero(), mt_dims);
+
+                    idxsel_out = nohash_hasher::IntMap::default();
+                    valsel_out = nohash_hasher::IntMap::default();
+
+                    cvec_out = nohash_hasher::IntMap::default();
+                    dXsel_out = nohash_hasher::IntMap::default();
+
+                    nrows_out = nohash_hasher::IntMap::default();
+                    sparseval_out = nohash_hasher::IntMap::default();
+                    sparsecol_out = nohash_hasher::IntMap::default();
+                    sparserow_out = nohash_hasher::IntMap::default();
+
+                    Wseqs = [arrayfire::Seq::default()];
+                    Hseqs = [arrayfire::Seq::default()];
+                    Aseqs = [arrayfire::Seq::default()];
+                    Bseqs = [arrayfire::Seq::default()];
+                    Cseqs = [arrayfire::Seq::default()];
+                    Dseqs = [arrayfire::Seq::default()];
+                    Eseqs = [arrayfire::Seq::default()];
+
+                    Hidxsel_out = nohash_hasher::IntMap::default();
+                    Aidxsel_out = nohash_hasher::IntMap::default();
+                    Bidxsel_out = nohash_hasher::IntMap::default();
+                    Cidxsel_out = nohash_hasher::IntMap::default();
+                    Didxsel_out = nohash_hasher::IntMap::default();
+                    Eidxsel_out = nohash_hasher::IntMap::default();
+                    combidxsel_out = nohash_hasher::IntMap::default();
+
+                    dAseqs_out = nohash_hasher::IntMap::default();
+                    dBseqs_out = nohash_hasher::IntMap::default();
+                    dCseqs_out = nohash_hasher::IntMap::default();
+                    dDseqs_out = nohash_hasher::IntMap::default();
+                    dEseqs_out = nohash_hasher::IntMap::default();
+
+                    find_path_backward_group2(
+                        &((*arch_search).neural_network.netdata),
+                        traj_steps,
+                        traj_size,
+                        &WRowIdxCOO,
+                        &((*arch_search).neural_network.WColIdx),
+                        &((*arch_search).neural_network.neuron_idx),
+                        (*arch_search).neural_network.WColIdx.dims()[0],
+                        neuron_size,
+                        neuron_size,
+                        neuron_size,
+                        neuron_size,
+                        neuron_size,
+                        neuron_size,
+                        &mut idxsel_out,
+                        &mut valsel_out,
+                        &mut cvec_out,
+                        &mut dXsel_out,
+                        &mut nrows_out,
+                        &mut sparseval_out,
+                        &mut sparserow_out,
+                        &mut sparsecol_out,
+                        &mut Hidxsel_out,
+                        &mut Aidxsel_out,
+                        &mut Bidxsel_out,
+                        &mut Cidxsel_out,
+                        &mut Didxsel_out,
+                        &mut Eidxsel_out,
+                        &mut combidxsel_out,
+                        &mut dAseqs_out,
+                        &mut dBseqs_out,
+                        &mut dCseqs_out,
+                        &mut dDseqs_out,
+                        &mut dEseqs_out,
+                        &mut Wseqs,
+                        &mut Hseqs,
+                        &mut Aseqs,
+                        &mut Bseqs,
+                        &mut Cseqs,
+                        &mut Dseqs,
+                        &mut Eseqs,
+                    );
+
+                    optimizer.set_groups(create_network_param_groups(
+                        total_param_size,
+                        &train_network_options.optimizer,
+                        &Hseqs,
+                        &Aseqs,
+                        &Bseqs,
+                        &Cseqs,
+                        &Dseqs,
+                        &Eseqs,
+                    ));
+                    optimizer.reset(total_param_size);
+
+                    WRowIdxCOO = CSR_to_COO(&((*arch_search).neural_network.WRowIdxCSR));
+
+                    //The mask does not match the new connections
+                    drop_mask = None;
+                    drop_params = (*arch_search).neural_network.network_params.clone();
+                }
+            }
+            lr_strategy_type::NONE => (),
+        }
+
+        state_space_backward_group2_norm(
+            &((*arch_search).neural_network.netdata),
+            &X,
+            &((*arch_search).neural_network.WRowIdxCSR),
+            &((*arch_search).neural_network.WColIdx),
+            &Wseqs,
+            &Hseqs,
+            &Aseqs,
+            &Bseqs,
+            &Cseqs,
+            &Dseqs,
+            &Eseqs,
+            &drop_params,
+            &Z,
+            &Q,
+            &Y,
+            eval_metric_grad,
+            &((*arch_search).neural_network.neuron_idx),
+            &idxsel_out,
+            &valsel_out,
+            &cvec_out,
+            &dXsel_out,
+            &nrows_out,
+            &sparseval_out,
+            &sparserow_out,
+            &sparsecol_out,
+            &Hidxsel_out,
+            &Aidxsel_out,
+            &Bidxsel_out,
+            &Cidxsel_out,
+            &Didxsel_out,
+            &Eidxsel_out,
+            &combidxsel_out,
+            &dAseqs_out,
+            &dBseqs_out,
+            &dCseqs_out,
+            &dDseqs_out,
+            &dEseqs_out,
+            &((*arch_search).neural_network.norm),
+            &mut grad,
+        );
+
+        step_direction(
+            &((*arch_search).neural_network.network_params),
+            &drop_mask,
+            &regularization,
+            &Wseqs,
+            &Aseqs,
+            &Bseqs,
+            &Cseqs,
+            &Dseqs,
+            &Eseqs,
+            &mut optimizer,
+            &mut grad,
+        );
+
+        let progress = create_train_progress(
+            i,
+            epoch_num,
+            max_epoch,
+            loss_val,
+            mean_loss,
+            crossval_mean_loss,
+            &eval_metric_out,
+            alpha,
+            &((*arch_search).neural_network),
+        );
+        let mut action =
+            dispatch_callbacks(callbacks, |callback| callback.on_batch_end(&progress))?;
+        if ((i + 1) % epoch_num) == 0 {
+            let epoch_action =
+                dispatch_callbacks(callbacks, |callback| callback.on_epoch_end(&progress))?;
+            if epoch_action == callback_action_type::STOP {
+                action = callback_action_type::STOP;
+            }
+
+            tracing::info!(
+                mean_loss = progress.mean_loss.to_f64().unwrap_or(f64::NAN),
+                crossval_loss = progress.crossval_loss.to_f64().unwrap_or(f64::NAN),
+                alpha = progress.alpha.to_f64().unwrap_or(f64::NAN),
+                "finished epoch"
+            );
+            drop(epoch_phase.take());
+        }
+        if action == callback_action_type::STOP {
+            *loss_status = loss_status_type::PREDETERMINED_STOP;
+            return Ok(());
+        }
+    }
+
+    Ok(())
+}
+
+pub fn train_network_with_callbacks<T: raybnn_float_trait>(
+    traindata_X: &nohash_hasher::IntMap<u64, Vec<T>>,
+    traindata_Y: &nohash_hasher::IntMap<u64, Vec<T>>,
+
+    validationdata_X: &nohash_hasher::IntMap<u64, Vec<T>>,
+    validationdata_Y: &nohash_hasher::IntMap<u64, Vec<T>>,
+
+    eval_metric: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> T + Copy,
+    eval_metric_grad: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> arrayfire::Array<T> + Copy,
+
+    train_network_options: train_network_options_type<T>,
+
+    alpha_max_vec: &mut Vec<T>,
+    loss_vec: &mut Vec<T>,
+    crossval_vec: &mut Vec<T>,
+    arch_search: &mut arch_search_type<T>,
+    loss_status: &mut loss_status_type,
+
+    checkpoint_file: &str,
+    checkpoint_interval: u64,
+    resume_state: Option<train_state_type<T>>,
+
+    callbacks: &mut [&mut dyn train_callback_trait<T>],
+) {
+    try_train_network_with_callbacks(
+        traindata_X,
+        traindata_Y,
+        validationdata_X,
+        validationdata_Y,
+        eval_metric,
+        eval_metric_grad,
+        train_network_options,
+        alpha_max_vec,
+        loss_vec,
+        crossval_vec,
+        arch_search,
+        loss_status,
+        checkpoint_file,
+        checkpoint_interval,
+        resume_state,
+        callbacks,
+    )
+    .unwrap_or_else(|e| panic!("{}", e));
+}
+
+// Continues training from a checkpoint saved by train_network_checkpoint.
+// With the same data and options, training continues exactly where it stopped
+
+//           Input arguments
+// checkpoint_file: Binary checkpoint written with save_train_checkpoint.
+//                  New checkpoints are saved to the same file
+// checkpoint_interval: Number of epochs between checkpoints. 0 disables checkpoints
+// Other arguments are the same as train_network
+pub fn resume_train_network<T: raybnn_float_trait>(
+    checkpoint_file: &str,
+    checkpoint_interval: u64,
+
+    traindata_X: &nohash_hasher::IntMap<u64, Vec<T>>,
+    traindata_Y: &nohash_hasher::IntMap<u64, Vec<T>>,
+
+    validationdata_X: &nohash_hasher::IntMap<u64, Vec<T>>,
+    validationdata_Y: &nohash_hasher::IntMap<u64, Vec<T>>,
+
+    eval_metric: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> T + Copy,
+    eval_metric_grad: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> arrayfire::Array<T> + Copy,
+
+    train_network_options: train_network_options_type<T>,
+
+    alpha_max_vec: &mut Vec<T>,
+    loss_vec: &mut Vec<T>,
+    crossval_vec: &mut Vec<T>,
+    arch_search: &mut arch_search_type<T>,
+    loss_status: &mut loss_status_type,
+) -> Result<(), Error> {
+    let (neural_network, train_state) = try_load_train_checkpoint(checkpoint_file)?;
+
+    (*arch_search).neural_network = neural_network;
+
+    let mut print_callback = print_callback_type {};
+
+    try_train_network_with_callbacks(
+        traindata_X,
+        traindata_Y,
+        validationdata_X,
+        validationdata_Y,
+        eval_metric,
+        eval_metric_grad,
+        train_network_options,
+        alpha_max_vec,
+        loss_vec,
+        crossval_vec,
+        arch_search,
+        loss_status,
+        checkpoint_file,
+        checkpoint_interval,
+        Some(train_state),
+        &mut [&mut print_callback],
+    )
+}
@@ -0,0 +1,517 @@
+extern crate arrayfire;
+
+use rand::Rng;
+
+use crate::precision::float_trait::raybnn_float_trait;
+
+use crate::neural::network::network_metadata_type;
+
+use crate::interface::automatic::arch_search_type;
+
+use crate::interface::automatic::create_start_archtecture_with_strategy;
+use crate::interface::automatic::try_create_start_archtecture_poisson_disk;
+
+use crate::error::Error;
+
+use crate::export::rng::seed_raybnn_rng;
+use crate::export::rng::with_raybnn_rng;
+
+use crate::optimal::evolution::evolution_search_type;
+
+use crate::physics::neighbor::neighbor_search_type;
+
+use crate::physics::poisson::cell_placement_type;
+
+use crate::physics::topology::connection_strategy_trait;
+use crate::physics::topology::create_raytrace_strategy;
+
+const MIN_ACTIVE_SIZE: u64 = 20;
+
+/*
+Builder for the starting neural network archtecture.
+Replaces filling in network_metadata_type by hand and calling create_start_archtecture2
+with positional arguments. Every setting has the same default as create_start_archtecture.
+Settings left as None are derived from the other settings when the network is built.
+
+Example
+let arch_search = network_builder_type::new(input_size, output_size, "/tmp/")
+    .max_neuron_size(2000)
+    .batch_size(100)
+    .proc_num(3)
+    .build()
+    .expect("invalid network settings");
+
+*/
+
+#[derive(Clone)]
+pub struct network_builder_type<T: raybnn_float_trait> {
+    pub input_size: u64,
+    pub max_input_size: Option<u64>,
+    pub output_size: u64,
+    pub max_output_size: Option<u64>,
+    pub active_size: Option<u64>,
+    pub max_neuron_size: Option<u64>,
+    pub batch_size: u64,
+    pub traj_size: u64,
+    pub proc_num: u64,
+    pub step_num: u64,
+    pub del_unused_neuron: bool,
+
+    pub time_step: T,
+    pub nratio: T,
+    pub neuron_std: T,
+    pub sphere_rad: Option<T>,
+    pub neuron_rad: T,
+    pub con_rad: Option<T>,
+    pub init_prob: T,
+    pub add_neuron_rate: T,
+    pub del_neuron_rate: T,
+    pub center_const: T,
+    pub spring_const: T,
+    pub repel_const: T,
+    pub neighbor_search: neighbor_search_type,
+    pub cell_placement: cell_placement_type,
+
+    pub search_strategy: evolution_search_type<T>,
+    pub seed: Option<u64>,
+    pub dir_path: String,
+}
+
+impl<T: raybnn_float_trait> network_builder_type<T> {
+    pub fn new(input_size: u64, output_size: u64, dir_path: &str) -> network_builder_type<T> {
+        network_builder_type {
+            input_size: input_size,
+            max_input_size: None,
+            output_size: output_size,
+            max_output_size: None,
+            active_size: None,
+            max_neuron_size: None,
+            batch_size: 1,
+            traj_size: 1,
+            proc_num: 2,
+            step_num: 5000,
+            del_unused_neuron: true,
+
+            time_step: T::from_f64(0.1),
+            nratio: T::from_f64(0.5),
+            neuron_std: T::from_f64(0.001),
+            sphere_rad: None,
+            neuron_rad: T::from_f64(0.1),
+            con_rad: None,
+            init_prob: T::from_f64(0.01),
+            add_neuron_rate: T::zero(),
+            del_neuron_rate: T::zero(),
+            center_const: T::from_f64(0.005),
+            spring_const: T::from_f64(0.01),
+            repel_const: T::from_f64(0.01),
+            neighbor_search: neighbor_search_type::DENSE,
+            cell_placement: cell_placement_type::RANDOM_COLLISION,
+
+            search_strategy: evolution_search_type::METROPOLIS_SEARCH,
+            seed: None,
+            dir_path: dir_path.to_string(),
+        }
+    }
+
+    pub fn max_input_size(mut self, max_input_size: u64) -> network_builder_type<T> {
+        self.max_input_size = Some(max_input_size);
+        self
+    }
+
+    pub fn max_output_size(mut self, max_output_size: u64) -> network_builder_type<T> {
+        self.max_output_size = Some(max_output_size);
+        self
+    }
+
+    pub fn active_size(mut self, active_size: u64) -> network_builder_type<T> {
+        self.active_size = Some(active_size);
+        self
+    }
+
+    pub fn max_neuron_size(mut self, max_neuron_size: u64) -> network_builder_type<T> {
+        self.max_neuron_size = Some(max_neuron_size);
+        self
+    }
+
+    pub fn batch_size(mut self, batch_size: u64) -> network_builder_type<T> {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn traj_size(mut self, traj_size: u64) -> network_builder_type<T> {
+        self.traj_size = traj_size;
+        self
+    }
+
+    pub fn proc_num(mut self, proc_num: u64) -> network_builder_type<T> {
+        self.proc_num = proc_num;
+        self
+    }
+
+    pub fn step_num(mut self, step_num: u64) -> network_builder_type<T> {
+        self.step_num = step_num;
+        self
+    }
+
+    pub fn del_unused_neuron(mut self, del_unused_neuron: bool) -> network_builder_type<T> {
+        self.del_unused_neuron = del_unused_neuron;
+        self
+    }
+
+    pub fn time_step(mut self, time_step: T) -> network_builder_type<T> {
+        self.time_step = time_step;
+        self
+    }
+
+    pub fn nratio(mut self, nratio: T) -> network_builder_type<T> {
+        self.nratio = nratio;
+        self
+    }
+
+    pub fn neuron_std(mut self, neuron_std: T) -> network_builder_type<T> {
+        self.neuron_std = neuron_std;
+        self
+    }
+
+    pub fn sphere_rad(mut self, sphere_rad: T) -> network_builder_type<T> {
+        self.sphere_rad = Some(sphere_rad);
+        self
+    }
+
+    pub fn neuron_rad(mut self, neuron_rad: T) -> network_builder_type<T> {
+        self.neuron_rad = neuron_rad;
+        self
+    }
+
+    pub fn con_rad(mut self, con_rad: T) -> network_builder_type<T> {
+        self.con_rad = Some(con_rad);
+        self
+    }
+
+    pub fn init_prob(mut self, init_prob: T) -> network_builder_type<T> {
+        self.init_prob = init_prob;
+        self
+    }
+
+    pub fn add_neuron_rate(mut self, add_neuron_rate: T) -> network_builder_type<T> {
+        self.add_neuron_rate = add_neuron_rate;
+        self
+    }
+
+    pub fn del_neuron_rate(mut self, del_neuron_rate: T) -> network_builder_type<T> {
+        self.del_neuron_rate = del_neuron_rate;
+        self
+    }
+
+    pub fn center_const(mut self, center_const: T) -> network_builder_type<T> {
+        self.center_const = center_const;
+        self
+    }
+
+    pub fn spring_const(mut self, spring_const: T) -> network_builder_type<T> {
+        self.spring_const = spring_const;
+        self
+    }
+
+    pub fn repel_const(mut self, repel_const: T) -> network_builder_type<T> {
+        self.repel_const = repel_const;
+        self
+    }
+
+    pub fn neighbor_search(
+        mut self,
+        neighbor_search: neighbor_search_type,
+    ) -> network_builder_type<T> {
+        self.neighbor_search = neighbor_search;
+        self
+    }
+
+    // POISSON_DISK places exactly 2*active_size cells with physics::poisson
+    pub fn cell_placement(
+        mut self,
+        cell_placement: cell_placement_type,
+    ) -> network_builder_type<T> {
+        self.cell_placement = cell_placement;
+        self
+    }
+
+    pub fn search_strategy(
+        mut self,
+        search_strategy: evolution_search_type<T>,
+    ) -> network_builder_type<T> {
+        self.search_strategy = search_strategy;
+        self
+    }
+
+    // Seeds the random number context (crate::export::rng) before the network is built
+    pub fn seed(mut self, seed: u64) -> network_builder_type<T> {
+        self.seed = Some(seed);
+        self
+    }
+
+    /*
+    Fills in the derived settings and returns the network metadata.
+    Returns Error::InvalidNetwork describing the first invalid setting instead of panicking
+    inside physics::initial or the raytracer
+
+    Outputs:
+    netdata:             Network metadata passed to create_start_archtecture_from_netdata
+
+    */
+    pub fn netdata(&self) -> Result<network_metadata_type<T>, Error> {
+        let input_size = self.input_size;
+        let output_size = self.output_size;
+
+        if input_size == 0 {
+            return Err(Error::InvalidNetwork(
+                "input_size must be greater than 0".to_string(),
+            ));
+        }
+        if output_size == 0 {
+            return Err(Error::InvalidNetwork(
+                "output_size must be greater than 0".to_string(),
+            ));
+        }
+
+        let max_input_size = self.max_input_size.unwrap_or(input_size);
+        let max_output_size = self.max_output_size.unwrap_or(output_size);
+
+        if input_size > max_input_size {
+            return Err(Error::InvalidNetwork(format!(
+                "input_size ({}) must be <= max_input_size ({})",
+                input_size, max_input_size
+            )));
+        }
+        if output_size > max_output_size {
+            return Err(Error::InvalidNetwork(format!(
+                "output_size ({}) must be <= max_output_size ({})",
+                output_size, max_output_size
+            )));
+        }
+
+        let active_size = match self.active_size {
+            Some(active_size) => {
+                if active_size < MIN_ACTIVE_SIZE {
+                    return Err(Error::InvalidNetwork(format!(
+                        "active_size ({}) must be >= {}",
+                        active_size, MIN_ACTIVE_SIZE
+                    )));
+                }
+                active_size
+            }
+            None => ((1.2 * ((input_size + output_size) as f32)) as u64).max(MIN_ACTIVE_SIZE),
+        };
+
+        if (input_size + output_size) > active_size {
+            return Err(Error::InvalidNetwork(format!(
+                "input_size + output_size ({}) must be <= active_size ({})",
+                input_size + output_size,
+                active_size
+            )));
+        }
+
+        let max_neuron_size = self.max_neuron_size.unwrap_or(4 * active_size);
+        if active_size > max_neuron_size {
+            return Err(Error::InvalidNetwork(format!(
+                "active_size ({}) must be <= max_neuron_size ({})",
+                active_size, max_neuron_size
+            )));
+        }
+        if (max_input_size + max_output_size) > max_neuron_size {
+            return Err(Error::InvalidNetwork(format!(
+                "max_input_size + max_output_size ({}) must be <= max_neuron_size ({})",
+                max_input_size + max_output_size,
+                max_neuron_size
+            )));
+        }
+
+        if self.batch_size == 0 {
+            return Err(Error::InvalidNetwork(
+                "batch_size must be greater than 0".to_string(),
+            ));
+        }
+        if self.traj_size == 0 {
+            return Err(Error::InvalidNetwork(
+                "traj_size must be greater than 0".to_string(),
+            ));
+        }
+        if self.proc_num == 0 {
+            return Err(Error::InvalidNetwork(
+                "proc_num must be greater than 0".to_string(),
+            ));
+        }
+
+        if !(self.neuron_rad > T::zero()) {
+            return Err(Error::InvalidNetwork(format!(
+                "neuron_rad ({}) must be greater than 0",
+                self.neuron_rad
+            )));
+        }
+
+        let sphere_rad = match self.sphere_rad {
+            Some(sphere_rad) => sphere_rad,
+            None => {
+                let SPHERE_RAD_MIN = T::from_f64(0.4);
+                let SPHERE_DENSITY = T::from_f64(0.28);
+
+                let mut sphere_radius = T::from_f64((2 * active_size) as f64) / SPHERE_DENSITY;
+                sphere_radius = (sphere_radius
+                    / (T::from_f64(4.0 / 3.0) * T::from_f64(std::f64::consts::PI)))
+                .cbrt();
+
+                if sphere_radius < SPHERE_RAD_MIN {
+                    sphere_radius = SPHERE_RAD_MIN;
+                }
+                sphere_radius
+            }
+        };
+
+        if !(self.neuron_rad < sphere_rad) {
+            return Err(Error::InvalidNetwork(format!(
+                "neuron_rad ({}) must be < sphere_rad ({})",
+                self.neuron_rad, sphere_rad
+            )));
+        }
+
+        let con_rad = self
+            .con_rad
+            .unwrap_or((sphere_rad / T::from_f64(self.proc_num as f64)) * T::from_f64(1.4));
+        if !(con_rad > T::zero()) {
+            return Err(Error::InvalidNetwork(format!(
+                "con_rad ({}) must be greater than 0",
+                con_rad
+            )));
+        }
+
+        if !(self.time_step > T::zero()) {
+            return Err(Error::InvalidNetwork(format!(
+                "time_step ({}) must be greater than 0",
+                self.time_step
+            )));
+        }
+        if !((T::zero() <= self.nratio) && (self.nratio <= T::one())) {
+            return Err(Error::InvalidNetwork(format!(
+                "nratio ({}) must be in [0, 1]",
+                self.nratio
+            )));
+        }
+        if !((T::zero() <= self.init_prob) && (self.init_prob <= T::one())) {
+            return Err(Error::InvalidNetwork(format!(
+                "init_prob ({}) must be in [0, 1]",
+                self.init_prob
+            )));
+        }
+        if !(self.neuron_std >= T::zero()) {
+            return Err(Error::InvalidNetwork(format!(
+                "neuron_std ({}) must be >= 0",
+                self.neuron_std
+            )));
+        }
+
+        let netdata: network_metadata_type<T> = network_metadata_type {
+            neuron_size: max_neuron_size,
+            input_size: input_size,
+            output_size: output_size,
+            proc_num: self.proc_num,
+            active_size: active_size,
+            space_dims: 3,
+            step_num: self.step_num,
+            batch_size: self.batch_size,
+            del_unused_neuron: self.del_unused_neuron,
+
+            time_step: self.time_step,
+            nratio: self.nratio,
+            neuron_std: self.neuron_std,
+            sphere_rad: sphere_rad,
+            neuron_rad: self.neuron_rad,
+            con_rad: con_rad,
+            init_prob: self.init_prob,
+            add_neuron_rate: self.add_neuron_rate,
+            del_neuron_rate: self.del_neuron_rate,
+            center_const: self.center_const,
+            spring_const: self.spring_const,
+            repel_const: self.repel_const,
+
+            neighbor_search: self.neighbor_search,
+        };
+
+        Ok(netdata)
+    }
+
+    /*
+    Validates the settings and creates the starting neural network archtecture
+
+    Outputs:
+    arch_search:         Entire neural network archtecture, or a description of the invalid setting
+
+    */
+    pub fn build(&self) -> Result<arch_search_type<T>, Error> {
+        self.build_with_strategy(&create_raytrace_strategy())
+    }
+
+    /*
+    Same as build, with the connections created by connection_strategy instead of the
+    ray tracer (crate::physics::topology)
+
+    Inputs
+    connection_strategy: Connects the input, hidden and output neurons
+
+    Outputs:
+    arch_search:         Entire neural network archtecture, or a description of the invalid setting
+
+    */
+    pub fn build_with_strategy(
+        &self,
+        connection_strategy: &dyn connection_strategy_trait<T>,
+    ) -> Result<arch_search_type<T>, Error> {
+        let netdata = self.netdata()?;
+
+        if !std::path::Path::new(&self.dir_path).is_dir() {
+            return Err(Error::InvalidNetwork(format!(
+                "dir_path ({}) is not a directory",
+                self.dir_path
+            )));
+        }
+
+        let max_input_size = self.max_input_size.unwrap_or(self.input_size);
+        let max_output_size = self.max_output_size.unwrap_or(self.output_size);
+
+        if let Some(seed) = self.seed {
+            seed_raybnn_rng(seed);
+        }
+
+        let mut arch_search = match self.cell_placement {
+            cell_placement_type::RANDOM_COLLISION => create_start_archtecture_with_strategy(
+                netdata,
+                connection_strategy,
+                max_input_size,
+                max_output_size,
+                self.traj_size,
+                &self.dir_path,
+            ),
+            cell_placement_type::POISSON_DISK => {
+                let cell_seed = with_raybnn_rng(|rng| rng.gen::<u64>());
+
+                try_create_start_archtecture_poisson_disk(
+                    netdata,
+                    connection_strategy,
+                    cell_seed,
+                    max_input_size,
+                    max_output_size,
+                    self.traj_size,
+                    &self.dir_path,
+                )?
+            }
+        };
+
+        arch_search.evolution_info.search_strategy = self.search_strategy;
+        if arch_search.evolution_info.max_proc_num < self.proc_num {
+            arch_search.evolution_info.max_proc_num = self.proc_num;
+        }
+        if arch_search.evolution_info.min_proc_num > self.proc_num {
+            arch_search.evolution_info.min_proc_num = self.proc_num;
+        }
+
+        Ok(arch_search)
+    }
+}
@@ -0,0 +1,3 @@
+// f64 instantiation of the generic crate::interface::builder module
+
+pub type network_builder_type = crate::interface::builder::network_builder_type<f64>;
@@ -0,0 +1,462 @@
+extern crate arrayfire;
+
+use std::fs::File;
+use std::io::Write;
+
+use crate::error::Error;
+
+use crate::export::checkpoint::try_save_checkpoint;
+
+use crate::neural::network::neural_network_type;
+
+use crate::precision::float_trait::raybnn_float_trait;
+
+/*
+Training callbacks of interface::autotrain_f32::train_network_with_callbacks
+
+The training loop runs max_epoch iterations. Every iteration trains on one batch, and an
+epoch is one pass over all batches (batch_num iterations).
+
+on_epoch_start:      Before the first batch of an epoch
+on_batch_end:        After every batch
+on_epoch_end:        After the last batch of an epoch
+on_validation:       After the validation loss is computed (CROSSVAL_STOPPING)
+on_checkpoint:       After the trainer state is saved (train_network_checkpoint)
+
+on_batch_end, on_epoch_end and on_validation return STOP to end training, which then returns
+loss_status_type::PREDETERMINED_STOP. An error from any callback ends training and is
+returned by try_train_network_with_callbacks
+
+Built-in callbacks
+
+print_callback_type:            Logs the loss and learning rate of every batch (tracing)
+csv_logger_callback_type:       Writes the progress of every batch to a CSV file
+early_stopping_callback_type:   Stops when the monitored loss does not improve
+model_checkpoint_callback_type: Saves the network when the monitored loss improves
+progress_bar_callback_type:     Draws a progress bar of the current epoch
+
+*/
+
+/*
+Training progress passed to every callback
+
+iteration:           Iteration of the training loop
+epoch:               Number of full passes over the batches
+batch_idx:           Batch of the iteration
+batch_num:           Number of batches
+max_iteration:       Last iteration (max_epoch of train_network_options_type)
+loss:                Loss of the current batch
+mean_loss:           Running mean of the batch loss
+crossval_loss:       Latest validation loss
+metrics:             Validation loss of every validation batch
+alpha:               Learning rate
+active_size:         Number of active neurons
+connection_num:      Number of connections
+neural_network:      Network being trained
+
+*/
+pub struct train_progress_type<'a, T: raybnn_float_trait> {
+    pub iteration: u64,
+    pub epoch: u64,
+    pub batch_idx: u64,
+    pub batch_num: u64,
+    pub max_iteration: u64,
+
+    pub loss: T,
+    pub mean_loss: T,
+    pub crossval_loss: T,
+    pub metrics: &'a [T],
+    pub alpha: T,
+
+    pub active_size: u64,
+    pub connection_num: u64,
+    pub neural_network: &'a neural_network_type<T>,
+}
+
+pub fn create_train_progress<'a, T: raybnn_float_trait>(
+    iteration: u64,
+    batch_num: u64,
+    max_iteration: u64,
+    loss: T,
+    mean_loss: T,
+    crossval_loss: T,
+    metrics: &'a [T],
+    alpha: T,
+    neural_network: &'a neural_network_type<T>,
+) -> train_progress_type<'a, T> {
+    let batch_num = batch_num.max(1);
+
+    train_progress_type {
+        iteration: iteration,
+        epoch: iteration / batch_num,
+        batch_idx: iteration % batch_num,
+        batch_num: batch_num,
+        max_iteration: max_iteration,
+
+        loss: loss,
+        mean_loss: mean_loss,
+        crossval_loss: crossval_loss,
+        metrics: metrics,
+        alpha: alpha,
+
+        active_size: neural_network.neuron_idx.dims()[0],
+        connection_num: neural_network.WColIdx.dims()[0],
+        neural_network: neural_network,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum callback_action_type {
+    CONTINUE,
+    STOP,
+}
+
+pub trait train_callback_trait<T: raybnn_float_trait> {
+    fn on_epoch_start(&mut self, _progress: &train_progress_type<T>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn on_batch_end(
+        &mut self,
+        _progress: &train_progress_type<T>,
+    ) -> Result<callback_action_type, Error> {
+        Ok(callback_action_type::CONTINUE)
+    }
+
+    fn on_epoch_end(
+        &mut self,
+        _progress: &train_progress_type<T>,
+    ) -> Result<callback_action_type, Error> {
+        Ok(callback_action_type::CONTINUE)
+    }
+
+    fn on_validation(
+        &mut self,
+        _progress: &train_progress_type<T>,
+    ) -> Result<callback_action_type, Error> {
+        Ok(callback_action_type::CONTINUE)
+    }
+
+    fn on_checkpoint(
+        &mut self,
+        _progress: &train_progress_type<T>,
+        _checkpoint_file: &str,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+// Runs event on every callback. Returns STOP when any callback returns STOP, and the
+// first error of any callback
+pub fn dispatch_callbacks<T: raybnn_float_trait>(
+    callbacks: &mut [&mut dyn train_callback_trait<T>],
+    mut event: impl FnMut(&mut dyn train_callback_trait<T>) -> Result<callback_action_type, Error>,
+) -> Result<callback_action_type, Error> {
+    let mut action = callback_action_type::CONTINUE;
+
+    for callback in callbacks.iter_mut() {
+        if event(&mut **callback)? == callback_action_type::STOP {
+            action = callback_action_type::STOP;
+        }
+    }
+
+    Ok(action)
+}
+
+/*
+Loss watched by early_stopping_callback_type and model_checkpoint_callback_type
+
+VALIDATION_LOSS:     Validation loss, checked in on_validation
+TRAIN_LOSS:          Running mean of the batch loss, checked in on_epoch_end
+
+*/
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum callback_monitor_type {
+    VALIDATION_LOSS,
+    TRAIN_LOSS,
+}
+
+// Logs the loss and learning rate of every batch as a debug event of crate::export::trace
+pub struct print_callback_type {}
+
+impl<T: raybnn_float_trait> train_callback_trait<T> for print_callback_type {
+    fn on_batch_end(
+        &mut self,
+        progress: &train_progress_type<T>,
+    ) -> Result<callback_action_type, Error> {
+        tracing::debug!(
+            iteration = progress.iteration,
+            batch_idx = progress.batch_idx,
+            loss = progress.loss.to_f64().unwrap_or(f64::NAN),
+            alpha = progress.alpha.to_f64().unwrap_or(f64::NAN),
+            "finished batch"
+        );
+
+        Ok(callback_action_type::CONTINUE)
+    }
+}
+
+/*
+Writes one CSV row per batch
+
+Columns
+iteration,epoch,batch_idx,loss,mean_loss,crossval_loss,alpha,active_size,connection_num
+
+filename:            CSV file
+file:                CSV file, kept open until the callback is dropped
+
+*/
+pub struct csv_logger_callback_type {
+    pub filename: String,
+    pub file: File,
+}
+
+// Creates the CSV file and writes the header
+pub fn try_create_csv_logger_callback(filename: &str) -> Result<csv_logger_callback_type, Error> {
+    let mut file0 = File::create(filename).map_err(|e| Error::io(filename, e))?;
+    writeln!(
+        file0,
+        "iteration,epoch,batch_idx,loss,mean_loss,crossval_loss,alpha,active_size,connection_num"
+    )
+    .map_err(|e| Error::io(filename, e))?;
+
+    Ok(csv_logger_callback_type {
+        filename: filename.to_string(),
+        file: file0,
+    })
+}
+
+pub fn create_csv_logger_callback(filename: &str) -> csv_logger_callback_type {
+    try_create_csv_logger_callback(filename).unwrap_or_else(|e| panic!("{}", e))
+}
+
+impl csv_logger_callback_type {
+    fn try_write_row<T: raybnn_float_trait>(
+        &mut self,
+        progress: &train_progress_type<T>,
+    ) -> Result<(), Error> {
+        writeln!(
+            self.file,
+            "{},{},{},{},{},{},{},{},{}",
+            progress.iteration,
+            progress.epoch,
+            progress.batch_idx,
+            progress.loss,
+            progress.mean_loss,
+            progress.crossval_loss,
+            progress.alpha,
+            progress.active_size,
+            progress.connection_num
+        )
+        .map_err(|e| Error::io(&self.filename, e))
+    }
+}
+
+impl<T: raybnn_float_trait> train_callback_trait<T> for csv_logger_callback_type {
+    fn on_batch_end(
+        &mut self,
+        progress: &train_progress_type<T>,
+    ) -> Result<callback_action_type, Error> {
+        self.try_write_row(progress)?;
+
+        Ok(callback_action_type::CONTINUE)
+    }
+
+    fn on_epoch_end(
+        &mut self,
+        _progress: &train_progress_type<T>,
+    ) -> Result<callback_action_type, Error> {
+        self.file
+            .flush()
+            .map_err(|e| Error::io(&self.filename, e))?;
+
+        Ok(callback_action_type::CONTINUE)
+    }
+}
+
+/*
+Stops training when the monitored loss has not improved by more than min_delta
+for patience checks
+
+monitor:             Loss to watch
+patience:            Number of checks without improvement before stopping
+min_delta:           Smallest decrease counted as an improvement
+best:                Lowest loss so far
+wait:                Number of checks since the last improvement
+
+*/
+pub struct early_stopping_callback_type<T: raybnn_float_trait> {
+    pub monitor: callback_monitor_type,
+    pub patience: u64,
+    pub min_delta: T,
+    pub best: T,
+    pub wait: u64,
+}
+
+pub fn create_early_stopping_callback<T: raybnn_float_trait>(
+    monitor: callback_monitor_type,
+    patience: u64,
+    min_delta: T,
+) -> early_stopping_callback_type<T> {
+    early_stopping_callback_type {
+        monitor: monitor,
+        patience: patience,
+        min_delta: min_delta,
+        best: T::infinity(),
+        wait: 0,
+    }
+}
+
+impl<T: raybnn_float_trait> early_stopping_callback_type<T> {
+    fn check(&mut self, loss: T) -> callback_action_type {
+        if loss < self.best - self.min_delta {
+            self.best = loss;
+            self.wait = 0;
+            return callback_action_type::CONTINUE;
+        }
+
+        self.wait = self.wait + 1;
+        if self.wait >= self.patience {
+            callback_action_type::STOP
+        } else {
+            callback_action_type::CONTINUE
+        }
+    }
+}
+
+impl<T: raybnn_float_trait> train_callback_trait<T> for early_stopping_callback_type<T> {
+    fn on_epoch_end(
+        &mut self,
+        progress: &train_progress_type<T>,
+    ) -> Result<callback_action_type, Error> {
+        Ok(match self.monitor {
+            callback_monitor_type::TRAIN_LOSS => self.check(progress.mean_loss),
+            callback_monitor_type::VALIDATION_LOSS => callback_action_type::CONTINUE,
+        })
+    }
+
+    fn on_validation(
+        &mut self,
+        progress: &train_progress_type<T>,
+    ) -> Result<callback_action_type, Error> {
+        Ok(match self.monitor {
+            callback_monitor_type::VALIDATION_LOSS => self.check(progress.crossval_loss),
+            callback_monitor_type::TRAIN_LOSS => callback_action_type::CONTINUE,
+        })
+    }
+}
+
+/*
+Saves the network as a binary checkpoint (crate::export::checkpoint)
+
+filename:            Checkpoint file, overwritten by every save
+monitor:             Loss to watch
+save_best_only:      Only save when the monitored loss is the lowest so far
+best:                Lowest loss so far
+
+A failed save is returned to the training loop, which stops and returns the error
+
+*/
+pub struct model_checkpoint_callback_type<T: raybnn_float_trait> {
+    pub filename: String,
+    pub monitor: callback_monitor_type,
+    pub save_best_only: bool,
+    pub best: T,
+}
+
+pub fn create_model_checkpoint_callback<T: raybnn_float_trait>(
+    filename: &str,
+    monitor: callback_monitor_type,
+    save_best_only: bool,
+) -> model_checkpoint_callback_type<T> {
+    model_checkpoint_callback_type {
+        filename: filename.to_string(),
+        monitor: monitor,
+        save_best_only: save_best_only,
+        best: T::infinity(),
+    }
+}
+
+impl<T: raybnn_float_trait> model_checkpoint_callback_type<T> {
+    fn check(&mut self, loss: T, neural_network: &neural_network_type<T>) -> Result<(), Error> {
+        let improved = loss < self.best;
+        if improved {
+            self.best = loss;
+        }
+
+        if improved || !self.save_best_only {
+            try_save_checkpoint(&self.filename, neural_network)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: raybnn_float_trait> train_callback_trait<T> for model_checkpoint_callback_type<T> {
+    fn on_epoch_end(
+        &mut self,
+        progress: &train_progress_type<T>,
+    ) -> Result<callback_action_type, Error> {
+        if self.monitor == callback_monitor_type::TRAIN_LOSS {
+            self.check(progress.mean_loss, progress.neural_network)?;
+        }
+
+        Ok(callback_action_type::CONTINUE)
+    }
+
+    fn on_validation(
+        &mut self,
+        progress: &train_progress_type<T>,
+    ) -> Result<callback_action_type, Error> {
+        if self.monitor == callback_monitor_type::VALIDATION_LOSS {
+            self.check(progress.crossval_loss, progress.neural_network)?;
+        }
+
+        Ok(callback_action_type::CONTINUE)
+    }
+}
+
+/*
+Draws a progress bar of the current epoch on stdout
+
+width:               Number of characters of the bar
+
+*/
+pub struct progress_bar_callback_type {
+    pub width: u64,
+}
+
+impl<T: raybnn_float_trait> train_callback_trait<T> for progress_bar_callback_type {
+    fn on_batch_end(
+        &mut self,
+        progress: &train_progress_type<T>,
+    ) -> Result<callback_action_type, Error> {
+        let done = ((progress.batch_idx + 1) * self.width) / progress.batch_num;
+
+        let bar: String = (0..self.width)
+            .map(|k| if k < done { '=' } else { ' ' })
+            .collect();
+
+        print!(
+            "\repoch {} [{}] {}/{} loss: {} alpha: {}",
+            progress.epoch,
+            bar,
+            progress.batch_idx + 1,
+            progress.batch_num,
+            progress.loss,
+            progress.alpha
+        );
+        std::io::stdout().flush().unwrap_or(());
+
+        Ok(callback_action_type::CONTINUE)
+    }
+
+    fn on_epoch_end(
+        &mut self,
+        _progress: &train_progress_type<T>,
+    ) -> Result<callback_action_type, Error> {
+        println!();
+
+        Ok(callback_action_type::CONTINUE)
+    }
+}
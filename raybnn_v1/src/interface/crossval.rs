@@ -0,0 +1,324 @@
+extern crate arrayfire;
+
+use nohash_hasher;
+
+use crate::precision::float_trait::raybnn_float_trait;
+
+use crate::error::Error;
+
+use crate::export::dataloader::fold_split_type;
+use crate::export::dataloader::hash_to_samples;
+use crate::export::dataloader::samples_to_hash;
+use crate::export::dataloader::samples_to_labels;
+use crate::export::dataloader::try_kfold_idx;
+
+use crate::export::trace::enter_phase;
+
+use crate::interface::automatic::arch_search_type;
+
+use crate::interface::autotest::validate_network;
+
+use crate::interface::autotrain::loss_status_type;
+use crate::interface::autotrain::train_network;
+use crate::interface::autotrain::train_network_options_type;
+
+use crate::optimal::metrics::metric_trait;
+
+/*
+k-fold cross-validation of train_network
+
+Every fold trains a fresh network from create_network and evaluates it once on the held-out
+fold after training. The fold after the held-out fold is the validation data of train_network,
+so CROSSVAL_STOPPING never sees the held-out fold, and the network trains on the other
+fold_num - 2 folds. fold_num must be at least 3.
+
+Folds are made of samples, not batches, and are batched again with the batch_size of the
+network. Samples that do not fill a last batch of a fold are not used.
+
+*/
+
+/*
+Settings of cross_validate_network
+
+fold_num:            Number of folds
+split:               Fold assignment, CONTIGUOUS, SHUFFLED, STRATIFIED by label or GROUP
+seed:                Seed of the random order of SHUFFLED and STRATIFIED
+
+*/
+#[derive(Clone)]
+pub struct crossval_options_type {
+    pub fold_num: u64,
+    pub split: fold_split_type,
+    pub seed: u64,
+}
+
+/*
+Result of one fold
+
+fold:                Index of the fold
+train_num:           Number of training samples used
+valid_num:           Number of validation samples used
+test_num:            Number of held-out samples used
+loss_vec:            Training loss of train_network
+crossval_vec:        Validation loss of train_network
+eval_metric_out:     eval_metric of every held-out batch
+metrics:             Every metric of cross_validate_network on all held-out samples
+
+*/
+pub struct crossval_fold_type<T: raybnn_float_trait> {
+    pub fold: u64,
+    pub train_num: u64,
+    pub valid_num: u64,
+    pub test_num: u64,
+
+    pub loss_vec: Vec<T>,
+    pub crossval_vec: Vec<T>,
+
+    pub eval_metric_out: Vec<T>,
+    pub metrics: Vec<T>,
+}
+
+/*
+Result of all folds
+
+folds:               Result of every fold
+mean_eval_metric:    Mean over the folds of the mean eval_metric of the held-out batches
+mean_metrics:        Mean of every metric over the folds
+std_metrics:         Standard deviation of every metric over the folds
+
+*/
+pub struct crossval_result_type<T: raybnn_float_trait> {
+    pub folds: Vec<crossval_fold_type<T>>,
+
+    pub mean_eval_metric: T,
+    pub mean_metrics: Vec<T>,
+    pub std_metrics: Vec<T>,
+}
+
+/*
+Runs k-fold cross-validation
+
+Inputs
+data_X:              Input batches, dimensions (input_size, batch_size, traj_size)
+data_Y:              Output batches, dimensions (output_size, batch_size, traj_size)
+create_network:      Creates the untrained network of a fold from the index of the fold
+eval_metric:         Loss of train_network and metric of every held-out batch
+eval_metric_grad:    Gradient of eval_metric
+train_network_options: Training settings of every fold
+metrics:             Metrics evaluated on all held-out samples of a fold
+crossval_options:    Number of folds and fold assignment
+
+Outputs:
+result:              Per fold and mean results
+
+*/
+pub fn try_cross_validate_network<T: raybnn_float_trait>(
+    data_X: &nohash_hasher::IntMap<u64, Vec<T>>,
+    data_Y: &nohash_hasher::IntMap<u64, Vec<T>>,
+
+    create_network: impl Fn(u64) -> arch_search_type<T>,
+
+    eval_metric: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> T + Copy,
+    eval_metric_grad: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> arrayfire::Array<T> + Copy,
+
+    train_network_options: &train_network_options_type<T>,
+    metrics: &[&dyn metric_trait<T>],
+    crossval_options: &crossval_options_type,
+) -> Result<crossval_result_type<T>, Error> {
+    let mut arch_search = create_network(0);
+
+    let input_size = arch_search.neural_network.netdata.input_size;
+    let output_size = arch_search.neural_network.netdata.output_size;
+    let batch_size = arch_search.neural_network.netdata.batch_size;
+
+    let samples_X = hash_to_samples(data_X, input_size, batch_size);
+    let samples_Y = hash_to_samples(data_Y, output_size, batch_size);
+    if samples_X.len() != samples_Y.len() {
+        return Err(Error::InvalidDataset(format!(
+            "{} input samples and {} output samples",
+            samples_X.len(),
+            samples_Y.len()
+        )));
+    }
+
+    if crossval_options.fold_num < 3 {
+        return Err(Error::InvalidDataset(format!(
+            "fold_num {} must be at least 3 to hold out a test and a validation fold",
+            crossval_options.fold_num
+        )));
+    }
+
+    let labels = samples_to_labels(&samples_Y, output_size);
+    let fold_idx = try_kfold_idx(
+        &labels,
+        crossval_options.fold_num,
+        &crossval_options.split,
+        crossval_options.seed,
+    )?;
+
+    let mut folds = Vec::new();
+    for fold in 0..crossval_options.fold_num {
+        let _phase = enter_phase(tracing::info_span!("crossval_fold", fold));
+
+        if fold > 0 {
+            arch_search = create_network(fold);
+        }
+
+        let valid_fold = (fold + 1) % crossval_options.fold_num;
+
+        let test_idx = &fold_idx[fold as usize];
+        let valid_idx = &fold_idx[valid_fold as usize];
+        let train_idx: Vec<u64> = (0..crossval_options.fold_num)
+            .filter(|f| (*f != fold) && (*f != valid_fold))
+            .flat_map(|f| fold_idx[f as usize].iter().cloned())
+            .collect();
+
+        let train_X = samples_to_hash(&samples_X, &train_idx, input_size, batch_size);
+        let train_Y = samples_to_hash(&samples_Y, &train_idx, output_size, batch_size);
+        let valid_X = samples_to_hash(&samples_X, valid_idx, input_size, batch_size);
+        let valid_Y = samples_to_hash(&samples_Y, valid_idx, output_size, batch_size);
+        let test_X = samples_to_hash(&samples_X, test_idx, input_size, batch_size);
+        let test_Y = samples_to_hash(&samples_Y, test_idx, output_size, batch_size);
+
+        if train_X.is_empty() || valid_X.is_empty() || test_X.is_empty() {
+            return Err(Error::InvalidDataset(format!(
+                "fold {} has {} training, {} validation and {} held-out samples, fewer than batch_size {}",
+                fold,
+                train_idx.len(),
+                valid_idx.len(),
+                test_idx.len(),
+                batch_size
+            )));
+        }
+
+        let mut alpha_max_vec = Vec::new();
+        let mut loss_vec = Vec::new();
+        let mut crossval_vec = Vec::new();
+        let mut loss_status = loss_status_type::LOSS_OVERFLOW;
+
+        train_network(
+            &train_X,
+            &train_Y,
+            &valid_X,
+            &valid_Y,
+            eval_metric,
+            eval_metric_grad,
+            train_network_options.clone(),
+            &mut alpha_max_vec,
+            &mut loss_vec,
+            &mut crossval_vec,
+            &mut arch_search,
+            &mut loss_status,
+        );
+
+        let mut Yhat_out = nohash_hasher::IntMap::default();
+        let mut eval_metric_out = Vec::new();
+
+        validate_network(
+            &test_X,
+            &test_Y,
+            eval_metric,
+            &arch_search,
+            &mut Yhat_out,
+            &mut eval_metric_out,
+        );
+
+        //Every column of the held-out outputs is one sample
+        let mut Yhat_cpu = Vec::new();
+        let mut Y_cpu = Vec::new();
+        for batch_idx in 0..(test_X.len() as u64) {
+            Yhat_cpu.extend_from_slice(&Yhat_out[&batch_idx]);
+            Y_cpu.extend_from_slice(&test_Y[&batch_idx]);
+        }
+        let col_num = (Y_cpu.len() as u64) / output_size;
+        let Yhat = arrayfire::Array::new(
+            &Yhat_cpu,
+            arrayfire::Dim4::new(&[output_size, col_num, 1, 1]),
+        );
+        let Y = arrayfire::Array::new(&Y_cpu, arrayfire::Dim4::new(&[output_size, col_num, 1, 1]));
+
+        let fold_metrics: Vec<T> = metrics
+            .iter()
+            .map(|metric| metric.metric(&Yhat, &Y))
+            .collect();
+
+        tracing::info!(
+            fold,
+            eval_metric = mean(&eval_metric_out).to_f64().unwrap_or(f64::NAN),
+            metrics = ?fold_metrics,
+            "finished fold"
+        );
+
+        folds.push(crossval_fold_type {
+            fold: fold,
+            train_num: (train_X.len() as u64) * batch_size,
+            valid_num: (valid_X.len() as u64) * batch_size,
+            test_num: (test_X.len() as u64) * batch_size,
+
+            loss_vec: loss_vec,
+            crossval_vec: crossval_vec,
+
+            eval_metric_out: eval_metric_out,
+            metrics: fold_metrics,
+        });
+    }
+
+    let fold_eval_metric: Vec<T> = folds.iter().map(|f| mean(&f.eval_metric_out)).collect();
+
+    let mut mean_metrics = Vec::new();
+    let mut std_metrics = Vec::new();
+    for k in 0..metrics.len() {
+        let values: Vec<T> = folds.iter().map(|f| f.metrics[k]).collect();
+        let value_mean = mean(&values);
+        let value_var = values
+            .iter()
+            .map(|v| (*v - value_mean) * (*v - value_mean))
+            .sum::<T>()
+            / T::from_f64(values.len() as f64);
+
+        mean_metrics.push(value_mean);
+        std_metrics.push(value_var.sqrt());
+    }
+
+    Ok(crossval_result_type {
+        folds: folds,
+
+        mean_eval_metric: mean(&fold_eval_metric),
+        mean_metrics: mean_metrics,
+        std_metrics: std_metrics,
+    })
+}
+
+pub fn cross_validate_network<T: raybnn_float_trait>(
+    data_X: &nohash_hasher::IntMap<u64, Vec<T>>,
+    data_Y: &nohash_hasher::IntMap<u64, Vec<T>>,
+
+    create_network: impl Fn(u64) -> arch_search_type<T>,
+
+    eval_metric: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> T + Copy,
+    eval_metric_grad: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> arrayfire::Array<T> + Copy,
+
+    train_network_options: &train_network_options_type<T>,
+    metrics: &[&dyn metric_trait<T>],
+    crossval_options: &crossval_options_type,
+) -> crossval_result_type<T> {
+    try_cross_validate_network(
+        data_X,
+        data_Y,
+        create_network,
+        eval_metric,
+        eval_metric_grad,
+        train_network_options,
+        metrics,
+        crossval_options,
+    )
+    .unwrap_or_else(|e| panic!("{}", e))
+}
+
+fn mean<T: raybnn_float_trait>(values: &[T]) -> T {
+    if values.is_empty() {
+        return T::nan();
+    }
+
+    values.iter().cloned().sum::<T>() / T::from_f64(values.len() as f64)
+}
@@ -0,0 +1,6 @@
+// f32 instantiation of the generic crate::interface::crossval module
+pub use crate::interface::crossval::*;
+
+pub type crossval_fold_type = crate::interface::crossval::crossval_fold_type<f32>;
+
+pub type crossval_result_type = crate::interface::crossval::crossval_result_type<f32>;
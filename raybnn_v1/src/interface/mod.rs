@@ -1,9 +1,13 @@
+pub mod autotransfer;
 pub mod autotransfer_f32;
 
+pub mod automatic;
 pub mod automatic_f32;
 
+pub mod autotrain;
 pub mod autotrain_f32;
 
+pub mod autotest;
 pub mod autotest_f32;
 
 pub mod autotransfer_f64;
@@ -13,3 +17,15 @@ pub mod automatic_f64;
 pub mod autotrain_f64;
 
 pub mod autotest_f64;
+
+pub mod builder;
+pub mod builder_f32;
+
+pub mod builder_f64;
+
+pub mod callback;
+
+pub mod crossval;
+pub mod crossval_f32;
+
+pub mod crossval_f64;
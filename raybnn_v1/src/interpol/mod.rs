@@ -1,3 +1,4 @@
 pub mod linear_c64;
+pub mod linear;
 pub mod linear_f32;
 pub mod linear_f64;
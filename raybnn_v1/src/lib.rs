@@ -3,6 +3,7 @@
 #![allow(non_camel_case_types)]
 pub mod diffeq;
 pub mod distribute;
+pub mod error;
 pub mod export;
 pub mod graph;
 pub mod interface;
@@ -10,3 +11,6 @@ pub mod interpol;
 pub mod neural;
 pub mod optimal;
 pub mod physics;
+pub mod precision;
+
+pub use crate::error::Error;
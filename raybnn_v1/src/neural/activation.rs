@@ -0,0 +1,131 @@
+extern crate arrayfire;
+
+use crate::precision::float_trait::raybnn_float_trait;
+
+pub fn ReLU<T: raybnn_float_trait>(X: &arrayfire::Array<T>) -> arrayfire::Array<T> {
+    let zero = T::zero();
+    let high = T::from_f64(1000000.0);
+
+    arrayfire::clamp(X, &zero, &high, false)
+}
+
+pub fn Softplus<T: raybnn_float_trait>(X: &arrayfire::Array<T>) -> arrayfire::Array<T> {
+    let mut temp = -arrayfire::abs(X);
+    temp = arrayfire::exp(&temp);
+    ReLU(X) + arrayfire::log1p(&temp)
+}
+
+pub fn UAF<T: raybnn_float_trait>(
+    X: &arrayfire::Array<T>,
+    A: &arrayfire::Array<T>,
+    B: &arrayfire::Array<T>,
+    C: &arrayfire::Array<T>,
+    D: &arrayfire::Array<T>,
+    E: &arrayfire::Array<T>,
+) -> arrayfire::Array<T> {
+    let two = T::from_f64(2.0);
+
+    // X + B
+    let mut temp0 = arrayfire::add(X, B, true);
+    // X^2
+    let mut temp1 = arrayfire::pow(X, &two, false);
+
+    // -|C|
+    let mut temp2 = -arrayfire::abs(C);
+
+    //A(X + B)  +  -|C|( X^2 )
+    temp0 = arrayfire::mul(A, &temp0, true) + arrayfire::mul(&temp2, &temp1, true);
+
+    drop(temp2);
+
+    // X - B;
+    temp1 = arrayfire::sub(X, B, true);
+    // D (X - B)
+    temp1 = arrayfire::mul(D, &temp1, true);
+
+    //Softplus( A(X + B)  +  C( X^2 ) )
+    temp0 = Softplus(&temp0);
+    //Softplus( D (X - B) )
+    temp1 = Softplus(&temp1);
+
+    //  Softplus( A(X + B)  +  C( X^2 ) ) - Softplus( D (X - B) )
+    temp0 = temp0 - temp1;
+
+    // Softplus( A(X + B)  +  C( X^2 ) ) - Softplus( D (X - B) ) + E
+    arrayfire::add(&temp0, E, true)
+}
+
+pub fn deriUAF<T: raybnn_float_trait>(
+    X: &arrayfire::Array<T>,
+    A: &arrayfire::Array<T>,
+    B: &arrayfire::Array<T>,
+    C: &arrayfire::Array<T>,
+    D: &arrayfire::Array<T>,
+    E: &arrayfire::Array<T>,
+    dX: &mut arrayfire::Array<T>,
+    dA: &mut arrayfire::Array<T>,
+    dB: &mut arrayfire::Array<T>,
+    dC: &mut arrayfire::Array<T>,
+    dD: &mut arrayfire::Array<T>,
+    dE: &mut arrayfire::Array<T>,
+) {
+    let two = T::from_f64(2.0);
+    let half = T::from_f64(0.5);
+
+    // X + B
+    let mut temp0 = arrayfire::add(X, B, true);
+    // X^2
+    let mut temp1 = arrayfire::pow(X, &two, false);
+
+    // -|C|
+    let mut temp2 = -arrayfire::abs(C);
+
+    //A(X + B)   -  |C|( X^2 )
+    let mut expcal0 = arrayfire::mul(A, &temp0, true) + arrayfire::mul(&temp2, &temp1, true);
+
+    //Sigmoid( A(X + B)   -  |C|( X^2 ) )
+    expcal0 = arrayfire::sigmoid(&expcal0);
+
+    //dA = (X + B) Sigmoid( A(X + B)   -  |C|( X^2 ) )
+    *dA = arrayfire::mul(&temp0, &expcal0, false);
+    //dB = (A) Sigmoid( A(X + B)   -  |C|( X^2 ) )
+    *dB = arrayfire::mul(A, &expcal0, true);
+
+    // -sign(C)
+    temp2 = (arrayfire::sign(C) - half) * two;
+
+    // -sign(C) (X^2)
+    temp1 = arrayfire::mul(&temp2, &temp1, true);
+
+    //dC = -sign(C)(X^2) Sigmoid( A(X + B)  +  -|C|( X^2 ) )
+    *dC = arrayfire::mul(&temp1, &expcal0, false);
+
+    // -|C|
+    temp2 = -arrayfire::abs(C);
+
+    //A - 2|C|x
+    temp0 = arrayfire::mul(&temp2, X, true) * two;
+    temp0 = arrayfire::add(A, &temp0, true);
+    // (A - 2|C|x) Sigmoid( A(X + B)  -  |C|( X^2 ) )
+    expcal0 = arrayfire::mul(&temp0, &expcal0, false);
+
+    // X - B
+    temp0 = arrayfire::sub(X, B, true);
+    //D ( X - B )
+    let mut expcal1 = arrayfire::mul(D, &temp0, true);
+
+    //Sigmoid( D ( X - B )  )
+    expcal1 = arrayfire::sigmoid(&expcal1);
+
+    *dB = dB.clone() + arrayfire::mul(D, &expcal1, true);
+
+    //dD = - (X - B) Sigmoid( D ( X - B )  )
+    *dD = -arrayfire::mul(&temp0, &expcal1, false);
+    //dE = 1
+    *dE = arrayfire::constant::<T>(T::one(), X.dims());
+
+    expcal1 = arrayfire::mul(D, &expcal1, true);
+
+    //dX = (A - 2|C|x) Sigmoid( A(X + B)  - |C|( X^2 ) ) - D Sigmoid( D ( X - B )  )
+    *dX = (expcal0 - expcal1);
+}
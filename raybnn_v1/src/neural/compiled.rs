@@ -0,0 +1,273 @@
+extern crate arrayfire;
+
+use crate::neural::activation::UAF;
+
+use crate::neural::network::neural_network_type;
+
+use crate::neural::normalization::norm_forward;
+use crate::neural::normalization::norm_prepare;
+use crate::neural::normalization::norm_state_type;
+
+use crate::precision::float_trait::raybnn_float_trait;
+
+use crate::error::Error;
+
+/*
+Inference only neural network
+
+compile_network builds the sparse CSR weight matrix and splits network_params into H and the
+UAF parameters once, so predictions do not recompute the parameter layout of every call.
+Training only state (glia_pos, neuron_pos, the evolution info and the internal states Z and Q
+of every step) is not stored.
+
+Predictions are the same as interface::autotest_f32::test_network. The input of a sequence is
+given in the first traj_size steps, and the output is read from the output neurons after
+proc_num - 1 more steps. Batch norm uses the running statistics.
+
+Example
+let model = compile_network(&arch_search.neural_network).expect("invalid network");
+let yhat = model.predict(&x);
+
+*/
+
+pub struct compiled_model_type<T: raybnn_float_trait> {
+    pub neuron_size: u64,
+    pub input_size: u64,
+    pub output_size: u64,
+    pub proc_num: u64,
+
+    pub W: arrayfire::Array<T>,
+    pub H: arrayfire::Array<T>,
+    pub A: arrayfire::Array<T>,
+    pub B: arrayfire::Array<T>,
+    pub C: arrayfire::Array<T>,
+    pub D: arrayfire::Array<T>,
+    pub E: arrayfire::Array<T>,
+
+    pub output_idx: arrayfire::Array<i32>,
+    pub norm: norm_state_type<T>,
+}
+
+/*
+Creates the inference only model of a neural network
+
+Inputs
+neural_network:      Trained neural network
+
+Outputs:
+model:               Compiled model, or InvalidNetwork if input_size is 0 or network_params
+                     does not have the layout W, H, A, B, C, D, E used by
+                     state_space_forward_batch
+
+*/
+
+pub fn compile_network<T: raybnn_float_trait>(
+    neural_network: &neural_network_type<T>,
+) -> Result<compiled_model_type<T>, Error> {
+    let neuron_size: u64 = neural_network.netdata.neuron_size.clone();
+    let input_size: u64 = neural_network.netdata.input_size.clone();
+    let output_size: u64 = neural_network.netdata.output_size.clone();
+    let proc_num: u64 = neural_network.netdata.proc_num.clone();
+
+    if input_size == 0 {
+        return Err(Error::InvalidNetwork(
+            "input_size must be greater than 0".to_string(),
+        ));
+    }
+    if input_size > neuron_size {
+        return Err(Error::InvalidNetwork(format!(
+            "input_size ({}) must be <= neuron_size ({})",
+            input_size, neuron_size
+        )));
+    }
+
+    let W_size = neural_network.WColIdx.dims()[0];
+    let param_size = neural_network.network_params.dims()[0];
+
+    if param_size != W_size + 6 * neuron_size {
+        return Err(Error::InvalidNetwork(format!(
+            "network_params has {} values, expected {} weights and 6 * neuron_size ({})",
+            param_size,
+            W_size,
+            6 * neuron_size
+        )));
+    }
+
+    let active_size = neural_network.neuron_idx.dims()[0];
+    if active_size < output_size {
+        return Err(Error::InvalidNetwork(format!(
+            "neuron_idx has {} neurons, expected at least output_size ({})",
+            active_size, output_size
+        )));
+    }
+    if proc_num == 0 {
+        return Err(Error::InvalidNetwork(
+            "proc_num must be greater than 0".to_string(),
+        ));
+    }
+
+    let param_seqs = |start: u64, size: u64| {
+        [arrayfire::Seq::new(
+            start as i32,
+            (start + size - 1) as i32,
+            1i32,
+        )]
+    };
+
+    let Hstart = W_size;
+    let Astart = Hstart + neuron_size;
+    let Bstart = Astart + neuron_size;
+    let Cstart = Bstart + neuron_size;
+    let Dstart = Cstart + neuron_size;
+    let Estart = Dstart + neuron_size;
+
+    let params = &neural_network.network_params;
+
+    let WValues = arrayfire::index(params, &param_seqs(0, W_size));
+    let W = arrayfire::sparse::<T>(
+        neuron_size,
+        neuron_size,
+        &WValues,
+        &neural_network.WRowIdxCSR,
+        &neural_network.WColIdx,
+        arrayfire::SparseFormat::CSR,
+    );
+
+    let output_idx = arrayfire::rows(
+        &neural_network.neuron_idx,
+        (active_size - output_size) as i64,
+        (active_size - 1) as i64,
+    );
+
+    let mut norm = neural_network.norm.clone();
+    norm.training = false;
+
+    Ok(compiled_model_type {
+        neuron_size: neuron_size,
+        input_size: input_size,
+        output_size: output_size,
+        proc_num: proc_num,
+
+        W: W,
+        H: arrayfire::index(params, &param_seqs(Hstart, neuron_size)),
+        A: arrayfire::index(params, &param_seqs(Astart, neuron_size)),
+        B: arrayfire::index(params, &param_seqs(Bstart, neuron_size)),
+        C: arrayfire::index(params, &param_seqs(Cstart, neuron_size)),
+        D: arrayfire::index(params, &param_seqs(Dstart, neuron_size)),
+        E: arrayfire::index(params, &param_seqs(Estart, neuron_size)),
+
+        output_idx: output_idx,
+        norm: norm,
+    })
+}
+
+impl<T: raybnn_float_trait> compiled_model_type<T> {
+    /*
+    Predicts a batch
+
+    Inputs
+    X:                   Input array, dims (input_size, batch_size, traj_size)
+
+    Outputs:
+    Yhat:                Output array, dims (output_size, batch_size, traj_size), or
+                         ShapeMismatch if X does not have input_size rows
+
+    */
+    pub fn try_predict_batch(&self, X: &arrayfire::Array<T>) -> Result<arrayfire::Array<T>, Error> {
+        let X_dims = X.dims();
+        if X_dims[0] != self.input_size {
+            return Err(Error::ShapeMismatch {
+                file: "X".to_string(),
+                expected: self.input_size,
+                found: X_dims[0],
+            });
+        }
+
+        let batch_size = X_dims[1];
+        let traj_size = X_dims[2];
+        let traj_steps = traj_size + self.proc_num - 1;
+
+        let mut norm = self.norm.clone();
+        norm_prepare(self.neuron_size, batch_size, traj_steps, &mut norm);
+
+        let S_dims = arrayfire::Dim4::new(&[self.neuron_size, batch_size, 1, 1]);
+        let mut S = arrayfire::constant::<T>(T::zero(), S_dims);
+
+        let zero_dims = arrayfire::Dim4::new(&[self.input_size, batch_size, 1, 1]);
+        let zero_input = arrayfire::constant::<T>(T::zero(), zero_dims);
+
+        let seqs = &[
+            arrayfire::Seq::new(0.0f32, (self.input_size - 1) as f32, 1.0f32),
+            arrayfire::Seq::default(),
+        ];
+
+        let Yhat_dims = arrayfire::Dim4::new(&[self.output_size, batch_size, traj_size, 1]);
+        let mut Yhat = arrayfire::constant::<T>(T::zero(), Yhat_dims);
+
+        for i in 0u64..traj_steps {
+            if i < traj_size {
+                arrayfire::assign_seq(&mut S, seqs, &arrayfire::slice(X, i as i64));
+            } else {
+                arrayfire::assign_seq(&mut S, seqs, &zero_input);
+            }
+
+            S = arrayfire::matmul(
+                &self.W,
+                &S,
+                arrayfire::MatProp::NONE,
+                arrayfire::MatProp::NONE,
+            );
+            S = arrayfire::add(&S, &self.H, true);
+            S = norm_forward(&S, i as i64, &mut norm);
+            S = UAF(&S, &self.A, &self.B, &self.C, &self.D, &self.E);
+
+            if i >= (self.proc_num - 1) {
+                let output = arrayfire::lookup(&S, &self.output_idx, 0);
+                arrayfire::set_slice(&mut Yhat, &output, (i - (self.proc_num - 1)) as i64);
+            }
+        }
+
+        Ok(Yhat)
+    }
+
+    pub fn predict_batch(&self, X: &arrayfire::Array<T>) -> arrayfire::Array<T> {
+        self.try_predict_batch(X)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /*
+    Predicts one sample
+
+    Inputs
+    x:                   Input of one sample, input_size values for each step of the sequence
+
+    Outputs:
+    yhat:                Output of one sample, output_size values for each step of the sequence,
+                         or ShapeMismatch if the length of x is not a multiple of input_size
+
+    */
+    pub fn try_predict(&self, x: &[T]) -> Result<Vec<T>, Error> {
+        let input_size = self.input_size as usize;
+        if (x.len() == 0) || (x.len() % input_size != 0) {
+            return Err(Error::ShapeMismatch {
+                file: "x".to_string(),
+                expected: (input_size * (x.len() / input_size).max(1)) as u64,
+                found: x.len() as u64,
+            });
+        }
+
+        let traj_size = (x.len() / input_size) as u64;
+        let X = arrayfire::Array::new(x, arrayfire::Dim4::new(&[self.input_size, 1, traj_size, 1]));
+
+        let Yhat = self.try_predict_batch(&X)?;
+
+        let mut yhat = vec![T::zero(); Yhat.elements()];
+        Yhat.host(&mut yhat);
+
+        Ok(yhat)
+    }
+
+    pub fn predict(&self, x: &[T]) -> Vec<T> {
+        self.try_predict(x).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
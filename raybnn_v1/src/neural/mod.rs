@@ -1,3 +1,8 @@
+pub mod activation;
+pub mod compiled;
+pub mod network;
+pub mod normalization;
+
 pub mod activation_f64;
 pub mod network_f64;
 pub mod restructure_f64;
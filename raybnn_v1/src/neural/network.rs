@@ -0,0 +1,1223 @@
+extern crate arrayfire;
+
+use nohash_hasher;
+use std::collections::HashMap;
+
+use crate::neural::activation::deriUAF;
+use crate::neural::activation::UAF;
+
+use crate::neural::normalization::create_norm_options;
+use crate::neural::normalization::create_norm_state;
+use crate::neural::normalization::norm_backward;
+use crate::neural::normalization::norm_forward;
+use crate::neural::normalization::norm_kind_type;
+use crate::neural::normalization::norm_prepare;
+use crate::neural::normalization::norm_state_type;
+
+use crate::physics::neighbor::neighbor_search_type;
+
+use crate::precision::float_trait::raybnn_float_trait;
+use crate::precision::reduce::sum;
+
+use crate::graph::large_sparse_i32::COO_batch_find;
+use crate::graph::large_sparse_i32::CSR_to_COO;
+
+use crate::graph::tree_i32::find_unique;
+use serde::{Deserialize, Serialize};
+
+const COO_find_limit: u64 = 1500000000;
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound(serialize = "T: raybnn_float_trait", deserialize = "T: raybnn_float_trait"))]
+pub struct network_metadata_type<T: raybnn_float_trait> {
+    pub neuron_size: u64,
+    pub input_size: u64,
+    pub output_size: u64,
+    pub proc_num: u64,
+    pub active_size: u64,
+    pub space_dims: u64,
+    pub step_num: u64,
+    pub batch_size: u64,
+    pub del_unused_neuron: bool,
+    pub time_step: T,
+    pub nratio: T,
+    pub neuron_std: T,
+    pub sphere_rad: T,
+    pub neuron_rad: T,
+    pub con_rad: T,
+    pub init_prob: T,
+    pub add_neuron_rate: T,
+    pub del_neuron_rate: T,
+    pub center_const: T,
+    pub spring_const: T,
+    pub repel_const: T,
+    #[serde(default)]
+    pub neighbor_search: neighbor_search_type,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound(serialize = "T: raybnn_float_trait", deserialize = "T: raybnn_float_trait"))]
+pub struct neural_network_type<T: raybnn_float_trait> {
+    pub netdata: network_metadata_type<T>,
+    pub WRowIdxCSR: arrayfire::Array<i32>,
+    pub WColIdx: arrayfire::Array<i32>,
+    pub network_params: arrayfire::Array<T>,
+    pub glia_pos: arrayfire::Array<T>,
+    pub neuron_pos: arrayfire::Array<T>,
+    pub neuron_idx: arrayfire::Array<i32>,
+    pub norm: norm_state_type<T>,
+}
+
+pub fn print_netdata<T: raybnn_float_trait>(netdata: &network_metadata_type<T>) {
+    println!("\n\n******Network Information******");
+    println!("neuron_size: {}", netdata.neuron_size);
+    println!("input_size: {}", netdata.input_size);
+    println!("output_size: {}", netdata.output_size);
+    println!("proc_num: {}", netdata.proc_num);
+    println!("active_size: {}", netdata.active_size);
+    println!("space_dims: {}", netdata.space_dims);
+    println!("step_num: {}", netdata.step_num);
+    println!("batch_size: {}", netdata.batch_size);
+    println!("del_unused_neuron: {}", netdata.del_unused_neuron);
+
+    println!("time_step: {}", netdata.time_step);
+    println!("nratio: {}", netdata.nratio);
+    println!("neuron_std: {}", netdata.neuron_std);
+    println!("sphere_rad: {}", netdata.sphere_rad);
+    println!("neuron_rad: {}", netdata.neuron_rad);
+    println!("con_rad: {}", netdata.con_rad);
+    println!("init_prob: {}", netdata.init_prob);
+    println!("add_neuron_rate: {}", netdata.add_neuron_rate);
+    println!("del_neuron_rate: {}", netdata.del_neuron_rate);
+    println!("center_const: {}", netdata.center_const);
+    println!("spring_const: {}", netdata.spring_const);
+    println!("repel_const: {}", netdata.repel_const);
+    println!("neighbor_search: {:?}", netdata.neighbor_search);
+    println!("************\n\n");
+}
+
+pub fn clone_netdata<T: raybnn_float_trait>(netdata: &network_metadata_type<T>) -> network_metadata_type<T> {
+    let neuron_size: u64 = netdata.neuron_size.clone();
+    let input_size: u64 = netdata.input_size.clone();
+    let output_size: u64 = netdata.output_size.clone();
+    let proc_num: u64 = netdata.proc_num.clone();
+    let active_size: u64 = netdata.active_size.clone();
+    let space_dims: u64 = netdata.space_dims.clone();
+    let step_num: u64 = netdata.step_num.clone();
+    let batch_size: u64 = netdata.batch_size.clone();
+
+    let del_unused_neuron: bool = netdata.del_unused_neuron.clone();
+
+    let time_step: T = netdata.time_step.clone();
+    let nratio: T = netdata.nratio.clone();
+    let neuron_std: T = netdata.neuron_std.clone();
+    let sphere_rad: T = netdata.sphere_rad.clone();
+    let neuron_rad: T = netdata.neuron_rad.clone();
+    let con_rad: T = netdata.con_rad.clone();
+    let init_prob: T = netdata.init_prob.clone();
+    let add_neuron_rate: T = netdata.add_neuron_rate.clone();
+    let del_neuron_rate: T = netdata.del_neuron_rate.clone();
+    let center_const: T = netdata.center_const.clone();
+    let spring_const: T = netdata.spring_const.clone();
+    let repel_const: T = netdata.repel_const.clone();
+
+    let neighbor_search: neighbor_search_type = netdata.neighbor_search.clone();
+
+    let newnetdata = network_metadata_type {
+        neuron_size: neuron_size,
+        input_size: input_size,
+        output_size: output_size,
+        proc_num: proc_num,
+        active_size: active_size,
+        space_dims: space_dims,
+        step_num: step_num,
+        batch_size: batch_size,
+        del_unused_neuron: del_unused_neuron,
+
+        time_step: time_step,
+        nratio: nratio,
+        neuron_std: neuron_std,
+        sphere_rad: sphere_rad,
+        neuron_rad: neuron_rad,
+        con_rad: con_rad,
+        init_prob: init_prob,
+        add_neuron_rate: add_neuron_rate,
+        del_neuron_rate: del_neuron_rate,
+        center_const: center_const,
+        spring_const: spring_const,
+        repel_const: repel_const,
+
+        neighbor_search: neighbor_search,
+    };
+
+    newnetdata
+}
+
+pub fn clone_neural_network<T: raybnn_float_trait>(neural_network: &neural_network_type<T>) -> neural_network_type<T> {
+    let mut new_neural_network = neural_network_type {
+        netdata: clone_netdata(&neural_network.netdata),
+        WRowIdxCSR: neural_network.WRowIdxCSR.clone(),
+        WColIdx: neural_network.WColIdx.clone(),
+        network_params: neural_network.network_params.clone(),
+        glia_pos: neural_network.glia_pos.clone(),
+        neuron_pos: neural_network.neuron_pos.clone(),
+        neuron_idx: neural_network.neuron_idx.clone(),
+        norm: neural_network.norm.clone(),
+    };
+
+    new_neural_network
+}
+
+pub fn create_nullnetdata<T: raybnn_float_trait>() -> network_metadata_type<T> {
+    let netdata: network_metadata_type<T> = network_metadata_type {
+        neuron_size: 0,
+        input_size: 0,
+        output_size: 0,
+        proc_num: 0,
+        active_size: 0,
+        space_dims: 0,
+        step_num: 0,
+        batch_size: 0,
+        del_unused_neuron: false,
+
+        time_step: T::zero(),
+        nratio: T::zero(),
+        neuron_std: T::zero(),
+        sphere_rad: T::zero(),
+        neuron_rad: T::zero(),
+        con_rad: T::zero(),
+        init_prob: T::zero(),
+        add_neuron_rate: T::zero(),
+        del_neuron_rate: T::zero(),
+        center_const: T::zero(),
+        spring_const: T::zero(),
+        repel_const: T::zero(),
+
+        neighbor_search: neighbor_search_type::DENSE,
+    };
+
+    netdata
+}
+
+pub fn UAF_initial_as_identity<T: raybnn_float_trait>(
+    netdata: &network_metadata_type<T>,
+    //H: &mut arrayfire::Array<T>,
+    A: &mut arrayfire::Array<T>,
+    B: &mut arrayfire::Array<T>,
+    C: &mut arrayfire::Array<T>,
+    D: &mut arrayfire::Array<T>,
+    E: &mut arrayfire::Array<T>,
+) {
+    let neuron_size: u64 = netdata.neuron_size.clone();
+    let input_size: u64 = netdata.input_size.clone();
+    let output_size: u64 = netdata.output_size.clone();
+    let proc_num: u64 = netdata.proc_num.clone();
+    let active_size: u64 = netdata.active_size.clone();
+    let space_dims: u64 = netdata.space_dims.clone();
+    let step_num: u64 = netdata.step_num.clone();
+
+    let del_unused_neuron: bool = netdata.del_unused_neuron.clone();
+
+    let time_step: T = netdata.time_step.clone();
+    let nratio: T = netdata.nratio.clone();
+    let neuron_std: T = netdata.neuron_std.clone();
+    let sphere_rad: T = netdata.sphere_rad.clone();
+    let neuron_rad: T = netdata.neuron_rad.clone();
+    let con_rad: T = netdata.con_rad.clone();
+    let center_const: T = netdata.center_const.clone();
+    let spring_const: T = netdata.spring_const.clone();
+    let repel_const: T = netdata.repel_const.clone();
+
+    let H_dims = arrayfire::Dim4::new(&[neuron_size, 1, 1, 1]);
+    //  *H = 0.001*neuron_std*arrayfire::randn::<T>(H_dims);
+    let one = T::one();
+    let scale0 = T::from_f64(0.0001) * neuron_std;
+    let scale1 = T::from_f64(0.00001) * neuron_std;
+    *A = arrayfire::randn::<T>(H_dims) * scale0 + one;
+    *B = arrayfire::randn::<T>(H_dims) * scale0;
+    *C = arrayfire::randn::<T>(H_dims) * scale1;
+    *D = arrayfire::randn::<T>(H_dims) * scale1 - one;
+    *E = arrayfire::randn::<T>(H_dims) * scale1;
+}
+
+pub fn UAF_initial_as_tanh<T: raybnn_float_trait>(
+    netdata: &network_metadata_type<T>,
+    //H: &mut arrayfire::Array<T>,
+    A: &mut arrayfire::Array<T>,
+    B: &mut arrayfire::Array<T>,
+    C: &mut arrayfire::Array<T>,
+    D: &mut arrayfire::Array<T>,
+    E: &mut arrayfire::Array<T>,
+) {
+    let neuron_size: u64 = netdata.neuron_size.clone();
+    let input_size: u64 = netdata.input_size.clone();
+    let output_size: u64 = netdata.output_size.clone();
+    let proc_num: u64 = netdata.proc_num.clone();
+    let active_size: u64 = netdata.active_size.clone();
+    let space_dims: u64 = netdata.space_dims.clone();
+    let step_num: u64 = netdata.step_num.clone();
+
+    let del_unused_neuron: bool = netdata.del_unused_neuron.clone();
+
+    let time_step: T = netdata.time_step.clone();
+    let nratio: T = netdata.nratio.clone();
+    let neuron_std: T = netdata.neuron_std.clone();
+    let sphere_rad: T = netdata.sphere_rad.clone();
+    let neuron_rad: T = netdata.neuron_rad.clone();
+    let con_rad: T = netdata.con_rad.clone();
+    let center_const: T = netdata.center_const.clone();
+    let spring_const: T = netdata.spring_const.clone();
+    let repel_const: T = netdata.repel_const.clone();
+
+    let H_dims = arrayfire::Dim4::new(&[neuron_size, 1, 1, 1]);
+    //   *H = 0.0000001*neuron_std*arrayfire::randn::<T>(H_dims);
+    let tanh_const = T::from_f64(2.12616013);
+    let scale0 = T::from_f64(0.0001) * neuron_std;
+    let scale1 = T::from_f64(0.0000001) * neuron_std;
+    *A = arrayfire::randn::<T>(H_dims) * scale0 + tanh_const;
+    *B = arrayfire::randn::<T>(H_dims) * scale0 + (T::one() / tanh_const);
+    *C = arrayfire::randn::<T>(H_dims) * scale1;
+    *D = arrayfire::randn::<T>(H_dims) * scale0 + tanh_const;
+    *E = arrayfire::randn::<T>(H_dims) * scale0 - T::one();
+}
+
+pub fn xavier_init<T: raybnn_float_trait>(
+    in_idx: &arrayfire::Array<i32>,
+    WRowIdxCOO: &arrayfire::Array<i32>,
+    WColIdx: &arrayfire::Array<i32>,
+    neuron_size: u64,
+    depth: u64,
+
+    WValues: &mut arrayfire::Array<T>,
+    H: &mut arrayfire::Array<T>,
+) {
+    let init_scale = T::from_f64(0.000001);
+    let half = T::from_f64(0.5);
+
+    *WValues = arrayfire::randn::<T>(WValues.dims()) * init_scale;
+
+    let H_dims = arrayfire::Dim4::new(&[neuron_size, 1, 1, 1]);
+    *H = arrayfire::randn::<T>(H_dims) * init_scale;
+
+    let temp_dims = arrayfire::Dim4::new(&[1, 1, 1, 1]);
+
+    let mut out_idx = in_idx.clone();
+    let mut valsel = arrayfire::constant::<i32>(0, temp_dims);
+
+    let COO_batch_size = 1 + ((COO_find_limit / WColIdx.dims()[0]) as u64);
+
+    let mut input_degree = 0;
+    let mut output_degree = 0;
+
+    for i in 0..depth {
+        valsel = COO_batch_find(WColIdx, &out_idx, COO_batch_size);
+        if valsel.dims()[0] == 0 {
+            break;
+        }
+        out_idx = arrayfire::lookup(WRowIdxCOO, &valsel, 0);
+
+        out_idx = find_unique(&out_idx, neuron_size);
+
+        output_degree = valsel.dims()[0];
+
+        let mulitiplier =
+            T::from_f64((6.0f64 / ((input_degree + output_degree) as f64)).sqrt() * 2.0f64);
+        let mut newWValues = arrayfire::randu::<T>(valsel.dims());
+        newWValues = (newWValues - half) * (mulitiplier);
+
+        let mut idxrs = arrayfire::Indexer::default();
+        idxrs.set_index(&valsel, 0, None);
+        arrayfire::assign_gen(WValues, &idxrs, &newWValues);
+
+        drop(newWValues);
+        drop(idxrs);
+
+        let mut newH = arrayfire::randu::<T>(out_idx.dims());
+        newH = (newH - half) * (mulitiplier);
+
+        let mut idxrs2 = arrayfire::Indexer::default();
+        idxrs2.set_index(&out_idx, 0, None);
+        arrayfire::assign_gen(H, &idxrs2, &newH);
+
+        if out_idx.dims()[0] == 0 {
+            break;
+        }
+
+        input_degree = valsel.dims()[0];
+    }
+}
+
+/*
+Forward pass using CSR weighted adjacency sparse matrices and UAF.
+Generates all internal states and the neural network output
+
+
+Inputs
+netdata:             Neural Network Metadata
+X:                   Input Matrix
+WRowIdxCSR:          Row sparse matrix of the weighted adjacency matrix
+WColIdx:             Column sparse matrix of the weighted adjacency matrix
+Wseqs:               Indexes of the weight parameters
+Hseqs:               Indexes of the bias parameters
+Aseqs:               Indexes of the UAF A parameters
+Bseqs:               Indexes of the UAF B parameters
+Cseqs:               Indexes of the UAF C parameters
+Dseqs:               Indexes of the UAF D parameters
+Eseqs:               Indexes of the UAF E parameters
+network_params:      All trainable parameters in the network
+
+
+Outputs:
+Z:                   Internal State Matrix Z
+Q:                   Internal State Matrix Q
+
+*/
+
+pub fn state_space_forward_batch<T: raybnn_float_trait>(
+    netdata: &network_metadata_type<T>,
+    X: &arrayfire::Array<T>,
+
+    WRowIdxCSR: &arrayfire::Array<i32>,
+    WColIdx: &arrayfire::Array<i32>,
+
+    Wseqs: &[arrayfire::Seq<i32>; 1],
+    Hseqs: &[arrayfire::Seq<i32>; 1],
+    Aseqs: &[arrayfire::Seq<i32>; 1],
+    Bseqs: &[arrayfire::Seq<i32>; 1],
+    Cseqs: &[arrayfire::Seq<i32>; 1],
+    Dseqs: &[arrayfire::Seq<i32>; 1],
+    Eseqs: &[arrayfire::Seq<i32>; 1],
+    network_params: &arrayfire::Array<T>,
+
+    Z: &mut arrayfire::Array<T>,
+    Q: &mut arrayfire::Array<T>,
+) {
+    let mut norm = create_norm_state(create_norm_options(norm_kind_type::NONE));
+
+    state_space_forward_batch_norm(
+        netdata,
+        X,
+        WRowIdxCSR,
+        WColIdx,
+        Wseqs,
+        Hseqs,
+        Aseqs,
+        Bseqs,
+        Cseqs,
+        Dseqs,
+        Eseqs,
+        network_params,
+        &mut norm,
+        Z,
+        Q,
+    );
+}
+
+/*
+Forward pass of state_space_forward_batch with the per step normalization of
+crate::neural::normalization. Networks trained with normalization must use this
+forward pass, with norm.training set to false for evaluation.
+
+Inputs
+norm:                Normalization state of the neural network
+Other inputs are the same as state_space_forward_batch
+
+Outputs:
+Z:                   Internal State Matrix Z with normalized pre-activations
+Q:                   Internal State Matrix Q
+norm:                Statistics of this forward pass
+
+*/
+
+pub fn state_space_forward_batch_norm<T: raybnn_float_trait>(
+    netdata: &network_metadata_type<T>,
+    X: &arrayfire::Array<T>,
+
+    WRowIdxCSR: &arrayfire::Array<i32>,
+    WColIdx: &arrayfire::Array<i32>,
+
+    Wseqs: &[arrayfire::Seq<i32>; 1],
+    Hseqs: &[arrayfire::Seq<i32>; 1],
+    Aseqs: &[arrayfire::Seq<i32>; 1],
+    Bseqs: &[arrayfire::Seq<i32>; 1],
+    Cseqs: &[arrayfire::Seq<i32>; 1],
+    Dseqs: &[arrayfire::Seq<i32>; 1],
+    Eseqs: &[arrayfire::Seq<i32>; 1],
+    network_params: &arrayfire::Array<T>,
+    norm: &mut norm_state_type<T>,
+
+    Z: &mut arrayfire::Array<T>,
+    Q: &mut arrayfire::Array<T>,
+) {
+    let neuron_size: u64 = netdata.neuron_size.clone();
+    //let proc_num: u64 = netdata.proc_num.clone();
+    let input_size: u64 = netdata.input_size.clone();
+    let batch_size: u64 = netdata.batch_size.clone();
+
+    // Zslices should be traj_steps
+    let Zslices: i64 = Z.dims()[2] as i64;
+
+    let X_slices: i64 = X.dims()[2] as i64;
+
+    // S would initiate to zero in every batch
+    let S_dims = arrayfire::Dim4::new(&[neuron_size, batch_size, 1, 1]);
+    let mut S = arrayfire::constant::<T>(T::zero(), S_dims);
+
+    let mut tempx = arrayfire::slice(X, 0);
+    let seqs = &[
+        arrayfire::Seq::new(0.0f32, (input_size - 1) as f32, 1.0f32),
+        arrayfire::Seq::default(),
+    ];
+
+    let H = arrayfire::index(&network_params, Hseqs);
+    let A = arrayfire::index(&network_params, Aseqs);
+    let B = arrayfire::index(&network_params, Bseqs);
+    let C = arrayfire::index(&network_params, Cseqs);
+    let D = arrayfire::index(&network_params, Dseqs);
+    let E = arrayfire::index(&network_params, Eseqs);
+
+    let WValues = arrayfire::index(network_params, Wseqs);
+
+    norm_prepare(neuron_size, batch_size, Zslices as u64, norm);
+
+    let W = arrayfire::sparse::<T>(
+        neuron_size,
+        neuron_size,
+        &WValues,
+        WRowIdxCSR,
+        WColIdx,
+        arrayfire::SparseFormat::CSR,
+    );
+
+    for i in 0i64..Zslices {
+        if X_slices > 1 {
+            tempx = arrayfire::slice(X, i);
+            arrayfire::assign_seq(&mut S, seqs, &tempx);
+            drop(tempx);
+        } else {
+            arrayfire::assign_seq(&mut S, seqs, &X);
+        }
+
+        S = arrayfire::matmul(&W, &S, arrayfire::MatProp::NONE, arrayfire::MatProp::NONE);
+        S = arrayfire::add(&S, &H, true);
+        S = norm_forward(&S, i, norm);
+        arrayfire::set_slice(Z, &S, i);
+
+        S = UAF(&S, &A, &B, &C, &D, &E);
+        arrayfire::set_slice(Q, &S, i);
+    }
+}
+
+/*
+Backward pass using CSR weighted adjacency sparse matrices and UAF.
+Generates the gradients of the sparse weighted adjacency matrix
+
+
+Inputs
+netdata:             Neural Network Metadata
+X:                   Input Matrix
+network_params:      All trainable parameters in the network
+Z:                   Internal State matrix Z
+Q:                   Internal State matrix Q
+Y:                   Target Matrix to fit
+loss_grad:           Loss function
+neuron_idx:          Indexes of the neurons
+idxsel_out:          Indexes of the UAF parameters
+valsel_out:          Indexes of the UAF values
+cvec_out:            Indexes of the UAF column sparse vector
+dXsel_out:           Indexes of the dX values
+nrows_out:           Number of rows in UAF
+sparseval_out:       Indexes of the values in the sparse matrix
+sparserow_out:       Indexes of the rows in the sparse matrix
+sparsecol_out:       Indexes of the columns in the sparse matrix
+Hidxsel_out:         Indexes of the bias vector H
+Aidxsel_out:         Indexes of the UAF vector A
+Bidxsel_out:         Indexes of the UAF vector B
+Cidxsel_out:         Indexes of the UAF vector C
+Didxsel_out:         Indexes of the UAF vector D
+Eidxsel_out:         Indexes of the UAF vector E
+combidxsel_out:      Indexes of the all vectors
+dAseqs_out:          Indexes of the dA
+dBseqs_out:          Indexes of the dB
+dCseqs_out:          Indexes of the dC
+dDseqs_out:          Indexes of the dD
+dEseqs_out:          Indexes of the dE
+
+
+Outputs:
+grad:                   Gradient of all trainable parameters
+
+
+*/
+
+pub fn state_space_backward_group2<T: raybnn_float_trait>(
+    netdata: &network_metadata_type<T>,
+    X: &arrayfire::Array<T>,
+
+    network_params: &arrayfire::Array<T>,
+
+    Z: &arrayfire::Array<T>,
+    Q: &arrayfire::Array<T>,
+    Y: &arrayfire::Array<T>,
+    loss_grad: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> arrayfire::Array<T>,
+    neuron_idx: &arrayfire::Array<i32>,
+
+    idxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    valsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+
+    cvec_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    dXsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+
+    nrows_out: &nohash_hasher::IntMap<i64, u64>,
+    sparseval_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    sparserow_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    sparsecol_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+
+    Hidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    Aidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    Bidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    Cidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    Didxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    Eidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    combidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+
+    dAseqs_out: &nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]>,
+    dBseqs_out: &nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]>,
+    dCseqs_out: &nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]>,
+    dDseqs_out: &nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]>,
+    dEseqs_out: &nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]>,
+
+    grad: &mut arrayfire::Array<T>,
+) {
+    let norm = create_norm_state(create_norm_options(norm_kind_type::NONE));
+
+    state_space_backward_selected(
+        netdata,
+        X,
+        network_params,
+        Z,
+        Q,
+        Y,
+        loss_grad,
+        neuron_idx,
+        idxsel_out,
+        valsel_out,
+        cvec_out,
+        dXsel_out,
+        nrows_out,
+        sparseval_out,
+        sparserow_out,
+        sparsecol_out,
+        Hidxsel_out,
+        Aidxsel_out,
+        Bidxsel_out,
+        Cidxsel_out,
+        Didxsel_out,
+        Eidxsel_out,
+        combidxsel_out,
+        dAseqs_out,
+        dBseqs_out,
+        dCseqs_out,
+        dDseqs_out,
+        dEseqs_out,
+        &norm,
+        grad,
+    );
+}
+
+/*
+Backward pass of state_space_backward_group2 through the per step normalization
+of state_space_forward_batch_norm
+
+The layer norm statistics of a step mix every neuron, so LAYER_NORM uses
+state_space_backward_layer_norm over all neurons instead of the neurons on a path
+to the output.
+
+Inputs
+WRowIdxCSR:          Row sparse matrix of the weights
+WColIdx:             Column sparse matrix of the weights
+Wseqs..Eseqs:        Location of W, H and of the UAF parameters A..E in network_params
+norm:                Normalization state of the forward pass that computed Z and Q
+Other inputs are the same as state_space_backward_group2
+
+Outputs:
+grad:                   Gradient of all trainable parameters
+
+*/
+
+pub fn state_space_backward_group2_norm<T: raybnn_float_trait>(
+    netdata: &network_metadata_type<T>,
+    X: &arrayfire::Array<T>,
+
+    WRowIdxCSR: &arrayfire::Array<i32>,
+    WColIdx: &arrayfire::Array<i32>,
+
+    Wseqs: &[arrayfire::Seq<i32>; 1],
+    Hseqs: &[arrayfire::Seq<i32>; 1],
+    Aseqs: &[arrayfire::Seq<i32>; 1],
+    Bseqs: &[arrayfire::Seq<i32>; 1],
+    Cseqs: &[arrayfire::Seq<i32>; 1],
+    Dseqs: &[arrayfire::Seq<i32>; 1],
+    Eseqs: &[arrayfire::Seq<i32>; 1],
+    network_params: &arrayfire::Array<T>,
+
+    Z: &arrayfire::Array<T>,
+    Q: &arrayfire::Array<T>,
+    Y: &arrayfire::Array<T>,
+    loss_grad: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> arrayfire::Array<T>,
+    neuron_idx: &arrayfire::Array<i32>,
+
+    idxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    valsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+
+    cvec_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    dXsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+
+    nrows_out: &nohash_hasher::IntMap<i64, u64>,
+    sparseval_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    sparserow_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    sparsecol_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+
+    Hidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    Aidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    Bidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    Cidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    Didxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    Eidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    combidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+
+    dAseqs_out: &nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]>,
+    dBseqs_out: &nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]>,
+    dCseqs_out: &nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]>,
+    dDseqs_out: &nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]>,
+    dEseqs_out: &nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]>,
+
+    norm: &norm_state_type<T>,
+
+    grad: &mut arrayfire::Array<T>,
+) {
+    if norm.options.kind == norm_kind_type::LAYER_NORM {
+        state_space_backward_layer_norm(
+            netdata,
+            X,
+            WRowIdxCSR,
+            WColIdx,
+            Wseqs,
+            Hseqs,
+            Aseqs,
+            Bseqs,
+            Cseqs,
+            Dseqs,
+            Eseqs,
+            network_params,
+            Z,
+            Q,
+            Y,
+            loss_grad,
+            neuron_idx,
+            norm,
+            grad,
+        );
+        return;
+    }
+
+    state_space_backward_selected(
+        netdata,
+        X,
+        network_params,
+        Z,
+        Q,
+        Y,
+        loss_grad,
+        neuron_idx,
+        idxsel_out,
+        valsel_out,
+        cvec_out,
+        dXsel_out,
+        nrows_out,
+        sparseval_out,
+        sparserow_out,
+        sparsecol_out,
+        Hidxsel_out,
+        Aidxsel_out,
+        Bidxsel_out,
+        Cidxsel_out,
+        Didxsel_out,
+        Eidxsel_out,
+        combidxsel_out,
+        dAseqs_out,
+        dBseqs_out,
+        dCseqs_out,
+        dDseqs_out,
+        dEseqs_out,
+        norm,
+        grad,
+    );
+}
+
+/*
+Backward pass through the per step layer norm of state_space_forward_batch_norm
+
+Every neuron of a step changes the layer norm statistics, so the error is propagated
+through the full sparse weight matrix instead of the neurons on a path to the output.
+The gradient includes the neurons that only reach the output through the statistics.
+
+Inputs
+Same as state_space_forward_batch_norm and state_space_backward_group2
+
+Outputs:
+grad:                Gradient of all trainable parameters
+
+*/
+
+pub fn state_space_backward_layer_norm<T: raybnn_float_trait>(
+    netdata: &network_metadata_type<T>,
+    X: &arrayfire::Array<T>,
+
+    WRowIdxCSR: &arrayfire::Array<i32>,
+    WColIdx: &arrayfire::Array<i32>,
+
+    Wseqs: &[arrayfire::Seq<i32>; 1],
+    Hseqs: &[arrayfire::Seq<i32>; 1],
+    Aseqs: &[arrayfire::Seq<i32>; 1],
+    Bseqs: &[arrayfire::Seq<i32>; 1],
+    Cseqs: &[arrayfire::Seq<i32>; 1],
+    Dseqs: &[arrayfire::Seq<i32>; 1],
+    Eseqs: &[arrayfire::Seq<i32>; 1],
+    network_params: &arrayfire::Array<T>,
+
+    Z: &arrayfire::Array<T>,
+    Q: &arrayfire::Array<T>,
+    Y: &arrayfire::Array<T>,
+    loss_grad: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> arrayfire::Array<T>,
+    neuron_idx: &arrayfire::Array<i32>,
+
+    norm: &norm_state_type<T>,
+
+    grad: &mut arrayfire::Array<T>,
+) {
+    let neuron_size: u64 = netdata.neuron_size.clone();
+    let input_size: u64 = netdata.input_size.clone();
+    let output_size: u64 = netdata.output_size.clone();
+    let proc_num: u64 = netdata.proc_num.clone();
+
+    let batch_size: u64 = netdata.batch_size.clone();
+
+    //Set output to zero
+    *grad = arrayfire::constant::<T>(T::zero(), network_params.dims());
+
+    //Get current selection of neurons
+    let active_size = neuron_idx.dims()[0];
+    let idxsel = arrayfire::rows(
+        neuron_idx,
+        (active_size - output_size) as i64,
+        (active_size - 1) as i64,
+    );
+
+    let Qslices: u64 = Q.dims()[2];
+
+    //Get Yhat
+    let mut idxrs = arrayfire::Indexer::default();
+    let seq1 = arrayfire::Seq::new(0.0f32, (batch_size - 1) as f32, 1.0);
+    let seq2 = arrayfire::Seq::new((proc_num - 1) as f32, (Qslices - 1) as f32, 1.0);
+    idxrs.set_index(&idxsel, 0, None);
+    idxrs.set_index(&seq1, 1, None);
+    idxrs.set_index(&seq2, 2, None);
+    let Yhat = arrayfire::index_gen(Q, idxrs);
+
+    //Calculate error
+    let total_error = loss_grad(&Yhat, Y);
+
+    let Zslices: i64 = Z.dims()[2] as i64;
+    let X_slices: i64 = X.dims()[2] as i64;
+
+    let S_dims = arrayfire::Dim4::new(&[neuron_size, batch_size, 1, 1]);
+    let seqs = &[
+        arrayfire::Seq::new(0.0f32, (input_size - 1) as f32, 1.0f32),
+        arrayfire::Seq::default(),
+    ];
+    let zero_input = arrayfire::constant::<T>(
+        T::zero(),
+        arrayfire::Dim4::new(&[input_size, batch_size, 1, 1]),
+    );
+
+    let WValues = arrayfire::index(network_params, Wseqs);
+    let W = arrayfire::sparse::<T>(
+        neuron_size,
+        neuron_size,
+        &WValues,
+        WRowIdxCSR,
+        WColIdx,
+        arrayfire::SparseFormat::CSR,
+    );
+    let WRowIdxCOO = CSR_to_COO(WRowIdxCSR);
+
+    let all_rows = arrayfire::iota::<i32>(
+        arrayfire::Dim4::new(&[neuron_size, 1, 1, 1]),
+        arrayfire::Dim4::new(&[1, 1, 1, 1]),
+    );
+
+    let A = arrayfire::index(network_params, Aseqs);
+    let B = arrayfire::index(network_params, Bseqs);
+    let C = arrayfire::index(network_params, Cseqs);
+    let D = arrayfire::index(network_params, Dseqs);
+    let E = arrayfire::index(network_params, Eseqs);
+
+    let mut gW = arrayfire::constant::<T>(T::zero(), WValues.dims());
+    let mut gH = arrayfire::constant::<T>(T::zero(), A.dims());
+    let mut gA = arrayfire::constant::<T>(T::zero(), A.dims());
+    let mut gB = arrayfire::constant::<T>(T::zero(), A.dims());
+    let mut gC = arrayfire::constant::<T>(T::zero(), A.dims());
+    let mut gD = arrayfire::constant::<T>(T::zero(), A.dims());
+    let mut gE = arrayfire::constant::<T>(T::zero(), A.dims());
+
+    let temp_dims = arrayfire::Dim4::new(&[1, 1, 1, 1]);
+    let mut dX = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut dA = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut dB = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut dC = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut dD = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut dE = arrayfire::constant::<T>(T::zero(), temp_dims);
+
+    //Gradient with respect to Q of the current step
+    let mut error = arrayfire::constant::<T>(T::zero(), S_dims);
+
+    //Main loop
+    for i in (0i64..Zslices).rev() {
+        //Add Y error of the output neurons
+        if i >= ((proc_num - 1) as i64) {
+            let derror = arrayfire::slice(&total_error, i - ((proc_num - 1) as i64));
+            let newerror = arrayfire::lookup(&error, &idxsel, 0) + derror;
+
+            let mut idxrs = arrayfire::Indexer::default();
+            idxrs.set_index(&idxsel, 0, None);
+            arrayfire::assign_gen(&mut error, &idxrs, &newerror);
+        }
+
+        //Compute derivative of UAF at the normalized pre-activation
+        let Shat = arrayfire::slice(Z, i);
+        deriUAF(
+            &Shat, &A, &B, &C, &D, &E, &mut dX, &mut dA, &mut dB, &mut dC, &mut dD, &mut dE,
+        );
+
+        gA = gA + sum(&arrayfire::mul(&dA, &error, false), 1);
+        gB = gB + sum(&arrayfire::mul(&dB, &error, false), 1);
+        gC = gC + sum(&arrayfire::mul(&dC, &error, false), 1);
+        gD = gD + sum(&arrayfire::mul(&dD, &error, false), 1);
+        gE = gE + sum(&arrayfire::mul(&dE, &error, false), 1);
+
+        //Gradient through the normalization of every neuron
+        dX = arrayfire::mul(&dX, &error, false);
+        dX = norm_backward(&dX, &Shat, &all_rows, neuron_size, i, norm);
+        drop(Shat);
+
+        //Update H
+        gH = gH + sum(&dX, 1);
+
+        //Get input values
+        let mut inx = arrayfire::constant::<T>(T::zero(), S_dims);
+        if i > 0 {
+            inx = arrayfire::slice(Q, i - 1);
+        }
+
+        if X_slices > 1 {
+            arrayfire::assign_seq(&mut inx, seqs, &arrayfire::slice(X, i));
+        } else {
+            arrayfire::assign_seq(&mut inx, seqs, X);
+        }
+
+        //Update gW
+        let tempgW = arrayfire::mul(
+            &arrayfire::lookup(&dX, &WRowIdxCOO, 0),
+            &arrayfire::lookup(&inx, WColIdx, 0),
+            false,
+        );
+        gW = gW + sum(&tempgW, 1);
+        drop(inx);
+
+        //Propagate Errors, the input rows of Q are replaced by X
+        error = arrayfire::matmul(
+            &W,
+            &dX,
+            arrayfire::MatProp::TRANS,
+            arrayfire::MatProp::NONE,
+        );
+        arrayfire::assign_seq(&mut error, seqs, &zero_input);
+    }
+
+    arrayfire::assign_seq(grad, Wseqs, &gW);
+    arrayfire::assign_seq(grad, Hseqs, &gH);
+    arrayfire::assign_seq(grad, Aseqs, &gA);
+    arrayfire::assign_seq(grad, Bseqs, &gB);
+    arrayfire::assign_seq(grad, Cseqs, &gC);
+    arrayfire::assign_seq(grad, Dseqs, &gD);
+    arrayfire::assign_seq(grad, Eseqs, &gE);
+}
+
+// Backward pass of state_space_backward_group2_norm over the neurons on a path to the output
+fn state_space_backward_selected<T: raybnn_float_trait>(
+    netdata: &network_metadata_type<T>,
+    X: &arrayfire::Array<T>,
+
+    network_params: &arrayfire::Array<T>,
+
+    Z: &arrayfire::Array<T>,
+    Q: &arrayfire::Array<T>,
+    Y: &arrayfire::Array<T>,
+    loss_grad: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> arrayfire::Array<T>,
+    neuron_idx: &arrayfire::Array<i32>,
+
+    idxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    valsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+
+    cvec_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    dXsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+
+    nrows_out: &nohash_hasher::IntMap<i64, u64>,
+    sparseval_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    sparserow_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    sparsecol_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+
+    Hidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    Aidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    Bidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    Cidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    Didxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    Eidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    combidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+
+    dAseqs_out: &nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]>,
+    dBseqs_out: &nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]>,
+    dCseqs_out: &nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]>,
+    dDseqs_out: &nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]>,
+    dEseqs_out: &nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]>,
+
+    norm: &norm_state_type<T>,
+
+    grad: &mut arrayfire::Array<T>,
+) {
+    let neuron_size: u64 = netdata.neuron_size.clone();
+    let input_size: u64 = netdata.input_size.clone();
+    let output_size: u64 = netdata.output_size.clone();
+    let proc_num: u64 = netdata.proc_num.clone();
+
+    let batch_size: u64 = netdata.batch_size.clone();
+
+    //Set output to zero
+    *grad = arrayfire::constant::<T>(T::zero(), network_params.dims());
+
+    //Get current selection of neurons
+    let active_size = neuron_idx.dims()[0];
+    let idxsel = arrayfire::rows(
+        neuron_idx,
+        (active_size - output_size) as i64,
+        (active_size - 1) as i64,
+    );
+
+    let Qslices: u64 = Q.dims()[2];
+    let Yslices: u64 = Y.dims()[2];
+
+    //Get Yhat
+    let mut idxrs = arrayfire::Indexer::default();
+    let seq1 = arrayfire::Seq::new(0.0f32, (batch_size - 1) as f32, 1.0);
+    let seq2 = arrayfire::Seq::new((proc_num - 1) as f32, (Qslices - 1) as f32, 1.0);
+    idxrs.set_index(&idxsel, 0, None);
+    idxrs.set_index(&seq1, 1, None);
+    idxrs.set_index(&seq2, 2, None);
+    let Yhat = arrayfire::index_gen(Q, idxrs);
+    drop(idxsel);
+
+    let temp_dims = arrayfire::Dim4::new(&[1, 1, 1, 1]);
+    let S_dims = arrayfire::Dim4::new(&[neuron_size, batch_size, 1, 1]);
+
+    //Calculate error
+    let total_error = loss_grad(&Yhat, Y);
+    let mut yslicidx: i64 = (Yslices - 1) as i64;
+    let mut error = arrayfire::slice(&total_error, yslicidx);
+
+    let Zslices: i64 = Z.dims()[2] as i64;
+
+    let X_slices: i64 = X.dims()[2] as i64;
+
+    let mut inx = arrayfire::constant::<T>(T::zero(), S_dims);
+
+    let mut tempx = arrayfire::slice(X, 0);
+
+    let seqs = &[
+        arrayfire::Seq::new(0.0f32, (input_size - 1) as f32, 1.0f32),
+        arrayfire::Seq::default(),
+    ];
+
+    let mut Xtemp = arrayfire::slice(Z, 0);
+
+    let mut sA = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut sB = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut sC = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut sD = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut sE = arrayfire::constant::<T>(T::zero(), temp_dims);
+
+    let mut dX = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut dA = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut dB = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut dC = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut dD = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut dE = arrayfire::constant::<T>(T::zero(), temp_dims);
+
+    let mut tempW = arrayfire::constant::<T>(T::zero(), temp_dims);
+
+    let mut gtemperr = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut tempinx = arrayfire::constant::<T>(T::zero(), temp_dims);
+
+    let mut tempdX = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut tempgW = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut tempgH = arrayfire::constant::<T>(T::zero(), temp_dims);
+
+    let mut derror = arrayfire::slice(&total_error, 0);
+
+    let batchseq = arrayfire::Seq::new(0.0f32, (batch_size - 1) as f32, 1.0);
+    let mut sliceseq = arrayfire::Seq::new((proc_num - 1) as f32, (Qslices - 1) as f32, 1.0);
+
+    let mut keys = arrayfire::constant::<i32>(0, temp_dims);
+    let mut vals = arrayfire::constant::<T>(T::zero(), temp_dims);
+
+    let mut UAFgroup = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut tileerror = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let tileerror_dims = arrayfire::Dim4::new(&[5, 1, 1, 1]);
+
+    //Main loop
+    for i in (0i64..Zslices).rev() {
+        //Select X value
+        let mut idxrs = arrayfire::Indexer::default();
+        sliceseq = arrayfire::Seq::new(i as f32, i as f32, 1.0);
+        idxrs.set_index(&idxsel_out[&i], 0, None);
+        idxrs.set_index(&batchseq, 1, None);
+        idxrs.set_index(&sliceseq, 2, None);
+        Xtemp = arrayfire::index_gen(Z, idxrs);
+
+        //Get current UAF parameters
+        sA = arrayfire::lookup(&network_params, &Aidxsel_out[&i], 0);
+
+        sB = arrayfire::lookup(&network_params, &Bidxsel_out[&i], 0);
+
+        sC = arrayfire::lookup(&network_params, &Cidxsel_out[&i], 0);
+
+        sD = arrayfire::lookup(&network_params, &Didxsel_out[&i], 0);
+
+        sE = arrayfire::lookup(&network_params, &Eidxsel_out[&i], 0);
+
+        //Compute derivative of UAF
+        deriUAF(
+            &Xtemp, &sA, &sB, &sC, &sD, &sE, &mut dX, &mut dA, &mut dB, &mut dC, &mut dD, &mut dE,
+        );
+        drop(sA);
+        drop(sB);
+        drop(sC);
+        drop(sD);
+        drop(sE);
+
+        //Compute dX
+        dX = arrayfire::mul(&dX, &error, false);
+
+        //Gradient through the normalization, dA..dE stay with respect to the normalized input
+        dX = norm_backward(&dX, &Xtemp, &idxsel_out[&i], neuron_size, i, norm);
+        drop(Xtemp);
+
+        //Update H
+        tempgH = arrayfire::lookup(grad, &Hidxsel_out[&i], 0) + (sum(&dX, 1));
+
+        let mut idxrs = arrayfire::Indexer::default();
+        idxrs.set_index(&Hidxsel_out[&i], 0, None);
+        arrayfire::assign_gen(grad, &idxrs, &tempgH);
+        drop(tempgH);
+
+        //Join all
+
+        UAFgroup = arrayfire::constant::<T>(
+            T::zero(),
+            arrayfire::Dim4::new(&[dA.dims()[0] * 5, dA.dims()[1], 1, 1]),
+        );
+        arrayfire::assign_seq(&mut UAFgroup, &dAseqs_out[&i], &dA);
+        arrayfire::assign_seq(&mut UAFgroup, &dBseqs_out[&i], &dB);
+        arrayfire::assign_seq(&mut UAFgroup, &dCseqs_out[&i], &dC);
+        arrayfire::assign_seq(&mut UAFgroup, &dDseqs_out[&i], &dD);
+        arrayfire::assign_seq(&mut UAFgroup, &dEseqs_out[&i], &dE);
+
+        tileerror = arrayfire::tile(&error, tileerror_dims);
+
+        UAFgroup = arrayfire::mul(&tileerror, &UAFgroup, false);
+        drop(tileerror);
+
+        UAFgroup = sum(&UAFgroup, 1) + arrayfire::lookup(grad, &combidxsel_out[&i], 0);
+
+        let mut idxrs = arrayfire::Indexer::default();
+        idxrs.set_index(&combidxsel_out[&i], 0, None);
+        arrayfire::assign_gen(grad, &idxrs, &UAFgroup);
+        drop(UAFgroup);
+
+        //Get dX of each row
+        let mut idxrs = arrayfire::Indexer::default();
+        idxrs.set_index(&dXsel_out[&i], 0, None);
+        idxrs.set_index(&batchseq, 1, None);
+        tempdX = arrayfire::index_gen(&dX, idxrs);
+
+        //Get input values
+        inx = arrayfire::constant::<T>(T::zero(), S_dims);
+
+        if (i > 0) {
+            inx = arrayfire::slice(Q, (i - 1));
+        }
+
+        if X_slices > 1 {
+            tempx = arrayfire::slice(X, i);
+            arrayfire::assign_seq(&mut inx, seqs, &tempx);
+            drop(tempx);
+        } else {
+            arrayfire::assign_seq(&mut inx, seqs, &X);
+        }
+
+        //Upadate gW
+        let mut idxrs = arrayfire::Indexer::default();
+        idxrs.set_index(&cvec_out[&i], 0, None);
+        idxrs.set_index(&batchseq, 1, None);
+        tempinx = arrayfire::index_gen(&inx, idxrs);
+        drop(inx);
+
+        tempgW = arrayfire::mul(&tempdX, &tempinx, false);
+        drop(tempinx);
+        tempgW = (sum(&tempgW, 1)) + arrayfire::lookup(grad, &valsel_out[&i], 0);
+
+        let mut idxrs = arrayfire::Indexer::default();
+        idxrs.set_index(&valsel_out[&i], 0, None);
+        arrayfire::assign_gen(grad, &idxrs, &tempgW);
+        drop(tempgW);
+
+        //Propagate Errors
+        tempW = arrayfire::lookup(network_params, &sparseval_out[&i], 0);
+
+        tempW = arrayfire::sparse::<T>(
+            nrows_out[&i],
+            dX.dims()[0],
+            &tempW,
+            &sparserow_out[&i],
+            &sparsecol_out[&i],
+            arrayfire::SparseFormat::CSR,
+        );
+
+        error = arrayfire::matmul(
+            &tempW,
+            &dX,
+            arrayfire::MatProp::NONE,
+            arrayfire::MatProp::NONE,
+        );
+
+        drop(tempW);
+
+        //Add new Y error
+        if (yslicidx > 0) {
+            yslicidx = yslicidx - 1;
+            derror = arrayfire::slice(&total_error, yslicidx);
+
+            error = arrayfire::join(0, &error, &derror);
+        }
+    }
+}
@@ -0,0 +1,261 @@
+extern crate arrayfire;
+
+use serde::{Deserialize, Serialize};
+
+use crate::precision::float_trait::raybnn_float_trait;
+use crate::precision::reduce::sum;
+
+/*
+Per step normalization of the state space neurons
+
+The pre-activation S = W*S + H of every step is normalized before the UAF.
+
+LAYER_NORM:          Normalizes each sample over the neurons
+BATCH_NORM:          Normalizes each neuron over the batch. Running statistics of every
+                     step are kept for evaluation
+
+Z stores the normalized pre-activation, so that the backward pass takes the UAF derivative
+at the same point as the forward pass. norm_backward then turns the gradient with respect
+to the normalized values into the gradient with respect to W and H.
+
+The layer norm statistics mix every neuron, so state_space_backward_group2_norm computes the
+layer norm gradient of every neuron with state_space_backward_layer_norm, while NONE and
+BATCH_NORM only visit the neurons on a path to the output.
+
+*/
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum norm_kind_type {
+    NONE,
+    LAYER_NORM,
+    BATCH_NORM,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: raybnn_float_trait",
+    deserialize = "T: raybnn_float_trait"
+))]
+pub struct norm_options_type<T: raybnn_float_trait> {
+    pub kind: norm_kind_type,
+    pub momentum: T,
+    pub epsilon: T,
+}
+
+/*
+Normalization settings and statistics stored in the neural network
+
+training:            Batch norm uses the batch statistics and updates the running statistics
+running_mean:        Batch norm running mean, dims (neuron_size, 1, steps)
+running_var:         Batch norm running variance, dims (neuron_size, 1, steps)
+inv_std:             1/std of the last forward pass, used by the backward pass
+
+*/
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: raybnn_float_trait",
+    deserialize = "T: raybnn_float_trait"
+))]
+pub struct norm_state_type<T: raybnn_float_trait> {
+    pub options: norm_options_type<T>,
+    pub training: bool,
+
+    pub running_mean: arrayfire::Array<T>,
+    pub running_var: arrayfire::Array<T>,
+    pub inv_std: arrayfire::Array<T>,
+}
+
+pub fn create_norm_options<T: raybnn_float_trait>(kind: norm_kind_type) -> norm_options_type<T> {
+    norm_options_type {
+        kind: kind,
+        momentum: T::from_f64(0.9),
+        epsilon: T::from_f64(1.0e-5),
+    }
+}
+
+// Normalization state without statistics. The statistics are created by the first forward pass
+pub fn create_norm_state<T: raybnn_float_trait>(
+    options: norm_options_type<T>,
+) -> norm_state_type<T> {
+    let temp_dims = arrayfire::Dim4::new(&[1, 1, 1, 1]);
+
+    norm_state_type {
+        options: options,
+        training: false,
+
+        running_mean: arrayfire::constant::<T>(T::zero(), temp_dims),
+        running_var: arrayfire::constant::<T>(T::one(), temp_dims),
+        inv_std: arrayfire::constant::<T>(T::one(), temp_dims),
+    }
+}
+
+/*
+Allocates the statistics of a forward pass
+
+The running statistics restart when the number of neurons changes, or when training
+uses more steps than the statistics have.
+
+Inputs
+neuron_size:         Number of neurons
+batch_size:          Batch size
+steps:               Number of steps of the forward pass
+
+Outputs:
+state:               Normalization state with statistics of the right size
+
+*/
+pub fn norm_prepare<T: raybnn_float_trait>(
+    neuron_size: u64,
+    batch_size: u64,
+    steps: u64,
+    state: &mut norm_state_type<T>,
+) {
+    match state.options.kind {
+        norm_kind_type::NONE => (),
+        norm_kind_type::LAYER_NORM => {
+            state.inv_std = arrayfire::constant::<T>(
+                T::one(),
+                arrayfire::Dim4::new(&[1, batch_size, steps, 1]),
+            );
+        }
+        norm_kind_type::BATCH_NORM => {
+            let stats_dims = state.running_mean.dims();
+            if (stats_dims[0] != neuron_size) || (state.training && (stats_dims[2] < steps)) {
+                let new_dims = arrayfire::Dim4::new(&[neuron_size, 1, steps, 1]);
+                state.running_mean = arrayfire::constant::<T>(T::zero(), new_dims);
+                state.running_var = arrayfire::constant::<T>(T::one(), new_dims);
+            }
+
+            state.inv_std = arrayfire::constant::<T>(
+                T::one(),
+                arrayfire::Dim4::new(&[neuron_size, 1, steps, 1]),
+            );
+        }
+    }
+}
+
+// Mean and variance over dim
+fn mean_var<T: raybnn_float_trait>(
+    S: &arrayfire::Array<T>,
+    dim: i64,
+) -> (arrayfire::Array<T>, arrayfire::Array<T>) {
+    let mean = arrayfire::mean(S, dim);
+    let centered = arrayfire::sub(S, &mean, true);
+    let var = arrayfire::mean(&arrayfire::mul(&centered, &centered, false), dim);
+
+    (mean, var)
+}
+
+fn inv_sqrt<T: raybnn_float_trait>(arr: &arrayfire::Array<T>) -> arrayfire::Array<T> {
+    arrayfire::pow(arr, &T::from_f64(-0.5), false)
+}
+
+/*
+Normalizes the pre-activation of one step
+
+Inputs
+S:                   Pre-activation, dims (neuron_size, batch_size)
+step:                Step of the forward pass
+
+Outputs:
+Shat:                Normalized pre-activation
+state:               inv_std of the step, and the running statistics when training batch norm
+
+*/
+pub fn norm_forward<T: raybnn_float_trait>(
+    S: &arrayfire::Array<T>,
+    step: i64,
+    state: &mut norm_state_type<T>,
+) -> arrayfire::Array<T> {
+    let one = T::one();
+    let epsilon = state.options.epsilon;
+    let momentum = state.options.momentum;
+
+    let (mean, inv_std) = match state.options.kind {
+        norm_kind_type::NONE => {
+            return S.clone();
+        }
+        norm_kind_type::LAYER_NORM => {
+            let (mean, var) = mean_var(S, 0);
+            (mean, inv_sqrt(&(var + epsilon)))
+        }
+        norm_kind_type::BATCH_NORM => {
+            if state.training {
+                let (mean, var) = mean_var(S, 1);
+
+                let running_mean = (arrayfire::slice(&state.running_mean, step) * momentum)
+                    + (mean.clone() * (one - momentum));
+                let running_var = (arrayfire::slice(&state.running_var, step) * momentum)
+                    + (var.clone() * (one - momentum));
+                arrayfire::set_slice(&mut state.running_mean, &running_mean, step);
+                arrayfire::set_slice(&mut state.running_var, &running_var, step);
+
+                (mean, inv_sqrt(&(var + epsilon)))
+            } else {
+                // Evaluation can run more steps than training, those use the last statistics
+                let stat_step = step.min((state.running_mean.dims()[2] as i64) - 1);
+                let mean = arrayfire::slice(&state.running_mean, stat_step);
+                let var = arrayfire::slice(&state.running_var, stat_step);
+
+                (mean, inv_sqrt(&(var + epsilon)))
+            }
+        }
+    };
+
+    arrayfire::set_slice(&mut state.inv_std, &inv_std, step);
+
+    arrayfire::mul(&arrayfire::sub(S, &mean, true), &inv_std, true)
+}
+
+/*
+Gradient through the normalization of one step
+
+Inputs
+dShat:               Gradient with respect to the normalized values of the selected neurons.
+                     Layer norm needs every neuron
+Shat:                Normalized values of the selected neurons
+rows:                Indexes of the selected neurons
+neuron_size:         Number of neurons
+step:                Step of the forward pass
+state:               Normalization state of the forward pass
+
+Outputs:
+dS:                  Gradient with respect to the pre-activation of the selected neurons
+
+*/
+pub fn norm_backward<T: raybnn_float_trait>(
+    dShat: &arrayfire::Array<T>,
+    Shat: &arrayfire::Array<T>,
+    rows: &arrayfire::Array<i32>,
+    neuron_size: u64,
+    step: i64,
+    state: &norm_state_type<T>,
+) -> arrayfire::Array<T> {
+    match state.options.kind {
+        norm_kind_type::NONE => dShat.clone(),
+        norm_kind_type::LAYER_NORM => {
+            let scale = T::one() / T::from_f64(neuron_size as f64);
+            let inv_std = arrayfire::slice(&state.inv_std, step);
+
+            // dShat holds every neuron, so the sums are the sums of the statistics
+            let m0 = sum(dShat, 0) * scale;
+            let m1 = sum(&arrayfire::mul(dShat, Shat, false), 0) * scale;
+
+            let dS = arrayfire::sub(dShat, &m0, true) - arrayfire::mul(Shat, &m1, true);
+            arrayfire::mul(&dS, &inv_std, true)
+        }
+        norm_kind_type::BATCH_NORM => {
+            let inv_std = arrayfire::lookup(&arrayfire::slice(&state.inv_std, step), rows, 0);
+
+            if !state.training {
+                return arrayfire::mul(dShat, &inv_std, true);
+            }
+
+            let m0 = arrayfire::mean(dShat, 1);
+            let m1 = arrayfire::mean(&arrayfire::mul(dShat, Shat, false), 1);
+
+            let dS = arrayfire::sub(dShat, &m0, true) - arrayfire::mul(Shat, &m1, true);
+            arrayfire::mul(&dS, &inv_std, true)
+        }
+    }
+}
@@ -0,0 +1,588 @@
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::precision::float_trait::raybnn_float_trait;
+
+use crate::error::Error;
+
+use crate::export::rand::single_random_uniform;
+
+use crate::interface::automatic::arch_search_type;
+
+use crate::optimal::evolution::extract_info;
+
+/*
+Bayesian optimization of the architecture search
+
+A Gaussian process with a Matern 5/2 kernel is fitted to the crossval metric of the evaluated
+models over (active_size, proc_num, prune_num), scaled to the bounds of evolution_info_type<T>.
+The length scale is chosen by the log marginal likelihood. Candidates are steps of the
+evaluated models within the active_size and proc_num step limits, with a prune_num between
+min_prune_num and max_prune_num, and the candidate with the largest acquisition is proposed.
+
+prune_num is the fraction of the connections deleted after resizing. It is not stored in the
+network files, so every proposal is appended to bayesian_history.csv in dir_path, and the joined
+observations are written to bayesian_observations.csv. Models without a proposal, like the
+initial models, have prune_num 0.
+
+*/
+
+const CANDIDATE_NUM: u64 = 32;
+const LENGTH_SCALES: [f64; 5] = [0.05, 0.1, 0.2, 0.4, 0.8];
+
+const HISTORY_FILE: &str = "bayesian_history.csv";
+const OBSERVATIONS_FILE: &str = "bayesian_observations.csv";
+
+/*
+Acquisition function of the minimized crossval metric
+
+EXPECTED_IMPROVEMENT:    Expected improvement over the lowest crossval by more than xi
+UPPER_CONFIDENCE_BOUND:  Upper confidence bound of the negative crossval, -mean + kappa * std
+
+*/
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: raybnn_float_trait",
+    deserialize = "T: raybnn_float_trait"
+))]
+pub enum acquisition_type<T: raybnn_float_trait> {
+    EXPECTED_IMPROVEMENT { xi: T },
+    UPPER_CONFIDENCE_BOUND { kappa: T },
+}
+
+// Proposed network. path is the untrained model file without .csv
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: raybnn_float_trait",
+    deserialize = "T: raybnn_float_trait"
+))]
+pub struct bayesian_proposal_type<T: raybnn_float_trait> {
+    pub path: String,
+    pub active_size: u64,
+    pub proc_num: u64,
+    pub prune_num: T,
+}
+
+// Evaluated network with its mean crossval metric
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: raybnn_float_trait",
+    deserialize = "T: raybnn_float_trait"
+))]
+pub struct bayesian_observation_type<T: raybnn_float_trait> {
+    pub path: String,
+    pub active_size: u64,
+    pub proc_num: u64,
+    pub prune_num: T,
+    pub crossval: T,
+}
+
+pub fn try_load_bayesian_history<T: raybnn_float_trait>(
+    dir_path: &str,
+) -> Result<Vec<bayesian_proposal_type<T>>, Error> {
+    let filename = format!("{}/{}", dir_path, HISTORY_FILE);
+
+    let contents = match std::fs::read_to_string(&filename) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(Error::io(&filename, e)),
+    };
+
+    let mut history = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let parse_error = || Error::Parse {
+            file: filename.clone(),
+            line: (i + 1) as u64,
+            value: line.to_string(),
+        };
+
+        let fields: Vec<&str> = line.rsplitn(4, ',').collect();
+        if fields.len() != 4 {
+            return Err(parse_error());
+        }
+
+        history.push(bayesian_proposal_type {
+            path: fields[3].to_string(),
+            active_size: fields[2].trim().parse().map_err(|_| parse_error())?,
+            proc_num: fields[1].trim().parse().map_err(|_| parse_error())?,
+            prune_num: fields[0].trim().parse().map_err(|_| parse_error())?,
+        });
+    }
+
+    Ok(history)
+}
+
+pub fn load_bayesian_history<T: raybnn_float_trait>(
+    dir_path: &str,
+) -> Vec<bayesian_proposal_type<T>> {
+    try_load_bayesian_history(dir_path).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_append_bayesian_history<T: raybnn_float_trait>(
+    dir_path: &str,
+    proposal: &bayesian_proposal_type<T>,
+) -> Result<(), Error> {
+    let filename = format!("{}/{}", dir_path, HISTORY_FILE);
+
+    let mut file0 = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&filename)
+        .map_err(|e| Error::io(&filename, e))?;
+
+    writeln!(
+        file0,
+        "{},{},{},{}",
+        proposal.path, proposal.active_size, proposal.proc_num, proposal.prune_num
+    )
+    .map_err(|e| Error::io(&filename, e))
+}
+
+pub fn append_bayesian_history<T: raybnn_float_trait>(
+    dir_path: &str,
+    proposal: &bayesian_proposal_type<T>,
+) {
+    try_append_bayesian_history(dir_path, proposal).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn try_save_bayesian_observations<T: raybnn_float_trait>(
+    dir_path: &str,
+    observations: &[bayesian_observation_type<T>],
+) -> Result<(), Error> {
+    let filename = format!("{}/{}", dir_path, OBSERVATIONS_FILE);
+
+    let mut file0 = File::create(&filename).map_err(|e| Error::io(&filename, e))?;
+
+    writeln!(file0, "path,active_size,proc_num,prune_num,crossval")
+        .map_err(|e| Error::io(&filename, e))?;
+    for obs in observations {
+        writeln!(
+            file0,
+            "{},{},{},{},{}",
+            obs.path, obs.active_size, obs.proc_num, obs.prune_num, obs.crossval
+        )
+        .map_err(|e| Error::io(&filename, e))?;
+    }
+
+    Ok(())
+}
+
+pub fn save_bayesian_observations<T: raybnn_float_trait>(
+    dir_path: &str,
+    observations: &[bayesian_observation_type<T>],
+) {
+    try_save_bayesian_observations(dir_path, observations).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/*
+Joins the evaluated models of get_crossval_data with the proposal history
+
+Inputs
+arch_search:         Evolution info with crossval_vec and netdata_vec of the evaluated models
+checked_paths:       Model file of every evaluated model
+
+Outputs:
+observations:        Evaluated models with their prune_num, or the error of reading the
+                     proposal history
+
+*/
+pub fn bayesian_observations<T: raybnn_float_trait>(
+    arch_search: &arch_search_type<T>,
+    checked_paths: &[String],
+) -> Result<Vec<bayesian_observation_type<T>>, Error> {
+    let history = try_load_bayesian_history(&(*arch_search).evolution_info.dir_path)?;
+
+    let mut active_size_vec: Vec<u64> = Vec::new();
+    let mut proc_num_vec: Vec<u64> = Vec::new();
+    extract_info(
+        &(*arch_search).evolution_info.netdata_vec,
+        &mut active_size_vec,
+        &mut proc_num_vec,
+    );
+
+    let mut observations = Vec::new();
+    for i in 0..checked_paths.len() {
+        //Trained models are saved as <path>_train_num_<success_idx>.csv
+        let prune_num = history
+            .iter()
+            .rev()
+            .find(|proposal| checked_paths[i].starts_with(&format!("{}_train_num_", proposal.path)))
+            .map_or(T::zero(), |proposal| proposal.prune_num);
+
+        observations.push(bayesian_observation_type {
+            path: checked_paths[i].clone(),
+            active_size: active_size_vec[i],
+            proc_num: proc_num_vec[i],
+            prune_num: prune_num,
+            crossval: (*arch_search).evolution_info.crossval_vec[i],
+        });
+    }
+
+    Ok(observations)
+}
+
+/*
+Bayesian optimization step
+
+Inputs
+arch_search:         Evolution info with the evaluated models and the search bounds
+checked_paths:       Model file of every evaluated model
+acquisition:         Acquisition function
+
+Outputs:
+(selected_idx, new_active_size, new_proc_num, new_prune_num): Model to start from and the
+                     candidate with the largest acquisition. None if there are fewer than 3
+                     evaluated models. Error if the proposal history can not be read or the
+                     observations can not be saved
+
+*/
+pub fn search_bayesian<T: raybnn_float_trait>(
+    arch_search: &arch_search_type<T>,
+    checked_paths: &[String],
+    acquisition: acquisition_type<T>,
+) -> Result<Option<(usize, u64, u64, T)>, Error> {
+    let LARGE_POS_NUM = T::from_f64(1.0e9);
+    let observations = bayesian_observations(arch_search, checked_paths)?;
+
+    try_save_bayesian_observations(&(*arch_search).evolution_info.dir_path, &observations)?;
+
+    if observations.len() < 3 {
+        return Ok(None);
+    }
+
+    let info = &(*arch_search).evolution_info;
+
+    let scale = |active_size: T, proc_num: T, prune_num: T| -> [T; 3] {
+        [
+            (active_size - T::from_f64(info.min_active_size as f64))
+                / T::from_f64((info.max_active_size - info.min_active_size).max(1) as f64),
+            (proc_num - T::from_f64(info.min_proc_num as f64))
+                / T::from_f64((info.max_proc_num - info.min_proc_num).max(1) as f64),
+            prune_num / info.max_prune_num.max(T::from_f64(1.0e-6)),
+        ]
+    };
+
+    //Failed models have LARGE_POS_NUM crossval, which is replaced by the worst finite crossval
+    let worst = observations
+        .iter()
+        .map(|obs| obs.crossval)
+        .filter(|c| *c < LARGE_POS_NUM)
+        .fold(T::neg_infinity(), T::max);
+    if worst == T::neg_infinity() {
+        return Ok(None);
+    }
+
+    let x: Vec<[T; 3]> = observations
+        .iter()
+        .map(|obs| {
+            scale(
+                T::from_f64(obs.active_size as f64),
+                T::from_f64(obs.proc_num as f64),
+                obs.prune_num,
+            )
+        })
+        .collect();
+    let y: Vec<T> = observations
+        .iter()
+        .map(|obs| obs.crossval.min(worst))
+        .collect();
+
+    let Some(gp) = fit_gp(&x, &y) else {
+        return Ok(None);
+    };
+
+    let best_y = y.iter().cloned().fold(T::infinity(), T::min);
+
+    //Candidates around every evaluated model
+    let mut best: Option<(T, usize, u64, u64, T)> = None;
+    for i in 0..observations.len() {
+        let cur_active_size = T::from_f64(observations[i].active_size as f64);
+        let cur_proc_num = T::from_f64(observations[i].proc_num as f64);
+
+        for _ in 0..CANDIDATE_NUM {
+            let sign = if single_random_uniform::<T>() > T::from_f64(0.5) {
+                T::one()
+            } else {
+                -T::one()
+            };
+            let step = info.min_active_size_step
+                + (info.max_active_size_step - info.min_active_size_step)
+                    * single_random_uniform::<T>();
+            let active_size = (cur_active_size * (T::one() + sign * step)).round();
+
+            let sign = if single_random_uniform::<T>() > T::from_f64(0.5) {
+                T::one()
+            } else {
+                -T::one()
+            };
+            let step = T::from_f64(info.min_proc_num_step as f64)
+                + T::from_f64((info.max_proc_num_step - info.min_proc_num_step) as f64)
+                    * single_random_uniform::<T>();
+            let proc_num = (cur_proc_num + sign * step).round();
+
+            let prune_num = info.min_prune_num
+                + (info.max_prune_num - info.min_prune_num) * single_random_uniform::<T>();
+
+            if (active_size <= T::one()) || (proc_num < T::one()) {
+                continue;
+            }
+            let active_size_u64 = active_size.to_u64().unwrap();
+            let proc_num_u64 = proc_num.to_u64().unwrap();
+            if !((info.min_active_size < active_size_u64)
+                && (active_size_u64 < info.max_active_size)
+                && (info.min_proc_num <= proc_num_u64)
+                && (proc_num_u64 <= info.max_proc_num))
+            {
+                continue;
+            }
+
+            let (mean, std) = predict_gp(&gp, &scale(active_size, proc_num, prune_num));
+
+            let value = match acquisition {
+                acquisition_type::EXPECTED_IMPROVEMENT { xi } => {
+                    expected_improvement(mean, std, best_y, xi)
+                }
+                acquisition_type::UPPER_CONFIDENCE_BOUND { kappa } => -mean + kappa * std,
+            };
+
+            if value.is_nan() {
+                continue;
+            }
+
+            let better = match best {
+                Some(b) => value > b.0,
+                None => true,
+            };
+
+            if better {
+                best = Some((value, i, active_size_u64, proc_num_u64, prune_num));
+            }
+        }
+    }
+
+    let Some((acquisition_value, selected_idx, new_active_size, new_proc_num, new_prune_num)) =
+        best
+    else {
+        return Ok(None);
+    };
+
+    tracing::debug!(
+        acquisition_value = acquisition_value.to_f64().unwrap_or(f64::NAN),
+        length_scale = gp.length_scale.to_f64().unwrap_or(f64::NAN),
+        new_active_size,
+        new_proc_num,
+        new_prune_num = new_prune_num.to_f64().unwrap_or(f64::NAN),
+        selected_idx,
+        "bayesian proposal"
+    );
+
+    Ok(Some((
+        selected_idx,
+        new_active_size,
+        new_proc_num,
+        new_prune_num,
+    )))
+}
+
+/*
+Gaussian process fitted to standardized targets
+
+x:                   Scaled inputs of the observations
+L:                   Cholesky factor of the kernel matrix with noise, row major
+alpha:               K^-1 y of the standardized targets
+length_scale:        Length scale of the Matern 5/2 kernel
+y_mean, y_std:       Mean and standard deviation of the targets
+
+*/
+pub struct gp_type<T: raybnn_float_trait> {
+    pub x: Vec<[T; 3]>,
+    pub L: Vec<T>,
+    pub alpha: Vec<T>,
+    pub length_scale: T,
+    pub y_mean: T,
+    pub y_std: T,
+}
+
+// Matern 5/2 kernel of two scaled inputs
+pub fn matern52<T: raybnn_float_trait>(a: &[T; 3], b: &[T; 3], length_scale: T) -> T {
+    let r = ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt();
+    let s = (T::from_f64(5.0)).sqrt() * r / length_scale;
+
+    (T::one() + s + s * s / T::from_f64(3.0)) * (-s).exp()
+}
+
+// Lower triangular L with L L^T = K, row major. None if K is not positive definite
+pub fn cholesky<T: raybnn_float_trait>(K: &[T], n: usize) -> Option<Vec<T>> {
+    let mut L = vec![T::zero(); n * n];
+    for i in 0..n {
+        for j in 0..(i + 1) {
+            let mut sum = K[i * n + j];
+            for k in 0..j {
+                sum = sum - L[i * n + k] * L[j * n + k];
+            }
+
+            if i == j {
+                if sum <= T::zero() {
+                    return None;
+                }
+                L[i * n + i] = sum.sqrt();
+            } else {
+                L[i * n + j] = sum / L[j * n + j];
+            }
+        }
+    }
+
+    Some(L)
+}
+
+// Solves L v = b
+pub fn forward_solve<T: raybnn_float_trait>(L: &[T], b: &[T]) -> Vec<T> {
+    let n = b.len();
+    let mut v = vec![T::zero(); n];
+    for i in 0..n {
+        let mut sum = b[i];
+        for k in 0..i {
+            sum = sum - L[i * n + k] * v[k];
+        }
+        v[i] = sum / L[i * n + i];
+    }
+
+    v
+}
+
+// Solves L^T v = b
+pub fn backward_solve<T: raybnn_float_trait>(L: &[T], b: &[T]) -> Vec<T> {
+    let n = b.len();
+    let mut v = vec![T::zero(); n];
+    for i in (0..n).rev() {
+        let mut sum = b[i];
+        for k in (i + 1)..n {
+            sum = sum - L[k * n + i] * v[k];
+        }
+        v[i] = sum / L[i * n + i];
+    }
+
+    v
+}
+
+// Fits the length scale with the largest log marginal likelihood
+pub fn fit_gp<T: raybnn_float_trait>(x: &[[T; 3]], y: &[T]) -> Option<gp_type<T>> {
+    let NOISE_VAR = T::from_f64(1.0e-3);
+    let n = y.len();
+
+    let y_mean = y.iter().cloned().sum::<T>() / T::from_f64(n as f64);
+    let y_var = y.iter().map(|v| (*v - y_mean) * (*v - y_mean)).sum::<T>() / T::from_f64(n as f64);
+    let y_std = y_var.sqrt().max(T::from_f64(1.0e-6));
+    let y_norm: Vec<T> = y.iter().map(|v| (*v - y_mean) / y_std).collect();
+
+    let mut best: Option<(T, gp_type<T>)> = None;
+    for length_scale in LENGTH_SCALES.map(T::from_f64) {
+        let mut K = vec![T::zero(); n * n];
+        for i in 0..n {
+            for j in 0..n {
+                K[i * n + j] = matern52(&x[i], &x[j], length_scale);
+            }
+            K[i * n + i] = K[i * n + i] + NOISE_VAR;
+        }
+
+        let L = match cholesky(&K, n) {
+            Some(L) => L,
+            None => continue,
+        };
+
+        let v = forward_solve(&L, &y_norm);
+        let alpha = backward_solve(&L, &v);
+
+        let log_det: T = (0..n).map(|i| L[i * n + i].ln()).sum();
+        let log_likelihood = T::from_f64(-0.5) * v.iter().map(|e| *e * *e).sum::<T>() - log_det;
+
+        let better = match &best {
+            Some(b) => log_likelihood > b.0,
+            None => true,
+        };
+
+        if better {
+            best = Some((
+                log_likelihood,
+                gp_type {
+                    x: x.to_vec(),
+                    L: L,
+                    alpha: alpha,
+                    length_scale: length_scale,
+                    y_mean: y_mean,
+                    y_std: y_std,
+                },
+            ));
+        }
+    }
+
+    best.map(|b| b.1)
+}
+
+// Predicted mean and standard deviation of the crossval at x
+pub fn predict_gp<T: raybnn_float_trait>(gp: &gp_type<T>, x: &[T; 3]) -> (T, T) {
+    let k: Vec<T> =
+        gp.x.iter()
+            .map(|xi| matern52(xi, x, gp.length_scale))
+            .collect();
+
+    let mean = k
+        .iter()
+        .zip(gp.alpha.iter())
+        .map(|(a, b)| *a * *b)
+        .sum::<T>();
+
+    let v = forward_solve(&gp.L, &k);
+    let var = (T::one() - v.iter().map(|e| *e * *e).sum::<T>()).max(T::from_f64(1.0e-9));
+
+    (gp.y_mean + gp.y_std * mean, gp.y_std * var.sqrt())
+}
+
+// Density of the standard normal distribution
+pub fn normal_pdf<T: raybnn_float_trait>(z: T) -> T {
+    (T::from_f64(-0.5) * z * z).exp() / (T::from_f64(std::f64::consts::TAU)).sqrt()
+}
+
+// Abramowitz and Stegun 7.1.26 approximation of erf
+pub fn normal_cdf<T: raybnn_float_trait>(z: T) -> T {
+    let x = z.abs() / (T::from_f64(2.0)).sqrt();
+    let t = T::one() / (T::one() + T::from_f64(0.327_591_1) * x);
+    let poly = t
+        * (T::from_f64(0.254_829_6)
+            + t * (T::from_f64(-0.284_496_74)
+                + t * (T::from_f64(1.421_413_7)
+                    + t * (T::from_f64(-1.453_152) + t * T::from_f64(1.061_405_4)))));
+    let erf = T::one() - poly * (-x * x).exp();
+
+    if z >= T::zero() {
+        T::from_f64(0.5) * (T::one() + erf)
+    } else {
+        T::from_f64(0.5) * (T::one() - erf)
+    }
+}
+
+/*
+Expected improvement of a minimized metric
+
+Inputs
+mean:                Predicted mean at the candidate
+std:                 Predicted standard deviation at the candidate
+best_y:              Lowest observed metric
+xi:                  Improvement required over best_y
+
+Outputs:
+ei:                  E[max(best_y - xi - y, 0)] of y ~ N(mean, std^2)
+
+*/
+pub fn expected_improvement<T: raybnn_float_trait>(mean: T, std: T, best_y: T, xi: T) -> T {
+    let improvement = best_y - mean - xi;
+    let z = improvement / std;
+    improvement * normal_cdf(z) + std * normal_pdf(z)
+}
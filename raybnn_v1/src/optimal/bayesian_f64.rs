@@ -0,0 +1,22 @@
+// f64 instantiation of the generic crate::optimal::bayesian module
+pub use crate::optimal::bayesian::*;
+
+pub type acquisition_type = crate::optimal::bayesian::acquisition_type<f64>;
+
+pub type bayesian_proposal_type = crate::optimal::bayesian::bayesian_proposal_type<f64>;
+
+pub type bayesian_observation_type = crate::optimal::bayesian::bayesian_observation_type<f64>;
+
+pub type gp_type = crate::optimal::bayesian::gp_type<f64>;
+
+// Functions whose precision only appears in the return type are pinned here
+
+pub fn try_load_bayesian_history(
+    dir_path: &str,
+) -> Result<Vec<bayesian_proposal_type>, crate::error::Error> {
+    crate::optimal::bayesian::try_load_bayesian_history::<f64>(dir_path)
+}
+
+pub fn load_bayesian_history(dir_path: &str) -> Vec<bayesian_proposal_type> {
+    crate::optimal::bayesian::load_bayesian_history::<f64>(dir_path)
+}
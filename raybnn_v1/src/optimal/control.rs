@@ -0,0 +1,416 @@
+extern crate arrayfire;
+
+use crate::precision::float_trait::raybnn_float_trait;
+use crate::precision::reduce::sum_all;
+
+use nohash_hasher;
+use std::collections::HashMap;
+
+use crate::interface::autotrain::loss_wrapper;
+
+use crate::neural::network::network_metadata_type;
+
+use crate::neural::network::state_space_backward_group2_norm;
+
+use crate::neural::normalization::norm_state_type;
+
+use crate::optimal::scheduler::create_lr_scheduler;
+use crate::optimal::scheduler::lr_schedule_type;
+
+use serde::{Deserialize, Serialize};
+
+const LR_BUFFER: usize = 20;
+const LARGE_POS_NUM_u64: u64 = 1000000000;
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: raybnn_float_trait",
+    deserialize = "T: raybnn_float_trait"
+))]
+pub struct neural_controller_type<T: raybnn_float_trait> {
+    pub start_epoch: u64,
+    pub end_epoch: u64,
+    pub window_epoch: u64,
+    pub counter0: u64,
+    pub counter1: u64,
+
+    pub min_loss: T,
+    pub mean_loss: T,
+
+    pub decrease_alpha: T,
+    pub increase_alpha: T,
+    pub max_alpha: T,
+    pub min_alpha: T,
+}
+
+pub fn create_nullcontrol<T: raybnn_float_trait>() -> neural_controller_type<T> {
+    let LARGE_POS_NUM = T::from_f64(1.0e9);
+    let testdata: neural_controller_type<T> = neural_controller_type {
+        start_epoch: 0,
+        end_epoch: LARGE_POS_NUM_u64,
+        window_epoch: 100,
+        counter0: 0,
+        counter1: 0,
+
+        min_loss: LARGE_POS_NUM,
+        mean_loss: LARGE_POS_NUM,
+
+        decrease_alpha: T::from_f64(0.7),
+        increase_alpha: T::from_f64(1.2),
+        max_alpha: LARGE_POS_NUM,
+        min_alpha: T::zero(),
+    };
+
+    testdata
+}
+
+/*
+Cosine annealing of the learning rate, kept for callers of the old controller
+
+Runs lr_schedule_type::COSINE_ANNEALING with period window_epoch on the smoothed loss
+mean_loss. The plateau state of the schedule is kept in min_loss and counter1, and the epoch
+in counter0. Before start_epoch the learning rate is min_alpha.
+
+Inputs
+control_state:       Controller state, updated
+
+Outputs:
+alpha0, alpha1:      max_alpha times the factor of the schedule, at least min_alpha
+
+*/
+#[deprecated(note = "use lr_schedule_type::COSINE_ANNEALING of optimal::scheduler")]
+pub fn cosine_annealing<T: raybnn_float_trait>(
+    control_state: &mut neural_controller_type<T>,
+    alpha0: &mut T,
+    alpha1: &mut T,
+) {
+    (*control_state).counter0 = (*control_state).counter0 + 1;
+
+    if (*control_state).counter0 <= (*control_state).start_epoch {
+        *alpha0 = (*control_state).min_alpha;
+        *alpha1 = *alpha0;
+        return;
+    }
+
+    let mut scheduler = create_lr_scheduler(&lr_schedule_type::COSINE_ANNEALING {
+        period_epoch: (*control_state).window_epoch,
+    });
+    scheduler
+        .set_state(&[
+            (*control_state).min_loss,
+            T::from_f64((*control_state).counter1 as f64),
+        ])
+        .unwrap();
+
+    let factor = scheduler.factor((*control_state).counter0, (*control_state).mean_loss);
+
+    let state = scheduler.state();
+    (*control_state).min_loss = state[0];
+    (*control_state).counter1 = state[1].to_u64().unwrap();
+
+    *alpha0 = ((*control_state).max_alpha * factor).max((*control_state).min_alpha);
+    *alpha1 = *alpha0;
+}
+
+/*
+Reduces the learning rate when the loss stops improving, kept for callers of the old
+controller
+
+Smooths loss_val into mean_loss and runs lr_schedule_type::REDUCE_ON_PLATEAU with patience
+window_epoch, factor decrease_alpha and a threshold of 5%. The best loss of the schedule is
+kept in min_loss and its counter in counter0. When alpha0 falls below min_alpha, it restarts
+at max_alpha and alpha1 is set to zero.
+
+Inputs
+loss_val:            Training loss of the epoch
+control_state:       Controller state, updated
+
+Outputs:
+alpha0, alpha1:      Learning rates, multiplied by decrease_alpha on a plateau
+
+*/
+#[deprecated(note = "use lr_schedule_type::REDUCE_ON_PLATEAU of optimal::scheduler")]
+pub fn plateau<T: raybnn_float_trait>(
+    loss_val: T,
+    control_state: &mut neural_controller_type<T>,
+    alpha0: &mut T,
+    alpha1: &mut T,
+) {
+    (*control_state).mean_loss =
+        (*control_state).mean_loss * T::from_f64(0.9) + loss_val * T::from_f64(0.1);
+
+    let mut scheduler = create_lr_scheduler(&lr_schedule_type::REDUCE_ON_PLATEAU {
+        patience: (*control_state).window_epoch,
+        factor: (*control_state).decrease_alpha,
+        threshold: T::from_f64(0.05),
+        min_factor: T::zero(),
+    });
+    scheduler
+        .set_state(&[
+            (*control_state).min_loss,
+            T::from_f64((*control_state).counter0 as f64),
+            T::one(),
+        ])
+        .unwrap();
+
+    let factor = scheduler.factor((*control_state).counter0, (*control_state).mean_loss);
+
+    let state = scheduler.state();
+    (*control_state).min_loss = state[0];
+    (*control_state).counter0 = state[1].to_u64().unwrap();
+
+    *alpha0 = (*alpha0) * factor;
+    *alpha1 = (*alpha1) * factor;
+
+    if (*alpha0 < (*control_state).min_alpha) && (*alpha1 > T::zero()) {
+        *alpha0 = (*control_state).max_alpha;
+        *alpha1 = T::zero();
+    }
+}
+
+pub fn BTLS<T: raybnn_float_trait>(
+    loss: impl Fn(&arrayfire::Array<T>) -> T,
+    loss_grad: impl Fn(&arrayfire::Array<T>) -> arrayfire::Array<T>,
+    init_point: &arrayfire::Array<T>,
+    direction: &arrayfire::Array<T>,
+    gamma: T,
+    rho: T,
+) -> T {
+    let LR_MAX = T::one();
+    let mut alpha: T = LR_MAX;
+    let init_loss = loss(init_point);
+
+    let mut next_point = init_point.clone() + direction.clone() * alpha;
+    let mut f0 = loss(&next_point);
+
+    let init_grad = loss_grad(init_point);
+    let v0 = arrayfire::mul(direction, &init_grad, false) * rho;
+    let (t0, t1) = sum_all(&v0);
+    let mut f1 = init_loss.clone() + (alpha) * t0;
+
+    while (f0 > f1) {
+        alpha = (alpha) * gamma;
+        next_point = init_point.clone() + direction.clone() * alpha;
+        f0 = loss(&next_point);
+        f1 = init_loss.clone() + (alpha) * t0;
+    }
+    alpha
+}
+
+pub fn statespace_BTLS<T: raybnn_float_trait>(
+    max_loss: T,
+    min_alpha: T,
+
+    init_point: &arrayfire::Array<T>,
+    direction: &arrayfire::Array<T>,
+    gamma: T,
+    rho: T,
+
+    netdata: &network_metadata_type<T>,
+    X: &arrayfire::Array<T>,
+
+    WRowIdxCSR: &arrayfire::Array<i32>,
+    WColIdx: &arrayfire::Array<i32>,
+
+    Wseqs: &[arrayfire::Seq<i32>; 1],
+    Hseqs: &[arrayfire::Seq<i32>; 1],
+    Aseqs: &[arrayfire::Seq<i32>; 1],
+    Bseqs: &[arrayfire::Seq<i32>; 1],
+    Cseqs: &[arrayfire::Seq<i32>; 1],
+    Dseqs: &[arrayfire::Seq<i32>; 1],
+    Eseqs: &[arrayfire::Seq<i32>; 1],
+
+    idxsel: &arrayfire::Array<i32>,
+    Y: &arrayfire::Array<T>,
+    eval_metric: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> T + Copy,
+    eval_metric_grad: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> arrayfire::Array<T> + Copy,
+
+    neuron_idx: &arrayfire::Array<i32>,
+
+    idxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    valsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+
+    cvec_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    dXsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+
+    nrows_out: &nohash_hasher::IntMap<i64, u64>,
+    sparseval_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    sparserow_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    sparsecol_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+
+    Hidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    Aidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    Bidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    Cidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    Didxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    Eidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+    combidxsel_out: &nohash_hasher::IntMap<i64, arrayfire::Array<i32>>,
+
+    dAseqs_out: &nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]>,
+    dBseqs_out: &nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]>,
+    dCseqs_out: &nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]>,
+    dDseqs_out: &nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]>,
+    dEseqs_out: &nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]>,
+
+    norm: &norm_state_type<T>,
+
+    Z: &mut arrayfire::Array<T>,
+    Q: &mut arrayfire::Array<T>,
+
+    alpha: &mut T,
+    loss_output: &mut T,
+) {
+    let LARGE_POS_NUM = T::from_f64(1.0e9);
+    //The line search runs in evaluation mode, so it does not change the batch norm
+    //running statistics
+    let mut eval_norm = norm.clone();
+    eval_norm.training = false;
+
+    //let init_loss = loss(init_point);
+    let mut init_loss = LARGE_POS_NUM;
+
+    loss_wrapper(
+        netdata,
+        X,
+        WRowIdxCSR,
+        WColIdx,
+        Wseqs,
+        Hseqs,
+        Aseqs,
+        Bseqs,
+        Cseqs,
+        Dseqs,
+        Eseqs,
+        init_point,
+        &mut eval_norm,
+        idxsel,
+        Y,
+        eval_metric,
+        Z,
+        Q,
+        &mut init_loss,
+    );
+
+    if (init_loss.is_nan()) || (init_loss.is_infinite()) {
+        *loss_output = T::infinity();
+        return;
+    }
+
+    let mut init_grad = arrayfire::constant::<T>(T::zero(), init_point.dims());
+
+    state_space_backward_group2_norm(
+        netdata,
+        X,
+        WRowIdxCSR,
+        WColIdx,
+        Wseqs,
+        Hseqs,
+        Aseqs,
+        Bseqs,
+        Cseqs,
+        Dseqs,
+        Eseqs,
+        init_point,
+        Z,
+        Q,
+        Y,
+        eval_metric_grad,
+        neuron_idx,
+        idxsel_out,
+        valsel_out,
+        cvec_out,
+        dXsel_out,
+        nrows_out,
+        sparseval_out,
+        sparserow_out,
+        sparsecol_out,
+        Hidxsel_out,
+        Aidxsel_out,
+        Bidxsel_out,
+        Cidxsel_out,
+        Didxsel_out,
+        Eidxsel_out,
+        combidxsel_out,
+        dAseqs_out,
+        dBseqs_out,
+        dCseqs_out,
+        dDseqs_out,
+        dEseqs_out,
+        &eval_norm,
+        &mut init_grad,
+    );
+
+    let mut next_point = init_point.clone() + direction.clone() * (*alpha);
+    //let mut f0  = loss(&next_point);
+    let mut f0 = LARGE_POS_NUM;
+
+    loss_wrapper(
+        netdata,
+        X,
+        WRowIdxCSR,
+        WColIdx,
+        Wseqs,
+        Hseqs,
+        Aseqs,
+        Bseqs,
+        Cseqs,
+        Dseqs,
+        Eseqs,
+        &next_point,
+        &mut eval_norm,
+        idxsel,
+        Y,
+        eval_metric,
+        Z,
+        Q,
+        &mut f0,
+    );
+
+    if (f0.is_nan()) || (f0.is_infinite()) {
+        *loss_output = T::infinity();
+        return;
+    }
+
+    //let init_grad = loss_grad(init_point);
+
+    let v0 = arrayfire::mul(direction, &init_grad, false) * rho;
+    let (t0, t1) = sum_all(&v0);
+    let mut f1 = init_loss.clone() + (*alpha) * t0;
+
+    while (f0 > f1) || (f0 > max_loss) {
+        *alpha = (*alpha) * gamma;
+
+        if (*alpha) < min_alpha {
+            break;
+        }
+
+        next_point = init_point.clone() + direction.clone() * (*alpha);
+        //f0  = loss(&next_point);
+
+        loss_wrapper(
+            netdata,
+            X,
+            WRowIdxCSR,
+            WColIdx,
+            Wseqs,
+            Hseqs,
+            Aseqs,
+            Bseqs,
+            Cseqs,
+            Dseqs,
+            Eseqs,
+            &next_point,
+            &mut eval_norm,
+            idxsel,
+            Y,
+            eval_metric,
+            Z,
+            Q,
+            &mut f0,
+        );
+
+        f1 = init_loss.clone() + (*alpha) * t0;
+    }
+
+    *loss_output = f0.clone();
+}
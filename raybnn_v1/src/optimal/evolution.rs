@@ -0,0 +1,1722 @@
+extern crate arrayfire;
+
+use crate::precision::float_trait::raybnn_float_trait;
+use crate::precision::reduce::sum_all;
+use nohash_hasher;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+
+use num::Float;
+use num::ToPrimitive;
+
+use crate::export::rand::single_random_uniform;
+
+use crate::neural::network::network_metadata_type;
+use crate::physics::update::add_neuron_to_existing;
+use crate::physics::update::add_neuron_to_existing2;
+use crate::physics::update::reduce_network_size;
+
+use crate::interpol::linear::find;
+use crate::physics::distance::vec_min_dist;
+
+use crate::error::Error;
+
+use crate::export::dataloader::try_save_network2;
+
+use crate::export::dataloader::load_network;
+use crate::export::dataloader::save_network;
+use crate::graph::large_sparse_u64::COO_batch_find;
+
+use crate::export::dataloader::vec_cpu_to_str;
+use crate::export::dataloader_u64::vec_cpu_to_str as vec_cpu_to_str_u64;
+
+use crate::graph::adjacency::delete_neurons_at_idx;
+use crate::graph::adjacency::delete_smallest_weights;
+use crate::graph::adjacency::delete_unused_neurons;
+
+use crate::export::dataloader::load_network_structure;
+
+use crate::graph::large_sparse_i32::CSR_to_COO;
+
+use crate::interface::automatic::arch_search_type;
+
+use crate::export::dataloader_u64::extract_file_info;
+
+use crate::export::dataloader_u64::find_model_paths;
+
+use crate::export::dataloader_u64::find_cube_paths;
+
+use crate::export::dataloader::extract_file_info2;
+
+use crate::export::dataloader::load_network2;
+use crate::export::dataloader::try_load_network2;
+
+use crate::neural::network::clone_netdata;
+
+use crate::export::dataloader::file_to_vec_cpu;
+
+use crate::interface::automatic::set_network_seed2;
+
+use crate::export::rand_u64::random_uniform_range;
+
+use crate::export::trace::enter_phase;
+
+use crate::interface::autotransfer::reduce_weights;
+
+use crate::optimal::bayesian::acquisition_type;
+use crate::optimal::bayesian::bayesian_observations;
+use crate::optimal::bayesian::bayesian_proposal_type;
+use crate::optimal::bayesian::search_bayesian;
+use crate::optimal::bayesian::try_append_bayesian_history;
+
+use crate::optimal::genetic::search_genetic;
+use crate::optimal::genetic::try_crossover_network;
+
+use serde::{Deserialize, Serialize};
+
+const COO_find_limit: u64 = 1500000000;
+
+const high_u64: u64 = 1000000;
+const low_u64: u64 = 0;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: raybnn_float_trait",
+    deserialize = "T: raybnn_float_trait"
+))]
+pub enum evolution_search_type<T: raybnn_float_trait> {
+    TOP5_SEARCH,
+    METROPOLIS_SEARCH,
+    SURROGATE_SPLINE_SEARCH,
+    BAYESIAN_SEARCH {
+        acquisition: acquisition_type<T>,
+    },
+    GENETIC_SEARCH {
+        population_size: u64,
+        crossover_prob: T,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: raybnn_float_trait",
+    deserialize = "T: raybnn_float_trait"
+))]
+pub struct evolution_info_type<T: raybnn_float_trait> {
+    pub dir_path: String,
+    pub cur_path: String,
+
+    pub search_strategy: evolution_search_type<T>,
+    pub success_idx: u64,
+    pub total_tries: u64,
+
+    pub crossval_vec: Vec<T>,
+    pub netdata_vec: Vec<network_metadata_type<T>>,
+
+    pub traj_size: u64,
+
+    pub max_input_size: u64,
+    pub max_output_size: u64,
+
+    pub max_active_size: u64,
+    pub min_active_size: u64,
+
+    pub max_proc_num: u64,
+    pub min_proc_num: u64,
+
+    pub max_proc_num_step: u64,
+    pub min_proc_num_step: u64,
+
+    pub max_active_size_step: T,
+    pub min_active_size_step: T,
+
+    pub max_prune_num: T,
+    pub min_prune_num: T,
+
+    pub max_search_num: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct evolution_prop {
+    pub dir_name: String,
+
+    pub max_active_size: u64,
+    pub min_active_size: u64,
+
+    pub max_proc_num: u64,
+    pub min_proc_num: u64,
+
+    pub max_proc_step: u64,
+    pub min_proc_step: u64,
+
+    pub max_search_num: u64,
+    pub min_search_num: u64,
+
+    pub max_prune_num: u64,
+    pub min_prune_num: u64,
+}
+
+pub fn extract_info<T: raybnn_float_trait>(
+    netdata_vec: &Vec<network_metadata_type<T>>,
+
+    active_size_vec: &mut Vec<u64>,
+    proc_num_vec: &mut Vec<u64>,
+) {
+    //Retrieve data
+    let vecsize = netdata_vec.len();
+    for i in 0..vecsize {
+        active_size_vec.push(netdata_vec[i].active_size.clone());
+        proc_num_vec.push(netdata_vec[i].proc_num.clone());
+    }
+}
+
+pub fn sort_info<T: raybnn_float_trait>(
+    evolutiondata: &evolution_prop,
+    crossval_vec: &Vec<T>,
+    active_size_vec: &Vec<u64>,
+    proc_num_vec: &Vec<u64>,
+
+    newcrossval_hash: &mut nohash_hasher::IntMap<u64, arrayfire::Array<T>>,
+    newactive_size_hash: &mut nohash_hasher::IntMap<u64, arrayfire::Array<u64>>,
+    newproc_num_vec: &mut Vec<u64>,
+) {
+    *newcrossval_hash = nohash_hasher::IntMap::default();
+    *newactive_size_hash = nohash_hasher::IntMap::default();
+    *newproc_num_vec = Vec::new();
+
+    let max_active_size = evolutiondata.max_active_size;
+    let min_active_size = evolutiondata.min_active_size;
+
+    let max_proc_num = evolutiondata.max_proc_num;
+    let min_proc_num = evolutiondata.min_proc_num;
+
+    let max_proc_step = evolutiondata.max_proc_step;
+    let min_proc_step = evolutiondata.min_proc_step;
+
+    let max_search_num = evolutiondata.max_search_num;
+    let min_search_num = evolutiondata.min_search_num;
+
+    let max_prune_num = evolutiondata.max_prune_num;
+    let min_prune_num = evolutiondata.min_prune_num;
+
+    let mut crossval_arr = arrayfire::Array::new(
+        &crossval_vec,
+        arrayfire::Dim4::new(&[crossval_vec.len() as u64, 1, 1, 1]),
+    );
+
+    let mut active_size_arr = arrayfire::Array::new(
+        &active_size_vec,
+        arrayfire::Dim4::new(&[active_size_vec.len() as u64, 1, 1, 1]),
+    );
+
+    let mut proc_num_arr = arrayfire::Array::new(
+        &proc_num_vec,
+        arrayfire::Dim4::new(&[proc_num_vec.len() as u64, 1, 1, 1]),
+    );
+
+    let gidx = (proc_num_arr.clone() * (max_active_size)) + active_size_arr.clone();
+
+    let (_, idx) = arrayfire::sort_index(&gidx, 0, true);
+
+    crossval_arr = arrayfire::lookup(&crossval_arr, &idx, 0);
+    active_size_arr = arrayfire::lookup(&active_size_arr, &idx, 0);
+    proc_num_arr = arrayfire::lookup(&proc_num_arr, &idx, 0);
+
+    let mut crossval_vec_cpu = vec![T::default(); crossval_arr.elements()];
+    crossval_arr.host(&mut crossval_vec_cpu);
+
+    let mut active_size_vec_cpu = vec![u64::default(); active_size_arr.elements()];
+    active_size_arr.host(&mut active_size_vec_cpu);
+
+    let mut proc_num_vec_cpu = vec![u64::default(); proc_num_arr.elements()];
+    proc_num_arr.host(&mut proc_num_vec_cpu);
+
+    //newcrossval_vec.push(Vec::new());
+    //newactive_size_vec.push(Vec::new());
+
+    //let mut j: usize = 0;
+    let vecsize = crossval_vec_cpu.len();
+    let mut cur_proc_num = proc_num_vec_cpu[0].clone();
+    newproc_num_vec.push(cur_proc_num);
+
+    let temp_dims = arrayfire::Dim4::new(&[1, 1, 1, 1]);
+    let mut cur_crossval = arrayfire::constant::<T>(crossval_vec_cpu[0].clone(), temp_dims);
+    let mut cur_active_size = arrayfire::constant::<u64>(active_size_vec_cpu[0].clone(), temp_dims);
+
+    let mut crossval_arr = cur_crossval.clone();
+    let mut active_size_arr = cur_active_size.clone();
+
+    for i in 1..vecsize {
+        if (cur_proc_num != proc_num_vec_cpu[i]) {
+            (*newcrossval_hash).insert(cur_proc_num, crossval_arr.clone());
+            (*newactive_size_hash).insert(cur_proc_num, active_size_arr.clone());
+
+            crossval_arr = arrayfire::constant::<T>(crossval_vec_cpu[i].clone(), temp_dims);
+            active_size_arr = arrayfire::constant::<u64>(active_size_vec_cpu[i].clone(), temp_dims);
+
+            cur_proc_num = proc_num_vec_cpu[i].clone();
+            newproc_num_vec.push(cur_proc_num);
+
+            //newcrossval_vec.push(Vec::new());
+            //newactive_size_vec.push(Vec::new());
+
+            //j = j + 1;
+        } else {
+            //newcrossval_vec[j].push( crossval_vec_cpu[i].clone());
+            //newactive_size_vec[j].push( active_size_vec_cpu[i].clone());
+
+            cur_crossval = arrayfire::constant::<T>(crossval_vec_cpu[i].clone(), temp_dims);
+            cur_active_size = arrayfire::constant::<u64>(active_size_vec_cpu[i].clone(), temp_dims);
+
+            crossval_arr = arrayfire::join(0, &crossval_arr, &cur_crossval);
+            active_size_arr = arrayfire::join(0, &active_size_arr, &cur_active_size);
+        }
+    }
+
+    (*newcrossval_hash).insert(cur_proc_num, crossval_arr.clone());
+    (*newactive_size_hash).insert(cur_proc_num, active_size_arr.clone());
+}
+
+pub fn linear_sampling<T: raybnn_float_trait>(
+    evolutiondata: &evolution_prop,
+    crossval_vec: &arrayfire::Array<T>,
+    active_size_vec: &arrayfire::Array<u64>,
+
+    out_crossval_vec: &mut arrayfire::Array<T>,
+    out_active_size_vec: &mut arrayfire::Array<u64>,
+) {
+    let low = T::from_f64(-1000000.0);
+    let high = T::from_f64(1000000.0);
+    let max_active_size = evolutiondata.max_active_size;
+    let min_active_size = evolutiondata.min_active_size;
+
+    let max_proc_num = evolutiondata.max_proc_num;
+    let min_proc_num = evolutiondata.min_proc_num;
+
+    let max_proc_step = evolutiondata.max_proc_step;
+    let min_proc_step = evolutiondata.min_proc_step;
+
+    let max_search_num = evolutiondata.max_search_num;
+    let min_search_num = evolutiondata.min_search_num;
+
+    let max_prune_num = evolutiondata.max_prune_num;
+    let min_prune_num = evolutiondata.min_prune_num;
+
+    let neuron_step = max_active_size / max_search_num;
+
+    let sample_num: u64 = 5000;
+
+    let active_size_float = active_size_vec.cast::<T>();
+    let (_, _, idx) = arrayfire::imin_all(crossval_vec);
+
+    let mut active_size_cpu = vec![T::default(); active_size_float.elements()];
+    active_size_float.host(&mut active_size_cpu);
+
+    let mut samples = arrayfire::randn::<T>(arrayfire::Dim4::new(&[sample_num, 1, 1, 1]));
+    samples = samples * T::from_f64(neuron_step as f64);
+    samples = samples + active_size_cpu[idx as usize];
+
+    //let cmp1 = (samples < max_active_size);
+    let cmp1 = arrayfire::lt(&samples, &T::from_f64(max_active_size as f64), false);
+    //let cmp2 = (min_active_size < samples);
+    let cmp2 = arrayfire::lt(&T::from_f64(min_active_size as f64), &samples, false);
+    //let select = af::where( (cmp1*cmp2) == 1);
+    let and1 = arrayfire::and(&cmp1, &cmp2, false);
+    let idxvec = arrayfire::locate(&and1);
+
+    samples = arrayfire::lookup(&samples, &idxvec, 0);
+
+    let dc = arrayfire::diff1(crossval_vec, 0);
+    let dn = arrayfire::diff1(&active_size_float, 0) + T::from_f64(1.0E-5);
+
+    let mut dcdn = dc / dn;
+
+    //let firstelem = arrayfire::row(&dcdn,0);
+    let lastelem = arrayfire::row(&dcdn, (dcdn.dims()[0] - 1) as i64);
+
+    //dcdn = arrayfire::join(0, &firstelem, &dcdn);
+    dcdn = arrayfire::join(0, &dcdn, &lastelem);
+
+    let mut pred_cv = find(&active_size_float, crossval_vec, &dcdn, &samples);
+
+    let (_, idxvec2) = arrayfire::sort_index(&pred_cv, 0, true);
+
+    samples = arrayfire::lookup(&samples, &idxvec2, 0);
+
+    pred_cv = arrayfire::lookup(&pred_cv, &idxvec2, 0);
+
+    let mut samples_u64 = samples.cast::<u64>();
+
+    *out_crossval_vec = arrayfire::row(&pred_cv, 0);
+    *out_active_size_vec = arrayfire::row(&samples_u64, 0);
+    let mut out_active_size_vec_float = out_active_size_vec.clone().cast::<T>();
+
+    let vecsize = samples_u64.dims()[0] as i64;
+
+    let neuron_step_float = T::from_f64(neuron_step as f64);
+    for i in 1..vecsize {
+        let curitem = arrayfire::row(&samples_u64, i);
+        let curitem_float = curitem.cast::<T>();
+
+        let curpred_cv = arrayfire::row(&pred_cv, i);
+
+        if (vec_min_dist(&curitem_float, &out_active_size_vec_float).sqrt() > neuron_step_float) {
+            *out_active_size_vec = arrayfire::join(0, out_active_size_vec, &curitem);
+            out_active_size_vec_float =
+                arrayfire::join(0, &out_active_size_vec_float, &curitem_float);
+
+            *out_crossval_vec = arrayfire::join(0, out_crossval_vec, &curpred_cv);
+        }
+    }
+
+    *out_active_size_vec = arrayfire::clamp(out_active_size_vec, &low_u64, &high_u64, false);
+
+    *out_crossval_vec = arrayfire::clamp(out_crossval_vec, &low, &high, false);
+}
+
+pub fn execute_sampling<T: raybnn_float_trait>(
+    evolutiondata: &evolution_prop,
+
+    sample_func: impl Fn(
+        &evolution_prop,
+        &arrayfire::Array<T>,
+        &arrayfire::Array<u64>,
+
+        &mut arrayfire::Array<T>,
+        &mut arrayfire::Array<u64>,
+    ),
+
+    newcrossval_hash: &nohash_hasher::IntMap<u64, arrayfire::Array<T>>,
+    newactive_size_hash: &nohash_hasher::IntMap<u64, arrayfire::Array<u64>>,
+    newproc_num_vec: &Vec<u64>,
+
+    out_crossval_arr: &mut arrayfire::Array<T>,
+    out_active_size_arr: &mut arrayfire::Array<u64>,
+    out_proc_num_arr: &mut arrayfire::Array<u64>,
+) {
+    //Fit spline to data and find next sampling point
+    let vecsize = newproc_num_vec.len() as usize;
+
+    let temp_dims = arrayfire::Dim4::new(&[1, 1, 1, 1]);
+    let mut cur_proc_num = newproc_num_vec[0].clone();
+
+    //let mut cur_crossval = newcrossval_hash[&cur_proc_num].clone();
+    let mut crossvalarr = newcrossval_hash[&cur_proc_num].clone();
+
+    //let mut cur_active_size = newactive_size_hash[&cur_proc_num].clone();
+    let mut active_sizearr = newactive_size_hash[&cur_proc_num].clone();
+
+    //let temp_dims = arrayfire::Dim4::new(&[1,1,1,1]);
+    //let mut out_crossval_arr = arrayfire::constant::<T>(T::zero(),temp_dims);
+    //let mut out_active_size_arr = arrayfire::constant::<u64>(0,temp_dims);
+    //let mut out_proc_num_arr = arrayfire::constant::<u64>(0,temp_dims);
+
+    let mut out_crossval = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut out_active_size = arrayfire::constant::<u64>(0, temp_dims);
+
+    sample_func(
+        evolutiondata,
+        &crossvalarr,
+        &active_sizearr,
+        out_crossval_arr,
+        out_active_size_arr,
+    );
+
+    let mut out_proc_num = arrayfire::constant::<u64>(cur_proc_num, out_active_size_arr.dims());
+
+    *out_proc_num_arr = out_proc_num.clone();
+
+    for i in 1..vecsize {
+        cur_proc_num = newproc_num_vec[i].clone();
+
+        //cur_crossval = newcrossval_hash[&cur_proc_num].clone();
+        crossvalarr = newcrossval_hash[&cur_proc_num].clone();
+
+        if (crossvalarr.dims()[0] < 3) {
+            continue;
+        }
+
+        //cur_active_size = newactive_size_hash[&cur_proc_num].clone();
+        active_sizearr = newactive_size_hash[&cur_proc_num].clone();
+
+        sample_func(
+            evolutiondata,
+            &crossvalarr,
+            &active_sizearr,
+            &mut out_crossval,
+            &mut out_active_size,
+        );
+
+        out_proc_num = arrayfire::constant::<u64>(cur_proc_num, out_active_size.dims());
+        *out_proc_num_arr = arrayfire::join(0, out_proc_num_arr, &out_proc_num);
+
+        *out_crossval_arr = arrayfire::join(0, out_crossval_arr, &out_crossval);
+        *out_active_size_arr = arrayfire::join(0, out_active_size_arr, &out_active_size);
+    }
+}
+
+pub fn filter_samples(
+    evolutiondata: &evolution_prop,
+
+    active_size_arrz: &arrayfire::Array<u64>,
+    proc_num_arrz: &arrayfire::Array<u64>,
+
+    out_active_size_arr: &mut arrayfire::Array<u64>,
+    out_proc_num_arr: &mut arrayfire::Array<u64>,
+) {
+    let max_active_size = evolutiondata.max_active_size;
+    let min_active_size = evolutiondata.min_active_size;
+
+    //Filter out existing
+    if out_proc_num_arr.dims()[0] > 50 {
+        *out_proc_num_arr = arrayfire::rows(out_proc_num_arr, 0, 49);
+        *out_active_size_arr = arrayfire::rows(out_active_size_arr, 0, 49);
+    }
+
+    let COO_batch_size = 1 + ((COO_find_limit / active_size_arrz.dims()[0]) as u64);
+
+    //Compute global index
+    let gidx1 = proc_num_arrz.clone() * (max_active_size) + active_size_arrz.clone();
+
+    let gidx2 = out_proc_num_arr.clone() * (max_active_size) + out_active_size_arr.clone();
+
+    let gidx3 = COO_batch_find(&gidx2, &gidx1, COO_batch_size);
+
+    //Filter out existing connections
+    if gidx3.dims()[0] > 0 {
+        let mut table = arrayfire::constant::<bool>(true, gidx2.dims());
+
+        let inarr = arrayfire::constant::<bool>(false, gidx3.dims());
+
+        let mut idxrs = arrayfire::Indexer::default();
+        idxrs.set_index(&gidx3, 0, None);
+        arrayfire::assign_gen(&mut table, &idxrs, &inarr);
+
+        let tempidx = arrayfire::locate(&table);
+
+        *out_proc_num_arr = arrayfire::lookup(out_proc_num_arr, &tempidx, 0);
+        *out_active_size_arr = arrayfire::lookup(out_active_size_arr, &tempidx, 0);
+    }
+}
+
+pub fn update<T: raybnn_float_trait>(
+    evolutiondata: &evolution_prop,
+    crossval_vec: &mut Vec<T>,
+    netdata_vec: &mut Vec<network_metadata_type<T>>,
+
+    cube_pos: &arrayfire::Array<T>,
+    cube_radius: T,
+
+    netdata: &mut network_metadata_type<T>,
+    WValues: &mut arrayfire::Array<T>,
+    WRowIdxCOO: &mut arrayfire::Array<i32>,
+    WRowIdxCSR: &mut arrayfire::Array<i32>,
+    WColIdx: &mut arrayfire::Array<i32>,
+    H: &mut arrayfire::Array<T>,
+    A: &mut arrayfire::Array<T>,
+    B: &mut arrayfire::Array<T>,
+    C: &mut arrayfire::Array<T>,
+    D: &mut arrayfire::Array<T>,
+    E: &mut arrayfire::Array<T>,
+    glia_pos: &mut arrayfire::Array<T>,
+    neuron_pos: &mut arrayfire::Array<T>,
+    neuron_idx: &mut arrayfire::Array<i32>,
+) {
+    //let active_size: u64 = netdata.active_size.clone();
+    let input_size: u64 = netdata.input_size.clone();
+    let output_size: u64 = netdata.output_size.clone();
+    let mut proc_num: u64 = netdata.proc_num.clone();
+    let active_size: u64 = netdata.active_size.clone();
+    let space_dims: u64 = netdata.space_dims.clone();
+    let step_num: u64 = netdata.step_num.clone();
+    let batch_size: u64 = netdata.batch_size.clone();
+
+    let del_unused_neuron: bool = netdata.del_unused_neuron.clone();
+
+    let time_step: T = netdata.time_step.clone();
+    let nratio: T = netdata.nratio.clone();
+    let neuron_std: T = netdata.neuron_std.clone();
+    let sphere_rad: T = netdata.sphere_rad.clone();
+    let neuron_rad: T = netdata.neuron_rad.clone();
+    let con_rad: T = netdata.con_rad.clone();
+    let init_prob: T = netdata.init_prob.clone();
+    let add_neuron_rate: T = netdata.add_neuron_rate.clone();
+    let del_neuron_rate: T = netdata.del_neuron_rate.clone();
+    let center_const: T = netdata.center_const.clone();
+    let spring_const: T = netdata.spring_const.clone();
+    let repel_const: T = netdata.repel_const.clone();
+
+    let dir_name = evolutiondata.dir_name.clone();
+
+    let max_active_size = evolutiondata.max_active_size;
+    let min_active_size = evolutiondata.min_active_size;
+
+    let max_proc_num = evolutiondata.max_proc_num;
+    let min_proc_num = evolutiondata.min_proc_num;
+
+    let max_proc_step = evolutiondata.max_proc_step;
+    let min_proc_step = evolutiondata.min_proc_step;
+
+    let max_search_num = evolutiondata.max_search_num;
+    let min_search_num = evolutiondata.min_search_num;
+
+    let max_prune_num = evolutiondata.max_prune_num;
+    let min_prune_num = evolutiondata.min_prune_num;
+
+    //Save current network
+    let mut active_size = neuron_idx.dims()[0];
+    let mut hidden_size = active_size - output_size - input_size;
+    (*netdata).active_size = active_size;
+
+    let filename = format!(
+        "{}/active_size_{}_proc_num_{}.csv",
+        dir_name, active_size, proc_num
+    );
+
+    save_network(
+        &filename, netdata, WValues, WRowIdxCSR, WColIdx, H, A, B, C, D, E, glia_pos, neuron_pos,
+        neuron_idx,
+    );
+
+    //Add more neurons
+    if crossval_vec.len() < 4 {
+        let new_neuron_num = (T::from_f64(hidden_size as f64) * add_neuron_rate)
+            .to_u64()
+            .unwrap()
+            - hidden_size;
+
+        add_neuron_to_existing(
+            cube_pos,
+            cube_radius,
+            new_neuron_num,
+            netdata,
+            WValues,
+            WRowIdxCOO,
+            WColIdx,
+            neuron_pos,
+            neuron_idx,
+        );
+
+        return;
+    }
+
+    //Get network information
+    let mut active_size_vec: Vec<u64> = Vec::new();
+    let mut proc_num_vec: Vec<u64> = Vec::new();
+
+    extract_info(netdata_vec, &mut active_size_vec, &mut proc_num_vec);
+
+    //Save Info
+    let mut saveInfo: Vec<String> = Vec::new();
+
+    let s0 = vec_cpu_to_str(crossval_vec);
+    saveInfo.push(s0);
+
+    let s0 = vec_cpu_to_str_u64(&active_size_vec);
+    saveInfo.push(s0);
+
+    let s0 = vec_cpu_to_str_u64(&proc_num_vec);
+    saveInfo.push(s0);
+
+    let filename2 = format!("{}/info.csv", dir_name);
+
+    let mut fileInfo = File::create(filename2).unwrap();
+    writeln!(fileInfo, "{}", saveInfo.join("\n"));
+
+    //Sort network in to vec
+    let mut newcrossval_hash: nohash_hasher::IntMap<u64, arrayfire::Array<T>> =
+        nohash_hasher::IntMap::default();
+    let mut newactive_size_hash: nohash_hasher::IntMap<u64, arrayfire::Array<u64>> =
+        nohash_hasher::IntMap::default();
+    let mut newproc_num_vec: Vec<u64> = Vec::new();
+
+    sort_info(
+        evolutiondata,
+        crossval_vec,
+        &active_size_vec,
+        &proc_num_vec,
+        &mut newcrossval_hash,
+        &mut newactive_size_hash,
+        &mut newproc_num_vec,
+    );
+
+    //Sample on all proc num
+    let temp_dims = arrayfire::Dim4::new(&[1, 1, 1, 1]);
+    let mut out_crossval_arr = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut out_active_size_arr = arrayfire::constant::<u64>(0, temp_dims);
+    let mut out_proc_num_arr = arrayfire::constant::<u64>(0, temp_dims);
+
+    execute_sampling(
+        &evolutiondata,
+        linear_sampling,
+        &newcrossval_hash,
+        &newactive_size_hash,
+        &newproc_num_vec,
+        &mut out_crossval_arr,
+        &mut out_active_size_arr,
+        &mut out_proc_num_arr,
+    );
+
+    //Find closest network
+    let (_, idxvec) = arrayfire::sort_index(&out_crossval_arr, 0, true);
+
+    //out_crossval_arr = arrayfire::lookup(&out_crossval_arr, &idxvec, 0);
+    out_proc_num_arr = arrayfire::lookup(&out_proc_num_arr, &idxvec, 0);
+    out_active_size_arr = arrayfire::lookup(&out_active_size_arr, &idxvec, 0);
+
+    //Filter samples
+    let active_size_arrz = arrayfire::Array::new(
+        &active_size_vec,
+        arrayfire::Dim4::new(&[active_size_vec.len() as u64, 1, 1, 1]),
+    );
+    let proc_num_arrz = arrayfire::Array::new(
+        &proc_num_vec,
+        arrayfire::Dim4::new(&[proc_num_vec.len() as u64, 1, 1, 1]),
+    );
+
+    filter_samples(
+        &evolutiondata,
+        &active_size_arrz,
+        &proc_num_arrz,
+        &mut out_active_size_arr,
+        &mut out_proc_num_arr,
+    );
+
+    //Select the best
+    //out_crossval_arr = arrayfire::row(&out_crossval_arr,  0);
+    out_proc_num_arr = arrayfire::row(&out_proc_num_arr, 0);
+    out_active_size_arr = arrayfire::row(&out_active_size_arr, 0);
+
+    //Convert to CPU
+    let mut next_proc_num_vec = vec![u64::default(); out_proc_num_arr.elements()];
+    out_proc_num_arr.host(&mut next_proc_num_vec);
+
+    proc_num = next_proc_num_vec[0];
+
+    //Convert to CPU
+    let mut next_active_size_vec = vec![u64::default(); out_active_size_arr.elements()];
+    out_active_size_arr.host(&mut next_active_size_vec);
+
+    let mut next_active_size = next_active_size_vec[0];
+
+    //Compute dist to nearest model
+    let mut sel_active_size = newactive_size_hash[&proc_num].clone();
+
+    //sel_active_size.retain(|&x| x <  next_active_size);
+
+    let sel_active_size_arr_float = sel_active_size.cast::<T>();
+    let out_active_size_arr_float = out_active_size_arr.cast::<T>();
+
+    let mut dist = arrayfire::sub(&sel_active_size_arr_float, &out_active_size_arr_float, true);
+
+    dist = arrayfire::abs(&dist);
+
+    let (_, _, idx) = arrayfire::imin_all(&dist);
+
+    let mut sel_active_size_vec = vec![u64::default(); sel_active_size.elements()];
+    sel_active_size.host(&mut sel_active_size_vec);
+
+    //Load nearest network
+    active_size = sel_active_size_vec[idx as usize].clone();
+    (*netdata).active_size = active_size;
+    (*netdata).proc_num = proc_num;
+
+    let filename = format!(
+        "{}/active_size_{}_proc_num_{}.csv",
+        dir_name, active_size, proc_num
+    );
+
+    load_network(
+        &filename, netdata, WValues, WRowIdxCSR, WColIdx, H, A, B, C, D, E, glia_pos, neuron_pos,
+        neuron_idx,
+    );
+
+    *WRowIdxCOO = CSR_to_COO(WRowIdxCSR);
+
+    let neuron_step = max_active_size / max_search_num;
+
+    /*
+    let rounds = ((crossval_vec.len() as u64) / min_search_num);
+
+    if (rounds > 0) && ((rounds + min_proc_num) <= max_proc_num)
+    {
+        (*netdata).proc_num = min_proc_num+rounds;
+        proc_num = (*netdata).proc_num;
+    }
+    */
+
+    if (next_active_size > active_size) {
+        //Add neurons
+        let mut new_neuron_num = 2 * (next_active_size - active_size);
+
+        if next_active_size >= (max_active_size - 1) {
+            new_neuron_num = 2 * (max_active_size - active_size);
+        }
+
+        if new_neuron_num > (4 * neuron_step) {
+            new_neuron_num = (4 * neuron_step);
+        }
+
+        let del_num = (T::from_f64(active_size as f64) * T::from_f64(0.01))
+            .to_u64()
+            .unwrap();
+
+        delete_smallest_weights(WValues, WRowIdxCOO, WColIdx, del_num);
+
+        delete_unused_neurons(
+            netdata, WValues, WRowIdxCOO, WColIdx, glia_pos, neuron_pos, neuron_idx,
+        );
+
+        add_neuron_to_existing(
+            cube_pos,
+            cube_radius,
+            new_neuron_num,
+            netdata,
+            WValues,
+            WRowIdxCOO,
+            WColIdx,
+            neuron_pos,
+            neuron_idx,
+        );
+    } else if (next_active_size < active_size) {
+        let mut del_neuron_num = (active_size - next_active_size) as usize;
+
+        let mut delvec: Vec<u64> = Vec::new();
+
+        let mut curidx = 0;
+        while (delvec.len() < del_neuron_num) {
+            curidx = random_uniform_range(active_size);
+
+            if (input_size <= curidx) && (curidx < (active_size - output_size)) {
+                if (delvec.contains(&curidx) == false) {
+                    delvec.push(curidx);
+                }
+            }
+        }
+
+        let delete_idx = arrayfire::Array::new(
+            &delvec,
+            arrayfire::Dim4::new(&[delvec.len() as u64, 1, 1, 1]),
+        );
+
+        let delete_idx2 = arrayfire::sort(&delete_idx, 0, true).cast::<i32>();
+
+        delete_neurons_at_idx(&delete_idx2, WValues, WRowIdxCOO, WColIdx);
+
+        delete_unused_neurons(
+            netdata, WValues, WRowIdxCOO, WColIdx, glia_pos, neuron_pos, neuron_idx,
+        );
+    }
+}
+
+pub fn get_crossval_data<T: raybnn_float_trait>(
+    arch_search: &mut arch_search_type<T>,
+
+    checked_paths: &mut Vec<String>,
+    max_success_idx: &mut u64,
+) {
+    let LARGE_POS_NUM = T::from_f64(1.0e9);
+    //Find all model paths
+    let model_path_vec3 = find_model_paths(&arch_search.evolution_info.dir_path);
+
+    let mut model_path_vec2 = Vec::new();
+    for path in model_path_vec3 {
+        let info_vec = extract_file_info(&path);
+        if info_vec.len() == 4 {
+            model_path_vec2.push(path.clone());
+        }
+    }
+
+    tracing::debug!(model_path_vec2 = ?model_path_vec2, "found trained models");
+
+    //Sort all models
+    let mut unordered_vec: Vec<u64> = Vec::new();
+
+    for path in model_path_vec2.clone() {
+        let info_vec = extract_file_info(&path);
+        unordered_vec.push(info_vec[3].clone());
+    }
+
+    tracing::debug!(unordered_vec = ?unordered_vec, "success indices of the models");
+    let unordered_arr = arrayfire::Array::new(
+        &unordered_vec,
+        arrayfire::Dim4::new(&[unordered_vec.len() as u64, 1, 1, 1]),
+    );
+
+    let (_, idx) = arrayfire::sort_index(&unordered_arr, 0, true);
+
+    let mut idx_cpu = vec![u32::default(); idx.elements()];
+    idx.host(&mut idx_cpu);
+
+    let mut model_path_vec: Vec<String> = Vec::new();
+    for qq in idx_cpu {
+        let item = model_path_vec2[qq as usize].clone();
+        model_path_vec.push(item);
+    }
+
+    tracing::debug!(model_path_vec = ?model_path_vec, "sorted models");
+
+    //Reset info
+    (*arch_search).evolution_info.crossval_vec = Vec::new();
+
+    (*arch_search).evolution_info.netdata_vec = Vec::new();
+
+    //let mut checked_paths: Vec<String> = Vec::new();
+    checked_paths.clear();
+
+    *max_success_idx = 0;
+
+    //Search all models
+    for path in model_path_vec {
+        let info_vec = extract_file_info(&path);
+
+        if info_vec.len() == 4 {
+            tracing::debug!(path = %path, info_vec = ?info_vec, "loading model");
+
+            if info_vec[3].clone() > *max_success_idx {
+                *max_success_idx = info_vec[3].clone();
+            }
+
+            //Load neural network file
+            let neural_network = load_network2(&path);
+
+            let metadata = clone_netdata(&(neural_network.netdata));
+            drop(neural_network);
+
+            //Append metadata
+            (*arch_search).evolution_info.netdata_vec.push(metadata);
+
+            //Add to path vector
+            checked_paths.push(path.clone());
+
+            //Load metric
+            let rootname = path.clone().replace(".csv", "");
+
+            let targetpath = format!("{}.metric", rootname);
+
+            let eval_metric_out = file_to_vec_cpu::<T>(&targetpath);
+
+            //Take average
+            let mut avgelem = eval_metric_out.iter().cloned().sum::<T>()
+                / T::from_f64(eval_metric_out.len() as f64);
+
+            if avgelem.is_infinite() || avgelem.is_nan() {
+                avgelem = LARGE_POS_NUM;
+            }
+
+            tracing::debug!(
+                path = %path,
+                avgelem = avgelem.to_f64().unwrap_or(f64::NAN),
+                "mean crossval metric"
+            );
+
+            //Append crossval
+            (*arch_search).evolution_info.crossval_vec.push(avgelem);
+        }
+    }
+}
+
+pub fn search_top5<T: raybnn_float_trait>(
+    arch_search: &mut arch_search_type<T>,
+    checked_paths: &mut Vec<String>,
+) -> usize {
+    let SMALL_POS_NUM = T::from_f64(1.0e-6);
+    let mut crossval_arr = arrayfire::Array::new(
+        &(*arch_search).evolution_info.crossval_vec,
+        arrayfire::Dim4::new(&[
+            (*arch_search).evolution_info.crossval_vec.len() as u64,
+            1,
+            1,
+            1,
+        ]),
+    );
+
+    //Get lowest cross val
+    let (_, crossidx) = arrayfire::sort_index(&crossval_arr, 0, true);
+
+    let mut crossidx_cpu = vec![u32::default(); crossidx.elements()];
+    crossidx.host(&mut crossidx_cpu);
+
+    let mut tempcrossval_vec = Vec::new();
+
+    let mut tempnetdata_vec = Vec::new();
+
+    let mut tempchecked_paths: Vec<String> = Vec::new();
+
+    //Enumerate across lowest crossval to get network data
+    for qq in 0..crossidx_cpu.len().min(5) {
+        let tempidx = crossidx_cpu[qq];
+
+        tempcrossval_vec.push((*arch_search).evolution_info.crossval_vec[tempidx as usize].clone());
+
+        let tempnetdata =
+            clone_netdata(&((*arch_search).evolution_info.netdata_vec[tempidx as usize]));
+
+        tempnetdata_vec.push(tempnetdata);
+
+        tempchecked_paths.push(checked_paths[tempidx as usize].clone());
+    }
+
+    //Get lowest 5 values
+    (*arch_search).evolution_info.crossval_vec = tempcrossval_vec.clone();
+    crossval_arr = arrayfire::Array::new(
+        &(*arch_search).evolution_info.crossval_vec,
+        arrayfire::Dim4::new(&[
+            (*arch_search).evolution_info.crossval_vec.len() as u64,
+            1,
+            1,
+            1,
+        ]),
+    );
+
+    (*arch_search).evolution_info.netdata_vec = tempnetdata_vec;
+
+    *checked_paths = tempchecked_paths.clone();
+
+    tracing::debug!(
+        crossval_vec = ?(*arch_search).evolution_info.crossval_vec,
+        "crossval of the top 5 models"
+    );
+
+    //Generate selection probabilities
+    let (min_val, _) = arrayfire::min_all(&crossval_arr);
+    crossval_arr = crossval_arr + min_val + SMALL_POS_NUM;
+
+    let (max_val, _) = arrayfire::max_all(&crossval_arr);
+
+    crossval_arr = crossval_arr / (max_val + SMALL_POS_NUM);
+
+    let mut prob = -crossval_arr;
+    prob = arrayfire::exp(&prob);
+
+    let (probsum, _) = sum_all(&prob);
+    prob = prob.clone() / probsum;
+
+    //Select next point
+    let mut quit = false;
+    let mut selected_idx = 0;
+    while 1 == 1 {
+        let randidx = arrayfire::randu::<T>(prob.dims());
+        let randarr = arrayfire::randu::<T>(prob.dims());
+
+        let (_, probidx) = arrayfire::sort_index(&randidx, 0, false);
+
+        let mut probidx_cpu = vec![u32::default(); probidx.elements()];
+        probidx.host(&mut probidx_cpu);
+
+        let mut randarr_cpu = vec![T::default(); randarr.elements()];
+        randarr.host(&mut randarr_cpu);
+
+        let mut prob_cpu = vec![T::default(); prob.elements()];
+        prob.host(&mut prob_cpu);
+
+        for q in 0..(prob_cpu.len()) {
+            let cur_index = probidx_cpu[q].clone() as usize;
+
+            if prob_cpu[cur_index] > randarr_cpu[cur_index] {
+                selected_idx = q.clone();
+                quit = true;
+                break;
+            }
+        }
+
+        if quit == true {
+            break;
+        }
+    }
+
+    selected_idx
+}
+
+pub fn search_metropolis<T: raybnn_float_trait>(
+    arch_search: &mut arch_search_type<T>,
+
+    checked_paths: &mut Vec<String>,
+    max_success_idx: u64,
+) -> usize {
+    let SMALL_POS_NUM = T::from_f64(1.0e-6);
+    let crossval_arr = arrayfire::Array::new(
+        &(*arch_search).evolution_info.crossval_vec,
+        arrayfire::Dim4::new(&[
+            (*arch_search).evolution_info.crossval_vec.len() as u64,
+            1,
+            1,
+            1,
+        ]),
+    );
+
+    let (mut min_val, _) = arrayfire::min_all(&crossval_arr);
+    min_val = min_val.abs() + SMALL_POS_NUM;
+
+    let mut selected_idx = 0;
+
+    let cur_idx = checked_paths.len() - 1;
+    let cur_crossval = (*arch_search).evolution_info.crossval_vec[cur_idx].clone();
+    let cur_path = checked_paths[cur_idx].clone();
+
+    let prev_idx = checked_paths.len() - 2;
+    let prev_crossval = (*arch_search).evolution_info.crossval_vec[prev_idx].clone();
+
+    tracing::debug!(
+        prev_crossval = prev_crossval.to_f64().unwrap_or(f64::NAN),
+        cur_crossval = cur_crossval.to_f64().unwrap_or(f64::NAN),
+        "metropolis step"
+    );
+
+    if prev_crossval > cur_crossval {
+        selected_idx = cur_idx as usize;
+        tracing::debug!("accepted lower crossval");
+    } else {
+        let diff = (prev_crossval - cur_crossval) / min_val;
+        let expval = diff.exp();
+
+        tracing::debug!(
+            diff = diff.to_f64().unwrap_or(f64::NAN),
+            expval = expval.to_f64().unwrap_or(f64::NAN),
+            "metropolis acceptance probability"
+        );
+
+        let rand_number = single_random_uniform::<T>();
+
+        if (expval > rand_number) {
+            tracing::debug!("accepted current model");
+
+            selected_idx = cur_idx as usize;
+        } else {
+            tracing::debug!(cur_path = %cur_path, "rejected current model");
+
+            selected_idx = prev_idx as usize;
+            std::fs::remove_file(cur_path);
+        }
+    }
+
+    selected_idx
+}
+
+/*
+Surrogate spline search over the evaluated models
+
+Fits a spline of the crossval metric over active_size for every proc_num, with the slopes of
+the linear interpolation find(), a spline of the lowest crossval of every proc_num over
+proc_num and a spline of the mean crossval over prune_num. The predicted crossval of a
+candidate is the active_size spline of the nearest evaluated proc_num plus the change of the
+proc_num spline and the change of the prune_num spline from the mean prune_num of the models
+of the nearest evaluated proc_num.
+
+Candidates are steps of max_active_size_step or less from evaluated models, steps of
+max_proc_num_step or less from evaluated proc_num and prune_num between min_prune_num and
+max_prune_num. Smaller candidates are reached by pruning the selected model with
+reduce_network_size.
+
+prune_num is not stored in the network files. The prune_num of the evaluated models is read
+from the proposal history of bayesian_observations, which evolve_network appends to for this
+search too. While fewer than two prune_num have been evaluated, the prune_num spline is flat
+and the prune_num of the proposal is drawn uniformly between min_prune_num and max_prune_num.
+
+Inputs
+arch_search:         Evolution info with crossval_vec and netdata_vec of the evaluated models
+checked_paths:       Model file of every evaluated model
+
+Outputs:
+(selected_idx, new_active_size, new_proc_num, new_prune_num): Model to start from and the
+                     candidate with the lowest predicted crossval. None if there are too few
+                     models or no unevaluated candidates. Error if the proposal history can
+                     not be read
+
+*/
+pub fn search_surrogate_spline<T: raybnn_float_trait>(
+    arch_search: &arch_search_type<T>,
+    checked_paths: &[String],
+) -> Result<Option<(usize, u64, u64, T)>, Error> {
+    let crossval_vec = &(*arch_search).evolution_info.crossval_vec;
+
+    let mut active_size_vec: Vec<u64> = Vec::new();
+    let mut proc_num_vec: Vec<u64> = Vec::new();
+    extract_info(
+        &(*arch_search).evolution_info.netdata_vec,
+        &mut active_size_vec,
+        &mut proc_num_vec,
+    );
+
+    if crossval_vec.len() < 3 {
+        return Ok(None);
+    }
+
+    let max_active_size = (*arch_search).evolution_info.max_active_size;
+    let min_active_size = (*arch_search).evolution_info.min_active_size;
+
+    let max_proc_num = (*arch_search).evolution_info.max_proc_num;
+    let min_proc_num = (*arch_search).evolution_info.min_proc_num;
+
+    let max_proc_num_step = (*arch_search).evolution_info.max_proc_num_step;
+
+    let max_active_size_step = (*arch_search).evolution_info.max_active_size_step;
+    let min_active_size_step = (*arch_search).evolution_info.min_active_size_step;
+
+    let max_prune_num = (*arch_search).evolution_info.max_prune_num;
+    let min_prune_num = (*arch_search).evolution_info.min_prune_num;
+
+    let prune_num_vec: Vec<T> = bayesian_observations(arch_search, checked_paths)?
+        .iter()
+        .map(|obs| obs.prune_num)
+        .collect();
+
+    //Group the mean crossval of every active_size by proc_num
+    let mut group_hash: HashMap<u64, HashMap<u64, (T, T)>> = HashMap::new();
+    for i in 0..crossval_vec.len() {
+        let point = group_hash
+            .entry(proc_num_vec[i])
+            .or_default()
+            .entry(active_size_vec[i])
+            .or_insert((T::zero(), T::zero()));
+        point.0 = point.0 + crossval_vec[i];
+        point.1 = point.1 + T::one();
+    }
+
+    let mut group_proc_num: Vec<u64> = group_hash.keys().cloned().collect();
+    group_proc_num.sort();
+
+    //Spline of crossval over active_size for every proc_num
+    let mut knot_hash: HashMap<u64, (Vec<T>, Vec<T>)> = HashMap::new();
+    for proc_num in group_proc_num.clone() {
+        let mut knots: Vec<(u64, T)> = group_hash[&proc_num]
+            .iter()
+            .map(|(active_size, (total, count))| (*active_size, *total / *count))
+            .collect();
+        knots.sort_by_key(|knot| knot.0);
+
+        let t: Vec<T> = knots
+            .iter()
+            .map(|knot| T::from_f64(knot.0 as f64))
+            .collect();
+        let f: Vec<T> = knots.iter().map(|knot| knot.1).collect();
+        knot_hash.insert(proc_num, (t, f));
+    }
+
+    if knot_hash.values().all(|(t, _)| t.len() < 2) {
+        return Ok(None);
+    }
+
+    let proc_t: Vec<T> = group_proc_num
+        .iter()
+        .map(|p| T::from_f64(*p as f64))
+        .collect();
+    let proc_f: Vec<T> = group_proc_num
+        .iter()
+        .map(|p| knot_hash[p].1.iter().cloned().fold(T::infinity(), T::min))
+        .collect();
+
+    //Spline of the mean crossval over prune_num
+    let mut prune_order: Vec<usize> = (0..crossval_vec.len()).collect();
+    prune_order.sort_by(|a, b| {
+        let a = prune_num_vec[*a].to_f64().unwrap();
+        let b = prune_num_vec[*b].to_f64().unwrap();
+        a.total_cmp(&b)
+    });
+
+    let mut prune_knots: Vec<(T, T, T)> = Vec::new();
+    for i in prune_order {
+        match prune_knots.last_mut() {
+            Some(knot) if knot.0 == prune_num_vec[i] => {
+                knot.1 = knot.1 + crossval_vec[i];
+                knot.2 = knot.2 + T::one();
+            }
+            _ => prune_knots.push((prune_num_vec[i], crossval_vec[i], T::one())),
+        }
+    }
+
+    let prune_t: Vec<T> = prune_knots.iter().map(|knot| knot.0).collect();
+    let prune_f: Vec<T> = prune_knots.iter().map(|knot| knot.1 / knot.2).collect();
+
+    //Candidates around the evaluated models
+    let step_num = 20;
+
+    let prune_candidates: Vec<T> = if prune_t.len() < 2 {
+        vec![min_prune_num + (max_prune_num - min_prune_num) * single_random_uniform::<T>()]
+    } else {
+        (0..step_num)
+            .map(|j| {
+                min_prune_num
+                    + (max_prune_num - min_prune_num) * T::from_f64(j as f64)
+                        / T::from_f64((step_num - 1) as f64)
+            })
+            .collect()
+    };
+    let prune_pred = spline(&prune_t, &prune_f, &prune_candidates);
+
+    let mut best: Option<(T, u64, u64, u64, T)> = None;
+    for proc_num in min_proc_num..(max_proc_num + 1) {
+        //Nearest evaluated proc_num
+        let mut base_proc_num = group_proc_num[0];
+        for p in group_proc_num.clone() {
+            if (p as i64 - proc_num as i64).abs() < (base_proc_num as i64 - proc_num as i64).abs() {
+                base_proc_num = p;
+            }
+        }
+
+        if (base_proc_num as i64 - proc_num as i64).abs() > (max_proc_num_step as i64) {
+            continue;
+        }
+
+        let (t, f) = &knot_hash[&base_proc_num];
+        if t.len() < 2 {
+            continue;
+        }
+
+        let mut candidates: Vec<T> = Vec::new();
+        for knot in t.clone() {
+            for j in 0..step_num {
+                let step = min_active_size_step
+                    + (max_active_size_step - min_active_size_step) * T::from_f64(j as f64)
+                        / T::from_f64((step_num - 1) as f64);
+                candidates.push((knot * (T::one() + step)).round());
+                candidates.push((knot * (T::one() - step)).round());
+            }
+        }
+        candidates.retain(|a| {
+            let a_u64 = a.to_u64().unwrap();
+            (*a > T::one())
+                && (min_active_size < a_u64)
+                && (a_u64 < max_active_size)
+                && !group_hash
+                    .get(&proc_num)
+                    .is_some_and(|group| group.contains_key(&a_u64))
+        });
+
+        if candidates.is_empty() {
+            continue;
+        }
+
+        //Mean prune_num of the models of the nearest evaluated proc_num
+        let mut ref_prune_num = T::zero();
+        let mut ref_num = T::zero();
+        for i in 0..crossval_vec.len() {
+            if proc_num_vec[i] == base_proc_num {
+                ref_prune_num = ref_prune_num + prune_num_vec[i];
+                ref_num = ref_num + T::one();
+            }
+        }
+        ref_prune_num = ref_prune_num / ref_num;
+        let ref_prune_pred = spline(&prune_t, &prune_f, &[ref_prune_num])[0];
+
+        let pred = spline(t, f, &candidates);
+        let proc_pred = spline(
+            &proc_t,
+            &proc_f,
+            &[
+                T::from_f64(proc_num as f64),
+                T::from_f64(base_proc_num as f64),
+            ],
+        );
+
+        for i in 0..candidates.len() {
+            for k in 0..prune_candidates.len() {
+                let value =
+                    pred[i] + (proc_pred[0] - proc_pred[1]) + (prune_pred[k] - ref_prune_pred);
+
+                if value.is_nan() {
+                    continue;
+                }
+
+                let better = match best {
+                    Some(b) => value < b.0,
+                    None => true,
+                };
+
+                if better {
+                    best = Some((
+                        value,
+                        candidates[i].to_u64().unwrap(),
+                        proc_num,
+                        base_proc_num,
+                        prune_candidates[k],
+                    ));
+                }
+            }
+        }
+    }
+
+    let Some((predicted_crossval, new_active_size, new_proc_num, base_proc_num, new_prune_num)) =
+        best
+    else {
+        return Ok(None);
+    };
+
+    //Start from the evaluated model of the nearest proc_num with the nearest active_size
+    let mut selected_idx = 0;
+    let mut selected_dist = u64::MAX;
+    for i in 0..crossval_vec.len() {
+        if proc_num_vec[i] != base_proc_num {
+            continue;
+        }
+
+        let dist = (active_size_vec[i] as i64 - new_active_size as i64).unsigned_abs();
+        if (dist < selected_dist)
+            || ((dist == selected_dist) && (crossval_vec[i] < crossval_vec[selected_idx]))
+        {
+            selected_idx = i;
+            selected_dist = dist;
+        }
+    }
+
+    tracing::debug!(
+        predicted_crossval = predicted_crossval.to_f64().unwrap_or(f64::NAN),
+        new_active_size,
+        new_proc_num,
+        new_prune_num = new_prune_num.to_f64().unwrap_or(f64::NAN),
+        selected_idx,
+        "surrogate spline proposal"
+    );
+
+    Ok(Some((
+        selected_idx,
+        new_active_size,
+        new_proc_num,
+        new_prune_num,
+    )))
+}
+
+//Evaluates the spline through the knots (t, f) at s, with the slopes of the next knot
+fn spline<T: raybnn_float_trait>(t: &[T], f: &[T], s: &[T]) -> Vec<T> {
+    if t.len() < 2 {
+        return vec![f[0]; s.len()];
+    }
+
+    let t_arr = arrayfire::Array::new(t, arrayfire::Dim4::new(&[t.len() as u64, 1, 1, 1]));
+    let f_arr = arrayfire::Array::new(f, arrayfire::Dim4::new(&[f.len() as u64, 1, 1, 1]));
+    let s_arr = arrayfire::Array::new(s, arrayfire::Dim4::new(&[s.len() as u64, 1, 1, 1]));
+
+    let dc = arrayfire::diff1(&f_arr, 0);
+    let dn = arrayfire::diff1(&t_arr, 0) + T::from_f64(1.0E-5);
+
+    let mut dcdn = dc / dn;
+
+    let lastelem = arrayfire::row(&dcdn, (dcdn.dims()[0] - 1) as i64);
+    dcdn = arrayfire::join(0, &dcdn, &lastelem);
+
+    let pred = find(&t_arr, &f_arr, &dcdn, &s_arr);
+
+    let mut pred_cpu = vec![T::default(); pred.elements()];
+    pred.host(&mut pred_cpu);
+
+    pred_cpu
+}
+
+/*
+Creates, changes and saves the next network of the architecture search
+
+The evaluated networks in dir_path are searched with the search_strategy of evolution_info.
+The selected network is loaded, resized and saved to evolution_info.cur_path, and becomes
+neural_network of arch_search.
+
+Inputs
+arch_search:         Evolution info and dir_path of the architecture search
+
+*/
+pub fn try_evolve_network<T: raybnn_float_trait>(
+    arch_search: &mut arch_search_type<T>,
+) -> Result<(), Error> {
+    let _phase = enter_phase(tracing::info_span!(
+        "evolution_step",
+        total_tries = (*arch_search).evolution_info.total_tries,
+        search_strategy = ?(*arch_search).evolution_info.search_strategy
+    ));
+
+    //Save total number of function calls
+    let total_tries = (*arch_search).evolution_info.total_tries;
+    (*arch_search).evolution_info.total_tries = total_tries + 1;
+
+    //Find all cube paths
+    let cube_path_vec = find_cube_paths(&arch_search.evolution_info.dir_path);
+
+    tracing::debug!(cube_path_vec = ?cube_path_vec, "found cube paths");
+
+    let cube_path = cube_path_vec[0].clone();
+
+    let cube_radius = extract_file_info2::<T>(&cube_path)[0];
+
+    tracing::debug!(
+        cube_radius = cube_radius.to_f64().unwrap_or(f64::NAN),
+        "loaded cube radius"
+    );
+
+    let mut checked_paths: Vec<String> = Vec::new();
+    let mut max_success_idx = 0;
+
+    // Get cross validation data
+    get_crossval_data(arch_search, &mut checked_paths, &mut max_success_idx);
+    //
+
+    (*arch_search).evolution_info.success_idx = max_success_idx + 1;
+
+    //Set arrayfire random seed
+    let mut tempseedarr = (*arch_search).evolution_info.crossval_vec.clone();
+    tempseedarr.push(T::from_f64(total_tries as f64));
+
+    set_network_seed2(&tempseedarr);
+
+    // Search for network data using various methods
+    let mut selected_idx = 0;
+    let mut surrogate: Option<(u64, u64)> = None;
+    let mut new_prune_num: Option<T> = None;
+    let mut crossover_idx: Option<usize> = None;
+
+    match arch_search.evolution_info.search_strategy {
+        evolution_search_type::TOP5_SEARCH => {
+            selected_idx = search_top5(arch_search, &mut checked_paths);
+        }
+        evolution_search_type::METROPOLIS_SEARCH => {
+            if max_success_idx <= 3 {
+                selected_idx = search_top5(arch_search, &mut checked_paths);
+            } else {
+                selected_idx = search_metropolis(arch_search, &mut checked_paths, max_success_idx);
+            }
+        }
+        evolution_search_type::SURROGATE_SPLINE_SEARCH => {
+            match search_surrogate_spline(arch_search, &checked_paths)? {
+                Some((idx, new_active_size, new_proc_num, prune_num)) => {
+                    selected_idx = idx;
+                    surrogate = Some((new_active_size, new_proc_num));
+                    new_prune_num = Some(prune_num);
+                }
+                None => {
+                    selected_idx = search_top5(arch_search, &mut checked_paths);
+                }
+            }
+        }
+        evolution_search_type::BAYESIAN_SEARCH { acquisition } => {
+            match search_bayesian(arch_search, &checked_paths, acquisition)? {
+                Some((idx, new_active_size, new_proc_num, prune_num)) => {
+                    selected_idx = idx;
+                    surrogate = Some((new_active_size, new_proc_num));
+                    new_prune_num = Some(prune_num);
+                }
+                None => {
+                    selected_idx = search_top5(arch_search, &mut checked_paths);
+                }
+            }
+        }
+        evolution_search_type::GENETIC_SEARCH {
+            population_size,
+            crossover_prob,
+        } => {
+            let (parent_a, parent_b) = search_genetic(arch_search, population_size)?;
+            selected_idx = parent_a;
+
+            if (parent_a != parent_b) && (single_random_uniform::<T>() < crossover_prob) {
+                crossover_idx = Some(parent_b);
+            }
+        }
+    };
+
+    //Generate new point
+    let selected_netdata =
+        clone_netdata(&((*arch_search).evolution_info.netdata_vec[selected_idx]));
+
+    //Select active size
+    let mut cur_active_size = selected_netdata.active_size.clone();
+
+    let max_active_size = (*arch_search).evolution_info.max_active_size.clone();
+    let min_active_size = (*arch_search).evolution_info.min_active_size.clone();
+
+    let max_active_size_step = (*arch_search).evolution_info.max_active_size_step.clone();
+    let min_active_size_step = (*arch_search).evolution_info.min_active_size_step.clone();
+
+    let neuron_max = T::from_f64(cur_active_size as f64) * max_active_size_step;
+    let neuron_min = T::from_f64(cur_active_size as f64) * min_active_size_step;
+
+    let mut new_active_size = cur_active_size.clone();
+    while surrogate.is_none() {
+        let samplearr_dims = arrayfire::Dim4::new(&[100, 1, 1, 1]);
+        let mut samplearr = arrayfire::randu::<T>(samplearr_dims);
+        samplearr = (samplearr) * (neuron_max - neuron_min) + neuron_min;
+
+        let mut minusarr = arrayfire::randu::<T>(samplearr_dims);
+        minusarr = minusarr - T::from_f64(0.5);
+
+        minusarr = (arrayfire::sign(&minusarr) - T::from_f64(0.5)) * T::from_f64(2.0);
+
+        samplearr = arrayfire::mul(&samplearr, &minusarr, false);
+
+        samplearr = samplearr + T::from_f64(cur_active_size as f64);
+
+        let mut samplearr_cpu = vec![T::default(); samplearr.elements()];
+        samplearr.host(&mut samplearr_cpu);
+
+        for nsize in samplearr_cpu {
+            if (nsize > T::one()) {
+                let nsize_u64 = nsize.to_u64().unwrap();
+                if (min_active_size < nsize_u64) && (nsize_u64 < max_active_size) {
+                    new_active_size = nsize_u64.clone();
+                    break;
+                }
+            }
+        }
+
+        if (new_active_size != cur_active_size) {
+            break;
+        }
+    }
+
+    //Select proc_num
+    let cur_proc_num = selected_netdata.proc_num.clone();
+
+    let max_proc_num = (*arch_search).evolution_info.max_proc_num.clone();
+    let min_proc_num = (*arch_search).evolution_info.min_proc_num.clone();
+
+    let max_proc_num_step = (*arch_search).evolution_info.max_proc_num_step.clone();
+    let min_proc_num_step = (*arch_search).evolution_info.min_proc_num_step.clone();
+
+    let mut new_proc_num = cur_proc_num.clone();
+    let mut breakflag = false;
+    while surrogate.is_none() {
+        let samplearr_dims = arrayfire::Dim4::new(&[100, 1, 1, 1]);
+        let mut samplearr = arrayfire::randu::<T>(samplearr_dims);
+        samplearr = (samplearr) * T::from_f64((max_proc_num_step - min_proc_num_step) as f64)
+            + T::from_f64(min_proc_num_step as f64);
+
+        let mut minusarr = arrayfire::randu::<T>(samplearr_dims);
+        minusarr = minusarr - T::from_f64(0.5);
+
+        minusarr = (arrayfire::sign(&minusarr) - T::from_f64(0.5)) * T::from_f64(2.0);
+
+        samplearr = arrayfire::mul(&samplearr, &minusarr, false);
+
+        samplearr = samplearr + T::from_f64(cur_proc_num as f64);
+
+        samplearr = arrayfire::round(&samplearr);
+
+        let mut samplearr_cpu = vec![T::default(); samplearr.elements()];
+        samplearr.host(&mut samplearr_cpu);
+
+        for pnum in samplearr_cpu {
+            if (pnum > T::one()) {
+                let pnum_u64 = pnum.to_u64().unwrap();
+                if (min_proc_num <= pnum_u64) && (pnum_u64 <= max_proc_num) {
+                    new_proc_num = pnum_u64.clone();
+                    breakflag = true;
+                    break;
+                }
+            }
+        }
+
+        if (breakflag) {
+            break;
+        }
+    }
+
+    if let Some((surrogate_active_size, surrogate_proc_num)) = surrogate {
+        new_active_size = surrogate_active_size;
+        new_proc_num = surrogate_proc_num;
+    }
+
+    //Get filename
+    let filename = checked_paths[selected_idx].clone();
+
+    //Load network
+    (*arch_search).neural_network = try_load_network2(&filename)?;
+
+    //Crossover with the second parent
+    if let Some(partner_idx) = crossover_idx {
+        let partner = try_load_network2(&checked_paths[partner_idx])?;
+
+        match try_crossover_network(&(*arch_search).neural_network, &partner, arch_search) {
+            Ok(child) => {
+                (*arch_search).neural_network = child;
+                cur_active_size = (*arch_search).neural_network.neuron_idx.dims()[0];
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "crossover failed, mutating the first parent");
+            }
+        }
+    }
+
+    //Load cube pos
+    let temp_dims = arrayfire::Dim4::new(&[1, 1, 1, 1]);
+    let mut cube_glia_pos = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut cube_neuron_pos = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut cube_neuron_idx = arrayfire::constant::<i32>(0, temp_dims);
+
+    load_network_structure(
+        &cube_path,
+        &mut cube_glia_pos,
+        &mut cube_neuron_pos,
+        &mut cube_neuron_idx,
+    );
+    drop(cube_glia_pos);
+    drop(cube_neuron_idx);
+
+    tracing::info!(
+        cur_active_size,
+        cur_proc_num,
+        new_active_size,
+        new_proc_num,
+        "changed network size"
+    );
+
+    //Change process num
+    (*arch_search).neural_network.netdata.proc_num = new_proc_num;
+
+    if (new_active_size > cur_active_size) {
+        //Add new neurons
+        add_neuron_to_existing2(&cube_neuron_pos, cube_radius, new_active_size, arch_search);
+    } else if (new_active_size < cur_active_size) {
+        //Make the neural network smaller
+        reduce_network_size(new_active_size, arch_search);
+
+        let rand_number = single_random_uniform::<T>();
+
+        if rand_number > T::from_f64(0.5) {
+            //Add new neurons
+            add_neuron_to_existing2(
+                &cube_neuron_pos,
+                cube_radius,
+                new_active_size + 2,
+                arch_search,
+            );
+        }
+    }
+
+    //Delete the proposed fraction of connections
+    if let Some(prune_num) = new_prune_num {
+        let con_num = (*arch_search).neural_network.WColIdx.dims()[0];
+        reduce_weights(
+            (T::from_f64(con_num as f64) * prune_num).to_u64().unwrap(),
+            arch_search,
+        );
+    }
+
+    //Save neural network
+    let active_size = (*arch_search).neural_network.neuron_idx.dims()[0];
+    let proc_num = (*arch_search).neural_network.netdata.proc_num.clone();
+    let con_num = (*arch_search).neural_network.WColIdx.dims()[0];
+    let dir_path = (*arch_search).evolution_info.dir_path.clone();
+    (*arch_search).evolution_info.cur_path = format!(
+        "{}/active_size_{}_proc_num_{}_con_num_{}.csv",
+        dir_path, active_size, proc_num, con_num
+    );
+
+    try_save_network2(
+        &((*arch_search).evolution_info.cur_path),
+        &((*arch_search).neural_network),
+    )?;
+
+    if let Some(prune_num) = new_prune_num {
+        try_append_bayesian_history(
+            &dir_path,
+            &bayesian_proposal_type {
+                path: (*arch_search).evolution_info.cur_path.replace(".csv", ""),
+                active_size: active_size,
+                proc_num: proc_num,
+                prune_num: prune_num,
+            },
+        )?;
+    }
+
+    Ok(())
+}
+
+pub fn evolve_network<T: raybnn_float_trait>(arch_search: &mut arch_search_type<T>) {
+    try_evolve_network(arch_search).unwrap_or_else(|e| panic!("{}", e))
+}
@@ -0,0 +1,34 @@
+extern crate arrayfire;
+
+use crate::precision::float_trait::raybnn_float_trait;
+
+pub fn adam<T: raybnn_float_trait>(
+    beta0: T,
+    beta1: T,
+    direction: &mut arrayfire::Array<T>,
+    mt: &mut arrayfire::Array<T>,
+    vt: &mut arrayfire::Array<T>,
+) {
+    let two = T::from_f64(2.0);
+    let one = T::one();
+    let epsilon = T::from_f64(1.0e-8);
+
+    *mt = (mt.clone()) * beta0 + (direction.clone()) * (one - beta0);
+    *vt = (vt.clone()) * beta1 + arrayfire::pow(direction, &two, false) * (one - beta1);
+
+    let nmt = mt.clone() / (one - beta0);
+    let mut nvt = vt.clone() / (one - beta1);
+    nvt = arrayfire::sqrt(&nvt) + epsilon;
+
+    *direction = (nmt / nvt);
+}
+
+pub fn momentum<T: raybnn_float_trait>(
+    beta: T,
+    grad: &arrayfire::Array<T>,
+    dir: &mut arrayfire::Array<T>,
+) {
+    let one = T::one();
+
+    *dir = (dir.clone() * beta) + (grad.clone()) * (one - beta);
+}
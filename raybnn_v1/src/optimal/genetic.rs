@@ -0,0 +1,452 @@
+extern crate arrayfire;
+
+use crate::precision::float_trait::raybnn_float_trait;
+use nohash_hasher;
+
+use crate::error::Error;
+
+use crate::export::rand::single_random_uniform;
+use crate::export::rand_u64::random_uniform_range;
+
+use crate::export::trace::enter_phase;
+
+use crate::graph::adjacency::clear_input;
+use crate::graph::adjacency::clear_output;
+use crate::graph::adjacency::select_forward_sphere;
+
+use crate::graph::large_sparse_i32::COO_to_CSR;
+use crate::graph::large_sparse_i32::CSR_to_COO;
+
+use crate::interface::automatic::arch_search_type;
+
+use crate::neural::network::clone_netdata;
+use crate::neural::network::neural_network_type;
+use crate::neural::normalization::create_norm_state;
+
+use crate::physics::initial::assign_neuron_idx_with_buffer;
+use crate::physics::initial::hidden_layers;
+
+/*
+Population based genetic architecture search
+
+The population is the population_size evaluated models with the lowest crossval metric.
+Parents are chosen by tournaments of two models of the population. With crossover_prob the
+child is the hemisphere crossover of both parents, otherwise a copy of the first parent. The
+child is then mutated by the growth and pruning of evolve_network, add_neuron_to_existing2
+and reduce_network_size.
+
+*/
+
+const TOURNAMENT_SIZE: u64 = 2;
+
+/*
+Selects two parents from the population by tournament selection
+
+Inputs
+arch_search:         Evolution info with crossval_vec of the evaluated models
+population_size:     Number of models with the lowest crossval in the population
+
+Outputs:
+(parent_a, parent_b): Indices of the parents in crossval_vec. Different if the population
+                     has more than one model
+
+*/
+pub fn search_genetic<T: raybnn_float_trait>(
+    arch_search: &arch_search_type<T>,
+    population_size: u64,
+) -> Result<(usize, usize), Error> {
+    let crossval_vec = &(*arch_search).evolution_info.crossval_vec;
+
+    if population_size == 0 {
+        return Err(Error::InvalidNetwork(
+            "genetic search needs a population_size of at least 1".to_string(),
+        ));
+    }
+    if crossval_vec.is_empty() {
+        return Err(Error::InvalidNetwork(
+            "genetic search needs at least one evaluated model in crossval_vec".to_string(),
+        ));
+    }
+
+    let mut order: Vec<usize> = (0..crossval_vec.len()).collect();
+    order.sort_by(|a, b| {
+        let a = crossval_vec[*a].to_f64().unwrap();
+        let b = crossval_vec[*b].to_f64().unwrap();
+        a.total_cmp(&b)
+    });
+    order.truncate(population_size as usize);
+
+    let tournament = |exclude: Option<usize>| -> usize {
+        let mut winner: Option<usize> = None;
+        for _ in 0..TOURNAMENT_SIZE {
+            let mut idx = order[random_uniform_range(order.len() as u64) as usize];
+            if (Some(idx) == exclude) && (order.len() > 1) {
+                idx = *order.iter().find(|i| Some(**i) != exclude).unwrap();
+            }
+
+            let better = match winner {
+                Some(w) => crossval_vec[idx] < crossval_vec[w],
+                None => true,
+            };
+
+            if better {
+                winner = Some(idx);
+            }
+        }
+
+        winner.unwrap()
+    };
+
+    let parent_a = tournament(None);
+    let parent_b = tournament(Some(parent_a));
+
+    tracing::debug!(
+        population = ?order,
+        parent_a,
+        parent_b,
+        "genetic selection"
+    );
+
+    Ok((parent_a, parent_b))
+}
+
+/*
+Hemisphere crossover of two spatial networks
+
+A random plane through the center along a random axis divides the space. The child has the
+input and output neurons of parent_a, the hidden neurons and glial cells of parent_a on one
+side and those of parent_b on the other side. Connections and H, A, B, C, D, E of the neurons
+are inherited from the parent of the neurons. Connections between the two sides are created
+again by ray tracing the child with hidden_layers.
+
+Inputs
+parent_a:            First parent, which gives the input and output neurons
+parent_b:            Second parent
+arch_search:         Evolution info with max_input_size, max_output_size and traj_size
+
+Outputs:
+child:               Child network
+
+*/
+pub fn try_crossover_network<T: raybnn_float_trait>(
+    parent_a: &neural_network_type<T>,
+    parent_b: &neural_network_type<T>,
+    arch_search: &arch_search_type<T>,
+) -> Result<neural_network_type<T>, Error> {
+    let netdata_a = &parent_a.netdata;
+    let netdata_b = &parent_b.netdata;
+
+    if (netdata_a.neuron_size != netdata_b.neuron_size)
+        || (netdata_a.input_size != netdata_b.input_size)
+        || (netdata_a.output_size != netdata_b.output_size)
+        || (netdata_a.space_dims != netdata_b.space_dims)
+    {
+        return Err(Error::InvalidNetwork(
+            "crossover parents have different neuron_size, input_size, output_size or space_dims"
+                .to_string(),
+        ));
+    }
+
+    let _phase = enter_phase(tracing::info_span!(
+        "network_update",
+        method = "crossover_network",
+        active_size_a = parent_a.neuron_idx.dims()[0],
+        active_size_b = parent_b.neuron_idx.dims()[0]
+    ));
+
+    let neuron_size: u64 = netdata_a.neuron_size;
+    let input_size = netdata_a.input_size as usize;
+    let output_size = netdata_a.output_size as usize;
+    let space_dims = netdata_a.space_dims as usize;
+
+    let parents = [parent_a, parent_b];
+
+    //Random hemisphere of parent_a
+    let axis = random_uniform_range(space_dims as u64) as usize;
+    let flip = single_random_uniform::<T>() > T::from_f64(0.5);
+    let from_parent = |p: usize, x: T| -> bool { ((x < T::zero()) != flip) == (p == 0) };
+
+    //Rows of the child neurons in their parent
+    let mut neuron_pos_cpu: Vec<Vec<T>> = Vec::new();
+    let mut neuron_idx_cpu: Vec<Vec<i32>> = Vec::new();
+    for parent in parents {
+        let mut pos_cpu = vec![T::default(); parent.neuron_pos.elements()];
+        parent.neuron_pos.host(&mut pos_cpu);
+        neuron_pos_cpu.push(pos_cpu);
+
+        let mut idx_cpu = vec![i32::default(); parent.neuron_idx.elements()];
+        parent.neuron_idx.host(&mut idx_cpu);
+        neuron_idx_cpu.push(idx_cpu);
+    }
+
+    let neuron_num = [neuron_idx_cpu[0].len(), neuron_idx_cpu[1].len()];
+
+    let mut child_rows: Vec<(usize, usize)> = Vec::new();
+    for r in 0..input_size {
+        child_rows.push((0, r));
+    }
+    for p in 0..2 {
+        for r in input_size..(neuron_num[p] - output_size) {
+            if from_parent(p, neuron_pos_cpu[p][axis * neuron_num[p] + r]) {
+                child_rows.push((p, r));
+            }
+        }
+    }
+    for r in (neuron_num[0] - output_size)..neuron_num[0] {
+        child_rows.push((0, r));
+    }
+
+    let active_size = child_rows.len();
+    if active_size == input_size + output_size {
+        return Err(Error::InvalidNetwork(
+            "crossover of the hemispheres has no hidden neurons".to_string(),
+        ));
+    }
+
+    let mut child_pos_cpu = Vec::new();
+    for c in 0..space_dims {
+        for (p, r) in child_rows.iter() {
+            child_pos_cpu.push(neuron_pos_cpu[*p][c * neuron_num[*p] + r]);
+        }
+    }
+    let neuron_pos = arrayfire::Array::new(
+        &child_pos_cpu,
+        arrayfire::Dim4::new(&[active_size as u64, space_dims as u64, 1, 1]),
+    );
+
+    //Glial cells of both hemispheres
+    let mut glia_rows: Vec<(usize, usize)> = Vec::new();
+    let mut glia_pos_cpu: Vec<Vec<T>> = Vec::new();
+    for p in 0..2 {
+        let mut pos_cpu = vec![T::default(); parents[p].glia_pos.elements()];
+        parents[p].glia_pos.host(&mut pos_cpu);
+
+        let glia_num = parents[p].glia_pos.dims()[0] as usize;
+        for r in 0..glia_num {
+            if from_parent(p, pos_cpu[axis * glia_num + r]) {
+                glia_rows.push((p, r));
+            }
+        }
+        glia_pos_cpu.push(pos_cpu);
+    }
+
+    let glia_pos = if glia_rows.is_empty() {
+        parent_a.glia_pos.clone()
+    } else {
+        let mut child_glia_cpu = Vec::new();
+        for c in 0..space_dims {
+            for (p, r) in glia_rows.iter() {
+                let glia_num = parents[*p].glia_pos.dims()[0] as usize;
+                child_glia_cpu.push(glia_pos_cpu[*p][c * glia_num + r]);
+            }
+        }
+        arrayfire::Array::new(
+            &child_glia_cpu,
+            arrayfire::Dim4::new(&[glia_rows.len() as u64, space_dims as u64, 1, 1]),
+        )
+    };
+
+    let mut netdata = clone_netdata(netdata_a);
+    netdata.active_size = active_size as u64;
+
+    let temp_dims = arrayfire::Dim4::new(&[1, 1, 1, 1]);
+    let mut neuron_idx = arrayfire::constant::<i32>(0, temp_dims);
+    assign_neuron_idx_with_buffer(
+        (*arch_search).evolution_info.max_input_size,
+        (*arch_search).evolution_info.max_output_size,
+        &netdata,
+        &neuron_pos,
+        &mut neuron_idx,
+    );
+
+    let mut child_idx_cpu = vec![i32::default(); neuron_idx.elements()];
+    neuron_idx.host(&mut child_idx_cpu);
+
+    //Neuron index of the parents to the neuron index of the child
+    let mut idx_map: Vec<nohash_hasher::IntMap<i32, i32>> =
+        vec![nohash_hasher::IntMap::default(); 2];
+    //Parent of every child neuron. 2 for input and output neurons
+    let mut origin: nohash_hasher::IntMap<i32, usize> = nohash_hasher::IntMap::default();
+    for (k, (p, r)) in child_rows.iter().enumerate() {
+        idx_map[*p].insert(neuron_idx_cpu[*p][*r], child_idx_cpu[k]);
+
+        let is_hidden = (input_size <= k) && (k < active_size - output_size);
+        origin.insert(child_idx_cpu[k], if is_hidden { *p } else { 2 });
+    }
+    for r in 0..input_size {
+        idx_map[1].insert(neuron_idx_cpu[1][r], child_idx_cpu[r]);
+    }
+    for k in 0..output_size {
+        idx_map[1].insert(
+            neuron_idx_cpu[1][neuron_num[1] - output_size + k],
+            child_idx_cpu[active_size - output_size + k],
+        );
+    }
+
+    //Split the parameters
+    let mut W_cpu: Vec<Vec<T>> = Vec::new();
+    let mut params_cpu: Vec<Vec<T>> = Vec::new();
+    let mut Hdims0_vec = Vec::new();
+    for parent in parents {
+        let con_num = parent.WColIdx.dims()[0] as usize;
+
+        let mut cpu = vec![T::default(); parent.network_params.elements()];
+        parent.network_params.host(&mut cpu);
+
+        Hdims0_vec.push((cpu.len() - con_num) / 6);
+        params_cpu.push(cpu.split_off(con_num));
+        W_cpu.push(cpu);
+    }
+
+    if Hdims0_vec[0] != Hdims0_vec[1] {
+        return Err(Error::InvalidNetwork(
+            "crossover parents have different numbers of neuron parameters".to_string(),
+        ));
+    }
+    let Hdims0 = Hdims0_vec[0];
+
+    //Connections within each hemisphere
+    let mut join_W: nohash_hasher::IntMap<u64, (i32, i32, T)> = nohash_hasher::IntMap::default();
+    for p in [1, 0] {
+        let WRowIdxCOO = CSR_to_COO(&parents[p].WRowIdxCSR);
+
+        let mut row_cpu = vec![i32::default(); WRowIdxCOO.elements()];
+        WRowIdxCOO.host(&mut row_cpu);
+
+        let mut col_cpu = vec![i32::default(); parents[p].WColIdx.elements()];
+        parents[p].WColIdx.host(&mut col_cpu);
+
+        for k in 0..row_cpu.len() {
+            if let (Some(row), Some(col)) =
+                (idx_map[p].get(&row_cpu[k]), idx_map[p].get(&col_cpu[k]))
+            {
+                let gidx = (*row as u64) * neuron_size + (*col as u64);
+                join_W.insert(gidx, (*row, *col, W_cpu[p][k]));
+            }
+        }
+    }
+
+    //Ray trace the connections across the seam
+    let mut newWValues = arrayfire::constant::<T>(T::zero(), temp_dims);
+    let mut newWRowIdxCOO = arrayfire::constant::<i32>(0, temp_dims);
+    let mut newWColIdx = arrayfire::constant::<i32>(0, temp_dims);
+
+    hidden_layers(
+        &netdata,
+        &neuron_pos,
+        &neuron_idx,
+        &mut newWValues,
+        &mut newWRowIdxCOO,
+        &mut newWColIdx,
+    );
+
+    clear_input(
+        &mut newWValues,
+        &mut newWRowIdxCOO,
+        &mut newWColIdx,
+        input_size as u64,
+    );
+
+    clear_output(
+        &mut newWValues,
+        &mut newWRowIdxCOO,
+        &mut newWColIdx,
+        neuron_size - (output_size as u64),
+    );
+
+    if (*arch_search).evolution_info.traj_size == 1 {
+        select_forward_sphere(
+            &netdata,
+            &mut newWValues,
+            &mut newWRowIdxCOO,
+            &mut newWColIdx,
+            &neuron_pos,
+            &neuron_idx,
+        );
+    }
+
+    let mut newW_cpu = vec![T::default(); newWValues.elements()];
+    newWValues.host(&mut newW_cpu);
+
+    let mut newrow_cpu = vec![i32::default(); newWRowIdxCOO.elements()];
+    newWRowIdxCOO.host(&mut newrow_cpu);
+
+    let mut newcol_cpu = vec![i32::default(); newWColIdx.elements()];
+    newWColIdx.host(&mut newcol_cpu);
+
+    let mut seam_num: u64 = 0;
+    for k in 0..newW_cpu.len() {
+        let row_origin = origin.get(&newrow_cpu[k]).copied();
+        let col_origin = origin.get(&newcol_cpu[k]).copied();
+
+        let across = matches!(
+            (row_origin, col_origin),
+            (Some(0), Some(1)) | (Some(1), Some(0))
+        );
+        let gidx = (newrow_cpu[k] as u64) * neuron_size + (newcol_cpu[k] as u64);
+        if across && !join_W.contains_key(&gidx) {
+            join_W.insert(gidx, (newrow_cpu[k], newcol_cpu[k], newW_cpu[k]));
+            seam_num = seam_num + 1;
+        }
+    }
+
+    let mut gidx_vec: Vec<u64> = join_W.keys().cloned().collect();
+    gidx_vec.sort_unstable();
+
+    let mut WValues_cpu = Vec::new();
+    let mut WRowIdxCOO_cpu = Vec::new();
+    let mut WColIdx_cpu = Vec::new();
+    for gidx in gidx_vec {
+        let (row, col, value) = join_W[&gidx];
+        WRowIdxCOO_cpu.push(row);
+        WColIdx_cpu.push(col);
+        WValues_cpu.push(value);
+    }
+
+    let con_num = WValues_cpu.len() as u64;
+    let WRowIdxCOO =
+        arrayfire::Array::new(&WRowIdxCOO_cpu, arrayfire::Dim4::new(&[con_num, 1, 1, 1]));
+    let WColIdx = arrayfire::Array::new(&WColIdx_cpu, arrayfire::Dim4::new(&[con_num, 1, 1, 1]));
+
+    //H, A, B, C, D, E of every neuron from its parent
+    let mut child_params_cpu = params_cpu[0].clone();
+    for (p, r) in child_rows.iter() {
+        let old_idx = neuron_idx_cpu[*p][*r] as usize;
+        let new_idx = idx_map[*p][&neuron_idx_cpu[*p][*r]] as usize;
+
+        if (old_idx >= Hdims0) || (new_idx >= Hdims0) {
+            continue;
+        }
+
+        for block in 0..6 {
+            child_params_cpu[block * Hdims0 + new_idx] = params_cpu[*p][block * Hdims0 + old_idx];
+        }
+    }
+
+    WValues_cpu.extend_from_slice(&child_params_cpu);
+    let network_params = arrayfire::Array::new(
+        &WValues_cpu,
+        arrayfire::Dim4::new(&[WValues_cpu.len() as u64, 1, 1, 1]),
+    );
+
+    tracing::debug!(axis, active_size, con_num, seam_num, "hemisphere crossover");
+
+    Ok(neural_network_type {
+        netdata: netdata,
+        WRowIdxCSR: COO_to_CSR(&WRowIdxCOO, neuron_size),
+        WColIdx: WColIdx,
+        network_params: network_params,
+        glia_pos: glia_pos,
+        neuron_pos: neuron_pos,
+        neuron_idx: neuron_idx,
+        norm: create_norm_state(parent_a.norm.options),
+    })
+}
+
+pub fn crossover_network<T: raybnn_float_trait>(
+    parent_a: &neural_network_type<T>,
+    parent_b: &neural_network_type<T>,
+    arch_search: &arch_search_type<T>,
+) -> neural_network_type<T> {
+    try_crossover_network(parent_a, parent_b, arch_search).unwrap_or_else(|e| panic!("{}", e))
+}
@@ -0,0 +1,2 @@
+// f64 instantiation of the generic crate::optimal::genetic module
+pub use crate::optimal::genetic::*;
@@ -0,0 +1,485 @@
+extern crate arrayfire;
+
+use crate::precision::float_trait::raybnn_float_trait;
+
+use nohash_hasher;
+
+use serde::{Deserialize, Serialize};
+
+use crate::neural::network::neural_network_type;
+
+use crate::neural::network::state_space_backward_group2_norm;
+
+use crate::neural::normalization::norm_state_type;
+
+use crate::interface::autotrain::loss_wrapper;
+
+use crate::graph::large_sparse_i32::CSR_to_COO;
+
+use crate::graph::path::find_path_backward_group2;
+
+use crate::optimal::regularization::add_regularization_grad;
+use crate::optimal::regularization::create_regularization_options;
+use crate::optimal::regularization::regularization_options_type;
+use crate::optimal::regularization::regularization_penalty;
+
+use crate::error::Error;
+
+/*
+Gradient checking of state_space_backward_group2
+
+The analytic gradient of state_space_backward_group2_norm and add_regularization_grad is
+compared with the central finite difference (L(p + epsilon) - L(p - epsilon)) / (2 epsilon)
+of the loss of state_space_forward_batch_norm plus regularization_penalty, for single
+entries p of network_params.
+
+A parameter passes when |analytic - numeric| <= abs_tol + rel_tol * max(|analytic|, |numeric|)
+
+epsilon:             Finite difference step
+rel_tol:             Relative tolerance
+abs_tol:             Absolute tolerance, also the smallest gradient used for the relative error
+sample_size:         Number of parameters checked in each of W, H, A, B, C, D and E.
+                     Zero checks every parameter
+regularization:      L1 and L2 penalties added to the loss. max_norm, dropout and
+                     dropconnect are not used
+
+create_gradcheck_options uses the step and tolerances of the precision T from
+gradcheck_tolerances, without regularization
+
+*/
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: raybnn_float_trait",
+    deserialize = "T: raybnn_float_trait"
+))]
+pub struct gradcheck_options_type<T: raybnn_float_trait> {
+    pub epsilon: T,
+    pub rel_tol: T,
+    pub abs_tol: T,
+    pub sample_size: u64,
+    pub regularization: regularization_options_type<T>,
+}
+
+// (epsilon, rel_tol, abs_tol) of the precision named dtype_name
+fn gradcheck_tolerances(dtype_name: &str) -> (f64, f64, f64) {
+    match dtype_name {
+        "f64" => (1.0e-6, 1.0e-4, 1.0e-7),
+        "f16" => (1.0e-2, 1.0e-1, 1.0e-2),
+        _ => (1.0e-3, 1.0e-2, 1.0e-4),
+    }
+}
+
+pub fn create_gradcheck_options<T: raybnn_float_trait>() -> gradcheck_options_type<T> {
+    let (epsilon, rel_tol, abs_tol) = gradcheck_tolerances(T::DTYPE_NAME);
+
+    gradcheck_options_type {
+        epsilon: T::from_f64(epsilon),
+        rel_tol: T::from_f64(rel_tol),
+        abs_tol: T::from_f64(abs_tol),
+        sample_size: 0,
+        regularization: create_regularization_options(),
+    }
+}
+
+/*
+Result of gradient_check
+
+param_idx:           Indexes of the checked parameters in network_params
+analytic:            Gradient from state_space_backward_group2_norm and add_regularization_grad
+numeric:             Gradient from the finite difference
+max_abs_error:       Largest |analytic - numeric|
+max_rel_error:       Largest |analytic - numeric| / max(|analytic|, |numeric|, abs_tol)
+worst_idx:           Index in network_params with the largest relative error
+failed_idx:          Indexes in network_params that did not pass
+passed:              Every checked parameter passed
+
+*/
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: raybnn_float_trait",
+    deserialize = "T: raybnn_float_trait"
+))]
+pub struct gradcheck_result_type<T: raybnn_float_trait> {
+    pub param_idx: Vec<u64>,
+    pub analytic: Vec<T>,
+    pub numeric: Vec<T>,
+    pub max_abs_error: T,
+    pub max_rel_error: T,
+    pub worst_idx: u64,
+    pub failed_idx: Vec<u64>,
+    pub passed: bool,
+}
+
+// Evenly spaced indexes of one parameter group
+fn sample_seqs(seqs: &[arrayfire::Seq<i32>; 1], sample_size: u64, param_idx: &mut Vec<u64>) {
+    let begin = seqs[0].begin() as i64;
+    let end = seqs[0].end() as i64;
+    if end < begin {
+        return;
+    }
+
+    let group_size = (end - begin + 1) as u64;
+    if (sample_size == 0) || (sample_size >= group_size) {
+        for idx in begin..(end + 1) {
+            param_idx.push(idx as u64);
+        }
+        return;
+    }
+
+    let stride = (group_size as f64) / (sample_size as f64);
+    for k in 0..sample_size {
+        param_idx.push((begin as u64) + (((k as f64) * stride) as u64));
+    }
+}
+
+/*
+Compares the gradient of state_space_backward_group2_norm with finite differences
+
+The forward pass uses a copy of neural_network.norm for every loss evaluation, so batch norm
+running statistics of the network are not changed.
+
+Inputs
+neural_network:      Neural network to check
+X:                   Input array, dims (input_size, batch_size, traj_size)
+Y:                   Ground truth output array, dims (output_size, batch_size, traj_size)
+eval_metric:         Loss function
+eval_metric_grad:    Gradient of eval_metric
+options:             Finite difference step, tolerances, number of checked parameters and
+                     regularization
+
+Outputs:
+result:              Analytic and numeric gradients of the checked parameters
+
+*/
+
+pub fn try_gradient_check<T: raybnn_float_trait>(
+    neural_network: &neural_network_type<T>,
+    X: &arrayfire::Array<T>,
+    Y: &arrayfire::Array<T>,
+    eval_metric: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> T + Copy,
+    eval_metric_grad: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> arrayfire::Array<T> + Copy,
+    options: &gradcheck_options_type<T>,
+) -> Result<gradcheck_result_type<T>, Error> {
+    let netdata = &neural_network.netdata;
+
+    let neuron_size: u64 = netdata.neuron_size.clone();
+    let input_size: u64 = netdata.input_size.clone();
+    let output_size: u64 = netdata.output_size.clone();
+    let proc_num: u64 = netdata.proc_num.clone();
+    let batch_size: u64 = netdata.batch_size.clone();
+
+    let X_dims = X.dims();
+    let Y_dims = Y.dims();
+
+    //Rows and batch size of X and Y
+    let expected_dims = [
+        ("X", X_dims[0], input_size),
+        ("X", X_dims[1], batch_size),
+        ("Y", Y_dims[0], output_size),
+        ("Y", Y_dims[1], batch_size),
+    ];
+    for (file, found, expected) in expected_dims {
+        if found != expected {
+            return Err(Error::ShapeMismatch {
+                file: file.to_string(),
+                expected: expected,
+                found: found,
+            });
+        }
+    }
+    if !(options.epsilon > T::zero()) {
+        return Err(Error::InvalidNetwork(format!(
+            "epsilon ({}) must be greater than 0",
+            options.epsilon
+        )));
+    }
+
+    let traj_size: u64 = Y_dims[2];
+    let traj_steps = traj_size + proc_num - 1;
+
+    let Z_dims = arrayfire::Dim4::new(&[neuron_size, batch_size, traj_steps, 1]);
+    let mut Z = arrayfire::constant::<T>(T::zero(), Z_dims);
+    let mut Q = arrayfire::constant::<T>(T::zero(), Z_dims);
+
+    let active_size = neural_network.neuron_idx.dims()[0];
+    let idxsel = arrayfire::rows(
+        &neural_network.neuron_idx,
+        (active_size - output_size) as i64,
+        (active_size - 1) as i64,
+    );
+
+    let WRowIdxCOO = CSR_to_COO(&neural_network.WRowIdxCSR);
+
+    let mut idxsel_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+    let mut valsel_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+
+    let mut cvec_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+    let mut dXsel_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+
+    let mut nrows_out: nohash_hasher::IntMap<i64, u64> = nohash_hasher::IntMap::default();
+    let mut sparseval_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+    let mut sparsecol_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+    let mut sparserow_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+
+    let mut Wseqs = [arrayfire::Seq::default()];
+    let mut Hseqs = [arrayfire::Seq::default()];
+    let mut Aseqs = [arrayfire::Seq::default()];
+    let mut Bseqs = [arrayfire::Seq::default()];
+    let mut Cseqs = [arrayfire::Seq::default()];
+    let mut Dseqs = [arrayfire::Seq::default()];
+    let mut Eseqs = [arrayfire::Seq::default()];
+
+    let mut Hidxsel_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+    let mut Aidxsel_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+    let mut Bidxsel_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+    let mut Cidxsel_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+    let mut Didxsel_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+    let mut Eidxsel_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+    let mut combidxsel_out: nohash_hasher::IntMap<i64, arrayfire::Array<i32>> =
+        nohash_hasher::IntMap::default();
+
+    let mut dAseqs_out: nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]> =
+        nohash_hasher::IntMap::default();
+    let mut dBseqs_out: nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]> =
+        nohash_hasher::IntMap::default();
+    let mut dCseqs_out: nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]> =
+        nohash_hasher::IntMap::default();
+    let mut dDseqs_out: nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]> =
+        nohash_hasher::IntMap::default();
+    let mut dEseqs_out: nohash_hasher::IntMap<i64, [arrayfire::Seq<i32>; 2]> =
+        nohash_hasher::IntMap::default();
+
+    find_path_backward_group2(
+        netdata,
+        traj_steps,
+        traj_size,
+        &WRowIdxCOO,
+        &neural_network.WColIdx,
+        &neural_network.neuron_idx,
+        neural_network.WColIdx.dims()[0],
+        neuron_size,
+        neuron_size,
+        neuron_size,
+        neuron_size,
+        neuron_size,
+        neuron_size,
+        &mut idxsel_out,
+        &mut valsel_out,
+        &mut cvec_out,
+        &mut dXsel_out,
+        &mut nrows_out,
+        &mut sparseval_out,
+        &mut sparserow_out,
+        &mut sparsecol_out,
+        &mut Hidxsel_out,
+        &mut Aidxsel_out,
+        &mut Bidxsel_out,
+        &mut Cidxsel_out,
+        &mut Didxsel_out,
+        &mut Eidxsel_out,
+        &mut combidxsel_out,
+        &mut dAseqs_out,
+        &mut dBseqs_out,
+        &mut dCseqs_out,
+        &mut dDseqs_out,
+        &mut dEseqs_out,
+        &mut Wseqs,
+        &mut Hseqs,
+        &mut Aseqs,
+        &mut Bseqs,
+        &mut Cseqs,
+        &mut Dseqs,
+        &mut Eseqs,
+    );
+
+    let loss_at = |network_params: &arrayfire::Array<T>,
+                   norm: &mut norm_state_type<T>,
+                   Z: &mut arrayfire::Array<T>,
+                   Q: &mut arrayfire::Array<T>|
+     -> T {
+        let mut loss_val = T::zero();
+        loss_wrapper(
+            netdata,
+            X,
+            &neural_network.WRowIdxCSR,
+            &neural_network.WColIdx,
+            &Wseqs,
+            &Hseqs,
+            &Aseqs,
+            &Bseqs,
+            &Cseqs,
+            &Dseqs,
+            &Eseqs,
+            network_params,
+            norm,
+            &idxsel,
+            Y,
+            eval_metric,
+            Z,
+            Q,
+            &mut loss_val,
+        );
+
+        loss_val
+            + regularization_penalty(
+                network_params,
+                &options.regularization,
+                &Wseqs,
+                &Aseqs,
+                &Bseqs,
+                &Cseqs,
+                &Dseqs,
+                &Eseqs,
+            )
+    };
+
+    //Analytic gradient
+    let mut norm = neural_network.norm.clone();
+    loss_at(&neural_network.network_params, &mut norm, &mut Z, &mut Q);
+
+    let mut grad = arrayfire::constant::<T>(T::zero(), neural_network.network_params.dims());
+    state_space_backward_group2_norm(
+        netdata,
+        X,
+        &neural_network.WRowIdxCSR,
+        &neural_network.WColIdx,
+        &Wseqs,
+        &Hseqs,
+        &Aseqs,
+        &Bseqs,
+        &Cseqs,
+        &Dseqs,
+        &Eseqs,
+        &neural_network.network_params,
+        &Z,
+        &Q,
+        Y,
+        eval_metric_grad,
+        &neural_network.neuron_idx,
+        &idxsel_out,
+        &valsel_out,
+        &cvec_out,
+        &dXsel_out,
+        &nrows_out,
+        &sparseval_out,
+        &sparserow_out,
+        &sparsecol_out,
+        &Hidxsel_out,
+        &Aidxsel_out,
+        &Bidxsel_out,
+        &Cidxsel_out,
+        &Didxsel_out,
+        &Eidxsel_out,
+        &combidxsel_out,
+        &dAseqs_out,
+        &dBseqs_out,
+        &dCseqs_out,
+        &dDseqs_out,
+        &dEseqs_out,
+        &norm,
+        &mut grad,
+    );
+
+    add_regularization_grad(
+        &neural_network.network_params,
+        &options.regularization,
+        &Wseqs,
+        &Aseqs,
+        &Bseqs,
+        &Cseqs,
+        &Dseqs,
+        &Eseqs,
+        &mut grad,
+    );
+
+    let param_dims = neural_network.network_params.dims();
+    let mut params_cpu = vec![T::zero(); neural_network.network_params.elements()];
+    neural_network.network_params.host(&mut params_cpu);
+    let mut grad_cpu = vec![T::zero(); grad.elements()];
+    grad.host(&mut grad_cpu);
+
+    let mut param_idx: Vec<u64> = Vec::new();
+    for seqs in [&Wseqs, &Hseqs, &Aseqs, &Bseqs, &Cseqs, &Dseqs, &Eseqs] {
+        sample_seqs(seqs, options.sample_size, &mut param_idx);
+    }
+
+    let mut result = gradcheck_result_type {
+        param_idx: Vec::new(),
+        analytic: Vec::new(),
+        numeric: Vec::new(),
+        max_abs_error: T::zero(),
+        max_rel_error: T::zero(),
+        worst_idx: 0,
+        failed_idx: Vec::new(),
+        passed: true,
+    };
+
+    //Numeric gradient
+    for idx in param_idx {
+        let cur = params_cpu[idx as usize];
+
+        params_cpu[idx as usize] = cur + options.epsilon;
+        let params_plus = arrayfire::Array::new(&params_cpu, param_dims);
+        norm = neural_network.norm.clone();
+        let loss_plus = loss_at(&params_plus, &mut norm, &mut Z, &mut Q);
+
+        params_cpu[idx as usize] = cur - options.epsilon;
+        let params_minus = arrayfire::Array::new(&params_cpu, param_dims);
+        norm = neural_network.norm.clone();
+        let loss_minus = loss_at(&params_minus, &mut norm, &mut Z, &mut Q);
+
+        params_cpu[idx as usize] = cur;
+
+        let numeric = (loss_plus - loss_minus) / (T::from_f64(2.0) * options.epsilon);
+        let analytic = grad_cpu[idx as usize];
+
+        let abs_error = (analytic - numeric).abs();
+        let scale = analytic.abs().max(numeric.abs());
+        let rel_error = abs_error / scale.max(options.abs_tol);
+
+        if abs_error > result.max_abs_error {
+            result.max_abs_error = abs_error;
+        }
+        if rel_error > result.max_rel_error {
+            result.max_rel_error = rel_error;
+            result.worst_idx = idx;
+        }
+        if !(abs_error <= options.abs_tol + options.rel_tol * scale) {
+            result.failed_idx.push(idx);
+            result.passed = false;
+        }
+
+        result.param_idx.push(idx);
+        result.analytic.push(analytic);
+        result.numeric.push(numeric);
+    }
+
+    Ok(result)
+}
+
+pub fn gradient_check<T: raybnn_float_trait>(
+    neural_network: &neural_network_type<T>,
+    X: &arrayfire::Array<T>,
+    Y: &arrayfire::Array<T>,
+    eval_metric: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> T + Copy,
+    eval_metric_grad: impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> arrayfire::Array<T> + Copy,
+    options: &gradcheck_options_type<T>,
+) -> gradcheck_result_type<T> {
+    try_gradient_check(neural_network, X, Y, eval_metric, eval_metric_grad, options)
+        .unwrap_or_else(|e| panic!("{}", e))
+}
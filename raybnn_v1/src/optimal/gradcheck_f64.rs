@@ -0,0 +1,12 @@
+// f64 instantiation of the generic crate::optimal::gradcheck module
+pub use crate::optimal::gradcheck::*;
+
+pub type gradcheck_options_type = crate::optimal::gradcheck::gradcheck_options_type<f64>;
+
+pub type gradcheck_result_type = crate::optimal::gradcheck::gradcheck_result_type<f64>;
+
+// Functions whose precision only appears in the return type are pinned here
+
+pub fn create_gradcheck_options() -> gradcheck_options_type {
+    crate::optimal::gradcheck::create_gradcheck_options::<f64>()
+}
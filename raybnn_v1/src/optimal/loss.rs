@@ -0,0 +1,585 @@
+extern crate arrayfire;
+
+use crate::precision::float_trait::raybnn_float_trait;
+use crate::precision::reduce::sum;
+use crate::precision::reduce::sum_all;
+
+pub fn softmax_cross_entropy<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+) -> T {
+    let one = T::one();
+
+    let output_size = y.dims()[0];
+    let batch_size = y.dims()[1];
+    let batch_size_T = T::from_f64(batch_size as f64);
+
+    let mut yhatmax = arrayfire::max(yhat, 0);
+    yhatmax = arrayfire::transpose(&yhatmax, false);
+
+    let (_, mut yidx) = arrayfire::imax(y, 0);
+    yidx = arrayfire::transpose(&yidx, false);
+
+    let mut actmax = arrayfire::flat(yhat);
+
+    let N_dims = arrayfire::Dim4::new(&[batch_size, 1, 1, 1]);
+    let repeat_dims = arrayfire::Dim4::new(&[1, 1, 1, 1]);
+    let offset = (output_size as u32) * arrayfire::iota::<u32>(N_dims, repeat_dims);
+
+    yidx = yidx + offset;
+    actmax = arrayfire::lookup(&actmax, &yidx, 0);
+
+    let diff = yhatmax - actmax;
+
+    let (r0, _) = sum_all(&diff);
+
+    (one / batch_size_T) * (r0)
+}
+
+pub fn softmax_cross_entropy_grad<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+) -> arrayfire::Array<T> {
+    let one = T::one();
+    let zero = T::zero();
+    let high = T::from_f64(1000000.0);
+
+    let batch_size = y.dims()[1];
+    let batch_size_T = T::from_f64(batch_size as f64);
+
+    let mut expyhat = arrayfire::exp(yhat);
+    expyhat = arrayfire::clamp(&expyhat, &zero, &high, false);
+
+    let mut sumyhat = sum(&expyhat, 0);
+    sumyhat = arrayfire::clamp(&sumyhat, &zero, &high, false);
+
+    expyhat = arrayfire::div(&expyhat, &sumyhat, true);
+
+    (expyhat - y.clone()) * (one / batch_size_T)
+}
+
+pub fn sigmoid_cross_entropy<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+) -> T {
+    weighted_sigmoid_cross_entropy(yhat, y, T::one())
+}
+
+pub fn weighted_sigmoid_cross_entropy<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+    weight: T,
+) -> T {
+    let one = T::one();
+    let epsilon = T::from_f64(1.0e-20);
+    let epsilon2 = T::from_f64(2.0e-20);
+
+    let minus = (-y.clone()) + one;
+    let sigmoid = arrayfire::sigmoid(yhat) + epsilon;
+    let logsigmoid = arrayfire::log(&sigmoid);
+    let minussigmoid = (-sigmoid) + (one + epsilon2);
+    let logminus = arrayfire::log(&minussigmoid);
+
+    let total = -((arrayfire::mul(y, &logsigmoid, false) * weight)
+        + arrayfire::mul(&minus, &logminus, false));
+    let size = T::from_f64(yhat.elements() as f64);
+    let (r0, _) = sum_all(&total);
+    (one / size) * r0
+}
+
+pub fn sigmoid_cross_entropy_grad<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+) -> arrayfire::Array<T> {
+    weighted_sigmoid_cross_entropy_grad(yhat, y, T::one())
+}
+
+pub fn weighted_sigmoid_cross_entropy_grad<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+    weight: T,
+) -> arrayfire::Array<T> {
+    let one = T::one();
+
+    let minus = (-y.clone()) + one;
+
+    let yhatneg = -yhat.clone();
+    let p0 = arrayfire::sigmoid(&yhatneg);
+    let p1 = -arrayfire::sigmoid(&yhat);
+
+    let size = T::from_f64(yhat.elements() as f64);
+    ((arrayfire::mul(y, &p0, false) * weight) + arrayfire::mul(&minus, &p1, false)) * (-one / size)
+}
+
+pub fn MAE<T: raybnn_float_trait>(yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+    let mut diff = yhat.clone() - y.clone();
+
+    diff = arrayfire::abs(&diff);
+
+    let (r0, _) = arrayfire::mean_all(&diff);
+
+    T::from_f64(r0)
+}
+
+pub fn MSE<T: raybnn_float_trait>(yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+    let one = T::one();
+    let two = T::from_f64(2.0);
+
+    let diff = yhat.clone() - y.clone();
+    let size = T::from_f64(yhat.elements() as f64);
+
+    let diff = arrayfire::pow(&diff, &two, false);
+    let (r0, _) = sum_all(&diff);
+    (one / size) * r0
+}
+
+pub fn MSE_grad<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+) -> arrayfire::Array<T> {
+    let two = T::from_f64(2.0);
+
+    let size = T::from_f64(yhat.elements() as f64);
+    (yhat.clone() - y.clone()) * (two / size)
+}
+
+pub fn RMSE<T: raybnn_float_trait>(yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+    MSE(yhat, y).sqrt()
+}
+
+/*
+Loss functions bundling the loss value and its gradient with respect to yhat
+
+yhat and y have dimensions (output_size, batch_size, traj_size). Classification losses take
+yhat as logits. class_weight is an optional (output_size, 1) array that scales the loss of
+each output class, None weighs every class by one.
+
+loss_metric() and loss_metric_grad() turn a loss into the eval_metric and eval_metric_grad
+closures of train_network
+
+*/
+pub trait loss_trait<T: raybnn_float_trait> {
+    fn loss(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T;
+
+    fn grad(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> arrayfire::Array<T>;
+}
+
+pub fn loss_metric<'a, T: raybnn_float_trait>(
+    loss: &'a dyn loss_trait<T>,
+) -> impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> T + Copy + 'a {
+    move |yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>| loss.loss(yhat, y)
+}
+
+pub fn loss_metric_grad<'a, T: raybnn_float_trait>(
+    loss: &'a dyn loss_trait<T>,
+) -> impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> arrayfire::Array<T> + Copy + 'a {
+    move |yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>| loss.grad(yhat, y)
+}
+
+// Multiplies each output class (row) by its class weight
+fn apply_class_weight<T: raybnn_float_trait>(
+    arr: &arrayfire::Array<T>,
+    class_weight: &Option<arrayfire::Array<T>>,
+) -> arrayfire::Array<T> {
+    match class_weight {
+        Some(weight) => arrayfire::mul(arr, weight, true),
+        None => arr.clone(),
+    }
+}
+
+fn sum_all_T<T: raybnn_float_trait>(arr: &arrayfire::Array<T>) -> T {
+    let (r0, _) = sum_all(arr);
+    r0
+}
+
+// Numerically stable log softmax over the output dimension
+fn log_softmax<T: raybnn_float_trait>(yhat: &arrayfire::Array<T>) -> arrayfire::Array<T> {
+    let yhatmax = arrayfire::max(yhat, 0);
+    let shifted = arrayfire::sub(yhat, &yhatmax, true);
+
+    let sumexp = sum(&arrayfire::exp(&shifted), 0);
+    let logsumexp = arrayfire::log(&sumexp);
+
+    arrayfire::sub(&shifted, &logsumexp, true)
+}
+
+/*
+Softmax cross entropy with optional label smoothing
+
+label_smoothing:     Mixes the targets with the uniform distribution, y*(1-s) + s/output_size
+class_weight:        Weight of each output class
+
+*/
+pub struct cross_entropy_loss_type<T: raybnn_float_trait> {
+    pub label_smoothing: T,
+    pub class_weight: Option<arrayfire::Array<T>>,
+}
+
+impl<T: raybnn_float_trait> cross_entropy_loss_type<T> {
+    fn smooth_target(&self, y: &arrayfire::Array<T>) -> arrayfire::Array<T> {
+        if self.label_smoothing == T::zero() {
+            return y.clone();
+        }
+
+        let output_size = T::from_f64(y.dims()[0] as f64);
+        (y.clone() * (T::one() - self.label_smoothing)) + (self.label_smoothing / output_size)
+    }
+}
+
+impl<T: raybnn_float_trait> loss_trait<T> for cross_entropy_loss_type<T> {
+    fn loss(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        let batch_size = T::from_f64((y.elements() as u64 / y.dims()[0]) as f64);
+
+        let target = apply_class_weight(&self.smooth_target(y), &self.class_weight);
+        let total = arrayfire::mul(&target, &log_softmax(yhat), false);
+
+        -sum_all_T(&total) / batch_size
+    }
+
+    fn grad(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> arrayfire::Array<T> {
+        let batch_size = T::from_f64((y.elements() as u64 / y.dims()[0]) as f64);
+
+        let target = apply_class_weight(&self.smooth_target(y), &self.class_weight);
+        let target_sum = sum(&target, 0);
+        let softmax = arrayfire::exp(&log_softmax(yhat));
+
+        (arrayfire::mul(&softmax, &target_sum, true) - target) * (T::one() / batch_size)
+    }
+}
+
+/*
+Sigmoid cross entropy for independent binary outputs
+
+pos_weight:          Weight of the positive targets, same as weighted_sigmoid_cross_entropy
+class_weight:        Weight of each output class
+
+*/
+pub struct sigmoid_cross_entropy_loss_type<T: raybnn_float_trait> {
+    pub pos_weight: T,
+    pub class_weight: Option<arrayfire::Array<T>>,
+}
+
+impl<T: raybnn_float_trait> loss_trait<T> for sigmoid_cross_entropy_loss_type<T> {
+    fn loss(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        let one = T::one();
+        let epsilon = T::from_f64(1.0e-20);
+        let epsilon2 = T::from_f64(2.0e-20);
+
+        let minus = (-y.clone()) + one;
+        let sigmoid = arrayfire::sigmoid(yhat) + epsilon;
+        let logsigmoid = arrayfire::log(&sigmoid);
+        let minussigmoid = (-sigmoid) + (one + epsilon2);
+        let logminus = arrayfire::log(&minussigmoid);
+
+        let total = -((arrayfire::mul(y, &logsigmoid, false) * self.pos_weight)
+            + arrayfire::mul(&minus, &logminus, false));
+        let total = apply_class_weight(&total, &self.class_weight);
+
+        let size = T::from_f64(yhat.elements() as f64);
+        sum_all_T(&total) / size
+    }
+
+    fn grad(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> arrayfire::Array<T> {
+        let grad = weighted_sigmoid_cross_entropy_grad(yhat, y, self.pos_weight);
+        apply_class_weight(&grad, &self.class_weight)
+    }
+}
+
+/*
+Binary focal loss on sigmoid outputs, -alpha_t*(1-p_t)^gamma*log(p_t)
+
+gamma:               Focusing parameter, 0 gives sigmoid cross entropy
+alpha:               Weight of the positive targets, the negative targets have weight 1-alpha
+class_weight:        Weight of each output class
+
+*/
+pub struct focal_loss_type<T: raybnn_float_trait> {
+    pub gamma: T,
+    pub alpha: T,
+    pub class_weight: Option<arrayfire::Array<T>>,
+}
+
+impl<T: raybnn_float_trait> focal_loss_type<T> {
+    // Returns p, p_t and alpha_t
+    fn terms(
+        &self,
+        yhat: &arrayfire::Array<T>,
+        y: &arrayfire::Array<T>,
+    ) -> (
+        arrayfire::Array<T>,
+        arrayfire::Array<T>,
+        arrayfire::Array<T>,
+    ) {
+        let one = T::one();
+        let epsilon = T::from_f64(1.0e-7);
+
+        let p = arrayfire::sigmoid(yhat);
+        let minus = (-y.clone()) + one;
+
+        let mut pt =
+            arrayfire::mul(y, &p, false) + arrayfire::mul(&minus, &((-p.clone()) + one), false);
+        pt = arrayfire::clamp(&pt, &epsilon, &(one - epsilon), false);
+
+        let at = (y.clone() * self.alpha) + (minus * (one - self.alpha));
+
+        (p, pt, at)
+    }
+}
+
+impl<T: raybnn_float_trait> loss_trait<T> for focal_loss_type<T> {
+    fn loss(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        let one = T::one();
+        let (_, pt, at) = self.terms(yhat, y);
+
+        let modulating = arrayfire::pow(&((-pt.clone()) + one), &self.gamma, false);
+        let total = -arrayfire::mul(
+            &arrayfire::mul(&at, &modulating, false),
+            &arrayfire::log(&pt),
+            false,
+        );
+        let total = apply_class_weight(&total, &self.class_weight);
+
+        let size = T::from_f64(yhat.elements() as f64);
+        sum_all_T(&total) / size
+    }
+
+    fn grad(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> arrayfire::Array<T> {
+        let one = T::one();
+        let two = T::from_f64(2.0);
+        let (p, pt, at) = self.terms(yhat, y);
+
+        let minuspt = (-pt.clone()) + one;
+
+        // dL/dp_t
+        let term0 = arrayfire::mul(
+            &(arrayfire::pow(&minuspt, &(self.gamma - one), false) * self.gamma),
+            &arrayfire::log(&pt),
+            false,
+        );
+        let term1 = arrayfire::div(&arrayfire::pow(&minuspt, &self.gamma, false), &pt, false);
+        let dLdpt = arrayfire::mul(&at, &(term0 - term1), false);
+
+        // dp_t/dyhat = (2y-1)*p*(1-p)
+        let dptdyhat = arrayfire::mul(
+            &((y.clone() * two) - one),
+            &arrayfire::mul(&p, &((-p.clone()) + one), false),
+            false,
+        );
+
+        let size = T::from_f64(yhat.elements() as f64);
+        let grad = arrayfire::mul(&dLdpt, &dptdyhat, false) * (one / size);
+        apply_class_weight(&grad, &self.class_weight)
+    }
+}
+
+/*
+Hinge loss, max(0, 1 - t*yhat) with targets y in {0,1} mapped to t in {-1,1}
+
+class_weight:        Weight of each output class
+
+*/
+pub struct hinge_loss_type<T: raybnn_float_trait> {
+    pub class_weight: Option<arrayfire::Array<T>>,
+}
+
+impl<T: raybnn_float_trait> loss_trait<T> for hinge_loss_type<T> {
+    fn loss(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        let one = T::one();
+        let zero = T::zero();
+        let two = T::from_f64(2.0);
+
+        let t = (y.clone() * two) - one;
+        let margin = (-arrayfire::mul(&t, yhat, false)) + one;
+        let total = arrayfire::clamp(&margin, &zero, &T::infinity(), false);
+        let total = apply_class_weight(&total, &self.class_weight);
+
+        let size = T::from_f64(yhat.elements() as f64);
+        sum_all_T(&total) / size
+    }
+
+    fn grad(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> arrayfire::Array<T> {
+        let one = T::one();
+        let zero = T::zero();
+        let two = T::from_f64(2.0);
+
+        let t = (y.clone() * two) - one;
+        let margin = (-arrayfire::mul(&t, yhat, false)) + one;
+        let active = arrayfire::gt(&margin, &zero, false).cast::<T>();
+
+        let size = T::from_f64(yhat.elements() as f64);
+        let grad = -arrayfire::mul(&t, &active, false) * (one / size);
+        apply_class_weight(&grad, &self.class_weight)
+    }
+}
+
+/*
+Kullback-Leibler divergence KL(y || softmax(yhat)) with target distributions y
+
+*/
+pub struct kl_divergence_loss_type {}
+
+impl<T: raybnn_float_trait> loss_trait<T> for kl_divergence_loss_type {
+    fn loss(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        let epsilon = T::from_f64(1.0e-20);
+        let high = T::from_f64(1.0e20);
+        let batch_size = T::from_f64((y.elements() as u64 / y.dims()[0]) as f64);
+
+        let logy = arrayfire::log(&arrayfire::clamp(y, &epsilon, &high, false));
+        let total = arrayfire::mul(y, &(logy - log_softmax(yhat)), false);
+
+        sum_all_T(&total) / batch_size
+    }
+
+    fn grad(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> arrayfire::Array<T> {
+        let batch_size = T::from_f64((y.elements() as u64 / y.dims()[0]) as f64);
+
+        let y_sum = sum(y, 0);
+        let softmax = arrayfire::exp(&log_softmax(yhat));
+
+        (arrayfire::mul(&softmax, &y_sum, true) - y.clone()) * (T::one() / batch_size)
+    }
+}
+
+/*
+Poisson negative log likelihood
+
+log_input:           If true, yhat is the log of the rate, exp(yhat) - y*yhat.
+                     If false, yhat is the rate, yhat - y*log(yhat). The rate must be
+                     positive, so rates below 1e-8 are clamped to 1e-8 inside the log
+
+*/
+pub struct poisson_nll_loss_type {
+    pub log_input: bool,
+}
+
+impl<T: raybnn_float_trait> loss_trait<T> for poisson_nll_loss_type {
+    fn loss(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        let epsilon = T::from_f64(1.0e-8);
+
+        let total = if self.log_input {
+            arrayfire::exp(yhat) - arrayfire::mul(y, yhat, false)
+        } else {
+            let rate = arrayfire::clamp(yhat, &epsilon, &T::infinity(), false);
+            yhat.clone() - arrayfire::mul(y, &arrayfire::log(&rate), false)
+        };
+
+        let size = T::from_f64(yhat.elements() as f64);
+        sum_all_T(&total) / size
+    }
+
+    fn grad(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> arrayfire::Array<T> {
+        let one = T::one();
+        let epsilon = T::from_f64(1.0e-8);
+
+        let grad = if self.log_input {
+            arrayfire::exp(yhat) - y.clone()
+        } else {
+            // The log term is constant where the rate is clamped
+            let rate = arrayfire::clamp(yhat, &epsilon, &T::infinity(), false);
+            let inside = arrayfire::gt(yhat, &epsilon, false).cast::<T>();
+            (-arrayfire::mul(&arrayfire::div(y, &rate, false), &inside, false)) + one
+        };
+
+        let size = T::from_f64(yhat.elements() as f64);
+        grad * (one / size)
+    }
+}
+
+/*
+Huber loss, quadratic for |yhat - y| <= delta and linear above
+
+*/
+pub struct huber_loss_type<T: raybnn_float_trait> {
+    pub delta: T,
+}
+
+impl<T: raybnn_float_trait> loss_trait<T> for huber_loss_type<T> {
+    fn loss(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        let half = T::from_f64(0.5);
+
+        let diff = arrayfire::abs(&(yhat.clone() - y.clone()));
+        let quadratic = arrayfire::clamp(&diff, &T::zero(), &self.delta, false);
+        let linear = diff - quadratic.clone();
+
+        let total = (arrayfire::mul(&quadratic, &quadratic, false) * half) + (linear * self.delta);
+
+        let size = T::from_f64(yhat.elements() as f64);
+        sum_all_T(&total) / size
+    }
+
+    fn grad(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> arrayfire::Array<T> {
+        let diff = yhat.clone() - y.clone();
+        let grad = arrayfire::clamp(&diff, &(-self.delta), &self.delta, false);
+
+        let size = T::from_f64(yhat.elements() as f64);
+        grad * (T::one() / size)
+    }
+}
+
+/*
+Quantile (pinball) loss, max(q*(y - yhat), (q - 1)*(y - yhat))
+
+quantile:            Quantile q between 0 and 1, 0.5 gives half of MAE
+
+*/
+pub struct quantile_loss_type<T: raybnn_float_trait> {
+    pub quantile: T,
+}
+
+impl<T: raybnn_float_trait> loss_trait<T> for quantile_loss_type<T> {
+    fn loss(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        let one = T::one();
+
+        let diff = y.clone() - yhat.clone();
+        let total = arrayfire::maxof(
+            &(diff.clone() * self.quantile),
+            &(diff * (self.quantile - one)),
+            false,
+        );
+
+        let size = T::from_f64(yhat.elements() as f64);
+        sum_all_T(&total) / size
+    }
+
+    fn grad(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> arrayfire::Array<T> {
+        let one = T::one();
+
+        // -q where y > yhat, 1 - q elsewhere
+        let above = arrayfire::gt(y, yhat, false).cast::<T>();
+        let grad = (-above) + (one - self.quantile);
+
+        let size = T::from_f64(yhat.elements() as f64);
+        grad * (one / size)
+    }
+}
+
+/*
+Mean squared and mean absolute error
+
+*/
+pub struct mse_loss_type {}
+
+impl<T: raybnn_float_trait> loss_trait<T> for mse_loss_type {
+    fn loss(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        MSE(yhat, y)
+    }
+
+    fn grad(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> arrayfire::Array<T> {
+        MSE_grad(yhat, y)
+    }
+}
+
+pub struct mae_loss_type {}
+
+impl<T: raybnn_float_trait> loss_trait<T> for mae_loss_type {
+    fn loss(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        MAE(yhat, y)
+    }
+
+    fn grad(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> arrayfire::Array<T> {
+        let size = T::from_f64(yhat.elements() as f64);
+        let sign =
+            arrayfire::gt(yhat, y, false).cast::<T>() - arrayfire::lt(yhat, y, false).cast::<T>();
+        sign * (T::one() / size)
+    }
+}
@@ -0,0 +1,827 @@
+extern crate arrayfire;
+
+use crate::optimal::measure_u32::confusion_matrix;
+
+use crate::precision::float_trait::raybnn_float_trait;
+
+/*
+Classification and regression metrics
+
+yhat and y have dimensions (output_size, batch_size, traj_size) like the loss functions. Every
+column is one sample.
+
+Classification metrics take yhat as logits with one row per class and y as one-hot targets. The
+predicted class is the row with the largest logit. With a single output row the problem is
+binary, yhat is the logit of the positive class and y is 0 or 1. Binary precision, recall, F1,
+ROC-AUC and PR-AUC are the metrics of the positive class.
+
+Every metric returns T, so the functions with two arguments can be passed as eval_metric of
+validate_network. metric_trait bundles a metric with its settings, eval_metric() and
+eval_metric_loss() turn it into the eval_metric argument of validate_network and train_network
+
+*/
+
+/*
+Averaging of per class metrics
+
+MACRO:               Mean over the classes
+MICRO:               Metric of the pooled counts (F1) or pooled one-vs-rest scores (AUC)
+WEIGHTED:            Mean over the classes weighted by the number of samples of each class
+
+*/
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum averaging_type {
+    MACRO,
+    MICRO,
+    WEIGHTED,
+}
+
+// Host copy of arr in f64
+fn host_f64<T: raybnn_float_trait>(arr: &arrayfire::Array<T>) -> Vec<f64> {
+    let mut arr_cpu = vec![T::default(); arr.elements()];
+    arr.host(&mut arr_cpu);
+
+    arr_cpu
+        .iter()
+        .map(|x| x.to_f64().unwrap_or(f64::NAN))
+        .collect()
+}
+
+// Returns output_size and the number of samples
+fn sample_dims<T: raybnn_float_trait>(y: &arrayfire::Array<T>) -> (usize, usize) {
+    let output_size = y.dims()[0].max(1) as usize;
+    (output_size, y.elements() / output_size)
+}
+
+fn argmax(values: &[f64]) -> usize {
+    let mut max_idx = 0;
+    for (k, value) in values.iter().enumerate() {
+        if *value > values[max_idx] {
+            max_idx = k;
+        }
+    }
+    max_idx
+}
+
+/*
+Predicted and true class of every sample
+
+Outputs:
+pred:                Predicted class of every sample
+truth:               True class of every sample
+label_num:           Number of classes, 2 for a single output row
+
+*/
+fn class_labels<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+) -> (Vec<u32>, Vec<u32>, u64) {
+    let (output_size, sample_num) = sample_dims(y);
+    let yhat_cpu = host_f64(yhat);
+    let y_cpu = host_f64(y);
+
+    if output_size == 1 {
+        let pred = yhat_cpu.iter().map(|x| (*x > 0.0) as u32).collect();
+        let truth = y_cpu.iter().map(|x| (*x > 0.5) as u32).collect();
+        return (pred, truth, 2);
+    }
+
+    let mut pred = Vec::with_capacity(sample_num);
+    let mut truth = Vec::with_capacity(sample_num);
+    for s in 0..sample_num {
+        let start = s * output_size;
+        pred.push(argmax(&yhat_cpu[start..start + output_size]) as u32);
+        truth.push(argmax(&y_cpu[start..start + output_size]) as u32);
+    }
+
+    (pred, truth, output_size as u64)
+}
+
+/*
+Confusion matrix of the predicted classes
+
+Outputs:
+confusion:           Dimensions (label_num, label_num). Row is the true class and column
+                     is the predicted class
+
+*/
+pub fn class_confusion_matrix<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+) -> arrayfire::Array<u32> {
+    let (pred, truth, label_num) = class_labels(yhat, y);
+
+    let dims = arrayfire::Dim4::new(&[pred.len() as u64, 1, 1, 1]);
+    confusion_matrix(
+        &arrayfire::Array::new(&pred, dims),
+        &arrayfire::Array::new(&truth, dims),
+        label_num,
+    )
+}
+
+// Host copy of the confusion matrix, confusion[true + pred*label_num]
+fn confusion_counts<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+) -> (Vec<f64>, usize) {
+    let confusion = class_confusion_matrix(yhat, y);
+    let label_num = confusion.dims()[0] as usize;
+
+    let mut confusion_cpu = vec![u32::default(); confusion.elements()];
+    confusion.host(&mut confusion_cpu);
+
+    (confusion_cpu.iter().map(|x| *x as f64).collect(), label_num)
+}
+
+// Returns the true positives, false positives and false negatives of every class
+fn class_counts(confusion: &[f64], label_num: usize) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+    let mut TP = vec![0.0; label_num];
+    let mut FP = vec![0.0; label_num];
+    let mut FN = vec![0.0; label_num];
+
+    for t in 0..label_num {
+        for p in 0..label_num {
+            let count = confusion[t + p * label_num];
+            if t == p {
+                TP[t] = TP[t] + count;
+            } else {
+                FP[p] = FP[p] + count;
+                FN[t] = FN[t] + count;
+            }
+        }
+    }
+
+    (TP, FP, FN)
+}
+
+fn safe_div(num: f64, den: f64) -> f64 {
+    if den > 0.0 {
+        num / den
+    } else {
+        0.0
+    }
+}
+
+// Fraction of samples with the correct class
+pub fn accuracy<T: raybnn_float_trait>(yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+    let (confusion, label_num) = confusion_counts(yhat, y);
+    let (TP, _, _) = class_counts(&confusion, label_num);
+
+    T::from_f64(safe_div(
+        TP.iter().sum::<f64>(),
+        confusion.iter().sum::<f64>(),
+    ))
+}
+
+// Fraction of samples with the true class among the k largest logits
+pub fn top_k_accuracy<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+    k: u64,
+) -> T {
+    let (output_size, sample_num) = sample_dims(y);
+    if output_size == 1 {
+        return if k >= 2 { T::one() } else { accuracy(yhat, y) };
+    }
+
+    let yhat_cpu = host_f64(yhat);
+    let y_cpu = host_f64(y);
+
+    let mut correct = 0.0;
+    for s in 0..sample_num {
+        let start = s * output_size;
+        let scores = &yhat_cpu[start..start + output_size];
+        let truth = argmax(&y_cpu[start..start + output_size]);
+
+        let higher = scores.iter().filter(|x| **x > scores[truth]).count() as u64;
+        if higher < k {
+            correct = correct + 1.0;
+        }
+    }
+
+    T::from_f64(safe_div(correct, sample_num as f64))
+}
+
+// Mean recall of the classes present in y
+pub fn balanced_accuracy<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+) -> T {
+    let (confusion, label_num) = confusion_counts(yhat, y);
+    let (TP, _, FN) = class_counts(&confusion, label_num);
+
+    let recall: Vec<f64> = (0..label_num)
+        .filter(|c| (TP[*c] + FN[*c]) > 0.0)
+        .map(|c| TP[c] / (TP[c] + FN[c]))
+        .collect();
+
+    T::from_f64(safe_div(recall.iter().sum::<f64>(), recall.len() as f64))
+}
+
+// Agreement between the predicted and true classes corrected for chance
+pub fn cohen_kappa<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+) -> T {
+    let (confusion, label_num) = confusion_counts(yhat, y);
+    let total: f64 = confusion.iter().sum();
+
+    let mut observed = 0.0;
+    let mut expected = 0.0;
+    for c in 0..label_num {
+        let true_num: f64 = (0..label_num).map(|p| confusion[c + p * label_num]).sum();
+        let pred_num: f64 = (0..label_num).map(|t| confusion[t + c * label_num]).sum();
+
+        observed = observed + confusion[c + c * label_num];
+        expected = expected + true_num * pred_num;
+    }
+    observed = safe_div(observed, total);
+    expected = safe_div(expected, total * total);
+
+    if expected >= 1.0 {
+        return T::from_f64(if observed >= 1.0 { 1.0 } else { 0.0 });
+    }
+
+    T::from_f64((observed - expected) / (1.0 - expected))
+}
+
+/*
+Per class score of the true positives, false positives and false negatives, averaged
+over the classes. A single output row returns the score of the positive class
+
+*/
+fn averaged_score<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+    averaging: averaging_type,
+    score: fn(f64, f64, f64) -> f64,
+) -> T {
+    let (confusion, label_num) = confusion_counts(yhat, y);
+    let (TP, FP, FN) = class_counts(&confusion, label_num);
+
+    let per_class: Vec<f64> = (0..label_num).map(|c| score(TP[c], FP[c], FN[c])).collect();
+
+    // Positive class of a single output row
+    if sample_dims(y).0 == 1 {
+        return T::from_f64(per_class[1]);
+    }
+
+    let result = match averaging {
+        averaging_type::MACRO => per_class.iter().sum::<f64>() / (label_num as f64),
+        averaging_type::MICRO => score(
+            TP.iter().sum::<f64>(),
+            FP.iter().sum::<f64>(),
+            FN.iter().sum::<f64>(),
+        ),
+        averaging_type::WEIGHTED => {
+            let support: Vec<f64> = (0..label_num).map(|c| TP[c] + FN[c]).collect();
+            safe_div(
+                (0..label_num)
+                    .map(|c| per_class[c] * support[c])
+                    .sum::<f64>(),
+                support.iter().sum::<f64>(),
+            )
+        }
+    };
+
+    T::from_f64(result)
+}
+
+pub fn F1_score<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+    averaging: averaging_type,
+) -> T {
+    averaged_score(yhat, y, averaging, |TP, FP, FN| {
+        safe_div(2.0 * TP, 2.0 * TP + FP + FN)
+    })
+}
+
+// Fraction of the predictions of a class that are correct
+pub fn precision<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+    averaging: averaging_type,
+) -> T {
+    averaged_score(yhat, y, averaging, |TP, FP, _| safe_div(TP, TP + FP))
+}
+
+// Fraction of the samples of a class that are predicted as the class
+pub fn recall<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+    averaging: averaging_type,
+) -> T {
+    averaged_score(yhat, y, averaging, |TP, _, FN| safe_div(TP, TP + FN))
+}
+
+pub fn F1_macro<T: raybnn_float_trait>(yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+    F1_score(yhat, y, averaging_type::MACRO)
+}
+
+pub fn F1_micro<T: raybnn_float_trait>(yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+    F1_score(yhat, y, averaging_type::MICRO)
+}
+
+pub fn F1_weighted<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+) -> T {
+    F1_score(yhat, y, averaging_type::WEIGHTED)
+}
+
+/*
+One-vs-rest scores and targets of every class
+
+Outputs:
+scores:              Probabilities of every class, softmax of the logits or sigmoid of a
+                     single output row
+targets:             True if the sample belongs to the class
+
+*/
+fn one_vs_rest<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+) -> (Vec<Vec<f64>>, Vec<Vec<bool>>) {
+    let (output_size, sample_num) = sample_dims(y);
+    let yhat_cpu = host_f64(yhat);
+    let y_cpu = host_f64(y);
+
+    if output_size == 1 {
+        let scores = yhat_cpu.iter().map(|x| 1.0 / (1.0 + (-x).exp())).collect();
+        let targets = y_cpu.iter().map(|x| *x > 0.5).collect();
+        return (vec![scores], vec![targets]);
+    }
+
+    let mut scores = vec![Vec::with_capacity(sample_num); output_size];
+    let mut targets = vec![Vec::with_capacity(sample_num); output_size];
+    for s in 0..sample_num {
+        let start = s * output_size;
+        let logits = &yhat_cpu[start..start + output_size];
+        let truth = argmax(&y_cpu[start..start + output_size]);
+
+        let max_logit = logits.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let exp_sum: f64 = logits.iter().map(|x| (x - max_logit).exp()).sum();
+
+        for c in 0..output_size {
+            scores[c].push((logits[c] - max_logit).exp() / exp_sum);
+            targets[c].push(c == truth);
+        }
+    }
+
+    (scores, targets)
+}
+
+// Sample order by decreasing score
+fn descending_order(scores: &[f64]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..scores.len()).collect();
+    order.sort_by(|a, b| {
+        scores[*b]
+            .partial_cmp(&scores[*a])
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    order
+}
+
+// Area under the ROC curve with tied scores counted as half. NaN without both classes
+fn binary_roc_auc(scores: &[f64], targets: &[bool]) -> f64 {
+    let order = descending_order(scores);
+
+    let pos_num = targets.iter().filter(|x| **x).count() as f64;
+    let neg_num = (targets.len() as f64) - pos_num;
+    if (pos_num == 0.0) || (neg_num == 0.0) {
+        return f64::NAN;
+    }
+
+    // Negatives ranked below each positive
+    let mut area = 0.0;
+    let mut neg_above = 0.0;
+    let mut i = 0;
+    while i < order.len() {
+        let mut j = i;
+        let mut group_pos = 0.0;
+        let mut group_neg = 0.0;
+        while (j < order.len()) && (scores[order[j]] == scores[order[i]]) {
+            if targets[order[j]] {
+                group_pos = group_pos + 1.0;
+            } else {
+                group_neg = group_neg + 1.0;
+            }
+            j = j + 1;
+        }
+
+        area = area + group_pos * (neg_num - neg_above - 0.5 * group_neg);
+        neg_above = neg_above + group_neg;
+        i = j;
+    }
+
+    area / (pos_num * neg_num)
+}
+
+// Average precision, the area under the precision-recall curve. NaN without positives
+fn binary_pr_auc(scores: &[f64], targets: &[bool]) -> f64 {
+    let order = descending_order(scores);
+
+    let pos_num = targets.iter().filter(|x| **x).count() as f64;
+    if pos_num == 0.0 {
+        return f64::NAN;
+    }
+
+    let mut area = 0.0;
+    let mut TP = 0.0;
+    let mut FP = 0.0;
+    let mut prev_recall = 0.0;
+    let mut i = 0;
+    while i < order.len() {
+        let mut j = i;
+        while (j < order.len()) && (scores[order[j]] == scores[order[i]]) {
+            if targets[order[j]] {
+                TP = TP + 1.0;
+            } else {
+                FP = FP + 1.0;
+            }
+            j = j + 1;
+        }
+
+        let recall = TP / pos_num;
+        area = area + (recall - prev_recall) * (TP / (TP + FP));
+        prev_recall = recall;
+        i = j;
+    }
+
+    area
+}
+
+// Averages a binary curve metric over the one-vs-rest classes. Skips undefined classes
+fn average_one_vs_rest<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+    averaging: averaging_type,
+    binary_metric: fn(&[f64], &[bool]) -> f64,
+) -> T {
+    let (scores, targets) = one_vs_rest(yhat, y);
+
+    if scores.len() == 1 {
+        return T::from_f64(binary_metric(&scores[0], &targets[0]));
+    }
+
+    if averaging == averaging_type::MICRO {
+        let pooled_scores: Vec<f64> = scores.concat();
+        let pooled_targets: Vec<bool> = targets.concat();
+        return T::from_f64(binary_metric(&pooled_scores, &pooled_targets));
+    }
+
+    let mut total = 0.0;
+    let mut weight_sum = 0.0;
+    for c in 0..scores.len() {
+        let value = binary_metric(&scores[c], &targets[c]);
+        if value.is_nan() {
+            continue;
+        }
+
+        let weight = match averaging {
+            averaging_type::WEIGHTED => targets[c].iter().filter(|x| **x).count() as f64,
+            _ => 1.0,
+        };
+        total = total + value * weight;
+        weight_sum = weight_sum + weight;
+    }
+
+    if weight_sum > 0.0 {
+        T::from_f64(total / weight_sum)
+    } else {
+        T::from_f64(f64::NAN)
+    }
+}
+
+// Binary or one-vs-rest area under the ROC curve
+pub fn ROC_AUC<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+    averaging: averaging_type,
+) -> T {
+    average_one_vs_rest(yhat, y, averaging, binary_roc_auc)
+}
+
+// Binary or one-vs-rest average precision
+pub fn PR_AUC<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+    averaging: averaging_type,
+) -> T {
+    average_one_vs_rest(yhat, y, averaging, binary_pr_auc)
+}
+
+// Negative log likelihood of the true classes, softmax or sigmoid of the logits
+pub fn log_loss<T: raybnn_float_trait>(yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+    let (output_size, sample_num) = sample_dims(y);
+    let (scores, targets) = one_vs_rest(yhat, y);
+    let epsilon = 1.0e-15;
+
+    let mut total = 0.0;
+    for s in 0..sample_num {
+        for c in 0..scores.len() {
+            let p = scores[c][s].clamp(epsilon, 1.0 - epsilon);
+            if targets[c][s] {
+                total = total - p.ln();
+            } else if output_size == 1 {
+                total = total - (1.0 - p).ln();
+            }
+        }
+    }
+
+    T::from_f64(safe_div(total, sample_num as f64))
+}
+
+// Returns Var(y), mean of (y - yhat)^2 and Var(y - yhat) of every output
+fn regression_moments<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+    let (output_size, sample_num) = sample_dims(y);
+    let yhat_cpu = host_f64(yhat);
+    let y_cpu = host_f64(y);
+
+    let n = sample_num.max(1) as f64;
+    let mut y_var = vec![0.0; output_size];
+    let mut res_sq = vec![0.0; output_size];
+    let mut res_var = vec![0.0; output_size];
+
+    for o in 0..output_size {
+        let y_mean: f64 = (0..sample_num)
+            .map(|s| y_cpu[o + s * output_size])
+            .sum::<f64>()
+            / n;
+        let res_mean: f64 = (0..sample_num)
+            .map(|s| y_cpu[o + s * output_size] - yhat_cpu[o + s * output_size])
+            .sum::<f64>()
+            / n;
+
+        for s in 0..sample_num {
+            let idx = o + s * output_size;
+            let res = y_cpu[idx] - yhat_cpu[idx];
+
+            y_var[o] = y_var[o] + (y_cpu[idx] - y_mean).powi(2) / n;
+            res_sq[o] = res_sq[o] + res.powi(2) / n;
+            res_var[o] = res_var[o] + (res - res_mean).powi(2) / n;
+        }
+    }
+
+    (y_var, res_sq, res_var)
+}
+
+// 1 - num/den averaged over the outputs. Constant outputs give 1 when predicted exactly
+fn uniform_score(num: &[f64], den: &[f64]) -> f64 {
+    let scores: Vec<f64> = num
+        .iter()
+        .zip(den.iter())
+        .map(|(n, d)| {
+            if *d > 0.0 {
+                1.0 - n / d
+            } else if *n == 0.0 {
+                1.0
+            } else {
+                0.0
+            }
+        })
+        .collect();
+
+    safe_div(scores.iter().sum::<f64>(), scores.len() as f64)
+}
+
+// Coefficient of determination averaged over the outputs
+pub fn R2<T: raybnn_float_trait>(yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+    let (y_var, res_sq, _) = regression_moments(yhat, y);
+    T::from_f64(uniform_score(&res_sq, &y_var))
+}
+
+// 1 - Var(y - yhat)/Var(y) averaged over the outputs
+pub fn explained_variance<T: raybnn_float_trait>(
+    yhat: &arrayfire::Array<T>,
+    y: &arrayfire::Array<T>,
+) -> T {
+    let (y_var, _, res_var) = regression_moments(yhat, y);
+    T::from_f64(uniform_score(&res_var, &y_var))
+}
+
+// Mean absolute percentage error, |y - yhat|/|y| as a fraction
+pub fn MAPE<T: raybnn_float_trait>(yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+    let yhat_cpu = host_f64(yhat);
+    let y_cpu = host_f64(y);
+
+    let total: f64 = y_cpu
+        .iter()
+        .zip(yhat_cpu.iter())
+        .map(|(a, b)| (a - b).abs() / a.abs().max(f64::EPSILON))
+        .sum();
+
+    T::from_f64(safe_div(total, y_cpu.len() as f64))
+}
+
+/*
+Metric with its settings
+
+higher_is_better:    True when larger values are better, e.g. accuracy. False for log_loss and MAPE
+
+*/
+pub trait metric_trait<T: raybnn_float_trait> {
+    fn metric(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T;
+
+    fn higher_is_better(&self) -> bool;
+}
+
+// Metric value as the eval_metric argument of validate_network
+pub fn eval_metric<'a, T: raybnn_float_trait>(
+    metric: &'a dyn metric_trait<T>,
+) -> impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> T + Copy + 'a {
+    move |yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>| metric.metric(yhat, y)
+}
+
+/*
+Metric as the eval_metric argument of train_network, which is minimized. Metrics with
+higher_is_better become 1 - metric. CROSSVAL_STOPPING then stops at the best validation metric
+
+eval_metric_grad stays the gradient of a differentiable loss, e.g. loss_metric_grad of
+cross_entropy_loss_type
+
+*/
+pub fn eval_metric_loss<'a, T: raybnn_float_trait>(
+    metric: &'a dyn metric_trait<T>,
+) -> impl Fn(&arrayfire::Array<T>, &arrayfire::Array<T>) -> T + Copy + 'a {
+    move |yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>| {
+        let value = metric.metric(yhat, y);
+        if metric.higher_is_better() {
+            T::one() - value
+        } else {
+            value
+        }
+    }
+}
+
+pub struct accuracy_metric_type {}
+
+impl<T: raybnn_float_trait> metric_trait<T> for accuracy_metric_type {
+    fn metric(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        accuracy(yhat, y)
+    }
+
+    fn higher_is_better(&self) -> bool {
+        true
+    }
+}
+
+pub struct top_k_accuracy_metric_type {
+    pub k: u64,
+}
+
+impl<T: raybnn_float_trait> metric_trait<T> for top_k_accuracy_metric_type {
+    fn metric(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        top_k_accuracy(yhat, y, self.k)
+    }
+
+    fn higher_is_better(&self) -> bool {
+        true
+    }
+}
+
+pub struct balanced_accuracy_metric_type {}
+
+impl<T: raybnn_float_trait> metric_trait<T> for balanced_accuracy_metric_type {
+    fn metric(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        balanced_accuracy(yhat, y)
+    }
+
+    fn higher_is_better(&self) -> bool {
+        true
+    }
+}
+
+pub struct cohen_kappa_metric_type {}
+
+impl<T: raybnn_float_trait> metric_trait<T> for cohen_kappa_metric_type {
+    fn metric(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        cohen_kappa(yhat, y)
+    }
+
+    fn higher_is_better(&self) -> bool {
+        true
+    }
+}
+
+pub struct f1_metric_type {
+    pub averaging: averaging_type,
+}
+
+impl<T: raybnn_float_trait> metric_trait<T> for f1_metric_type {
+    fn metric(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        F1_score(yhat, y, self.averaging)
+    }
+
+    fn higher_is_better(&self) -> bool {
+        true
+    }
+}
+
+pub struct precision_metric_type {
+    pub averaging: averaging_type,
+}
+
+impl<T: raybnn_float_trait> metric_trait<T> for precision_metric_type {
+    fn metric(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        precision(yhat, y, self.averaging)
+    }
+
+    fn higher_is_better(&self) -> bool {
+        true
+    }
+}
+
+pub struct recall_metric_type {
+    pub averaging: averaging_type,
+}
+
+impl<T: raybnn_float_trait> metric_trait<T> for recall_metric_type {
+    fn metric(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        recall(yhat, y, self.averaging)
+    }
+
+    fn higher_is_better(&self) -> bool {
+        true
+    }
+}
+
+pub struct roc_auc_metric_type {
+    pub averaging: averaging_type,
+}
+
+impl<T: raybnn_float_trait> metric_trait<T> for roc_auc_metric_type {
+    fn metric(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        ROC_AUC(yhat, y, self.averaging)
+    }
+
+    fn higher_is_better(&self) -> bool {
+        true
+    }
+}
+
+pub struct pr_auc_metric_type {
+    pub averaging: averaging_type,
+}
+
+impl<T: raybnn_float_trait> metric_trait<T> for pr_auc_metric_type {
+    fn metric(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        PR_AUC(yhat, y, self.averaging)
+    }
+
+    fn higher_is_better(&self) -> bool {
+        true
+    }
+}
+
+pub struct log_loss_metric_type {}
+
+impl<T: raybnn_float_trait> metric_trait<T> for log_loss_metric_type {
+    fn metric(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        log_loss(yhat, y)
+    }
+
+    fn higher_is_better(&self) -> bool {
+        false
+    }
+}
+
+pub struct r2_metric_type {}
+
+impl<T: raybnn_float_trait> metric_trait<T> for r2_metric_type {
+    fn metric(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        R2(yhat, y)
+    }
+
+    fn higher_is_better(&self) -> bool {
+        true
+    }
+}
+
+pub struct explained_variance_metric_type {}
+
+impl<T: raybnn_float_trait> metric_trait<T> for explained_variance_metric_type {
+    fn metric(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        explained_variance(yhat, y)
+    }
+
+    fn higher_is_better(&self) -> bool {
+        true
+    }
+}
+
+pub struct mape_metric_type {}
+
+impl<T: raybnn_float_trait> metric_trait<T> for mape_metric_type {
+    fn metric(&self, yhat: &arrayfire::Array<T>, y: &arrayfire::Array<T>) -> T {
+        MAPE(yhat, y)
+    }
+
+    fn higher_is_better(&self) -> bool {
+        false
+    }
+}
@@ -1,14 +1,32 @@
+pub mod gd;
+pub mod loss;
+pub mod metrics;
+pub mod optimizer;
+pub mod regularization;
+pub mod scheduler;
+pub mod bayesian;
+pub mod control;
+pub mod evolution;
+pub mod genetic;
+pub mod gradcheck;
+
 pub mod measure_f16;
 pub mod measure_f32;
 pub mod measure_f64;
 pub mod measure_u32;
 
+pub mod bayesian_f64;
 pub mod control_f64;
 pub mod evolution_f64;
 pub mod gd_f64;
+pub mod genetic_f64;
+pub mod gradcheck_f64;
 pub mod loss_f64;
 
+pub mod bayesian_f32;
 pub mod control_f32;
 pub mod evolution_f32;
 pub mod gd_f32;
+pub mod genetic_f32;
+pub mod gradcheck_f32;
 pub mod loss_f32;
@@ -0,0 +1,712 @@
+extern crate arrayfire;
+
+use serde::{Deserialize, Serialize};
+
+use crate::precision::float_trait::raybnn_float_trait;
+
+use crate::error::Error;
+
+/*
+Optimizers that turn the descent direction (negative gradient) into the parameter update
+
+The training loop computes the descent direction, lets the optimizer transform it with
+direction(), picks the learning rate alpha (for example with BTLS) and then calls apply().
+step() does both at once.
+
+Every optimizer has per element learning rate scales and weight decays (param_groups_type),
+so that the weights W, the bias H and the UAF parameters A..E can use different settings.
+Weight decay is added to the gradient (L2 penalty) for SGD, NESTEROV, ADAM, RMSPROP and ADAGRAD,
+and decoupled from the gradient for ADAMW and LION.
+
+*/
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum optimizer_kind_type {
+    SGD,
+    NESTEROV,
+    ADAM,
+    ADAMW,
+    RMSPROP,
+    ADAGRAD,
+    LION,
+}
+
+// Settings of one parameter group
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: raybnn_float_trait",
+    deserialize = "T: raybnn_float_trait"
+))]
+pub struct param_group_type<T: raybnn_float_trait> {
+    pub lr_scale: T,
+    pub weight_decay: T,
+}
+
+/*
+Optimizer settings
+
+kind:                Optimizer algorithm
+beta0:               Momentum (NESTEROV), first moment decay (ADAM, ADAMW, LION)
+beta1:               Second moment decay (ADAM, ADAMW, RMSPROP), momentum update decay (LION)
+epsilon:             Added to the denominator (ADAM, ADAMW, RMSPROP, ADAGRAD)
+weight:              Settings of the weights W
+bias:                Settings of the bias H
+uaf:                 Settings of the UAF parameters A, B, C, D, E
+
+*/
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: raybnn_float_trait",
+    deserialize = "T: raybnn_float_trait"
+))]
+pub struct optimizer_options_type<T: raybnn_float_trait> {
+    pub kind: optimizer_kind_type,
+    pub beta0: T,
+    pub beta1: T,
+    pub epsilon: T,
+
+    pub weight: param_group_type<T>,
+    pub bias: param_group_type<T>,
+    pub uaf: param_group_type<T>,
+}
+
+// Per element learning rate scale and weight decay of network_params
+#[derive(Clone)]
+pub struct param_groups_type<T: raybnn_float_trait> {
+    pub lr_scale: arrayfire::Array<T>,
+    pub weight_decay: arrayfire::Array<T>,
+}
+
+pub trait optimizer_trait<T: raybnn_float_trait> {
+    fn kind(&self) -> optimizer_kind_type;
+
+    fn groups(&self) -> &param_groups_type<T>;
+
+    fn set_groups(&mut self, groups: param_groups_type<T>);
+
+    // Transforms the descent direction in place and updates the optimizer state
+    fn direction(&mut self, params: &arrayfire::Array<T>, direction: &mut arrayfire::Array<T>);
+
+    // Clears the optimizer state for param_size parameters
+    fn reset(&mut self, param_size: u64);
+
+    // Optimizer state, for example the Adam moments
+    fn state(&self) -> Vec<arrayfire::Array<T>>;
+
+    // Restores the state returned by state(). InvalidTrainState when the number or size
+    // of the arrays does not match this optimizer
+    fn set_state(&mut self, state: Vec<arrayfire::Array<T>>) -> Result<(), Error>;
+
+    // Updates the parameters with learning rate alpha
+    fn apply(&self, alpha: T, params: &mut arrayfire::Array<T>, direction: &arrayfire::Array<T>) {
+        let groups = self.groups();
+        let scaled_alpha = groups.lr_scale.clone() * alpha;
+
+        if (self.kind() == optimizer_kind_type::ADAMW) || (self.kind() == optimizer_kind_type::LION)
+        {
+            let decay = arrayfire::mul(&scaled_alpha, &groups.weight_decay, false);
+            *params = params.clone() - arrayfire::mul(&decay, params, false);
+        }
+
+        *params = params.clone() + arrayfire::mul(&scaled_alpha, direction, false);
+    }
+
+    fn step(
+        &mut self,
+        alpha: T,
+        params: &mut arrayfire::Array<T>,
+        direction: &mut arrayfire::Array<T>,
+    ) {
+        self.direction(params, direction);
+        self.apply(alpha, params, direction);
+    }
+}
+
+/*
+Default settings of the optimizer. The learning rate scale is 1 and the weight decay is 0
+for every parameter group
+
+Inputs
+kind:                Optimizer algorithm
+
+Outputs:
+options:             Optimizer settings
+
+*/
+pub fn create_optimizer_options<T: raybnn_float_trait>(
+    kind: optimizer_kind_type,
+) -> optimizer_options_type<T> {
+    let (beta0, beta1) = match kind {
+        optimizer_kind_type::SGD => (0.0, 0.0),
+        optimizer_kind_type::NESTEROV => (0.9, 0.0),
+        optimizer_kind_type::ADAM => (0.9, 0.999),
+        optimizer_kind_type::ADAMW => (0.9, 0.999),
+        optimizer_kind_type::RMSPROP => (0.0, 0.99),
+        optimizer_kind_type::ADAGRAD => (0.0, 0.0),
+        optimizer_kind_type::LION => (0.9, 0.99),
+    };
+
+    let group = param_group_type {
+        lr_scale: T::one(),
+        weight_decay: T::zero(),
+    };
+
+    optimizer_options_type {
+        kind: kind,
+        beta0: T::from_f64(beta0),
+        beta1: T::from_f64(beta1),
+        epsilon: T::from_f64(1.0e-8),
+
+        weight: group,
+        bias: group,
+        uaf: group,
+    }
+}
+
+/*
+Same settings for every parameter
+
+Inputs
+param_size:          Number of parameters
+group:               Settings of all parameters
+
+Outputs:
+groups:              Per element settings
+
+*/
+pub fn create_uniform_param_groups<T: raybnn_float_trait>(
+    param_size: u64,
+    group: param_group_type<T>,
+) -> param_groups_type<T> {
+    let param_dims = arrayfire::Dim4::new(&[param_size, 1, 1, 1]);
+
+    param_groups_type {
+        lr_scale: arrayfire::constant::<T>(group.lr_scale, param_dims),
+        weight_decay: arrayfire::constant::<T>(group.weight_decay, param_dims),
+    }
+}
+
+/*
+Per element settings of network_params, using the W, H and A..E sequences from
+find_path_backward_group2
+
+Inputs
+param_size:          Number of parameters in network_params
+options:             Optimizer settings
+Hseqs:               Location of the bias H in network_params
+Aseqs..Eseqs:        Location of the UAF parameters in network_params
+
+Outputs:
+groups:              Per element settings
+
+*/
+pub fn create_network_param_groups<T: raybnn_float_trait>(
+    param_size: u64,
+    options: &optimizer_options_type<T>,
+
+    Hseqs: &[arrayfire::Seq<i32>; 1],
+    Aseqs: &[arrayfire::Seq<i32>; 1],
+    Bseqs: &[arrayfire::Seq<i32>; 1],
+    Cseqs: &[arrayfire::Seq<i32>; 1],
+    Dseqs: &[arrayfire::Seq<i32>; 1],
+    Eseqs: &[arrayfire::Seq<i32>; 1],
+) -> param_groups_type<T> {
+    let mut groups = create_uniform_param_groups(param_size, options.weight);
+
+    let mut set_group = |seqs: &[arrayfire::Seq<i32>; 1], group: param_group_type<T>| {
+        let size = seqs[0].end() - seqs[0].begin() + 1;
+        if size <= 0 {
+            return;
+        }
+        let group_dims = arrayfire::Dim4::new(&[size as u64, 1, 1, 1]);
+
+        let lr_scale = arrayfire::constant::<T>(group.lr_scale, group_dims);
+        arrayfire::assign_seq(&mut groups.lr_scale, seqs, &lr_scale);
+
+        let weight_decay = arrayfire::constant::<T>(group.weight_decay, group_dims);
+        arrayfire::assign_seq(&mut groups.weight_decay, seqs, &weight_decay);
+    };
+
+    set_group(Hseqs, options.bias);
+    set_group(Aseqs, options.uaf);
+    set_group(Bseqs, options.uaf);
+    set_group(Cseqs, options.uaf);
+    set_group(Dseqs, options.uaf);
+    set_group(Eseqs, options.uaf);
+
+    groups
+}
+
+/*
+Creates the optimizer selected in the settings
+
+Inputs
+options:             Optimizer settings
+groups:              Per element settings, which also set the number of parameters
+
+Outputs:
+optimizer:           Optimizer with cleared state
+
+*/
+pub fn create_optimizer<T: raybnn_float_trait>(
+    options: &optimizer_options_type<T>,
+    groups: param_groups_type<T>,
+) -> Box<dyn optimizer_trait<T>> {
+    let param_size = groups.lr_scale.dims()[0];
+
+    let mut optimizer: Box<dyn optimizer_trait<T>> = match options.kind {
+        optimizer_kind_type::SGD => Box::new(sgd_type::new(groups)),
+        optimizer_kind_type::NESTEROV => Box::new(nesterov_type::new(options.beta0, groups)),
+        optimizer_kind_type::ADAM => Box::new(adam_type::new(
+            options.beta0,
+            options.beta1,
+            options.epsilon,
+            false,
+            groups,
+        )),
+        optimizer_kind_type::ADAMW => Box::new(adam_type::new(
+            options.beta0,
+            options.beta1,
+            options.epsilon,
+            true,
+            groups,
+        )),
+        optimizer_kind_type::RMSPROP => {
+            Box::new(rmsprop_type::new(options.beta1, options.epsilon, groups))
+        }
+        optimizer_kind_type::ADAGRAD => Box::new(adagrad_type::new(options.epsilon, groups)),
+        optimizer_kind_type::LION => Box::new(lion_type::new(options.beta0, options.beta1, groups)),
+    };
+
+    optimizer.reset(param_size);
+
+    optimizer
+}
+
+// Adds the L2 penalty of the weight decay to the descent direction
+fn add_weight_decay<T: raybnn_float_trait>(
+    groups: &param_groups_type<T>,
+    params: &arrayfire::Array<T>,
+    direction: &mut arrayfire::Array<T>,
+) {
+    *direction = direction.clone() - arrayfire::mul(&groups.weight_decay, params, false);
+}
+
+// Checks the number of arrays of the optimizer state and the number of values of each array
+fn check_state<T: raybnn_float_trait>(
+    state: &Vec<arrayfire::Array<T>>,
+    expected_sizes: &[u64],
+) -> Result<(), Error> {
+    if state.len() != expected_sizes.len() {
+        return Err(Error::InvalidTrainState(format!(
+            "optimizer state has {} arrays, expected {}",
+            state.len(),
+            expected_sizes.len()
+        )));
+    }
+
+    for (k, (arr, expected)) in state.iter().zip(expected_sizes.iter()).enumerate() {
+        if arr.elements() as u64 != *expected {
+            return Err(Error::InvalidTrainState(format!(
+                "optimizer state array {} has {} values, expected {}",
+                k,
+                arr.elements(),
+                expected
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn zeros<T: raybnn_float_trait>(param_size: u64) -> arrayfire::Array<T> {
+    arrayfire::constant::<T>(T::zero(), arrayfire::Dim4::new(&[param_size, 1, 1, 1]))
+}
+
+// Gradient descent
+pub struct sgd_type<T: raybnn_float_trait> {
+    pub groups: param_groups_type<T>,
+}
+
+impl<T: raybnn_float_trait> sgd_type<T> {
+    pub fn new(groups: param_groups_type<T>) -> sgd_type<T> {
+        sgd_type { groups: groups }
+    }
+}
+
+impl<T: raybnn_float_trait> optimizer_trait<T> for sgd_type<T> {
+    fn kind(&self) -> optimizer_kind_type {
+        optimizer_kind_type::SGD
+    }
+
+    fn groups(&self) -> &param_groups_type<T> {
+        &self.groups
+    }
+
+    fn set_groups(&mut self, groups: param_groups_type<T>) {
+        self.groups = groups;
+    }
+
+    fn direction(&mut self, params: &arrayfire::Array<T>, direction: &mut arrayfire::Array<T>) {
+        add_weight_decay(&self.groups, params, direction);
+    }
+
+    fn reset(&mut self, _param_size: u64) {}
+
+    fn state(&self) -> Vec<arrayfire::Array<T>> {
+        Vec::new()
+    }
+
+    fn set_state(&mut self, state: Vec<arrayfire::Array<T>>) -> Result<(), Error> {
+        check_state(&state, &[])
+    }
+}
+
+// Gradient descent with Nesterov momentum
+pub struct nesterov_type<T: raybnn_float_trait> {
+    pub momentum: T,
+    pub groups: param_groups_type<T>,
+    pub velocity: arrayfire::Array<T>,
+}
+
+impl<T: raybnn_float_trait> nesterov_type<T> {
+    pub fn new(momentum: T, groups: param_groups_type<T>) -> nesterov_type<T> {
+        let param_size = groups.lr_scale.dims()[0];
+        nesterov_type {
+            momentum: momentum,
+            groups: groups,
+            velocity: zeros(param_size),
+        }
+    }
+}
+
+impl<T: raybnn_float_trait> optimizer_trait<T> for nesterov_type<T> {
+    fn kind(&self) -> optimizer_kind_type {
+        optimizer_kind_type::NESTEROV
+    }
+
+    fn groups(&self) -> &param_groups_type<T> {
+        &self.groups
+    }
+
+    fn set_groups(&mut self, groups: param_groups_type<T>) {
+        self.groups = groups;
+    }
+
+    fn direction(&mut self, params: &arrayfire::Array<T>, direction: &mut arrayfire::Array<T>) {
+        add_weight_decay(&self.groups, params, direction);
+
+        // v = mu v + d
+        self.velocity = self.velocity.clone() * self.momentum + direction.clone();
+
+        // d + mu v
+        *direction = direction.clone() + self.velocity.clone() * self.momentum;
+    }
+
+    fn reset(&mut self, param_size: u64) {
+        self.velocity = zeros(param_size);
+    }
+
+    fn state(&self) -> Vec<arrayfire::Array<T>> {
+        vec![self.velocity.clone()]
+    }
+
+    fn set_state(&mut self, state: Vec<arrayfire::Array<T>>) -> Result<(), Error> {
+        check_state(&state, &[self.groups.lr_scale.dims()[0]])?;
+        self.velocity = state[0].clone();
+        Ok(())
+    }
+}
+
+// Adam, or AdamW when decoupled is true. Unlike gd::adam, the moments are bias corrected
+// with 1 - beta^t, where t is the number of steps step_num
+pub struct adam_type<T: raybnn_float_trait> {
+    pub beta0: T,
+    pub beta1: T,
+    pub epsilon: T,
+    pub decoupled: bool,
+    pub groups: param_groups_type<T>,
+    pub mt: arrayfire::Array<T>,
+    pub vt: arrayfire::Array<T>,
+    pub step_num: u64,
+}
+
+impl<T: raybnn_float_trait> adam_type<T> {
+    pub fn new(
+        beta0: T,
+        beta1: T,
+        epsilon: T,
+        decoupled: bool,
+        groups: param_groups_type<T>,
+    ) -> adam_type<T> {
+        let param_size = groups.lr_scale.dims()[0];
+        adam_type {
+            beta0: beta0,
+            beta1: beta1,
+            epsilon: epsilon,
+            decoupled: decoupled,
+            groups: groups,
+            mt: zeros(param_size),
+            vt: zeros(param_size),
+            step_num: 0,
+        }
+    }
+}
+
+impl<T: raybnn_float_trait> optimizer_trait<T> for adam_type<T> {
+    fn kind(&self) -> optimizer_kind_type {
+        if self.decoupled {
+            optimizer_kind_type::ADAMW
+        } else {
+            optimizer_kind_type::ADAM
+        }
+    }
+
+    fn groups(&self) -> &param_groups_type<T> {
+        &self.groups
+    }
+
+    fn set_groups(&mut self, groups: param_groups_type<T>) {
+        self.groups = groups;
+    }
+
+    fn direction(&mut self, params: &arrayfire::Array<T>, direction: &mut arrayfire::Array<T>) {
+        if !self.decoupled {
+            add_weight_decay(&self.groups, params, direction);
+        }
+
+        let two = T::from_f64(2.0);
+        let one = T::one();
+        let beta0 = self.beta0;
+        let beta1 = self.beta1;
+
+        self.step_num = self.step_num + 1;
+        let t = T::from_f64(self.step_num as f64);
+
+        self.mt = (self.mt.clone()) * beta0 + (direction.clone()) * (one - beta0);
+        self.vt =
+            (self.vt.clone()) * beta1 + arrayfire::pow(direction, &two, false) * (one - beta1);
+
+        let nmt = self.mt.clone() / (one - beta0.powf(t));
+        let mut nvt = self.vt.clone() / (one - beta1.powf(t));
+        nvt = arrayfire::sqrt(&nvt) + self.epsilon;
+
+        *direction = (nmt / nvt);
+    }
+
+    fn reset(&mut self, param_size: u64) {
+        self.mt = zeros(param_size);
+        self.vt = zeros(param_size);
+        self.step_num = 0;
+    }
+
+    // The step count is the last array, with one value
+    fn state(&self) -> Vec<arrayfire::Array<T>> {
+        let step_num = [T::from_f64(self.step_num as f64)];
+
+        vec![
+            self.mt.clone(),
+            self.vt.clone(),
+            arrayfire::Array::new(&step_num, arrayfire::Dim4::new(&[1, 1, 1, 1])),
+        ]
+    }
+
+    fn set_state(&mut self, state: Vec<arrayfire::Array<T>>) -> Result<(), Error> {
+        let param_size = self.groups.lr_scale.dims()[0];
+        check_state(&state, &[param_size, param_size, 1])?;
+
+        let mut step_num = [T::zero()];
+        state[2].host(&mut step_num);
+        if !(step_num[0] >= T::zero()) || (step_num[0] != step_num[0].round()) {
+            return Err(Error::InvalidTrainState(format!(
+                "adam step count {} is not a whole number",
+                step_num[0]
+            )));
+        }
+
+        self.mt = state[0].clone();
+        self.vt = state[1].clone();
+        self.step_num = step_num[0].to_u64().unwrap_or(0);
+        Ok(())
+    }
+}
+
+// RMSprop
+pub struct rmsprop_type<T: raybnn_float_trait> {
+    pub rho: T,
+    pub epsilon: T,
+    pub groups: param_groups_type<T>,
+    pub sq: arrayfire::Array<T>,
+}
+
+impl<T: raybnn_float_trait> rmsprop_type<T> {
+    pub fn new(rho: T, epsilon: T, groups: param_groups_type<T>) -> rmsprop_type<T> {
+        let param_size = groups.lr_scale.dims()[0];
+        rmsprop_type {
+            rho: rho,
+            epsilon: epsilon,
+            groups: groups,
+            sq: zeros(param_size),
+        }
+    }
+}
+
+impl<T: raybnn_float_trait> optimizer_trait<T> for rmsprop_type<T> {
+    fn kind(&self) -> optimizer_kind_type {
+        optimizer_kind_type::RMSPROP
+    }
+
+    fn groups(&self) -> &param_groups_type<T> {
+        &self.groups
+    }
+
+    fn set_groups(&mut self, groups: param_groups_type<T>) {
+        self.groups = groups;
+    }
+
+    fn direction(&mut self, params: &arrayfire::Array<T>, direction: &mut arrayfire::Array<T>) {
+        add_weight_decay(&self.groups, params, direction);
+
+        let two = T::from_f64(2.0);
+        let one = T::one();
+
+        self.sq =
+            self.sq.clone() * self.rho + arrayfire::pow(direction, &two, false) * (one - self.rho);
+
+        *direction = direction.clone() / (arrayfire::sqrt(&self.sq) + self.epsilon);
+    }
+
+    fn reset(&mut self, param_size: u64) {
+        self.sq = zeros(param_size);
+    }
+
+    fn state(&self) -> Vec<arrayfire::Array<T>> {
+        vec![self.sq.clone()]
+    }
+
+    fn set_state(&mut self, state: Vec<arrayfire::Array<T>>) -> Result<(), Error> {
+        check_state(&state, &[self.groups.lr_scale.dims()[0]])?;
+        self.sq = state[0].clone();
+        Ok(())
+    }
+}
+
+// Adagrad
+pub struct adagrad_type<T: raybnn_float_trait> {
+    pub epsilon: T,
+    pub groups: param_groups_type<T>,
+    pub sum_sq: arrayfire::Array<T>,
+}
+
+impl<T: raybnn_float_trait> adagrad_type<T> {
+    pub fn new(epsilon: T, groups: param_groups_type<T>) -> adagrad_type<T> {
+        let param_size = groups.lr_scale.dims()[0];
+        adagrad_type {
+            epsilon: epsilon,
+            groups: groups,
+            sum_sq: zeros(param_size),
+        }
+    }
+}
+
+impl<T: raybnn_float_trait> optimizer_trait<T> for adagrad_type<T> {
+    fn kind(&self) -> optimizer_kind_type {
+        optimizer_kind_type::ADAGRAD
+    }
+
+    fn groups(&self) -> &param_groups_type<T> {
+        &self.groups
+    }
+
+    fn set_groups(&mut self, groups: param_groups_type<T>) {
+        self.groups = groups;
+    }
+
+    fn direction(&mut self, params: &arrayfire::Array<T>, direction: &mut arrayfire::Array<T>) {
+        add_weight_decay(&self.groups, params, direction);
+
+        let two = T::from_f64(2.0);
+
+        self.sum_sq = self.sum_sq.clone() + arrayfire::pow(direction, &two, false);
+
+        *direction = direction.clone() / (arrayfire::sqrt(&self.sum_sq) + self.epsilon);
+    }
+
+    fn reset(&mut self, param_size: u64) {
+        self.sum_sq = zeros(param_size);
+    }
+
+    fn state(&self) -> Vec<arrayfire::Array<T>> {
+        vec![self.sum_sq.clone()]
+    }
+
+    fn set_state(&mut self, state: Vec<arrayfire::Array<T>>) -> Result<(), Error> {
+        check_state(&state, &[self.groups.lr_scale.dims()[0]])?;
+        self.sum_sq = state[0].clone();
+        Ok(())
+    }
+}
+
+// Lion (sign of interpolated momentum) with decoupled weight decay
+pub struct lion_type<T: raybnn_float_trait> {
+    pub beta0: T,
+    pub beta1: T,
+    pub groups: param_groups_type<T>,
+    pub mt: arrayfire::Array<T>,
+}
+
+impl<T: raybnn_float_trait> lion_type<T> {
+    pub fn new(beta0: T, beta1: T, groups: param_groups_type<T>) -> lion_type<T> {
+        let param_size = groups.lr_scale.dims()[0];
+        lion_type {
+            beta0: beta0,
+            beta1: beta1,
+            groups: groups,
+            mt: zeros(param_size),
+        }
+    }
+}
+
+impl<T: raybnn_float_trait> optimizer_trait<T> for lion_type<T> {
+    fn kind(&self) -> optimizer_kind_type {
+        optimizer_kind_type::LION
+    }
+
+    fn groups(&self) -> &param_groups_type<T> {
+        &self.groups
+    }
+
+    fn set_groups(&mut self, groups: param_groups_type<T>) {
+        self.groups = groups;
+    }
+
+    fn direction(&mut self, _params: &arrayfire::Array<T>, direction: &mut arrayfire::Array<T>) {
+        let one = T::one();
+        let zero = T::zero();
+
+        // c = beta0 m + (1 - beta0) d
+        let interp = self.mt.clone() * self.beta0 + direction.clone() * (one - self.beta0);
+
+        // m = beta1 m + (1 - beta1) d
+        self.mt = self.mt.clone() * self.beta1 + direction.clone() * (one - self.beta1);
+
+        // sign(c)
+        let pos = arrayfire::gt(&interp, &zero, false).cast::<T>();
+        let neg = arrayfire::lt(&interp, &zero, false).cast::<T>();
+        *direction = pos - neg;
+    }
+
+    fn reset(&mut self, param_size: u64) {
+        self.mt = zeros(param_size);
+    }
+
+    fn state(&self) -> Vec<arrayfire::Array<T>> {
+        vec![self.mt.clone()]
+    }
+
+    fn set_state(&mut self, state: Vec<arrayfire::Array<T>>) -> Result<(), Error> {
+        check_state(&state, &[self.groups.lr_scale.dims()[0]])?;
+        self.mt = state[0].clone();
+        Ok(())
+    }
+}
@@ -0,0 +1,367 @@
+extern crate arrayfire;
+
+use serde::{Deserialize, Serialize};
+
+use crate::precision::float_trait::raybnn_float_trait;
+use crate::precision::reduce::sum_all;
+use crate::precision::reduce::sum_by_key;
+
+use crate::error::Error;
+
+/*
+Regularization of the state space training loop
+
+Penalties are added to the loss gradient after state_space_backward_group2. The penalty
+of one parameter w is l1*|w| + 0.5*l2*w^2, so setting both l1 and l2 gives elastic net.
+weight applies to the weights W and uaf applies to the UAF parameters A..E. The bias H
+is not penalized.
+
+max_norm rescales the incoming weights of every neuron after each update so that their
+L2 norm is at most max_norm.
+
+dropout drops the outputs of neurons and dropconnect drops single connections of the
+sparse CSR weights during state_space_forward_batch. Both use inverted scaling, so the
+weights do not need to be rescaled after training.
+
+Every setting is disabled with zero.
+
+*/
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: raybnn_float_trait",
+    deserialize = "T: raybnn_float_trait"
+))]
+pub struct penalty_type<T: raybnn_float_trait> {
+    pub l1: T,
+    pub l2: T,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: raybnn_float_trait",
+    deserialize = "T: raybnn_float_trait"
+))]
+pub struct regularization_options_type<T: raybnn_float_trait> {
+    pub weight: penalty_type<T>,
+    pub uaf: penalty_type<T>,
+
+    pub max_norm: T,
+
+    pub dropout: T,
+    pub dropconnect: T,
+}
+
+// Settings with every regularization disabled
+pub fn create_regularization_options<T: raybnn_float_trait>() -> regularization_options_type<T> {
+    let none = penalty_type {
+        l1: T::zero(),
+        l2: T::zero(),
+    };
+
+    regularization_options_type {
+        weight: none,
+        uaf: none,
+
+        max_norm: T::zero(),
+
+        dropout: T::zero(),
+        dropconnect: T::zero(),
+    }
+}
+
+/*
+Checks the regularization settings
+
+Inputs
+options:             Regularization settings
+
+Outputs:
+result:              InvalidTrainState if a penalty or max_norm is negative, or if dropout
+                     or dropconnect is outside [0, 1)
+
+*/
+pub fn check_regularization_options<T: raybnn_float_trait>(
+    options: &regularization_options_type<T>,
+) -> Result<(), Error> {
+    let zero = T::zero();
+    let one = T::one();
+
+    let non_negative = [
+        ("weight.l1", options.weight.l1),
+        ("weight.l2", options.weight.l2),
+        ("uaf.l1", options.uaf.l1),
+        ("uaf.l2", options.uaf.l2),
+        ("max_norm", options.max_norm),
+    ];
+    for (name, value) in non_negative {
+        if !(value >= zero) {
+            return Err(Error::InvalidTrainState(format!(
+                "regularization {} ({}) must be >= 0",
+                name, value
+            )));
+        }
+    }
+
+    for (name, p) in [
+        ("dropout", options.dropout),
+        ("dropconnect", options.dropconnect),
+    ] {
+        if !((p >= zero) && (p < one)) {
+            return Err(Error::InvalidTrainState(format!(
+                "{} probability ({}) must be in [0, 1)",
+                name, p
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn seqs_size(seqs: &[arrayfire::Seq<i32>; 1]) -> i32 {
+    seqs[0].end() - seqs[0].begin() + 1
+}
+
+fn penalty_value<T: raybnn_float_trait>(
+    params: &arrayfire::Array<T>,
+    penalty: &penalty_type<T>,
+) -> T {
+    let half = T::from_f64(0.5);
+
+    let (abs_sum, _) = sum_all(&arrayfire::abs(params));
+    let (sq_sum, _) = sum_all(&arrayfire::mul(params, params, false));
+
+    (penalty.l1 * abs_sum) + (half * penalty.l2 * sq_sum)
+}
+
+fn penalty_grad<T: raybnn_float_trait>(
+    params: &arrayfire::Array<T>,
+    penalty: &penalty_type<T>,
+) -> arrayfire::Array<T> {
+    let zero = T::zero();
+
+    let sign = arrayfire::gt(params, &zero, false).cast::<T>()
+        - arrayfire::lt(params, &zero, false).cast::<T>();
+
+    (sign * penalty.l1) + (params.clone() * penalty.l2)
+}
+
+/*
+Regularization penalty of the network parameters
+
+Inputs
+network_params:      Network parameters
+options:             Regularization settings
+Wseqs..Eseqs:        Location of W and of the UAF parameters A..E in network_params
+
+Outputs:
+penalty:             Value of the L1 and L2 penalties
+
+*/
+pub fn regularization_penalty<T: raybnn_float_trait>(
+    network_params: &arrayfire::Array<T>,
+    options: &regularization_options_type<T>,
+
+    Wseqs: &[arrayfire::Seq<i32>; 1],
+    Aseqs: &[arrayfire::Seq<i32>; 1],
+    Bseqs: &[arrayfire::Seq<i32>; 1],
+    Cseqs: &[arrayfire::Seq<i32>; 1],
+    Dseqs: &[arrayfire::Seq<i32>; 1],
+    Eseqs: &[arrayfire::Seq<i32>; 1],
+) -> T {
+    let mut total = T::zero();
+
+    if seqs_size(Wseqs) > 0 {
+        total = total + penalty_value(&arrayfire::index(network_params, Wseqs), &options.weight);
+    }
+
+    for seqs in [Aseqs, Bseqs, Cseqs, Dseqs, Eseqs] {
+        if seqs_size(seqs) > 0 {
+            total = total + penalty_value(&arrayfire::index(network_params, seqs), &options.uaf);
+        }
+    }
+
+    total
+}
+
+/*
+Adds the gradient of the L1 and L2 penalties to the loss gradient
+
+Inputs
+network_params:      Network parameters
+options:             Regularization settings
+Wseqs..Eseqs:        Location of W and of the UAF parameters A..E in network_params
+
+Outputs:
+grad:                Loss gradient with the penalty gradient added
+
+*/
+pub fn add_regularization_grad<T: raybnn_float_trait>(
+    network_params: &arrayfire::Array<T>,
+    options: &regularization_options_type<T>,
+
+    Wseqs: &[arrayfire::Seq<i32>; 1],
+    Aseqs: &[arrayfire::Seq<i32>; 1],
+    Bseqs: &[arrayfire::Seq<i32>; 1],
+    Cseqs: &[arrayfire::Seq<i32>; 1],
+    Dseqs: &[arrayfire::Seq<i32>; 1],
+    Eseqs: &[arrayfire::Seq<i32>; 1],
+
+    grad: &mut arrayfire::Array<T>,
+) {
+    let zero = T::zero();
+
+    let mut add_penalty = |seqs: &[arrayfire::Seq<i32>; 1], penalty: &penalty_type<T>| {
+        if (seqs_size(seqs) <= 0) || ((penalty.l1 == zero) && (penalty.l2 == zero)) {
+            return;
+        }
+
+        let params = arrayfire::index(network_params, seqs);
+        let newgrad = arrayfire::index(&*grad, seqs) + penalty_grad(&params, penalty);
+        arrayfire::assign_seq(&mut *grad, seqs, &newgrad);
+    };
+
+    add_penalty(Wseqs, &options.weight);
+    add_penalty(Aseqs, &options.uaf);
+    add_penalty(Bseqs, &options.uaf);
+    add_penalty(Cseqs, &options.uaf);
+    add_penalty(Dseqs, &options.uaf);
+    add_penalty(Eseqs, &options.uaf);
+}
+
+/*
+Rescales the incoming weights of every neuron to an L2 norm of at most max_norm
+
+Inputs
+max_norm:            Maximum norm, zero does nothing
+WRowIdxCOO:          Row (target neuron) of every weight in COO format
+Wseqs:               Location of W in network_params
+
+Outputs:
+network_params:      Network parameters with rescaled weights
+
+*/
+pub fn apply_max_norm<T: raybnn_float_trait>(
+    max_norm: T,
+    WRowIdxCOO: &arrayfire::Array<i32>,
+    Wseqs: &[arrayfire::Seq<i32>; 1],
+
+    network_params: &mut arrayfire::Array<T>,
+) {
+    if (max_norm <= T::zero()) || (seqs_size(Wseqs) <= 0) {
+        return;
+    }
+
+    let W = arrayfire::index(network_params, Wseqs);
+
+    let sq = arrayfire::mul(&W, &W, false);
+    let (rows, sumarr) = sum_by_key(WRowIdxCOO, &sq, 0);
+
+    // max_norm/max(norm, max_norm) is one for rows below max_norm
+    let norms = arrayfire::clamp(&arrayfire::sqrt(&sumarr), &max_norm, &T::infinity(), false);
+    let scale = arrayfire::constant::<T>(max_norm, norms.dims()) / norms;
+
+    let (row_max, _) = arrayfire::max_all::<i32>(WRowIdxCOO);
+    let mut table = arrayfire::constant::<T>(
+        T::one(),
+        arrayfire::Dim4::new(&[(row_max + 1) as u64, 1, 1, 1]),
+    );
+
+    let mut idxrs = arrayfire::Indexer::default();
+    idxrs.set_index(&rows, 0, None);
+    arrayfire::assign_gen(&mut table, &idxrs, &scale);
+
+    let W_scale = arrayfire::lookup(&table, WRowIdxCOO, 0);
+    arrayfire::assign_seq(network_params, Wseqs, &arrayfire::mul(&W, &W_scale, false));
+}
+
+/*
+Random dropout and dropconnect mask of the weights
+
+Dropping the output of a neuron drops every connection that starts at the neuron,
+so dropout is applied through the source neuron WColIdx of each weight.
+
+Inputs
+options:             Regularization settings
+WColIdx:             Column (source neuron) of every weight
+seed:                Seed of the random engine. The same seed gives the same mask
+
+Outputs:
+mask:                Mask with zero for dropped weights and 1/keep probability otherwise.
+                     None if dropout and dropconnect are disabled
+
+*/
+pub fn create_drop_mask<T: raybnn_float_trait>(
+    options: &regularization_options_type<T>,
+    WColIdx: &arrayfire::Array<i32>,
+    seed: u64,
+) -> Option<arrayfire::Array<T>> {
+    let zero = T::zero();
+    let one = T::one();
+
+    if (options.dropout <= zero) && (options.dropconnect <= zero) {
+        return None;
+    }
+
+    let engine = arrayfire::RandomEngine::new(arrayfire::DEFAULT_RANDOM_ENGINE, Some(seed));
+
+    let W_dims = WColIdx.dims();
+    let mut mask = arrayfire::constant::<T>(one, W_dims);
+
+    if options.dropout > zero {
+        let (col_max, _) = arrayfire::max_all::<i32>(WColIdx);
+        let neuron_dims = arrayfire::Dim4::new(&[(col_max + 1) as u64, 1, 1, 1]);
+
+        let keep = arrayfire::ge(
+            &arrayfire::random_uniform::<T>(neuron_dims, &engine),
+            &options.dropout,
+            false,
+        )
+        .cast::<T>()
+            * (one / (one - options.dropout));
+
+        mask = arrayfire::mul(&mask, &arrayfire::lookup(&keep, WColIdx, 0), false);
+    }
+
+    if options.dropconnect > zero {
+        let keep = arrayfire::ge(
+            &arrayfire::random_uniform::<T>(W_dims, &engine),
+            &options.dropconnect,
+            false,
+        )
+        .cast::<T>()
+            * (one / (one - options.dropconnect));
+
+        mask = arrayfire::mul(&mask, &keep, false);
+    }
+
+    Some(mask)
+}
+
+/*
+Multiplies the weights W by a mask from create_drop_mask
+
+Used on the network parameters before state_space_forward_batch and on the gradient
+after state_space_backward_group2
+
+Inputs
+mask:                Dropout and dropconnect mask
+Wseqs:               Location of W in arr
+
+Outputs:
+arr:                 Network parameters or gradient with masked weights
+
+*/
+pub fn apply_drop_mask<T: raybnn_float_trait>(
+    mask: &arrayfire::Array<T>,
+    Wseqs: &[arrayfire::Seq<i32>; 1],
+
+    arr: &mut arrayfire::Array<T>,
+) {
+    if seqs_size(Wseqs) <= 0 {
+        return;
+    }
+
+    let W = arrayfire::index(arr, Wseqs);
+    arrayfire::assign_seq(arr, Wseqs, &arrayfire::mul(&W, mask, false));
+}
@@ -0,0 +1,457 @@
+extern crate arrayfire;
+
+use serde::{Deserialize, Serialize};
+
+use crate::precision::float_trait::raybnn_float_trait;
+
+use crate::error::Error;
+
+/*
+Learning rate schedules
+
+A scheduler returns a factor for every epoch, which multiplies the learning rate chosen by
+the training loop (alpha_max_vec and BTLS). Schedules are described by lr_schedule_type,
+which can be serialized with the training options, and turned into a scheduler with
+create_lr_scheduler. COMPOSE multiplies the factors of several schedules, for example
+WARMUP followed by COSINE_RESTARTS.
+
+CONSTANT:            Factor is always 1
+WARMUP:              Factor rises linearly from 1/warmup_epoch to 1 over warmup_epoch epochs
+STEP_DECAY:          Factor is multiplied by gamma every step_epoch epochs
+EXPONENTIAL:         Factor is multiplied by gamma every epoch
+ONE_CYCLE:           Factor rises from 1/div_factor to 1 during the first pct_start of
+                     total_epoch, then falls to 1/(div_factor*final_div_factor)
+COSINE_RESTARTS:     Cosine from 1 to min_factor over period_epoch epochs, restarting with
+                     the period multiplied by period_mult
+COSINE_ANNEALING:    Factor is 4*|cos(2*pi*epoch/period_epoch)| once the smoothed loss has not
+                     improved by 5% for period_epoch epochs, otherwise 1. This is
+                     lr_strategy_type::COSINE_ANNEALING of train_network
+REDUCE_ON_PLATEAU:   Factor is multiplied by factor when the loss has not improved by
+                     threshold (relative) for patience epochs, down to min_factor
+COMPOSE:             Product of the factors of the schedules
+
+*/
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: raybnn_float_trait",
+    deserialize = "T: raybnn_float_trait"
+))]
+pub enum lr_schedule_type<T: raybnn_float_trait> {
+    CONSTANT,
+    WARMUP {
+        warmup_epoch: u64,
+    },
+    STEP_DECAY {
+        step_epoch: u64,
+        gamma: T,
+    },
+    EXPONENTIAL {
+        gamma: T,
+    },
+    ONE_CYCLE {
+        total_epoch: u64,
+        pct_start: T,
+        div_factor: T,
+        final_div_factor: T,
+    },
+    COSINE_RESTARTS {
+        period_epoch: u64,
+        period_mult: u64,
+        min_factor: T,
+    },
+    COSINE_ANNEALING {
+        period_epoch: u64,
+    },
+    REDUCE_ON_PLATEAU {
+        patience: u64,
+        factor: T,
+        threshold: T,
+        min_factor: T,
+    },
+    COMPOSE(Vec<lr_schedule_type<T>>),
+}
+
+pub trait lr_scheduler_trait<T: raybnn_float_trait> {
+    // Learning rate factor of the epoch. loss is the smoothed training loss
+    fn factor(&mut self, epoch: u64, loss: T) -> T;
+
+    // Scheduler state, saved in trainer checkpoints
+    fn state(&self) -> Vec<T>;
+
+    // Restores the state from the start of state and returns the number of values used
+    fn set_state(&mut self, state: &[T]) -> Result<usize, Error>;
+}
+
+/*
+Creates the scheduler of a schedule
+
+Inputs
+schedule:            Learning rate schedule
+
+Outputs:
+scheduler:           Scheduler at epoch 0
+
+*/
+pub fn create_lr_scheduler<T: raybnn_float_trait>(
+    schedule: &lr_schedule_type<T>,
+) -> Box<dyn lr_scheduler_trait<T>> {
+    match schedule {
+        lr_schedule_type::CONSTANT => Box::new(constant_scheduler_type {}),
+        lr_schedule_type::WARMUP { warmup_epoch } => Box::new(warmup_scheduler_type {
+            warmup_epoch: *warmup_epoch,
+        }),
+        lr_schedule_type::STEP_DECAY { step_epoch, gamma } => Box::new(step_decay_scheduler_type {
+            step_epoch: *step_epoch,
+            gamma: *gamma,
+        }),
+        lr_schedule_type::EXPONENTIAL { gamma } => {
+            Box::new(exponential_scheduler_type { gamma: *gamma })
+        }
+        lr_schedule_type::ONE_CYCLE {
+            total_epoch,
+            pct_start,
+            div_factor,
+            final_div_factor,
+        } => Box::new(one_cycle_scheduler_type {
+            total_epoch: *total_epoch,
+            pct_start: *pct_start,
+            div_factor: *div_factor,
+            final_div_factor: *final_div_factor,
+        }),
+        lr_schedule_type::COSINE_RESTARTS {
+            period_epoch,
+            period_mult,
+            min_factor,
+        } => Box::new(cosine_restarts_scheduler_type {
+            period_epoch: *period_epoch,
+            period_mult: *period_mult,
+            min_factor: *min_factor,
+        }),
+        lr_schedule_type::COSINE_ANNEALING { period_epoch } => {
+            Box::new(cosine_annealing_scheduler_type::new(*period_epoch))
+        }
+        lr_schedule_type::REDUCE_ON_PLATEAU {
+            patience,
+            factor,
+            threshold,
+            min_factor,
+        } => Box::new(plateau_scheduler_type::new(
+            *patience,
+            *factor,
+            *threshold,
+            *min_factor,
+        )),
+        lr_schedule_type::COMPOSE(schedules) => Box::new(composed_scheduler_type {
+            schedulers: schedules.iter().map(create_lr_scheduler).collect(),
+        }),
+    }
+}
+
+// Cosine interpolation from start to end, with t from 0 to 1
+fn cosine_interp<T: raybnn_float_trait>(start: T, end: T, t: T) -> T {
+    let half = T::from_f64(0.5);
+    let pi = T::from_f64(std::f64::consts::PI);
+
+    end + (start - end) * half * (T::one() + (pi * t).cos())
+}
+
+pub struct constant_scheduler_type {}
+
+impl<T: raybnn_float_trait> lr_scheduler_trait<T> for constant_scheduler_type {
+    fn factor(&mut self, _epoch: u64, _loss: T) -> T {
+        T::one()
+    }
+
+    fn state(&self) -> Vec<T> {
+        Vec::new()
+    }
+
+    fn set_state(&mut self, _state: &[T]) -> Result<usize, Error> {
+        Ok(0)
+    }
+}
+
+pub struct warmup_scheduler_type {
+    pub warmup_epoch: u64,
+}
+
+impl<T: raybnn_float_trait> lr_scheduler_trait<T> for warmup_scheduler_type {
+    fn factor(&mut self, epoch: u64, _loss: T) -> T {
+        if epoch >= self.warmup_epoch {
+            return T::one();
+        }
+
+        T::from_f64(((epoch + 1) as f64) / (self.warmup_epoch as f64))
+    }
+
+    fn state(&self) -> Vec<T> {
+        Vec::new()
+    }
+
+    fn set_state(&mut self, _state: &[T]) -> Result<usize, Error> {
+        Ok(0)
+    }
+}
+
+pub struct step_decay_scheduler_type<T: raybnn_float_trait> {
+    pub step_epoch: u64,
+    pub gamma: T,
+}
+
+impl<T: raybnn_float_trait> lr_scheduler_trait<T> for step_decay_scheduler_type<T> {
+    fn factor(&mut self, epoch: u64, _loss: T) -> T {
+        let steps = epoch / self.step_epoch.max(1);
+
+        self.gamma.powi(steps as i32)
+    }
+
+    fn state(&self) -> Vec<T> {
+        Vec::new()
+    }
+
+    fn set_state(&mut self, _state: &[T]) -> Result<usize, Error> {
+        Ok(0)
+    }
+}
+
+pub struct exponential_scheduler_type<T: raybnn_float_trait> {
+    pub gamma: T,
+}
+
+impl<T: raybnn_float_trait> lr_scheduler_trait<T> for exponential_scheduler_type<T> {
+    fn factor(&mut self, epoch: u64, _loss: T) -> T {
+        self.gamma.powf(T::from_f64(epoch as f64))
+    }
+
+    fn state(&self) -> Vec<T> {
+        Vec::new()
+    }
+
+    fn set_state(&mut self, _state: &[T]) -> Result<usize, Error> {
+        Ok(0)
+    }
+}
+
+pub struct one_cycle_scheduler_type<T: raybnn_float_trait> {
+    pub total_epoch: u64,
+    pub pct_start: T,
+    pub div_factor: T,
+    pub final_div_factor: T,
+}
+
+impl<T: raybnn_float_trait> lr_scheduler_trait<T> for one_cycle_scheduler_type<T> {
+    fn factor(&mut self, epoch: u64, _loss: T) -> T {
+        let one = T::one();
+        let start_factor = one / self.div_factor;
+        let end_factor = start_factor / self.final_div_factor;
+
+        let total = T::from_f64(self.total_epoch.max(1) as f64);
+        let warm = (self.pct_start * total).max(one);
+        let cur = T::from_f64(epoch as f64);
+
+        if cur >= total {
+            end_factor
+        } else if cur < warm {
+            cosine_interp(start_factor, one, cur / warm)
+        } else {
+            let rest = (total - warm).max(one);
+            cosine_interp(one, end_factor, (cur - warm) / rest)
+        }
+    }
+
+    fn state(&self) -> Vec<T> {
+        Vec::new()
+    }
+
+    fn set_state(&mut self, _state: &[T]) -> Result<usize, Error> {
+        Ok(0)
+    }
+}
+
+pub struct cosine_restarts_scheduler_type<T: raybnn_float_trait> {
+    pub period_epoch: u64,
+    pub period_mult: u64,
+    pub min_factor: T,
+}
+
+impl<T: raybnn_float_trait> lr_scheduler_trait<T> for cosine_restarts_scheduler_type<T> {
+    fn factor(&mut self, epoch: u64, _loss: T) -> T {
+        let mut period = self.period_epoch.max(1);
+        let mut start = 0;
+
+        // Find the period that contains the epoch
+        while epoch >= start + period {
+            start = start + period;
+            period = period * self.period_mult.max(1);
+        }
+
+        let t = T::from_f64(((epoch - start) as f64) / (period as f64));
+
+        cosine_interp(T::one(), self.min_factor, t)
+    }
+
+    fn state(&self) -> Vec<T> {
+        Vec::new()
+    }
+
+    fn set_state(&mut self, _state: &[T]) -> Result<usize, Error> {
+        Ok(0)
+    }
+}
+
+// Same plateau detection as the loss_counter of train_network
+pub struct cosine_annealing_scheduler_type<T: raybnn_float_trait> {
+    pub period_epoch: u64,
+
+    pub min_loss: T,
+    pub counter: u64,
+}
+
+impl<T: raybnn_float_trait> cosine_annealing_scheduler_type<T> {
+    pub fn new(period_epoch: u64) -> cosine_annealing_scheduler_type<T> {
+        cosine_annealing_scheduler_type {
+            period_epoch: period_epoch,
+
+            min_loss: T::infinity(),
+            counter: 0,
+        }
+    }
+}
+
+impl<T: raybnn_float_trait> lr_scheduler_trait<T> for cosine_annealing_scheduler_type<T> {
+    fn factor(&mut self, epoch: u64, loss: T) -> T {
+        if loss * T::from_f64(1.05) < self.min_loss {
+            self.min_loss = loss;
+            self.counter = 0;
+        } else {
+            self.counter = self.counter + 1;
+        }
+
+        if self.counter < self.period_epoch {
+            return T::one();
+        }
+
+        let two_pi = T::from_f64(2.0 * std::f64::consts::PI);
+        let t = T::from_f64(epoch as f64) / T::from_f64(self.period_epoch.max(1) as f64);
+
+        T::from_f64(4.0) * (two_pi * t).cos().abs()
+    }
+
+    fn state(&self) -> Vec<T> {
+        vec![self.min_loss, T::from_f64(self.counter as f64)]
+    }
+
+    fn set_state(&mut self, state: &[T]) -> Result<usize, Error> {
+        if state.len() < 2 {
+            return Err(Error::InvalidTrainState(format!(
+                "cosine annealing scheduler state has {} values, expected 2",
+                state.len()
+            )));
+        }
+
+        self.min_loss = state[0];
+        self.counter = state[1].to_u64().unwrap_or(0);
+
+        Ok(2)
+    }
+}
+
+// Plateau detection with a relative threshold
+pub struct plateau_scheduler_type<T: raybnn_float_trait> {
+    pub patience: u64,
+    pub factor: T,
+    pub threshold: T,
+    pub min_factor: T,
+
+    pub best_loss: T,
+    pub counter: u64,
+    pub cur_factor: T,
+}
+
+impl<T: raybnn_float_trait> plateau_scheduler_type<T> {
+    pub fn new(patience: u64, factor: T, threshold: T, min_factor: T) -> plateau_scheduler_type<T> {
+        plateau_scheduler_type {
+            patience: patience,
+            factor: factor,
+            threshold: threshold,
+            min_factor: min_factor,
+
+            best_loss: T::infinity(),
+            counter: 0,
+            cur_factor: T::one(),
+        }
+    }
+}
+
+impl<T: raybnn_float_trait> lr_scheduler_trait<T> for plateau_scheduler_type<T> {
+    fn factor(&mut self, _epoch: u64, loss: T) -> T {
+        if loss < self.best_loss * (T::one() - self.threshold) {
+            self.best_loss = loss;
+            self.counter = 0;
+        } else {
+            self.counter = self.counter + 1;
+        }
+
+        if self.counter > self.patience {
+            self.cur_factor = (self.cur_factor * self.factor).max(self.min_factor);
+            self.counter = 0;
+        }
+
+        self.cur_factor
+    }
+
+    fn state(&self) -> Vec<T> {
+        vec![
+            self.best_loss,
+            T::from_f64(self.counter as f64),
+            self.cur_factor,
+        ]
+    }
+
+    fn set_state(&mut self, state: &[T]) -> Result<usize, Error> {
+        if state.len() < 3 {
+            return Err(Error::InvalidTrainState(format!(
+                "plateau scheduler state has {} values, expected 3",
+                state.len()
+            )));
+        }
+
+        self.best_loss = state[0];
+        self.counter = state[1].to_u64().unwrap_or(0);
+        self.cur_factor = state[2];
+
+        Ok(3)
+    }
+}
+
+// Product of the factors of several schedulers
+pub struct composed_scheduler_type<T: raybnn_float_trait> {
+    pub schedulers: Vec<Box<dyn lr_scheduler_trait<T>>>,
+}
+
+impl<T: raybnn_float_trait> lr_scheduler_trait<T> for composed_scheduler_type<T> {
+    fn factor(&mut self, epoch: u64, loss: T) -> T {
+        let mut out = T::one();
+        for scheduler in self.schedulers.iter_mut() {
+            out = out * scheduler.factor(epoch, loss);
+        }
+        out
+    }
+
+    fn state(&self) -> Vec<T> {
+        let mut out: Vec<T> = Vec::new();
+        for scheduler in self.schedulers.iter() {
+            out.extend(scheduler.state());
+        }
+        out
+    }
+
+    fn set_state(&mut self, state: &[T]) -> Result<usize, Error> {
+        let mut used = 0;
+        for scheduler in self.schedulers.iter_mut() {
+            used = used + scheduler.set_state(&state[used..])?;
+        }
+        Ok(used)
+    }
+}
@@ -0,0 +1,166 @@
+extern crate arrayfire;
+
+use crate::precision::float_trait::raybnn_float_trait;
+
+use crate::physics::distance::vec_norm;
+
+pub fn get_outside_idx_cube<T: raybnn_float_trait>(
+    pos: &arrayfire::Array<T>,
+    cube_size: T,
+) -> arrayfire::Array<u32> {
+    let neg_cube_size = -cube_size;
+    //cube_size < pos   or    pos < neg_cube_size
+    let mut cmp1 = arrayfire::lt(&cube_size, pos, false);
+    let mut cmp2 = arrayfire::lt(pos, &neg_cube_size, false);
+    cmp1 = arrayfire::or(&cmp1, &cmp2, false);
+    cmp1 = arrayfire::any_true(&cmp1, 1);
+
+    arrayfire::locate(&cmp1)
+}
+
+pub fn get_inside_idx_cube<T: raybnn_float_trait>(
+    pos: &arrayfire::Array<T>,
+    cube_size: T,
+) -> arrayfire::Array<u32> {
+    let neg_cube_size = -cube_size;
+    //  neg_cube_size  <  pos <  cube_size
+    let mut cmp1 = arrayfire::lt(pos, &cube_size, false);
+    let mut cmp2 = arrayfire::lt(&neg_cube_size, pos, false);
+    cmp1 = arrayfire::and(&cmp1, &cmp2, false);
+    cmp1 = arrayfire::all_true(&cmp1, 1);
+
+    arrayfire::locate(&cmp1)
+}
+
+pub fn replicate_struct<T: raybnn_float_trait>(
+    position: &arrayfire::Array<T>,
+    direction: &arrayfire::Array<T>,
+    num: u64,
+) -> arrayfire::Array<T> {
+    let repeat_dims = arrayfire::Dim4::new(&[position.dims()[0], 1, 1, 1]);
+    let tile_dims3 = arrayfire::Dim4::new(&[1, num, 1, 1]);
+
+    let mut count = arrayfire::iota::<T>(tile_dims3, repeat_dims);
+
+    count = arrayfire::flat(&count);
+
+    let tile_dims = arrayfire::Dim4::new(&[num, 1, 1, 1]);
+
+    let mut out_position = arrayfire::tile(position, tile_dims);
+
+    let tile_dims2 = arrayfire::Dim4::new(&[out_position.dims()[0], 1, 1, 1]);
+
+    let mut dir_tile = arrayfire::tile(direction, tile_dims2);
+
+    dir_tile = arrayfire::mul(&dir_tile, &count, true);
+
+    out_position = dir_tile + out_position;
+
+    out_position
+}
+
+pub fn NDsphere_from_NDcube<T: raybnn_float_trait>(
+    cube_pos: &arrayfire::Array<T>,
+    cube_radius: T,
+
+    sphere_radius: T,
+) -> arrayfire::Array<T> {
+    let epsilon = T::from_f64(0.0001);
+    let one = T::one();
+    let two = T::from_f64(2.0);
+    let mut sphere_pos = cube_pos.clone();
+
+    let space_dims = cube_pos.dims()[1];
+
+    let dir_template: Vec<T> = vec![T::zero(); space_dims as usize];
+
+    let repnum = (sphere_radius / cube_radius).ceil().to_u64().unwrap();
+
+    let dir_dims = arrayfire::Dim4::new(&[1, space_dims, 1, 1]);
+
+    for i in 0..(space_dims as usize) {
+        let mut direction_cpu: Vec<T> = dir_template.clone();
+        direction_cpu[i] = two * cube_radius;
+
+        let direction = arrayfire::Array::new(&direction_cpu, dir_dims);
+
+        sphere_pos = replicate_struct(&sphere_pos, &direction, repnum);
+    }
+
+    let shift_cpu: Vec<T> =
+        vec![-(T::from_f64(repnum as f64) - one) * cube_radius; space_dims as usize];
+    let shift = arrayfire::Array::new(&shift_cpu, dir_dims);
+
+    sphere_pos = arrayfire::add(&shift, &sphere_pos, true);
+
+    let eps = arrayfire::randn::<T>(sphere_pos.dims()) * epsilon;
+    sphere_pos = sphere_pos + eps;
+
+    let sq = vec_norm(&sphere_pos);
+
+    let cmp1 = arrayfire::lt(&sq, &sphere_radius, false);
+
+    let idx1 = arrayfire::locate(&cmp1);
+
+    let mut idxrs1 = arrayfire::Indexer::default();
+    let seq1 = arrayfire::Seq::new(0.0, (space_dims - 1) as f32, 1.0);
+    idxrs1.set_index(&idx1, 0, None);
+    idxrs1.set_index(&seq1, 1, Some(false));
+    sphere_pos = arrayfire::index_gen(&sphere_pos, idxrs1);
+
+    sphere_pos
+}
+
+pub fn plane_surface_on_NDsphere<T: raybnn_float_trait>(
+    plane_shape: &Vec<u64>,
+
+    sphere_radius: T,
+) -> arrayfire::Array<T> {
+    let two = T::from_f64(2.0);
+    let space_dims = (plane_shape.len() + 1) as u64;
+
+    let mut cur_dim = 0;
+    let mut cur_size = plane_shape[cur_dim];
+
+    let plane_dims = arrayfire::Dim4::new(&[cur_size, space_dims, 1, 1]);
+    let mut plane = arrayfire::constant::<T>(T::zero(), plane_dims);
+
+    let single = arrayfire::Dim4::new(&[1, 1, 1, 1]);
+    let across_dims = arrayfire::Dim4::new(&[cur_size, 1, 1, 1]);
+
+    let mut newplane = arrayfire::iota::<T>(across_dims, single);
+
+    newplane = newplane * ((two * sphere_radius) / T::from_f64(cur_size as f64));
+
+    arrayfire::set_col(&mut plane, &newplane, 0);
+
+    let dir_template: Vec<T> = vec![T::zero(); space_dims as usize];
+
+    let dir_dims = arrayfire::Dim4::new(&[1, space_dims, 1, 1]);
+
+    cur_dim = cur_dim + 1;
+
+    while cur_dim < ((space_dims - 1) as usize) {
+        cur_size = plane_shape[cur_dim];
+
+        let mut direction_cpu: Vec<T> = dir_template.clone();
+        direction_cpu[cur_dim] = ((two * sphere_radius) / T::from_f64(cur_size as f64));
+
+        let direction = arrayfire::Array::new(&direction_cpu, dir_dims);
+
+        plane = replicate_struct(&plane, &direction, cur_size);
+
+        cur_dim = cur_dim + 1;
+    }
+
+    let shift_cpu: Vec<T> = vec![-sphere_radius; space_dims as usize];
+    let shift = arrayfire::Array::new(&shift_cpu, dir_dims);
+
+    plane = arrayfire::add(&shift, &plane, true);
+
+    let magsq = vec_norm(&plane);
+
+    plane = arrayfire::div(&plane, &magsq, true) * sphere_radius;
+
+    plane
+}
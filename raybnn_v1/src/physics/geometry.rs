@@ -0,0 +1,694 @@
+extern crate arrayfire;
+
+use rayon::prelude::*;
+
+use crate::neural::network::network_metadata_type;
+
+use crate::physics::dynamic::NEURON_RAD_FACTOR;
+use crate::physics::raytrace_grid::create_raytrace_grid;
+
+use crate::precision::float_trait::raybnn_float_trait;
+use crate::precision::reduce::sum;
+
+/*
+Neuron placement geometries
+
+physics::initial_f32::sphere_cell_collision_minibatch grows the network inside a sphere.
+A geometry describes any other volume by its signed distance function (sdf), negative inside,
+zero on the boundary and positive outside, and a bounding box containing the volume.
+
+ellipsoid_type:      Ellipsoid with a radius along every dimension
+cylinder_type:       Cylinder along one axis
+torus_type:          3D torus around the z axis
+slab_type:           Box split into layers along one axis, separated by gaps
+sdf_geometry_type:   Any volume given by a signed distance closure
+
+geometry_cell_collision places the neurons and glial cells inside a geometry.
+surface_neurons places input or output neurons on a region of the boundary, and
+project_to_surface moves given positions, e.g. an EEG electrode layout, onto the boundary.
+
+*/
+
+// Number of random positions per rejection sampling round
+const SAMPLE_BATCH: u64 = 100000;
+const MAX_SAMPLE_ROUNDS: u64 = 1000;
+
+// Newton steps of project_to_level
+const PROJECT_STEPS: u64 = 30;
+
+pub trait geometry_trait<T: raybnn_float_trait> {
+    /*
+    Signed distance to the boundary
+
+    Inputs
+    pos:                 Positions, dims (pos_num, space_dims)
+
+    Outputs:
+    dist:                Signed distance of each position, dims (pos_num, 1). Negative inside
+
+    */
+    fn sdf(&self, pos: &arrayfire::Array<T>) -> arrayfire::Array<T>;
+
+    // Lowest and highest corners of a box containing the volume
+    fn bounding_box(&self) -> (Vec<T>, Vec<T>);
+
+    fn space_dims(&self) -> u64 {
+        self.bounding_box().0.len() as u64
+    }
+}
+
+// Row vector, dims (1, values.len())
+fn row_vec<T: raybnn_float_trait>(values: &[T]) -> arrayfire::Array<T> {
+    arrayfire::Array::new(
+        values,
+        arrayfire::Dim4::new(&[1, values.len() as u64, 1, 1]),
+    )
+}
+
+fn norm_rows<T: raybnn_float_trait>(pos: &arrayfire::Array<T>) -> arrayfire::Array<T> {
+    let sq = arrayfire::mul(pos, pos, false);
+    arrayfire::sqrt(&sum(&sq, 1))
+}
+
+// Signed distance of the intersection of two volumes with perpendicular boundaries
+fn intersect_sdf<T: raybnn_float_trait>(
+    a: &arrayfire::Array<T>,
+    b: &arrayfire::Array<T>,
+) -> arrayfire::Array<T> {
+    let zero = T::zero();
+    let inf = T::infinity();
+
+    let a_out = arrayfire::clamp(a, &zero, &inf, false);
+    let b_out = arrayfire::clamp(b, &zero, &inf, false);
+    let outside = arrayfire::sqrt(
+        &(arrayfire::mul(&a_out, &a_out, false) + arrayfire::mul(&b_out, &b_out, false)),
+    );
+
+    let inside = arrayfire::clamp(&arrayfire::maxof(a, b, false), &(-inf), &zero, false);
+
+    outside + inside
+}
+
+/*
+Ellipsoid
+
+center:              Center of the ellipsoid
+radii:               Radius along every dimension. Equal radii give a sphere
+
+*/
+pub struct ellipsoid_type<T: raybnn_float_trait> {
+    pub center: Vec<T>,
+    pub radii: Vec<T>,
+}
+
+impl<T: raybnn_float_trait> geometry_trait<T> for ellipsoid_type<T> {
+    // Scaled distance to the unit sphere, exact for spheres
+    fn sdf(&self, pos: &arrayfire::Array<T>) -> arrayfire::Array<T> {
+        let rel = arrayfire::sub(pos, &row_vec(&self.center), true);
+        let scaled = arrayfire::div(&rel, &row_vec(&self.radii), true);
+
+        let min_rad = self.radii.iter().fold(T::infinity(), |a, &b| a.min(b));
+
+        (norm_rows(&scaled) - T::one()) * min_rad
+    }
+
+    fn bounding_box(&self) -> (Vec<T>, Vec<T>) {
+        let lo = (0..self.center.len())
+            .map(|d| self.center[d] - self.radii[d])
+            .collect();
+        let hi = (0..self.center.len())
+            .map(|d| self.center[d] + self.radii[d])
+            .collect();
+        (lo, hi)
+    }
+}
+
+/*
+Cylinder
+
+center:              Center of the cylinder
+radius:              Radius around the axis
+half_length:         Half of the length along the axis
+axis:                Dimension of the axis
+
+*/
+pub struct cylinder_type<T: raybnn_float_trait> {
+    pub center: Vec<T>,
+    pub radius: T,
+    pub half_length: T,
+    pub axis: u64,
+}
+
+impl<T: raybnn_float_trait> geometry_trait<T> for cylinder_type<T> {
+    fn sdf(&self, pos: &arrayfire::Array<T>) -> arrayfire::Array<T> {
+        let rel = arrayfire::sub(pos, &row_vec(&self.center), true);
+
+        let axial = arrayfire::abs(&arrayfire::col(&rel, self.axis as i64)) - self.half_length;
+
+        let mut mask = vec![T::one(); self.center.len()];
+        mask[self.axis as usize] = T::zero();
+        let radial_pos = arrayfire::mul(&rel, &row_vec(&mask), true);
+        let radial = norm_rows(&radial_pos) - self.radius;
+
+        intersect_sdf(&radial, &axial)
+    }
+
+    fn bounding_box(&self) -> (Vec<T>, Vec<T>) {
+        let extent = |d: usize| {
+            if d == (self.axis as usize) {
+                self.half_length
+            } else {
+                self.radius
+            }
+        };
+
+        let lo = (0..self.center.len())
+            .map(|d| self.center[d] - extent(d))
+            .collect();
+        let hi = (0..self.center.len())
+            .map(|d| self.center[d] + extent(d))
+            .collect();
+        (lo, hi)
+    }
+}
+
+/*
+3D torus around the z axis
+
+center:              Center of the torus
+major_rad:           Distance from the center to the center of the tube
+minor_rad:           Radius of the tube
+
+*/
+pub struct torus_type<T: raybnn_float_trait> {
+    pub center: Vec<T>,
+    pub major_rad: T,
+    pub minor_rad: T,
+}
+
+impl<T: raybnn_float_trait> geometry_trait<T> for torus_type<T> {
+    fn sdf(&self, pos: &arrayfire::Array<T>) -> arrayfire::Array<T> {
+        let rel = arrayfire::sub(pos, &row_vec(&self.center), true);
+
+        let planar = norm_rows(&arrayfire::cols(&rel, 0, 1)) - self.major_rad;
+        let height = arrayfire::col(&rel, 2);
+
+        let tube = arrayfire::join(1, &planar, &height);
+
+        norm_rows(&tube) - self.minor_rad
+    }
+
+    fn bounding_box(&self) -> (Vec<T>, Vec<T>) {
+        let outer = self.major_rad + self.minor_rad;
+        let extent = [outer, outer, self.minor_rad];
+
+        let lo = (0..3).map(|d| self.center[d] - extent[d]).collect();
+        let hi = (0..3).map(|d| self.center[d] + extent[d]).collect();
+        (lo, hi)
+    }
+}
+
+/*
+Layered slabs. The box from lo to hi is split into layer_num layers of equal thickness along
+axis, with gap between neighbouring layers
+
+lo:                  Lowest corner of the box
+hi:                  Highest corner of the box
+axis:                Dimension across the layers
+layer_num:           Number of layers
+gap:                 Distance between neighbouring layers
+
+*/
+pub struct slab_type<T: raybnn_float_trait> {
+    pub lo: Vec<T>,
+    pub hi: Vec<T>,
+    pub axis: u64,
+    pub layer_num: u64,
+    pub gap: T,
+}
+
+impl<T: raybnn_float_trait> geometry_trait<T> for slab_type<T> {
+    fn sdf(&self, pos: &arrayfire::Array<T>) -> arrayfire::Array<T> {
+        let two = T::from_f64(2.0);
+        let axis = self.axis as usize;
+        let layer_num = self.layer_num.max(1);
+
+        //Box around the other dimensions
+        let mut dist = arrayfire::constant::<T>(
+            T::neg_infinity(),
+            arrayfire::Dim4::new(&[pos.dims()[0], 1, 1, 1]),
+        );
+        for d in 0..self.lo.len() {
+            if d == axis {
+                continue;
+            }
+
+            let mid = (self.lo[d] + self.hi[d]) / two;
+            let half = (self.hi[d] - self.lo[d]) / two;
+            let side = arrayfire::abs(&(arrayfire::col(pos, d as i64) - mid)) - half;
+            dist = arrayfire::maxof(&dist, &side, false);
+        }
+
+        //Nearest layer along the axis
+        let gap_num = T::from_f64((layer_num - 1) as f64);
+        let thickness = ((self.hi[axis] - self.lo[axis]) - (self.gap * gap_num))
+            / T::from_f64(layer_num as f64);
+        let period = thickness + self.gap;
+
+        let along = arrayfire::col(pos, axis as i64) - self.lo[axis];
+        let mut layer = arrayfire::floor(&(along.clone() / period));
+        layer = arrayfire::clamp(&layer, &T::zero(), &gap_num, false);
+
+        let layer_mid = (layer * period) + (thickness / two);
+        let across = arrayfire::abs(&(along - layer_mid)) - (thickness / two);
+
+        arrayfire::maxof(&dist, &across, false)
+    }
+
+    fn bounding_box(&self) -> (Vec<T>, Vec<T>) {
+        (self.lo.clone(), self.hi.clone())
+    }
+}
+
+/*
+Volume given by a signed distance closure
+
+sdf:                 Signed distance, dims (pos_num, 1) for positions (pos_num, space_dims).
+                     Negative inside
+lo:                  Lowest corner of a box containing the volume
+hi:                  Highest corner of a box containing the volume
+
+*/
+pub struct sdf_geometry_type<T: raybnn_float_trait> {
+    pub sdf: Box<dyn Fn(&arrayfire::Array<T>) -> arrayfire::Array<T> + Send + Sync>,
+    pub lo: Vec<T>,
+    pub hi: Vec<T>,
+}
+
+impl<T: raybnn_float_trait> geometry_trait<T> for sdf_geometry_type<T> {
+    fn sdf(&self, pos: &arrayfire::Array<T>) -> arrayfire::Array<T> {
+        (self.sdf)(pos)
+    }
+
+    fn bounding_box(&self) -> (Vec<T>, Vec<T>) {
+        (self.lo.clone(), self.hi.clone())
+    }
+}
+
+// Uniform random positions in the box from lo to hi
+fn random_in_box<T: raybnn_float_trait>(lo: &[T], hi: &[T], pos_num: u64) -> arrayfire::Array<T> {
+    let extent: Vec<T> = (0..lo.len()).map(|d| hi[d] - lo[d]).collect();
+
+    let pos = arrayfire::randu::<T>(arrayfire::Dim4::new(&[pos_num, lo.len() as u64, 1, 1]));
+    let pos = arrayfire::mul(&pos, &row_vec(&extent), true);
+
+    arrayfire::add(&pos, &row_vec(lo), true)
+}
+
+/*
+Uniform random positions inside a geometry, at least margin away from the boundary
+
+Inputs
+geometry:            Volume
+margin:              Minimum distance to the boundary
+pos_num:             Number of positions
+
+Outputs:
+pos:                 Positions, dims (pos_num, space_dims). Fewer positions when the volume
+                     is too small to find pos_num positions with rejection sampling
+
+*/
+pub fn sample_inside<T: raybnn_float_trait>(
+    geometry: &dyn geometry_trait<T>,
+    margin: T,
+    pos_num: u64,
+) -> arrayfire::Array<T> {
+    let (lo, hi) = geometry.bounding_box();
+    let space_dims = lo.len() as u64;
+
+    let mut pos = arrayfire::constant::<T>(T::zero(), arrayfire::Dim4::new(&[0, space_dims, 1, 1]));
+
+    for _ in 0..MAX_SAMPLE_ROUNDS {
+        if pos.dims()[0] >= pos_num {
+            break;
+        }
+
+        let candidate = random_in_box(&lo, &hi, SAMPLE_BATCH.max(2 * pos_num));
+        let inside = arrayfire::le(&geometry.sdf(&candidate), &(-margin), false);
+        let idx = arrayfire::locate(&inside);
+
+        if idx.elements() == 0 {
+            continue;
+        }
+
+        let candidate = arrayfire::lookup(&candidate, &idx, 0);
+        pos = if pos.dims()[0] == 0 {
+            candidate
+        } else {
+            arrayfire::join(0, &pos, &candidate)
+        };
+    }
+
+    if pos.dims()[0] > pos_num {
+        pos = arrayfire::rows(&pos, 0, (pos_num as i64) - 1);
+    }
+
+    pos
+}
+
+/*
+Rows of the cells that do not overlap any other cell. Same collision test as
+physics::initial_f32::select_non_overlap, using a uniform grid instead of the distance matrix
+
+Inputs
+pos:                 Cell positions, dims (pos_num, space_dims)
+neuron_rad:          Cell radius
+
+Outputs:
+idx:                 Rows of pos without collisions
+
+*/
+pub fn non_overlap_idx<T: raybnn_float_trait>(
+    pos: &arrayfire::Array<T>,
+    neuron_rad: T,
+) -> arrayfire::Array<u32> {
+    let pos_num = pos.dims()[0] as usize;
+    let space_dims = pos.dims()[1] as usize;
+
+    let neuron_sq = T::from_f64(4.0) * neuron_rad * neuron_rad * T::from_f64(NEURON_RAD_FACTOR);
+    let search_rad = neuron_sq.sqrt();
+
+    let grid = create_raytrace_grid(pos, search_rad);
+
+    let keep: Vec<u32> = (0..pos_num)
+        .into_par_iter()
+        .filter(|&i| {
+            let lo: Vec<T> = (0..space_dims)
+                .map(|d| grid.get_pos(i, d) - search_rad)
+                .collect();
+            let hi: Vec<T> = (0..space_dims)
+                .map(|d| grid.get_pos(i, d) + search_rad)
+                .collect();
+
+            let mut collided = false;
+            grid.visit_box(&lo, &hi, |j| {
+                if j == i {
+                    return true;
+                }
+
+                let mut magsq = T::zero();
+                for d in 0..space_dims {
+                    let diff = grid.get_pos(j, d) - grid.get_pos(i, d);
+                    magsq = magsq + diff * diff;
+                }
+
+                collided = magsq < neuron_sq;
+                !collided
+            });
+
+            !collided
+        })
+        .map(|i| i as u32)
+        .collect();
+
+    arrayfire::Array::new(&keep, arrayfire::Dim4::new(&[keep.len() as u64, 1, 1, 1]))
+}
+
+/*
+Generates the neurons and glial cells inside a geometry and deletes collided cells.
+Same as physics::initial_f32::sphere_cell_collision_minibatch with the sphere replaced by
+the geometry
+
+Inputs
+netdata:             Neuron radius, number of neurons and glial cells to be created (2*active_size)
+                     and ratio of neurons (nratio)
+geometry:            Volume of the network
+
+Outputs:
+glia_pos:            Positions of the glial cells
+neuron_pos:          Positions of the neurons
+
+*/
+pub fn geometry_cell_collision<T: raybnn_float_trait>(
+    netdata: &network_metadata_type<T>,
+    geometry: &dyn geometry_trait<T>,
+    glia_pos: &mut arrayfire::Array<T>,
+    neuron_pos: &mut arrayfire::Array<T>,
+) {
+    let active_size: u64 = netdata.active_size.clone();
+    let nratio: T = netdata.nratio.clone();
+    let neuron_rad: T = netdata.neuron_rad.clone();
+
+    let empty_dims = arrayfire::Dim4::new(&[0, geometry.space_dims(), 1, 1]);
+
+    let mut total_obj = sample_inside(geometry, neuron_rad, 2 * active_size);
+
+    if total_obj.dims()[0] > 0 {
+        let idx = non_overlap_idx(&total_obj, neuron_rad);
+        total_obj = if idx.elements() == 0 {
+            arrayfire::constant::<T>(T::zero(), empty_dims)
+        } else {
+            arrayfire::lookup(&total_obj, &idx, 0)
+        };
+    }
+
+    let total_obj_size = total_obj.dims()[0];
+
+    let split_idx = (T::from_f64(total_obj_size as f64) * nratio)
+        .to_u64()
+        .unwrap_or(0)
+        .min(total_obj_size);
+
+    //arrayfire::rows with last = -1 returns every row, so empty parts are created here
+    *neuron_pos = if split_idx == 0 {
+        arrayfire::constant::<T>(T::zero(), empty_dims)
+    } else {
+        arrayfire::rows(&total_obj, 0, (split_idx as i64) - 1)
+    };
+
+    *glia_pos = if split_idx == total_obj_size {
+        arrayfire::constant::<T>(T::zero(), empty_dims)
+    } else {
+        arrayfire::rows(&total_obj, split_idx as i64, (total_obj_size as i64) - 1)
+    };
+}
+
+/*
+Sorts the neurons by signed distance, nearest to the boundary first.
+Same order as physics::distance_f32::sort_neuron_pos_sphere for spheres
+
+*/
+pub fn sort_neuron_pos_geometry<T: raybnn_float_trait>(
+    geometry: &dyn geometry_trait<T>,
+    neuron_pos: &mut arrayfire::Array<T>,
+) {
+    let space_dims = neuron_pos.dims()[1];
+
+    let dist = geometry.sdf(neuron_pos);
+    let (_, idx1) = arrayfire::sort_index(&dist, 0, false);
+
+    let mut idxrs1 = arrayfire::Indexer::default();
+    let seq1 = arrayfire::Seq::new(0.0, (space_dims - 1) as f32, 1.0);
+    idxrs1.set_index(&idx1, 0, None);
+    idxrs1.set_index(&seq1, 1, Some(false));
+    *neuron_pos = arrayfire::index_gen(neuron_pos, idxrs1);
+}
+
+// Central difference gradient of the signed distance
+fn sdf_grad<T: raybnn_float_trait>(
+    geometry: &dyn geometry_trait<T>,
+    pos: &arrayfire::Array<T>,
+    step: T,
+) -> arrayfire::Array<T> {
+    let space_dims = pos.dims()[1] as usize;
+
+    let mut grad_cols: Vec<arrayfire::Array<T>> = Vec::new();
+    for d in 0..space_dims {
+        let mut shift = vec![T::zero(); space_dims];
+        shift[d] = step;
+        let shift = row_vec(&shift);
+
+        let forward = geometry.sdf(&arrayfire::add(pos, &shift, true));
+        let backward = geometry.sdf(&arrayfire::sub(pos, &shift, true));
+
+        grad_cols.push((forward - backward) / (step * T::from_f64(2.0)));
+    }
+
+    let grad_refs: Vec<&arrayfire::Array<T>> = grad_cols.iter().collect();
+    arrayfire::join_many(1, grad_refs)
+}
+
+/*
+Moves positions onto the level set sdf = level with Newton steps along the gradient
+
+Inputs
+geometry:            Volume
+pos:                 Positions, dims (pos_num, space_dims)
+level:               Signed distance of the target surface. 0 is the boundary, positive
+                     levels are outside
+
+Outputs:
+pos:                 Positions on the surface
+
+*/
+pub fn project_to_level<T: raybnn_float_trait>(
+    geometry: &dyn geometry_trait<T>,
+    pos: &arrayfire::Array<T>,
+    level: T,
+) -> arrayfire::Array<T> {
+    let (lo, hi) = geometry.bounding_box();
+    let diag = (0..lo.len())
+        .map(|d| (hi[d] - lo[d]) * (hi[d] - lo[d]))
+        .fold(T::zero(), |a, b| a + b)
+        .sqrt();
+    let step = diag * T::from_f64(1.0e-4);
+    let min_grad_sq = T::from_f64(1.0e-12);
+
+    let mut pos = pos.clone();
+    for _ in 0..PROJECT_STEPS {
+        let dist = geometry.sdf(&pos) - level;
+        let grad = sdf_grad(geometry, &pos, step);
+
+        let mut grad_sq = sum(&arrayfire::mul(&grad, &grad, false), 1);
+        grad_sq = arrayfire::clamp(&grad_sq, &min_grad_sq, &T::infinity(), false);
+
+        let scale = arrayfire::div(&dist, &grad_sq, false);
+        pos = pos - arrayfire::mul(&grad, &scale, true);
+    }
+
+    pos
+}
+
+/*
+Moves positions onto the boundary of a geometry. Use it to place input neurons at an
+electrode layout
+
+Inputs
+geometry:            Volume
+pos:                 Positions, dims (pos_num, space_dims)
+
+Outputs:
+pos:                 Nearest positions on the boundary
+
+*/
+pub fn project_to_surface<T: raybnn_float_trait>(
+    geometry: &dyn geometry_trait<T>,
+    pos: &arrayfire::Array<T>,
+) -> arrayfire::Array<T> {
+    project_to_level(geometry, pos, T::zero())
+}
+
+/*
+Region of the boundary
+
+ALL:                 The entire boundary
+HALF_SPACE:          Positions p with dot(p, normal) >= offset
+BOX:                 Positions inside the box from lo to hi
+
+*/
+pub enum boundary_region_type<T: raybnn_float_trait> {
+    ALL,
+    HALF_SPACE { normal: Vec<T>, offset: T },
+    BOX { lo: Vec<T>, hi: Vec<T> },
+}
+
+// Rows of pos inside the region
+fn region_idx<T: raybnn_float_trait>(
+    region: &boundary_region_type<T>,
+    pos: &arrayfire::Array<T>,
+) -> arrayfire::Array<u32> {
+    let pos_num = pos.dims()[0];
+
+    let select = match region {
+        boundary_region_type::ALL => {
+            arrayfire::constant::<bool>(true, arrayfire::Dim4::new(&[pos_num, 1, 1, 1]))
+        }
+        boundary_region_type::HALF_SPACE { normal, offset } => {
+            let dot = sum(&arrayfire::mul(pos, &row_vec(normal), true), 1);
+            arrayfire::ge(&dot, offset, false)
+        }
+        boundary_region_type::BOX { lo, hi } => {
+            let above = arrayfire::ge(pos, &row_vec(lo), true);
+            let below = arrayfire::le(pos, &row_vec(hi), true);
+            arrayfire::all_true(&arrayfire::and(&above, &below, false), 1)
+        }
+    };
+
+    arrayfire::locate(&select)
+}
+
+/*
+Evenly spaced neurons on a region of the boundary, for input and output neurons.
+Random boundary positions are selected with farthest point sampling
+
+Inputs
+geometry:            Volume
+region:              Region of the boundary
+neuron_num:          Number of neurons
+level:               Signed distance of the neurons. 0 is the boundary, create_start_archtecture
+                     places the input neurons at 2*neuron_rad outside the sphere
+
+Outputs:
+pos:                 Neuron positions, dims (neuron_num, space_dims). Fewer neurons when the
+                     region is too small
+
+*/
+pub fn surface_neurons<T: raybnn_float_trait>(
+    geometry: &dyn geometry_trait<T>,
+    region: &boundary_region_type<T>,
+    neuron_num: u64,
+    level: T,
+) -> arrayfire::Array<T> {
+    let (lo, hi) = geometry.bounding_box();
+    let space_dims = lo.len();
+
+    let empty_dims = arrayfire::Dim4::new(&[0, space_dims as u64, 1, 1]);
+    if neuron_num == 0 {
+        return arrayfire::constant::<T>(T::zero(), empty_dims);
+    }
+
+    let candidate_num = (20 * neuron_num).max(1000);
+    let mut candidate = random_in_box(&lo, &hi, candidate_num);
+    candidate = project_to_level(geometry, &candidate, level);
+
+    let idx = region_idx(region, &candidate);
+    if idx.elements() == 0 {
+        return arrayfire::constant::<T>(T::zero(), empty_dims);
+    }
+    candidate = arrayfire::lookup(&candidate, &idx, 0);
+
+    let pos_num = candidate.dims()[0] as usize;
+    let mut pos_cpu = vec![T::zero(); candidate.elements()];
+    candidate.host(&mut pos_cpu);
+
+    //Farthest point sampling
+    let mut selected: Vec<usize> = vec![0];
+    let mut min_dist = vec![T::infinity(); pos_num];
+    while (selected.len() < (neuron_num as usize)) && (selected.len() < pos_num) {
+        let last = selected[selected.len() - 1];
+
+        let mut next = 0;
+        for i in 0..pos_num {
+            let mut magsq = T::zero();
+            for d in 0..space_dims {
+                let diff = pos_cpu[d * pos_num + i] - pos_cpu[d * pos_num + last];
+                magsq = magsq + diff * diff;
+            }
+            min_dist[i] = min_dist[i].min(magsq);
+
+            if min_dist[i] > min_dist[next] {
+                next = i;
+            }
+        }
+
+        selected.push(next);
+    }
+
+    let selected: Vec<u32> = selected.iter().map(|&i| i as u32).collect();
+    let selected = arrayfire::Array::new(
+        &selected,
+        arrayfire::Dim4::new(&[selected.len() as u64, 1, 1, 1]),
+    );
+
+    arrayfire::lookup(&candidate, &selected, 0)
+}
@@ -1,3 +1,15 @@
+pub mod raytrace;
+pub mod raytrace_grid;
+pub mod neighbor;
+pub mod topology;
+pub mod geometry;
+pub mod poisson;
+pub mod construct;
+pub mod distance;
+pub mod dynamic;
+pub mod initial;
+pub mod update;
+
 pub mod construct_f64;
 pub mod distance_f64;
 pub mod dynamic_f64;
@@ -0,0 +1,184 @@
+extern crate arrayfire;
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::neural::network::network_metadata_type;
+
+use crate::physics::dynamic::NEURON_RAD_FACTOR;
+use crate::physics::dynamic::PUSH;
+use crate::physics::raytrace_grid::create_raytrace_grid_host;
+
+use crate::precision::float_trait::raybnn_float_trait;
+
+/*
+Neighbor search of the cell migration simulation in physics::dynamic_f32::run
+
+DENSE:               Builds the neuron_num+glia_num by neuron_num+glia_num distance and force
+                     matrices on the GPU every step. Memory is quadratic in the number of cells
+CELL_LIST:           Sorts the cells into a uniform grid with cells of size 2*con_rad and only
+                     computes the forces between cells in neighbouring grid cells. Memory is
+                     linear in the number of cells
+
+*/
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum neighbor_search_type {
+    #[default]
+    DENSE,
+    CELL_LIST,
+}
+
+
+
+/*
+Cell list version of physics::dynamic_f32::run. Computes the same spring_const, repel_const
+and center_const forces without the dense distance matrices
+
+Cells closer than con_rad*2 and further than neuron_rad*2 attract (neuron and glial cell) or
+repel (two neurons or two glial cells). Every cell is pulled to the center with center_const.
+The velocity going into cells closer than neuron_rad*2 is removed
+
+Inputs
+netdata:             Network metadata. Uses step_num, time_step, neuron_rad, con_rad,
+                     center_const, spring_const and repel_const
+
+Outputs:
+glia_pos:            Positions of the glial cells after step_num steps
+neuron_pos:          Positions of the neurons after step_num steps
+
+*/
+pub fn run_cell_list<T: raybnn_float_trait>(
+    netdata: &network_metadata_type<T>,
+    glia_pos: &mut arrayfire::Array<T>,
+    neuron_pos: &mut arrayfire::Array<T>,
+) {
+    let step_num: u64 = netdata.step_num.clone();
+
+    let time_step: T = netdata.time_step.clone();
+    let neuron_rad: T = netdata.neuron_rad.clone();
+    let con_rad: T = netdata.con_rad.clone();
+    let center_const: T = netdata.center_const.clone();
+    let spring_const: T = netdata.spring_const.clone();
+    let repel_const: T = netdata.repel_const.clone();
+
+    let zero = T::zero();
+    let four = T::from_f64(4.0);
+    let push = T::from_f64(PUSH);
+
+    let neuron_sq: T = four * neuron_rad * neuron_rad * T::from_f64(NEURON_RAD_FACTOR);
+    let con_sq: T = four * con_rad * con_rad;
+    let search_rad = neuron_sq.max(con_sq).sqrt();
+
+    let neuron_num = neuron_pos.dims()[0] as usize;
+    let total_obj = arrayfire::join(0, &neuron_pos, &glia_pos);
+    let total_num = total_obj.dims()[0] as usize;
+    let space_dims = total_obj.dims()[1] as usize;
+
+    if total_num == 0 {
+        return;
+    }
+
+    let mut pos = vec![zero; total_obj.elements()];
+    total_obj.host(&mut pos);
+    drop(total_obj);
+
+    for _ in 0u64..step_num {
+        let grid = create_raytrace_grid_host(pos, total_num, space_dims, search_rad);
+
+        let newvel: Vec<Vec<T>> = (0..total_num)
+            .into_par_iter()
+            .map(|i| {
+                let lo: Vec<T> = (0..space_dims)
+                    .map(|d| grid.get_pos(i, d) - search_rad)
+                    .collect();
+                let hi: Vec<T> = (0..space_dims)
+                    .map(|d| grid.get_pos(i, d) + search_rad)
+                    .collect();
+
+                let mut vel = vec![zero; space_dims];
+                let mut dist = vec![zero; space_dims];
+                let mut close: Vec<usize> = Vec::new();
+
+                //Compute attraction and repulsion
+                grid.visit_box(&lo, &hi, |j| {
+                    if j == i {
+                        return true;
+                    }
+
+                    let mut magsq = zero;
+                    for d in 0..space_dims {
+                        dist[d] = grid.get_pos(j, d) - grid.get_pos(i, d);
+                        magsq = magsq + dist[d] * dist[d];
+                    }
+
+                    if (neuron_sq < magsq) && (magsq < con_sq) {
+                        let coef = if (i < neuron_num) == (j < neuron_num) {
+                            -repel_const
+                        } else {
+                            spring_const
+                        };
+
+                        for d in 0..space_dims {
+                            vel[d] = vel[d] + (coef / magsq) * dist[d];
+                        }
+                    }
+
+                    if magsq < neuron_sq {
+                        close.push(j);
+                    }
+
+                    true
+                });
+
+                //Center velocity
+                let mut norm = zero;
+                for d in 0..space_dims {
+                    norm = norm + grid.get_pos(i, d) * grid.get_pos(i, d);
+                }
+                norm = norm.sqrt();
+                for d in 0..space_dims {
+                    vel[d] = vel[d] - center_const * (grid.get_pos(i, d) / norm);
+                }
+
+                //Delete velocity going into other objects
+                let mut proj = vec![zero; space_dims];
+                for &j in close.iter() {
+                    let mut magsq = zero;
+                    let mut dot = zero;
+                    for d in 0..space_dims {
+                        dist[d] = grid.get_pos(j, d) - grid.get_pos(i, d);
+                        magsq = magsq + dist[d] * dist[d];
+                        dot = dot + vel[d] * dist[d];
+                    }
+
+                    let scale = dot.abs() / magsq;
+                    for d in 0..space_dims {
+                        proj[d] = proj[d] + scale * dist[d];
+                    }
+                }
+
+                for d in 0..space_dims {
+                    vel[d] = vel[d] - push * proj[d];
+                }
+
+                vel
+            })
+            .collect();
+
+        pos = grid.pos;
+        for i in 0..total_num {
+            for d in 0..space_dims {
+                pos[d * total_num + i] = pos[d * total_num + i] + newvel[i][d] * time_step;
+            }
+        }
+    }
+
+    let total_obj = arrayfire::Array::new(
+        &pos,
+        arrayfire::Dim4::new(&[total_num as u64, space_dims as u64, 1, 1]),
+    );
+
+    *neuron_pos = arrayfire::rows(&total_obj, 0, (neuron_num as i64) - 1);
+
+    *glia_pos = arrayfire::rows(&total_obj, neuron_num as i64, (total_num as i64) - 1);
+}
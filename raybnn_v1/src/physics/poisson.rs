@@ -0,0 +1,330 @@
+extern crate arrayfire;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rand::SeedableRng;
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+use crate::neural::network::network_metadata_type;
+
+use crate::physics::dynamic::NEURON_RAD_FACTOR;
+
+use crate::precision::float_trait::raybnn_float_trait;
+
+/*
+Poisson-disk (blue noise) sampling of cells inside a sphere
+
+physics::initial_f32::sphere_cell_collision_minibatch generates random positions and deletes
+collided cells, so the number of cells is not known in advance. The Bridson sampler only
+accepts positions at least min_dist away from every accepted position, so exactly pos_num
+cells are placed without collisions. min_dist uses the same collision test as
+physics::initial_f32::select_non_overlap, 2*neuron_rad*sqrt(1.1)
+
+The sphere is filled with a spacing of at least min_dist, starting from the spacing of
+pos_num evenly spread cells and shrinking it until at least pos_num cells fit. pos_num of
+the cells are then chosen at random. All random numbers come from seed
+
+*/
+
+/*
+Placement of the neurons and glial cells of the starting archtecture
+
+RANDOM_COLLISION:    physics::initial_f32::sphere_cell_collision_minibatch. Random positions
+                     with the collided cells deleted
+POISSON_DISK:        poisson_disk_cell_collision. Exactly 2*active_size cells without collisions
+
+*/
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum cell_placement_type {
+    #[default]
+    RANDOM_COLLISION,
+    POISSON_DISK,
+}
+
+// Candidates around an active cell before it is retired
+const CANDIDATE_NUM: u64 = 30;
+
+// Volume fraction of the spacing balls in a filled sphere
+const FILL_FRACTION: f64 = 0.3;
+
+// Spacing reduction when the sphere is full before pos_num cells
+const SPACING_DECAY: f64 = 0.9;
+
+// Standard normal value with the Box-Muller transform
+fn normal_sample(rng: &mut StdRng) -> f64 {
+    let u1: f64 = 1.0 - rng.gen::<f64>();
+    let u2: f64 = rng.gen::<f64>();
+
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+// Uniform random direction in space_dims dimensions
+fn direction_sample(rng: &mut StdRng, space_dims: usize) -> Vec<f64> {
+    loop {
+        let dir: Vec<f64> = (0..space_dims).map(|_| normal_sample(rng)).collect();
+        let norm = dir.iter().map(|x| x * x).sum::<f64>().sqrt();
+
+        if norm > 1.0e-12 {
+            return dir.iter().map(|x| x / norm).collect();
+        }
+    }
+}
+
+fn grid_key(pos: &[f64], cell_size: f64) -> Vec<i64> {
+    pos.iter().map(|x| (x / cell_size).floor() as i64).collect()
+}
+
+// Visits the keys of the 3^space_dims grid cells around key
+fn visit_neighbor_keys(key: &[i64], mut visit: impl FnMut(&Vec<i64>) -> bool) -> bool {
+    let space_dims = key.len();
+    let mut offset = vec![-1i64; space_dims];
+
+    loop {
+        let neighbor: Vec<i64> = (0..space_dims).map(|d| key[d] + offset[d]).collect();
+        if !visit(&neighbor) {
+            return false;
+        }
+
+        let mut d = 0;
+        loop {
+            if d == space_dims {
+                return true;
+            }
+
+            offset[d] = offset[d] + 1;
+            if offset[d] <= 1 {
+                break;
+            }
+            offset[d] = -1;
+            d = d + 1;
+        }
+    }
+}
+
+/*
+Bridson sampler. Fills the ball of radius inner_rad with cells at least spacing apart
+
+Outputs:
+pos:                 Positions, one Vec per cell
+
+*/
+fn bridson_fill(
+    rng: &mut StdRng,
+    space_dims: usize,
+    inner_rad: f64,
+    spacing: f64,
+) -> Vec<Vec<f64>> {
+    let spacing_sq = spacing * spacing;
+
+    let mut pos: Vec<Vec<f64>> = Vec::new();
+    let mut grid: HashMap<Vec<i64>, Vec<usize>> = HashMap::new();
+    let mut active: Vec<usize> = Vec::new();
+
+    //First cell uniform inside the ball
+    let dir = direction_sample(rng, space_dims);
+    let rad = inner_rad * rng.gen::<f64>().powf(1.0 / (space_dims as f64));
+    let first: Vec<f64> = dir.iter().map(|x| x * rad).collect();
+
+    grid.entry(grid_key(&first, spacing)).or_default().push(0);
+    pos.push(first);
+    active.push(0);
+
+    while !active.is_empty() {
+        let active_idx = rng.gen_range(0..active.len());
+        let center = pos[active[active_idx]].clone();
+
+        let mut found = false;
+        for _ in 0..CANDIDATE_NUM {
+            //Candidate between spacing and 2*spacing away
+            let dir = direction_sample(rng, space_dims);
+            let rad = spacing * (1.0 + rng.gen::<f64>());
+            let candidate: Vec<f64> = (0..space_dims).map(|d| center[d] + dir[d] * rad).collect();
+
+            let norm_sq: f64 = candidate.iter().map(|x| x * x).sum();
+            if norm_sq > inner_rad * inner_rad {
+                continue;
+            }
+
+            let key = grid_key(&candidate, spacing);
+            let free = visit_neighbor_keys(&key, |neighbor| match grid.get(neighbor) {
+                Some(cell) => cell.iter().all(|&j| {
+                    let magsq: f64 = (0..space_dims)
+                        .map(|d| (pos[j][d] - candidate[d]) * (pos[j][d] - candidate[d]))
+                        .sum();
+                    magsq >= spacing_sq
+                }),
+                None => true,
+            });
+
+            if free {
+                let new_idx = pos.len();
+                grid.entry(key).or_default().push(new_idx);
+                pos.push(candidate);
+                active.push(new_idx);
+                found = true;
+                break;
+            }
+        }
+
+        if !found {
+            active.swap_remove(active_idx);
+        }
+    }
+
+    pos
+}
+
+/*
+Places exactly pos_num non-overlapping cells inside a sphere with Poisson-disk sampling
+
+Inputs
+space_dims:          Number of dimensions
+sphere_rad:          Radius of the sphere. Cells are fully inside the sphere
+neuron_rad:          Radius of a cell
+pos_num:             Number of cells
+seed:                Seed of the random numbers. The same seed gives the same positions
+
+Outputs:
+pos:                 Positions, dims (pos_num, space_dims). Every pair of cells is at least
+                     2*neuron_rad*sqrt(1.1) apart
+
+Errors when pos_num cells do not fit in the sphere
+
+*/
+pub fn try_poisson_disk_sphere<T: raybnn_float_trait>(
+    space_dims: u64,
+    sphere_rad: T,
+    neuron_rad: T,
+    pos_num: u64,
+    seed: u64,
+) -> Result<arrayfire::Array<T>, Error> {
+    let dims = space_dims as usize;
+    let sphere_rad = sphere_rad.to_f64().unwrap_or(0.0);
+    let neuron_rad = neuron_rad.to_f64().unwrap_or(0.0);
+
+    let inner_rad = sphere_rad - neuron_rad;
+    if (dims == 0) || (inner_rad <= 0.0) {
+        return Err(Error::InvalidNetwork(format!(
+            "sphere_rad {} too small for neuron_rad {} in {} dimensions",
+            sphere_rad, neuron_rad, space_dims
+        )));
+    }
+
+    let mut pos_cpu = vec![T::zero(); (pos_num as usize) * dims];
+    if pos_num == 0 {
+        return Ok(arrayfire::Array::new(
+            &pos_cpu,
+            arrayfire::Dim4::new(&[0, space_dims, 1, 1]),
+        ));
+    }
+
+    let min_dist = 2.0 * neuron_rad * NEURON_RAD_FACTOR.sqrt();
+
+    //Spacing of pos_num evenly spread cells
+    let even_spacing =
+        2.0 * inner_rad * (FILL_FRACTION / (pos_num as f64)).powf(1.0 / (dims as f64));
+    let mut spacing = even_spacing.max(min_dist);
+
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    loop {
+        let mut pos = bridson_fill(&mut rng, dims, inner_rad, spacing);
+
+        if (pos.len() as u64) >= pos_num {
+            pos.shuffle(&mut rng);
+
+            for i in 0..(pos_num as usize) {
+                for d in 0..dims {
+                    pos_cpu[d * (pos_num as usize) + i] = T::from_f64(pos[i][d]);
+                }
+            }
+
+            return Ok(arrayfire::Array::new(
+                &pos_cpu,
+                arrayfire::Dim4::new(&[pos_num, space_dims, 1, 1]),
+            ));
+        }
+
+        if spacing <= min_dist {
+            return Err(Error::InvalidNetwork(format!(
+                "only {} of {} cells with radius {} fit in sphere_rad {}",
+                pos.len(),
+                pos_num,
+                neuron_rad,
+                sphere_rad
+            )));
+        }
+
+        spacing = (spacing * SPACING_DECAY).max(min_dist);
+    }
+}
+
+pub fn poisson_disk_sphere<T: raybnn_float_trait>(
+    space_dims: u64,
+    sphere_rad: T,
+    neuron_rad: T,
+    pos_num: u64,
+    seed: u64,
+) -> arrayfire::Array<T> {
+    try_poisson_disk_sphere(space_dims, sphere_rad, neuron_rad, pos_num, seed)
+        .unwrap_or_else(|e| panic!("{}", e))
+}
+
+/*
+Poisson-disk version of physics::initial_f32::sphere_cell_collision_minibatch. Places exactly
+2*active_size cells, the number sphere_cell_collision_minibatch generates before deleting
+collisions, and splits them into neurons and glial cells with nratio
+
+Inputs
+netdata:             The sphere radius, neuron radius, space_dims, active_size and nratio
+seed:                Seed of the random numbers
+
+Outputs:
+glia_pos:            Positions of the glial cells
+neuron_pos:          Positions of the neurons, 2*active_size*nratio cells
+
+*/
+pub fn try_poisson_disk_cell_collision<T: raybnn_float_trait>(
+    netdata: &network_metadata_type<T>,
+    seed: u64,
+    glia_pos: &mut arrayfire::Array<T>,
+    neuron_pos: &mut arrayfire::Array<T>,
+) -> Result<(), Error> {
+    let active_size: u64 = netdata.active_size.clone();
+    let nratio: T = netdata.nratio.clone();
+
+    let total_obj_size = 2 * active_size;
+    let total_obj = try_poisson_disk_sphere(
+        netdata.space_dims,
+        netdata.sphere_rad,
+        netdata.neuron_rad,
+        total_obj_size,
+        seed,
+    )?;
+
+    let split_idx = (T::from_f64(total_obj_size as f64) * nratio)
+        .to_u64()
+        .unwrap_or(0);
+
+    *neuron_pos = arrayfire::rows(&total_obj, 0, (split_idx as i64) - 1);
+
+    *glia_pos = arrayfire::rows(&total_obj, split_idx as i64, (total_obj_size as i64) - 1);
+
+    Ok(())
+}
+
+pub fn poisson_disk_cell_collision<T: raybnn_float_trait>(
+    netdata: &network_metadata_type<T>,
+    seed: u64,
+    glia_pos: &mut arrayfire::Array<T>,
+    neuron_pos: &mut arrayfire::Array<T>,
+) {
+    try_poisson_disk_cell_collision(netdata, seed, glia_pos, neuron_pos)
+        .unwrap_or_else(|e| panic!("{}", e))
+}
@@ -0,0 +1,418 @@
+extern crate arrayfire;
+
+use rayon::prelude::*;
+
+use crate::precision::float_trait::raybnn_float_trait;
+
+/*
+Uniform grid acceleration structure for the ray tracer
+
+line_sphere_intersect_batchV2 tests every ray against every neuron or glial cell, and
+rays_from_neuronsA_to_neuronsB creates a ray between every pair of neurons before removing
+the rays longer than con_rad. Both are quadratic in the number of cells.
+
+raytrace_grid_type sorts the positions into cubic cells of size cell_size. A ray only needs to
+be tested against the spheres in the cells overlapping the bounding box of the ray, grown by
+the sphere radius, and a neuron only needs to be paired with the neurons in the cells within
+con_rad. With cell_size close to con_rad each query visits a constant number of cells, so
+connection construction scales with the number of rays instead of rays times cells.
+
+The grid functions keep the same rays as the brute force functions, in the same order.
+
+*/
+
+// Maximum number of grid cells per position. Larger grids double cell_size
+const GRID_CELL_LIMIT: u64 = 8;
+
+/*
+pos:                 Positions on the host, column major like arrayfire. Dimension d of
+                     position i is pos[d*pos_num + i]
+cell_start:          Position of the first item of each cell in cell_items. Has cell_num+1 values
+cell_items:          Row indexes of the positions, sorted by cell
+
+*/
+pub struct raytrace_grid_type<T: raybnn_float_trait> {
+    pub pos_num: usize,
+    pub space_dims: usize,
+    pub cell_size: T,
+    pub min_pos: Vec<T>,
+    pub grid_dims: Vec<u64>,
+
+    pub pos: Vec<T>,
+    pub cell_start: Vec<u64>,
+    pub cell_items: Vec<u32>,
+}
+
+/*
+Creates a uniform grid over positions
+
+Inputs
+pos:                 Positions, dims (pos_num, space_dims)
+cell_size:           Requested cell size. Grown when the grid would have too many empty cells
+
+Outputs:
+grid:                Uniform grid
+
+*/
+pub fn create_raytrace_grid<T: raybnn_float_trait>(
+    pos: &arrayfire::Array<T>,
+    cell_size: T,
+) -> raytrace_grid_type<T> {
+    let pos_num = pos.dims()[0] as usize;
+    let space_dims = pos.dims()[1] as usize;
+
+    let mut pos_cpu = vec![T::zero(); pos.elements()];
+    pos.host(&mut pos_cpu);
+
+    create_raytrace_grid_host(pos_cpu, pos_num, space_dims, cell_size)
+}
+
+/*
+Creates a uniform grid over positions already on the host
+
+Inputs
+pos_cpu:             Positions, column major. Dimension d of position i is pos_cpu[d*pos_num + i]
+pos_num:             Number of positions
+space_dims:          Number of dimensions
+cell_size:           Requested cell size. Grown when the grid would have too many empty cells
+
+Outputs:
+grid:                Uniform grid, owning pos_cpu
+
+*/
+pub fn create_raytrace_grid_host<T: raybnn_float_trait>(
+    pos_cpu: Vec<T>,
+    pos_num: usize,
+    space_dims: usize,
+    cell_size: T,
+) -> raytrace_grid_type<T> {
+    let two = T::from_f64(2.0);
+
+    let mut min_pos = vec![T::zero(); space_dims];
+    let mut max_pos = vec![T::zero(); space_dims];
+    for d in 0..space_dims {
+        let column = &pos_cpu[(d * pos_num)..((d + 1) * pos_num)];
+        if pos_num > 0 {
+            min_pos[d] = column.iter().fold(T::infinity(), |a, &b| a.min(b));
+            max_pos[d] = column.iter().fold(T::neg_infinity(), |a, &b| a.max(b));
+        }
+    }
+
+    let mut cell_size = if cell_size > T::zero() {
+        cell_size
+    } else {
+        T::one()
+    };
+
+    let cell_limit = GRID_CELL_LIMIT * (pos_num.max(1) as u64);
+    let mut grid_dims = vec![1u64; space_dims];
+    loop {
+        for d in 0..space_dims {
+            let extent = ((max_pos[d] - min_pos[d]) / cell_size).floor();
+            grid_dims[d] = extent.to_u64().unwrap_or(u64::MAX).saturating_add(1);
+        }
+
+        let cell_num = grid_dims.iter().fold(1u64, |a, &b| a.saturating_mul(b));
+        if cell_num <= cell_limit {
+            break;
+        }
+        cell_size = cell_size * two;
+    }
+
+    let mut grid = raytrace_grid_type {
+        pos_num: pos_num,
+        space_dims: space_dims,
+        cell_size: cell_size,
+        min_pos: min_pos,
+        grid_dims: grid_dims,
+
+        pos: pos_cpu,
+        cell_start: Vec::new(),
+        cell_items: Vec::new(),
+    };
+
+    //Counting sort of the positions by cell
+    let cell_num = grid.grid_dims.iter().product::<u64>() as usize;
+    let cell_idx: Vec<usize> = (0..pos_num).map(|i| grid.cell_of_pos(i)).collect();
+
+    let mut cell_start = vec![0u64; cell_num + 1];
+    for &c in cell_idx.iter() {
+        cell_start[c + 1] = cell_start[c + 1] + 1;
+    }
+    for c in 0..cell_num {
+        cell_start[c + 1] = cell_start[c + 1] + cell_start[c];
+    }
+
+    let mut fill = cell_start.clone();
+    let mut cell_items = vec![0u32; pos_num];
+    for (i, &c) in cell_idx.iter().enumerate() {
+        cell_items[fill[c] as usize] = i as u32;
+        fill[c] = fill[c] + 1;
+    }
+
+    grid.cell_start = cell_start;
+    grid.cell_items = cell_items;
+
+    grid
+}
+
+impl<T: raybnn_float_trait> raytrace_grid_type<T> {
+    // Cell coordinate of value along dimension d, clamped to the grid
+    fn cell_coord(&self, value: T, d: usize) -> u64 {
+        let coord = ((value - self.min_pos[d]) / self.cell_size).floor();
+        if !(coord > T::zero()) {
+            return 0;
+        }
+
+        coord
+            .to_u64()
+            .unwrap_or(u64::MAX)
+            .min(self.grid_dims[d] - 1)
+    }
+
+    fn cell_of_pos(&self, i: usize) -> usize {
+        let mut cell = 0u64;
+        let mut stride = 1u64;
+        for d in 0..self.space_dims {
+            cell = cell + self.cell_coord(self.pos[d * self.pos_num + i], d) * stride;
+            stride = stride * self.grid_dims[d];
+        }
+        cell as usize
+    }
+
+    pub fn get_pos(&self, i: usize, d: usize) -> T {
+        self.pos[d * self.pos_num + i]
+    }
+
+    /*
+    Calls visit on the row index of every position in the cells overlapping the box from lo
+    to hi. Stops when visit returns false
+
+    */
+    pub fn visit_box(&self, lo: &[T], hi: &[T], mut visit: impl FnMut(usize) -> bool) {
+        if self.pos_num == 0 {
+            return;
+        }
+
+        let cell_lo: Vec<u64> = (0..self.space_dims)
+            .map(|d| self.cell_coord(lo[d], d))
+            .collect();
+        let cell_hi: Vec<u64> = (0..self.space_dims)
+            .map(|d| self.cell_coord(hi[d], d))
+            .collect();
+
+        //Visit every cell of the box, counting through the dimensions like an odometer
+        let mut cur = cell_lo.clone();
+        loop {
+            let mut cell = 0u64;
+            let mut stride = 1u64;
+            for d in 0..self.space_dims {
+                cell = cell + cur[d] * stride;
+                stride = stride * self.grid_dims[d];
+            }
+
+            let start = self.cell_start[cell as usize] as usize;
+            let end = self.cell_start[(cell as usize) + 1] as usize;
+            for &item in self.cell_items[start..end].iter() {
+                if !visit(item as usize) {
+                    return;
+                }
+            }
+
+            let mut d = 0;
+            loop {
+                if d == self.space_dims {
+                    return;
+                }
+                if cur[d] < cell_hi[d] {
+                    cur[d] = cur[d] + 1;
+                    break;
+                }
+                cur[d] = cell_lo[d];
+                d = d + 1;
+            }
+        }
+    }
+}
+
+/*
+Same rays as rays_from_neuronsA_to_neuronsB, without creating the rays between every pair of
+neurons. Creates the rays from neurons A to the neurons B within con_rad
+
+Inputs
+con_rad:             Maximum length of the rays
+neuronA_pos:         Positions of neurons A, start of the rays
+neuronA_idx:         Indexes of neurons A
+neuronB_pos:         Positions of neurons B, end of the rays
+neuronB_idx:         Indexes of neurons B
+neuronB_grid:        Uniform grid over neuronB_pos
+
+Outputs:
+start_line:          Start of the rays
+dir_line:            Direction of the rays, from start to end
+input_idx:           Neuron A of each ray
+hidden_idx:          Neuron B of each ray
+
+*/
+pub fn rays_from_neuronsA_to_neuronsB_grid<T: raybnn_float_trait>(
+    con_rad: T,
+
+    neuronA_pos: &arrayfire::Array<T>,
+    neuronA_idx: &arrayfire::Array<i32>,
+
+    neuronB_pos: &arrayfire::Array<T>,
+    neuronB_idx: &arrayfire::Array<i32>,
+    neuronB_grid: &raytrace_grid_type<T>,
+
+    start_line: &mut arrayfire::Array<T>,
+    dir_line: &mut arrayfire::Array<T>,
+
+    input_idx: &mut arrayfire::Array<i32>,
+    hidden_idx: &mut arrayfire::Array<i32>,
+) {
+    let con_rad_sq = con_rad * con_rad;
+
+    let neuronA_num = neuronA_pos.dims()[0] as usize;
+    let space_dims = neuronA_pos.dims()[1] as usize;
+
+    let mut neuronA_cpu = vec![T::zero(); neuronA_pos.elements()];
+    neuronA_pos.host(&mut neuronA_cpu);
+
+    let mut pairs: Vec<(u32, u32)> = (0..neuronA_num)
+        .into_par_iter()
+        .flat_map_iter(|a| {
+            let start: Vec<T> = (0..space_dims)
+                .map(|d| neuronA_cpu[d * neuronA_num + a])
+                .collect();
+            let lo: Vec<T> = start.iter().map(|&v| v - con_rad).collect();
+            let hi: Vec<T> = start.iter().map(|&v| v + con_rad).collect();
+
+            let mut out: Vec<(u32, u32)> = Vec::new();
+            neuronB_grid.visit_box(&lo, &hi, |b| {
+                let mut mag2 = T::zero();
+                for d in 0..space_dims {
+                    let diff = neuronB_grid.get_pos(b, d) - start[d];
+                    mag2 = mag2 + diff * diff;
+                }
+                if con_rad_sq >= mag2 {
+                    out.push((b as u32, a as u32));
+                }
+                true
+            });
+            out
+        })
+        .collect();
+
+    //Same order as the tiled rays of rays_from_neuronsA_to_neuronsB
+    pairs.par_sort_unstable();
+
+    let (B_cpu, A_cpu): (Vec<u32>, Vec<u32>) = pairs.into_iter().unzip();
+
+    let pair_dims = arrayfire::Dim4::new(&[A_cpu.len() as u64, 1, 1, 1]);
+    let A_idx = arrayfire::Array::new(&A_cpu, pair_dims);
+    let B_idx = arrayfire::Array::new(&B_cpu, pair_dims);
+
+    *start_line = arrayfire::lookup(neuronA_pos, &A_idx, 0);
+    *dir_line = arrayfire::lookup(neuronB_pos, &B_idx, 0) - start_line.clone();
+
+    *input_idx = arrayfire::lookup(neuronA_idx, &A_idx, 0);
+    *hidden_idx = arrayfire::lookup(neuronB_idx, &B_idx, 0);
+}
+
+/*
+Same result as line_sphere_intersect_batchV2 with spheres of the same radius. Removes the rays
+that intersect more than threshold spheres
+
+Inputs
+grid:                Uniform grid over the sphere centers
+circle_radius:       Radius of the spheres
+threshold:           Maximum number of intersected spheres
+
+Outputs:
+start_line:          Start of the remaining rays
+dir_line:            Direction of the remaining rays
+input_idx:           Start neuron of the remaining rays
+hidden_idx:          End neuron of the remaining rays
+
+*/
+pub fn line_sphere_intersect_grid<T: raybnn_float_trait>(
+    grid: &raytrace_grid_type<T>,
+    circle_radius: T,
+    threshold: u32,
+
+    start_line: &mut arrayfire::Array<T>,
+    dir_line: &mut arrayfire::Array<T>,
+
+    input_idx: &mut arrayfire::Array<i32>,
+    hidden_idx: &mut arrayfire::Array<i32>,
+) {
+    let zero = T::zero();
+    let one = T::one();
+
+    let ray_num = start_line.dims()[0] as usize;
+    let space_dims = start_line.dims()[1] as usize;
+
+    let mut start_cpu = vec![zero; start_line.elements()];
+    start_line.host(&mut start_cpu);
+    let mut dir_cpu = vec![zero; dir_line.elements()];
+    dir_line.host(&mut dir_cpu);
+
+    let radius_sq = circle_radius * circle_radius;
+
+    let keep_cpu: Vec<u32> = (0..ray_num)
+        .into_par_iter()
+        .filter(|&r| {
+            let start: Vec<T> = (0..space_dims)
+                .map(|d| start_cpu[d * ray_num + r])
+                .collect();
+            let dir: Vec<T> = (0..space_dims).map(|d| dir_cpu[d * ray_num + r]).collect();
+
+            let sq = dir.iter().fold(zero, |a, &b| a + b * b);
+
+            let lo: Vec<T> = (0..space_dims)
+                .map(|d| start[d].min(start[d] + dir[d]) - circle_radius)
+                .collect();
+            let hi: Vec<T> = (0..space_dims)
+                .map(|d| start[d].max(start[d] + dir[d]) + circle_radius)
+                .collect();
+
+            let mut counter = 0u32;
+            grid.visit_box(&lo, &hi, |c| {
+                // C - S
+                let center: Vec<T> = (0..space_dims)
+                    .map(|d| grid.get_pos(c, d) - start[d])
+                    .collect();
+
+                // Clamp(  dot(C - S, D)  /  |D|^2  ), zero length rays use 0
+                let mut dotret = zero;
+                for d in 0..space_dims {
+                    dotret = dotret + center[d] * dir[d];
+                }
+                dotret = (dotret / sq).max(zero).min(one);
+
+                // Mag( (C - S) - Clamp( dot(C - S, D) / |D|^2 ) D )
+                let mut mag2 = zero;
+                for d in 0..space_dims {
+                    let rej = center[d] - dotret * dir[d];
+                    mag2 = mag2 + rej * rej;
+                }
+
+                if radius_sq >= mag2 {
+                    counter = counter + 1;
+                }
+                counter <= threshold
+            });
+
+            counter <= threshold
+        })
+        .map(|r| r as u32)
+        .collect();
+
+    let keep_dims = arrayfire::Dim4::new(&[keep_cpu.len() as u64, 1, 1, 1]);
+    let idx_intersect = arrayfire::Array::new(&keep_cpu, keep_dims);
+
+    *start_line = arrayfire::lookup(start_line, &idx_intersect, 0);
+    *dir_line = arrayfire::lookup(dir_line, &idx_intersect, 0);
+    *input_idx = arrayfire::lookup(input_idx, &idx_intersect, 0);
+    *hidden_idx = arrayfire::lookup(hidden_idx, &idx_intersect, 0);
+}
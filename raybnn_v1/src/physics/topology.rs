@@ -0,0 +1,519 @@
+extern crate arrayfire;
+
+use rand::Rng;
+use rayon::prelude::*;
+
+use crate::export::rng::stream_raybnn_rng;
+use crate::export::rng::with_raybnn_rng;
+
+use crate::neural::network::network_metadata_type;
+
+use crate::physics::raytrace::raytrace_accel_type;
+use crate::physics::raytrace::raytrace_option_type;
+use crate::physics::raytrace::RT2_directly_connected;
+use crate::physics::raytrace::RT3_distance_limited_directly_connected;
+
+use crate::precision::float_trait::raybnn_float_trait;
+
+/*
+Connection topology strategies
+
+create_start_archtecture places the neurons and glial cells, then connects them in three
+stages: input neurons to hidden neurons, hidden neurons to hidden neurons and hidden neurons
+to output neurons. A connection strategy decides which pairs are connected in each stage, so
+different topologies can be compared on the same neuron placement.
+
+Every strategy connects neurons A (the sources, WColIdx) to neurons B (the targets,
+WRowIdxCOO) and keeps the connections already in WRowIdxCOO and WColIdx. The new and old
+connections are sorted by global weight index (row*neuron_size + col) without duplicates, the
+same as the ray tracers. Neurons are never connected to themselves, self loops are added
+separately by physics::initial_f32::self_loops
+
+raytrace_strategy_type:      RT2_directly_connected or RT3_distance_limited_directly_connected
+knn_strategy_type:           Connects every neuron A to its k nearest neurons B
+small_world_strategy_type:   Watts-Strogatz. k nearest neighbors, then every connection is
+                             rewired to a random neuron B with probability rewire_prob
+waxman_strategy_type:        Connects A to B with probability alpha*exp(-distance/(beta*L)),
+                             where L is the diagonal of the bounding box of the neurons
+
+RT1_random_rays does not have source and target neurons, so it is not a strategy.
+
+*/
+
+/*
+Connection stage of create_start_archtecture
+
+INPUT:               Input neurons to hidden neurons
+HIDDEN:              Hidden neurons to hidden neurons
+OUTPUT:              Hidden neurons to output neurons
+
+*/
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum connection_stage_type {
+    INPUT,
+    HIDDEN,
+    OUTPUT,
+}
+
+pub trait connection_strategy_trait<T: raybnn_float_trait> {
+    /*
+    Connects neurons A to neurons B
+
+    Inputs
+    stage:               Connection stage
+    connection_num:      Number of new connections requested. The ray tracers stop after
+                         connection_num connections, the other strategies set the number of
+                         connections with their own settings
+    netdata:             Network metadata. Uses neuron_size and for the ray tracers con_rad and
+                         neuron_rad
+    glia_pos:            Positions of the glial cells
+    neuronA_pos:         Positions of neurons A
+    neuronA_idx:         Indexes of neurons A
+    neuronB_pos:         Positions of neurons B
+    neuronB_idx:         Indexes of neurons B
+
+    Outputs:
+    WRowIdxCOO:          Row indexes of the old and new connections, neurons B
+    WColIdx:             Column indexes of the old and new connections, neurons A
+
+    */
+    fn connect(
+        &self,
+        stage: connection_stage_type,
+        connection_num: u64,
+
+        netdata: &network_metadata_type<T>,
+        glia_pos: &arrayfire::Array<T>,
+
+        neuronA_pos: &arrayfire::Array<T>,
+        neuronA_idx: &arrayfire::Array<i32>,
+
+        neuronB_pos: &arrayfire::Array<T>,
+        neuronB_idx: &arrayfire::Array<i32>,
+
+        WRowIdxCOO: &mut arrayfire::Array<i32>,
+        WColIdx: &mut arrayfire::Array<i32>,
+    );
+}
+
+/*
+Adds new connections to WRowIdxCOO and WColIdx, sorted by global weight index without
+duplicates. Like the ray tracers, the old connections are only kept when there is more than one
+
+*/
+pub fn merge_connections(
+    neuron_size: u64,
+    new_row: &[i32],
+    new_col: &[i32],
+    WRowIdxCOO: &mut arrayfire::Array<i32>,
+    WColIdx: &mut arrayfire::Array<i32>,
+) {
+    let mut join_all: Vec<(u64, i32, i32)> = new_row
+        .iter()
+        .zip(new_col.iter())
+        .map(|(&row, &col)| (((row as u64) * neuron_size) + (col as u64), row, col))
+        .collect();
+
+    if WColIdx.elements() > 1 {
+        let mut WRowIdxCOO_cpu = vec![i32::default(); WRowIdxCOO.elements()];
+        WRowIdxCOO.host(&mut WRowIdxCOO_cpu);
+
+        let mut WColIdx_cpu = vec![i32::default(); WColIdx.elements()];
+        WColIdx.host(&mut WColIdx_cpu);
+
+        for (&row, &col) in WRowIdxCOO_cpu.iter().zip(WColIdx_cpu.iter()) {
+            join_all.push((((row as u64) * neuron_size) + (col as u64), row, col));
+        }
+    }
+
+    join_all.par_sort_unstable_by_key(|pair| pair.0);
+    join_all.dedup_by_key(|pair| pair.0);
+
+    let (WRowIdxCOO_cpu, WColIdx_cpu): (Vec<i32>, Vec<i32>) =
+        join_all.par_iter().map(|&(_, row, col)| (row, col)).unzip();
+
+    let con_dims = arrayfire::Dim4::new(&[WColIdx_cpu.len() as u64, 1, 1, 1]);
+    *WRowIdxCOO = arrayfire::Array::new(&WRowIdxCOO_cpu, con_dims);
+    *WColIdx = arrayfire::Array::new(&WColIdx_cpu, con_dims);
+}
+
+// Positions and indexes on the host. Positions are column major, pos[d*pos_num + i]
+struct host_neurons_type<T: raybnn_float_trait> {
+    pos_num: usize,
+    space_dims: usize,
+    pos: Vec<T>,
+    idx: Vec<i32>,
+}
+
+impl<T: raybnn_float_trait> host_neurons_type<T> {
+    fn new(pos: &arrayfire::Array<T>, idx: &arrayfire::Array<i32>) -> host_neurons_type<T> {
+        let mut pos_cpu = vec![T::zero(); pos.elements()];
+        pos.host(&mut pos_cpu);
+
+        let mut idx_cpu = vec![i32::default(); idx.elements()];
+        idx.host(&mut idx_cpu);
+
+        host_neurons_type {
+            pos_num: pos.dims()[0] as usize,
+            space_dims: pos.dims()[1] as usize,
+            pos: pos_cpu,
+            idx: idx_cpu,
+        }
+    }
+
+    fn get_pos(&self, i: usize, d: usize) -> T {
+        self.pos[d * self.pos_num + i]
+    }
+
+    fn sq_dist(&self, i: usize, other: &host_neurons_type<T>, j: usize) -> T {
+        let mut magsq = T::zero();
+        for d in 0..self.space_dims {
+            let diff = other.get_pos(j, d) - self.get_pos(i, d);
+            magsq = magsq + diff * diff;
+        }
+        magsq
+    }
+}
+
+// Rows of neurons B nearest to neuron A i, nearest first. Skips neuron A itself
+fn nearest_neighbors<T: raybnn_float_trait>(
+    neuronA: &host_neurons_type<T>,
+    i: usize,
+    neuronB: &host_neurons_type<T>,
+    k: usize,
+) -> Vec<usize> {
+    let mut dist: Vec<(T, usize)> = (0..neuronB.pos_num)
+        .filter(|&j| neuronB.idx[j] != neuronA.idx[i])
+        .map(|j| (neuronA.sq_dist(i, neuronB, j), j))
+        .collect();
+
+    let cmp = |a: &(T, usize), b: &(T, usize)| {
+        a.0.partial_cmp(&b.0)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.1.cmp(&b.1))
+    };
+
+    if dist.len() > k {
+        dist.select_nth_unstable_by(k, cmp);
+        dist.truncate(k);
+    }
+    dist.sort_unstable_by(cmp);
+
+    dist.iter().map(|pair| pair.1).collect()
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum raytrace_algorithm_type {
+    RT2_DIRECTLY_CONNECTED,
+    RT3_DISTANCE_LIMITED_DIRECTLY_CONNECTED,
+}
+
+/*
+Ray tracing strategy. Rays longer than con_rad are removed and rays going through other
+neurons or glial cells are blocked. create_start_archtecture only blocks the rays between
+hidden neurons, so ray_neuron_intersect and ray_glia_intersect only apply to the HIDDEN stage
+
+algorithm:           RT2_directly_connected or RT3_distance_limited_directly_connected
+max_rounds:          Maximum number of ray tracing rounds
+ray_neuron_intersect: Block rays going through neurons
+ray_glia_intersect:  Block rays going through glial cells
+accel:               Intersection test acceleration
+
+*/
+pub struct raytrace_strategy_type {
+    pub algorithm: raytrace_algorithm_type,
+    pub max_rounds: u64,
+    pub ray_neuron_intersect: bool,
+    pub ray_glia_intersect: bool,
+    pub accel: raytrace_accel_type,
+}
+
+// Ray tracing strategy used by create_start_archtecture
+pub fn create_raytrace_strategy() -> raytrace_strategy_type {
+    raytrace_strategy_type {
+        algorithm: raytrace_algorithm_type::RT3_DISTANCE_LIMITED_DIRECTLY_CONNECTED,
+        max_rounds: 10000,
+        ray_neuron_intersect: true,
+        ray_glia_intersect: true,
+        accel: raytrace_accel_type::UNIFORM_GRID,
+    }
+}
+
+impl<T: raybnn_float_trait> connection_strategy_trait<T> for raytrace_strategy_type {
+    fn connect(
+        &self,
+        stage: connection_stage_type,
+        connection_num: u64,
+
+        netdata: &network_metadata_type<T>,
+        glia_pos: &arrayfire::Array<T>,
+
+        neuronA_pos: &arrayfire::Array<T>,
+        neuronA_idx: &arrayfire::Array<i32>,
+
+        neuronB_pos: &arrayfire::Array<T>,
+        neuronB_idx: &arrayfire::Array<i32>,
+
+        WRowIdxCOO: &mut arrayfire::Array<i32>,
+        WColIdx: &mut arrayfire::Array<i32>,
+    ) {
+        let hidden_stage = stage == connection_stage_type::HIDDEN;
+
+        let raytrace_options = raytrace_option_type {
+            max_rounds: self.max_rounds,
+            input_connection_num: connection_num,
+            ray_neuron_intersect: self.ray_neuron_intersect && hidden_stage,
+            ray_glia_intersect: self.ray_glia_intersect && hidden_stage,
+            accel: self.accel,
+        };
+
+        match self.algorithm {
+            raytrace_algorithm_type::RT2_DIRECTLY_CONNECTED => {
+                RT2_directly_connected(
+                    &raytrace_options,
+                    netdata,
+                    glia_pos,
+                    neuronA_pos,
+                    neuronA_idx,
+                    neuronB_pos,
+                    neuronB_idx,
+                    WRowIdxCOO,
+                    WColIdx,
+                );
+            }
+            raytrace_algorithm_type::RT3_DISTANCE_LIMITED_DIRECTLY_CONNECTED => {
+                RT3_distance_limited_directly_connected(
+                    &raytrace_options,
+                    netdata,
+                    glia_pos,
+                    neuronA_pos,
+                    neuronA_idx,
+                    neuronB_pos,
+                    neuronB_idx,
+                    WRowIdxCOO,
+                    WColIdx,
+                );
+            }
+        }
+    }
+}
+
+/*
+k nearest neighbors strategy
+
+k:                   Number of connections from every neuron A to its nearest neurons B
+
+*/
+pub struct knn_strategy_type {
+    pub k: u64,
+}
+
+impl<T: raybnn_float_trait> connection_strategy_trait<T> for knn_strategy_type {
+    fn connect(
+        &self,
+        _stage: connection_stage_type,
+        _connection_num: u64,
+
+        netdata: &network_metadata_type<T>,
+        _glia_pos: &arrayfire::Array<T>,
+
+        neuronA_pos: &arrayfire::Array<T>,
+        neuronA_idx: &arrayfire::Array<i32>,
+
+        neuronB_pos: &arrayfire::Array<T>,
+        neuronB_idx: &arrayfire::Array<i32>,
+
+        WRowIdxCOO: &mut arrayfire::Array<i32>,
+        WColIdx: &mut arrayfire::Array<i32>,
+    ) {
+        let neuronA = host_neurons_type::new(neuronA_pos, neuronA_idx);
+        let neuronB = host_neurons_type::new(neuronB_pos, neuronB_idx);
+
+        let pairs: Vec<(i32, i32)> = (0..neuronA.pos_num)
+            .into_par_iter()
+            .flat_map_iter(|i| {
+                nearest_neighbors(&neuronA, i, &neuronB, self.k as usize)
+                    .into_iter()
+                    .map(|j| (neuronB.idx[j], neuronA.idx[i]))
+                    .collect::<Vec<(i32, i32)>>()
+            })
+            .collect();
+
+        let (new_row, new_col): (Vec<i32>, Vec<i32>) = pairs.into_iter().unzip();
+
+        merge_connections(netdata.neuron_size, &new_row, &new_col, WRowIdxCOO, WColIdx);
+    }
+}
+
+/*
+Watts-Strogatz small world strategy. Starts from the k nearest neighbors lattice and rewires
+the target of every connection to a random neuron B with probability rewire_prob. Rewired
+connections never create self connections or duplicates
+
+k:                   Number of connections from every neuron A
+rewire_prob:         Probability of rewiring each connection, 0 is the k nearest neighbors
+                     lattice and 1 is a random graph
+
+*/
+pub struct small_world_strategy_type<T: raybnn_float_trait> {
+    pub k: u64,
+    pub rewire_prob: T,
+}
+
+impl<T: raybnn_float_trait> connection_strategy_trait<T> for small_world_strategy_type<T> {
+    fn connect(
+        &self,
+        _stage: connection_stage_type,
+        _connection_num: u64,
+
+        netdata: &network_metadata_type<T>,
+        _glia_pos: &arrayfire::Array<T>,
+
+        neuronA_pos: &arrayfire::Array<T>,
+        neuronA_idx: &arrayfire::Array<i32>,
+
+        neuronB_pos: &arrayfire::Array<T>,
+        neuronB_idx: &arrayfire::Array<i32>,
+
+        WRowIdxCOO: &mut arrayfire::Array<i32>,
+        WColIdx: &mut arrayfire::Array<i32>,
+    ) {
+        let neuronA = host_neurons_type::new(neuronA_pos, neuronA_idx);
+        let neuronB = host_neurons_type::new(neuronB_pos, neuronB_idx);
+
+        let rewire_prob = self.rewire_prob.to_f64().unwrap_or(0.0);
+
+        //One random stream per neuron A, independent of the number of threads
+        let rng_base = with_raybnn_rng(|rng| rng.gen::<u64>());
+
+        let pairs: Vec<(i32, i32)> = (0..neuronA.pos_num)
+            .into_par_iter()
+            .flat_map_iter(|i| {
+                let mut rng = stream_raybnn_rng(rng_base, i as u64);
+
+                let mut targets = nearest_neighbors(&neuronA, i, &neuronB, self.k as usize);
+                let candidate_num = neuronB
+                    .idx
+                    .iter()
+                    .filter(|&&idx| idx != neuronA.idx[i])
+                    .count();
+
+                //Rewiring is only possible when there is an unused neuron B
+                if candidate_num > targets.len() {
+                    for t in 0..targets.len() {
+                        if rng.gen::<f64>() >= rewire_prob {
+                            continue;
+                        }
+
+                        loop {
+                            let j = rng.gen_range(0..neuronB.pos_num);
+                            if (neuronB.idx[j] != neuronA.idx[i]) && !targets.contains(&j) {
+                                targets[t] = j;
+                                break;
+                            }
+                        }
+                    }
+                }
+
+                targets
+                    .into_iter()
+                    .map(|j| (neuronB.idx[j], neuronA.idx[i]))
+                    .collect::<Vec<(i32, i32)>>()
+            })
+            .collect();
+
+        let (new_row, new_col): (Vec<i32>, Vec<i32>) = pairs.into_iter().unzip();
+
+        merge_connections(netdata.neuron_size, &new_row, &new_col, WRowIdxCOO, WColIdx);
+    }
+}
+
+/*
+Waxman strategy. Connects every pair of neuron A and neuron B with probability
+alpha*exp(-distance/(beta*L)), where L is the diagonal of the bounding box of neurons A and B
+
+alpha:               Connection probability of neurons at distance 0
+beta:                Decay length relative to L. Small beta only connects close neurons
+
+*/
+pub struct waxman_strategy_type<T: raybnn_float_trait> {
+    pub alpha: T,
+    pub beta: T,
+}
+
+impl<T: raybnn_float_trait> connection_strategy_trait<T> for waxman_strategy_type<T> {
+    fn connect(
+        &self,
+        _stage: connection_stage_type,
+        _connection_num: u64,
+
+        netdata: &network_metadata_type<T>,
+        _glia_pos: &arrayfire::Array<T>,
+
+        neuronA_pos: &arrayfire::Array<T>,
+        neuronA_idx: &arrayfire::Array<i32>,
+
+        neuronB_pos: &arrayfire::Array<T>,
+        neuronB_idx: &arrayfire::Array<i32>,
+
+        WRowIdxCOO: &mut arrayfire::Array<i32>,
+        WColIdx: &mut arrayfire::Array<i32>,
+    ) {
+        let neuronA = host_neurons_type::new(neuronA_pos, neuronA_idx);
+        let neuronB = host_neurons_type::new(neuronB_pos, neuronB_idx);
+
+        //Diagonal of the bounding box
+        let mut diag_sq = T::zero();
+        for d in 0..neuronA.space_dims {
+            let mut min_pos = T::infinity();
+            let mut max_pos = T::neg_infinity();
+            for i in 0..neuronA.pos_num {
+                min_pos = min_pos.min(neuronA.get_pos(i, d));
+                max_pos = max_pos.max(neuronA.get_pos(i, d));
+            }
+            for j in 0..neuronB.pos_num {
+                min_pos = min_pos.min(neuronB.get_pos(j, d));
+                max_pos = max_pos.max(neuronB.get_pos(j, d));
+            }
+            if max_pos > min_pos {
+                diag_sq = diag_sq + (max_pos - min_pos) * (max_pos - min_pos);
+            }
+        }
+
+        let decay = (self.beta * diag_sq.sqrt()).to_f64().unwrap_or(0.0);
+        let alpha = self.alpha.to_f64().unwrap_or(0.0);
+
+        //One random stream per neuron A, independent of the number of threads
+        let rng_base = with_raybnn_rng(|rng| rng.gen::<u64>());
+
+        let pairs: Vec<(i32, i32)> = (0..neuronA.pos_num)
+            .into_par_iter()
+            .flat_map_iter(|i| {
+                let mut rng = stream_raybnn_rng(rng_base, i as u64);
+
+                (0..neuronB.pos_num)
+                    .filter(|&j| neuronB.idx[j] != neuronA.idx[i])
+                    .filter(|&j| {
+                        let dist = neuronA
+                            .sq_dist(i, &neuronB, j)
+                            .sqrt()
+                            .to_f64()
+                            .unwrap_or(0.0);
+                        let prob = if decay > 0.0 {
+                            alpha * (-dist / decay).exp()
+                        } else {
+                            alpha
+                        };
+
+                        rng.gen::<f64>() < prob
+                    })
+                    .map(|j| (neuronB.idx[j], neuronA.idx[i]))
+                    .collect::<Vec<(i32, i32)>>()
+            })
+            .collect();
+
+        let (new_row, new_col): (Vec<i32>, Vec<i32>) = pairs.into_iter().unzip();
+
+        merge_connections(netdata.neuron_size, &new_row, &new_col, WRowIdxCOO, WColIdx);
+    }
+}
@@ -0,0 +1,91 @@
+extern crate arrayfire;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/*
+Floating point precision shared by the generic modules (f32, f64 and half::f16)
+
+The arrayfire bounds pin every output type of the elementwise operators to Self,
+so that generic code can chain arrayfire calls the same way the _f32/_f64 modules
+do. sum, product, sum_by_key and sum_all return Acc, which is f32 for half::f16.
+crate::precision::reduce casts them back to Self.
+
+half::bf16 has no arrayfire type and can not implement the trait.
+
+Scalars must be written on the right hand side of arithmetic with arrays,
+e.g. X * T::from_f64(2.0) instead of 2.0*X
+*/
+
+pub trait raybnn_float_trait:
+    arrayfire::HasAfEnum<
+        InType = Self,
+        BaseType = Self,
+        AbsOutType = Self,
+        ArgOutType = Self,
+        UnaryOutType = Self,
+        MeanOutType = Self,
+        AggregateOutType = <Self as raybnn_float_trait>::Acc,
+        ProductOutType = <Self as raybnn_float_trait>::Acc,
+        SobelOutType = Self,
+    > + arrayfire::FloatingPoint
+    + arrayfire::RealFloating
+    + arrayfire::RealNumber
+    + arrayfire::ConstGenerator<OutType = Self>
+    + arrayfire::ImplicitPromote<Self, Output = Self>
+    + arrayfire::Fromf64
+    + num::Float
+    + std::iter::Sum
+    + FromStr
+    + Debug
+    + Display
+    + Default
+    + Serialize
+    + DeserializeOwned
+    + Copy
+    + Send
+    + Sync
+    + 'static
+{
+    // Name used in file headers and log messages
+    const DTYPE_NAME: &'static str;
+
+    // Output type of sum, product, sum_by_key and sum_all
+    type Acc: raybnn_float_trait;
+
+    fn from_f64(value: f64) -> Self;
+}
+
+impl raybnn_float_trait for f32 {
+    const DTYPE_NAME: &'static str = "f32";
+
+    type Acc = f32;
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+}
+
+impl raybnn_float_trait for f64 {
+    const DTYPE_NAME: &'static str = "f64";
+
+    type Acc = f64;
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+}
+
+impl raybnn_float_trait for half::f16 {
+    const DTYPE_NAME: &'static str = "f16";
+
+    type Acc = f32;
+
+    fn from_f64(value: f64) -> Self {
+        half::f16::from_f64(value)
+    }
+}
@@ -0,0 +1,2 @@
+pub mod float_trait;
+pub mod reduce;
@@ -0,0 +1,42 @@
+extern crate arrayfire;
+
+use crate::precision::float_trait::raybnn_float_trait;
+
+use num::ToPrimitive;
+
+/*
+Reductions of arrays of T with the result in T
+
+arrayfire returns sum, product, sum_by_key and sum_all in T::Acc, which is f32 for half::f16.
+The result is cast back to T, so generic code can use it with the other arrays of T. For f32
+and f64 T::Acc is T and the cast does not change the values.
+
+*/
+
+pub fn sum<T: raybnn_float_trait>(input: &arrayfire::Array<T>, dim: i32) -> arrayfire::Array<T> {
+    arrayfire::sum(input, dim).cast::<T>()
+}
+
+pub fn product<T: raybnn_float_trait>(
+    input: &arrayfire::Array<T>,
+    dim: i32,
+) -> arrayfire::Array<T> {
+    arrayfire::product(input, dim).cast::<T>()
+}
+
+pub fn sum_by_key<K: arrayfire::ReduceByKeyInput, T: raybnn_float_trait>(
+    keys: &arrayfire::Array<K>,
+    vals: &arrayfire::Array<T>,
+    dim: i32,
+) -> (arrayfire::Array<K>, arrayfire::Array<T>) {
+    let (out_keys, out_vals) = arrayfire::sum_by_key(keys, vals, dim);
+    (out_keys, out_vals.cast::<T>())
+}
+
+pub fn sum_all<T: raybnn_float_trait>(input: &arrayfire::Array<T>) -> (T, T) {
+    let (real, imag) = arrayfire::sum_all(input);
+    (
+        T::from_f64(real.to_f64().unwrap()),
+        T::from_f64(imag.to_f64().unwrap()),
+    )
+}
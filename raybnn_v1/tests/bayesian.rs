@@ -0,0 +1,139 @@
+#![allow(non_snake_case)]
+extern crate raybnn;
+
+use raybnn::optimal::bayesian::backward_solve;
+use raybnn::optimal::bayesian::cholesky;
+use raybnn::optimal::bayesian::expected_improvement;
+use raybnn::optimal::bayesian::fit_gp;
+use raybnn::optimal::bayesian::forward_solve;
+use raybnn::optimal::bayesian::matern52;
+use raybnn::optimal::bayesian::normal_cdf;
+use raybnn::optimal::bayesian::predict_gp;
+
+// Noise variance added to the diagonal of the kernel matrix by fit_gp
+const NOISE_VAR: f64 = 1.0e-3;
+
+fn assert_close(value: f64, expected: f64, tol: f64) {
+    assert!(
+        (value - expected).abs() <= tol,
+        "value {} expected {}",
+        value,
+        expected
+    );
+}
+
+#[test]
+fn cholesky_known_factor() {
+    let K = [4.0, 12.0, -16.0, 12.0, 37.0, -43.0, -16.0, -43.0, 98.0];
+    let L = cholesky(&K, 3).unwrap();
+
+    let expected = [2.0, 0.0, 0.0, 6.0, 1.0, 0.0, -8.0, 5.0, 3.0];
+    for i in 0..9 {
+        assert_close(L[i], expected[i], 1.0e-12);
+    }
+}
+
+#[test]
+fn cholesky_solve_matches_matrix() {
+    let K = [4.0, 12.0, -16.0, 12.0, 37.0, -43.0, -16.0, -43.0, 98.0];
+    let b = [1.0, -2.0, 3.0];
+
+    let L = cholesky(&K, 3).unwrap();
+    let x = backward_solve(&L, &forward_solve(&L, &b));
+
+    for i in 0..3 {
+        let Kx: f64 = (0..3).map(|j| K[i * 3 + j] * x[j]).sum();
+        assert_close(Kx, b[i], 1.0e-9);
+    }
+}
+
+#[test]
+fn cholesky_rejects_indefinite_matrix() {
+    let K = [1.0, 2.0, 2.0, 1.0];
+
+    assert!(cholesky(&K, 2).is_none());
+}
+
+#[test]
+fn gp_posterior_of_two_observations() {
+    // Targets 0 and 2 standardize to -1 and 1 with y_mean 1 and y_std 1
+    let x = [[0.0, 0.0, 0.0], [0.3, 0.0, 0.0]];
+    let y = [0.0, 2.0];
+
+    let gp = fit_gp(&x, &y).unwrap();
+    assert_close(gp.y_mean, 1.0, 1.0e-12);
+    assert_close(gp.y_std, 1.0, 1.0e-12);
+
+    // Closed form posterior at the first observation with K = [[1 + s, k], [k, 1 + s]]
+    let s = NOISE_VAR;
+    let k = matern52(&x[0], &x[1], gp.length_scale);
+    let det = (1.0 + s) * (1.0 + s) - k * k;
+
+    let mean = gp.y_mean - gp.y_std * (1.0 - k) / (1.0 + s - k);
+    let var = 1.0 - ((1.0 + s) * (1.0 + k * k) - 2.0 * k * k) / det;
+
+    let (pred_mean, pred_std) = predict_gp(&gp, &x[0]);
+    assert_close(pred_mean, mean, 1.0e-9);
+    assert_close(pred_std, gp.y_std * var.sqrt(), 1.0e-9);
+}
+
+#[test]
+fn gp_posterior_far_from_observations_is_the_prior() {
+    let x = [[0.0, 0.0, 0.0], [0.5, 0.2, 0.0], [1.0, 0.4, 0.5]];
+    let y = [1.0, 2.0, 3.0];
+
+    let gp = fit_gp(&x, &y).unwrap();
+    let (mean, std) = predict_gp(&gp, &[100.0, 100.0, 100.0]);
+
+    assert_close(mean, 2.0, 1.0e-9);
+    assert_close(std, (2.0f64 / 3.0).sqrt(), 1.0e-9);
+
+    // Observed points are predicted with little uncertainty
+    for i in 0..3 {
+        let (mean, std) = predict_gp(&gp, &x[i]);
+        assert_close(mean, y[i], 0.05);
+        assert!(std < 0.1, "std {}", std);
+    }
+}
+
+#[test]
+fn normal_cdf_known_values() {
+    assert_close(normal_cdf(0.0), 0.5, 1.0e-7);
+    assert_close(normal_cdf(1.0), 0.841_344_746, 1.0e-6);
+    assert_close(normal_cdf(-1.96), 0.024_997_895, 1.0e-6);
+}
+
+#[test]
+fn expected_improvement_known_values() {
+    // Mean at the best observation: std * pdf(0)
+    let pdf0 = 1.0 / (2.0 * std::f64::consts::PI).sqrt();
+    assert_close(expected_improvement(0.5, 1.0, 0.5, 0.0), pdf0, 1.0e-6);
+    assert_close(expected_improvement(0.5, 2.0, 0.5, 0.0), 2.0 * pdf0, 1.0e-6);
+
+    // Without uncertainty the improvement is max(best_y - xi - mean, 0)
+    assert_close(expected_improvement(0.2, 1.0e-9, 0.5, 0.1), 0.2, 1.0e-6);
+    assert_close(expected_improvement(0.9, 1.0e-9, 0.5, 0.1), 0.0, 1.0e-6);
+}
+
+#[test]
+fn expected_improvement_matches_integral() {
+    let (mean, std, best_y, xi) = (0.3, 0.2, 0.5, 0.05);
+
+    // E[max(best_y - xi - y, 0)] of y ~ N(mean, std^2) by the midpoint rule
+    let step_num = 200_000;
+    let (low, high) = (mean - 10.0 * std, mean + 10.0 * std);
+    let dy = (high - low) / (step_num as f64);
+    let mut integral = 0.0;
+    for i in 0..step_num {
+        let y = low + ((i as f64) + 0.5) * dy;
+        let density =
+            (-0.5 * ((y - mean) / std).powi(2)).exp() / (std * (2.0 * std::f64::consts::PI).sqrt());
+        integral += (best_y - xi - y).max(0.0) * density * dy;
+    }
+
+    assert_close(
+        expected_improvement(mean, std, best_y, xi),
+        integral,
+        1.0e-6,
+    );
+}
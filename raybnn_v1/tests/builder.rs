@@ -0,0 +1,61 @@
+extern crate raybnn;
+
+use raybnn::interface::builder;
+use raybnn::interface::builder_f64::network_builder_type;
+
+use raybnn::Error;
+
+#[test]
+fn derived_active_size_is_at_least_minimum() {
+    let netdata = network_builder_type::new(3, 2, "/tmp/").netdata().unwrap();
+
+    assert_eq!(netdata.active_size, 20);
+    assert_eq!(netdata.neuron_size, 80);
+}
+
+#[test]
+fn explicit_active_size_below_minimum_is_rejected() {
+    let result = network_builder_type::new(3, 2, "/tmp/")
+        .active_size(10)
+        .netdata();
+
+    match result {
+        Err(Error::InvalidNetwork(msg)) => assert!(msg.contains("active_size"), "{}", msg),
+        Err(e) => panic!("expected InvalidNetwork, found {}", e),
+        Ok(netdata) => panic!("expected an error, active_size {}", netdata.active_size),
+    }
+}
+
+#[test]
+fn invalid_settings_are_rejected() {
+    let builders = [
+        network_builder_type::new(0, 2, "/tmp/"),
+        network_builder_type::new(3, 2, "/tmp/").batch_size(0),
+        network_builder_type::new(3, 2, "/tmp/").proc_num(0),
+        network_builder_type::new(3, 2, "/tmp/")
+            .active_size(40)
+            .max_neuron_size(30),
+        network_builder_type::new(3, 2, "/tmp/").nratio(1.5),
+    ];
+
+    for builder in builders.iter() {
+        assert!(matches!(builder.netdata(), Err(Error::InvalidNetwork(_))));
+    }
+}
+
+#[test]
+fn f32_and_f64_derive_the_same_settings() {
+    let netdata32 = builder::network_builder_type::<f32>::new(30, 10, "/tmp/")
+        .proc_num(3)
+        .netdata()
+        .unwrap();
+    let netdata64 = builder::network_builder_type::<f64>::new(30, 10, "/tmp/")
+        .proc_num(3)
+        .netdata()
+        .unwrap();
+
+    assert_eq!(netdata32.active_size, netdata64.active_size);
+    assert_eq!(netdata32.neuron_size, netdata64.neuron_size);
+    assert!(((netdata32.sphere_rad as f64) - netdata64.sphere_rad).abs() < 1.0e-5);
+    assert!(((netdata32.con_rad as f64) - netdata64.con_rad).abs() < 1.0e-5);
+}
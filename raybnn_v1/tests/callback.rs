@@ -0,0 +1,90 @@
+#![allow(non_snake_case)]
+extern crate arrayfire;
+extern crate raybnn;
+
+mod common;
+
+use common::small_network;
+use common::test_dir;
+
+use raybnn::interface::callback::callback_action_type;
+use raybnn::interface::callback::callback_monitor_type;
+use raybnn::interface::callback::create_model_checkpoint_callback;
+use raybnn::interface::callback::create_train_progress;
+use raybnn::interface::callback::dispatch_callbacks;
+use raybnn::interface::callback::train_callback_trait;
+use raybnn::interface::callback::try_create_csv_logger_callback;
+
+use raybnn::Error;
+
+// Callback with only the default events
+struct default_callback_type {}
+
+impl train_callback_trait<f64> for default_callback_type {}
+
+#[test]
+fn dispatch_returns_stop_and_errors() {
+    let mut first = default_callback_type {};
+    let mut second = default_callback_type {};
+    let mut callbacks: [&mut dyn train_callback_trait<f64>; 2] = [&mut first, &mut second];
+
+    let mut calls = 0;
+    let action = dispatch_callbacks(&mut callbacks, |_callback| {
+        calls = calls + 1;
+        if calls == 1 {
+            Ok(callback_action_type::STOP)
+        } else {
+            Ok(callback_action_type::CONTINUE)
+        }
+    });
+    assert_eq!(action.unwrap(), callback_action_type::STOP);
+    assert_eq!(calls, 2);
+
+    let result = dispatch_callbacks(&mut callbacks, |_callback| {
+        Err(Error::InvalidTrainState("callback failed".to_string()))
+    });
+    assert!(matches!(result, Err(Error::InvalidTrainState(_))));
+}
+
+#[test]
+fn csv_logger_writes_every_batch() {
+    let neural_network = small_network("callback", "csv", 3, 2, 4, 2).neural_network;
+    let filename = test_dir("callback", "csv").join("log.csv");
+    let filename = filename.to_str().unwrap();
+
+    let mut logger = try_create_csv_logger_callback(filename).unwrap();
+    for iteration in 0..4 {
+        let progress =
+            create_train_progress(iteration, 2, 4, 1.0, 1.0, 0.0, &[], 0.1, &neural_network);
+        logger.on_batch_end(&progress).unwrap();
+        if iteration % 2 == 1 {
+            logger.on_epoch_end(&progress).unwrap();
+        }
+    }
+
+    let contents = std::fs::read_to_string(filename).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert!(lines[0].starts_with("iteration,epoch,batch_idx"));
+    assert!(lines[4].starts_with("3,1,1,"));
+}
+
+#[test]
+fn model_checkpoint_returns_save_error() {
+    let neural_network = small_network("callback", "checkpoint", 3, 2, 4, 2).neural_network;
+    let filename = test_dir("callback", "checkpoint")
+        .join("missing")
+        .join("network.ck");
+
+    let mut callback = create_model_checkpoint_callback::<f64>(
+        filename.to_str().unwrap(),
+        callback_monitor_type::TRAIN_LOSS,
+        true,
+    );
+    let progress = create_train_progress(1, 2, 4, 1.0, 1.0, 0.0, &[], 0.1, &neural_network);
+
+    assert!(matches!(
+        callback.on_epoch_end(&progress),
+        Err(Error::Io { .. })
+    ));
+}
@@ -0,0 +1,275 @@
+#![allow(non_snake_case)]
+extern crate arrayfire;
+extern crate raybnn;
+
+mod common;
+
+use common::small_network;
+use common::test_dir;
+
+use raybnn::export::checkpoint::crc32;
+use raybnn::export::checkpoint::train_state_type;
+use raybnn::export::checkpoint::try_load_checkpoint;
+use raybnn::export::checkpoint::try_load_train_checkpoint;
+use raybnn::export::checkpoint::try_migrate_text_network;
+use raybnn::export::checkpoint::try_save_checkpoint;
+use raybnn::export::checkpoint::try_save_train_checkpoint;
+use raybnn::export::checkpoint::CHECKPOINT_MAGIC;
+use raybnn::export::checkpoint::CHECKPOINT_VERSION;
+
+use raybnn::export::dataloader::try_save_network2;
+
+use raybnn::neural::network::neural_network_type;
+
+use raybnn::optimal::optimizer::optimizer_kind_type;
+
+use raybnn::Error;
+
+// Section element types of the checkpoint format
+const SECTION_F32: u8 = 0;
+const SECTION_F64: u8 = 1;
+const SECTION_U64: u8 = 3;
+
+fn to_host<T: arrayfire::HasAfEnum + Default + Clone>(arr: &arrayfire::Array<T>) -> Vec<T> {
+    let mut out = vec![T::default(); arr.elements()];
+    arr.host(&mut out);
+    out
+}
+
+fn assert_same_network(a: &neural_network_type<f64>, b: &neural_network_type<f64>) {
+    assert_eq!(a.netdata.neuron_size, b.netdata.neuron_size);
+    assert_eq!(a.netdata.active_size, b.netdata.active_size);
+    assert_eq!(a.netdata.con_rad, b.netdata.con_rad);
+    assert_eq!(to_host(&a.WRowIdxCSR), to_host(&b.WRowIdxCSR));
+    assert_eq!(to_host(&a.WColIdx), to_host(&b.WColIdx));
+    assert_eq!(to_host(&a.neuron_idx), to_host(&b.neuron_idx));
+    assert_eq!(to_host(&a.network_params), to_host(&b.network_params));
+    assert_eq!(to_host(&a.neuron_pos), to_host(&b.neuron_pos));
+    assert_eq!(to_host(&a.glia_pos), to_host(&b.glia_pos));
+}
+
+// Checkpoint bytes with the given dtype and sections (name, elem_type, dims, data)
+fn checkpoint_bytes(dtype: &str, sections: &[(&str, u8, [u64; 4], Vec<u8>)]) -> Vec<u8> {
+    let mut out: Vec<u8> = Vec::new();
+    out.extend_from_slice(&CHECKPOINT_MAGIC);
+    out.extend_from_slice(&CHECKPOINT_VERSION.to_le_bytes());
+    out.push(dtype.len() as u8);
+    out.extend_from_slice(dtype.as_bytes());
+    out.extend_from_slice(&(sections.len() as u32).to_le_bytes());
+
+    for (name, elem_type, dims, data) in sections {
+        out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        out.extend_from_slice(name.as_bytes());
+        out.push(*elem_type);
+        for d in dims {
+            out.extend_from_slice(&d.to_le_bytes());
+        }
+        out.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        out.extend_from_slice(data);
+        out.extend_from_slice(&crc32(data).to_le_bytes());
+    }
+
+    out
+}
+
+fn u64_data(values: &[u64]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn write_file(name: &str, bytes: &[u8]) -> String {
+    let filename = test_dir("checkpoint", name).join("network.ck");
+    std::fs::write(&filename, bytes).unwrap();
+    filename.to_str().unwrap().to_string()
+}
+
+fn assert_invalid_checkpoint(result: Result<neural_network_type<f64>, Error>) {
+    match result {
+        Err(Error::InvalidCheckpoint { .. }) => (),
+        Err(e) => panic!("expected InvalidCheckpoint, found {}", e),
+        Ok(_) => panic!("expected InvalidCheckpoint, checkpoint loaded"),
+    }
+}
+
+#[test]
+fn crc32_known_answer() {
+    assert_eq!(crc32(b""), 0);
+    assert_eq!(crc32(b"123456789"), 0xCBF43926);
+}
+
+#[test]
+fn save_load_round_trip() {
+    let neural_network = small_network("checkpoint", "round_trip", 3, 2, 4, 2).neural_network;
+    let filename = test_dir("checkpoint", "round_trip").join("network.ck");
+    let filename = filename.to_str().unwrap();
+
+    try_save_checkpoint(filename, &neural_network).unwrap();
+    let loaded: neural_network_type<f64> = try_load_checkpoint(filename).unwrap();
+
+    assert_same_network(&neural_network, &loaded);
+}
+
+#[test]
+fn migrate_text_network_matches_text_file() {
+    let neural_network = small_network("checkpoint", "migrate", 3, 2, 4, 2).neural_network;
+    let dir_path = test_dir("checkpoint", "migrate");
+    let text_filename = dir_path.join("network.csv");
+    let text_filename = text_filename.to_str().unwrap();
+    let checkpoint_filename = dir_path.join("network.ck");
+    let checkpoint_filename = checkpoint_filename.to_str().unwrap();
+
+    try_save_network2(text_filename, &neural_network).unwrap();
+    try_migrate_text_network::<f64>(text_filename, checkpoint_filename).unwrap();
+    let loaded: neural_network_type<f64> = try_load_checkpoint(checkpoint_filename).unwrap();
+
+    assert_same_network(&neural_network, &loaded);
+}
+
+#[test]
+fn checksum_mismatch_is_detected() {
+    let mut bytes = checkpoint_bytes(
+        "f64",
+        &[("netdata_u64", SECTION_U64, [9, 1, 1, 1], u64_data(&[1; 9]))],
+    );
+
+    // Flip one bit of the first data byte. The data is followed by the 4 byte checksum
+    let data_start = bytes.len() - 4 - 9 * 8;
+    bytes[data_start] ^= 1;
+    let filename = write_file("checksum", &bytes);
+
+    match try_load_checkpoint::<f64>(&filename) {
+        Err(Error::ChecksumMismatch { section, .. }) => assert_eq!(section, "netdata_u64"),
+        Err(e) => panic!("expected ChecksumMismatch, found {}", e),
+        Ok(_) => panic!("expected ChecksumMismatch, checkpoint loaded"),
+    }
+}
+
+#[test]
+fn truncated_file_is_rejected() {
+    let bytes = checkpoint_bytes(
+        "f64",
+        &[("netdata_u64", SECTION_U64, [9, 1, 1, 1], u64_data(&[1; 9]))],
+    );
+
+    for len in [0, 4, 12, bytes.len() - 1] {
+        let filename = write_file("truncated", &bytes[..len]);
+        assert_invalid_checkpoint(try_load_checkpoint::<f64>(&filename));
+    }
+}
+
+#[test]
+fn missing_header_is_rejected() {
+    let filename = write_file("header", b"not a checkpoint file");
+
+    assert_invalid_checkpoint(try_load_checkpoint::<f64>(&filename));
+}
+
+#[test]
+fn overflowing_dims_are_rejected() {
+    let bytes = checkpoint_bytes(
+        "f64",
+        &[(
+            "netdata_u64",
+            SECTION_U64,
+            [u64::MAX, 2, 1, 1],
+            u64_data(&[1; 9]),
+        )],
+    );
+    let filename = write_file("overflow", &bytes);
+
+    assert_invalid_checkpoint(try_load_checkpoint::<f64>(&filename));
+}
+
+#[test]
+fn unknown_dtype_is_rejected() {
+    let bytes = checkpoint_bytes(
+        "f16",
+        &[("netdata_u64", SECTION_U64, [9, 1, 1, 1], u64_data(&[1; 9]))],
+    );
+    let filename = write_file("unknown_dtype", &bytes);
+
+    assert_invalid_checkpoint(try_load_checkpoint::<f64>(&filename));
+}
+
+#[test]
+fn section_dtype_must_match_header() {
+    let bytes = checkpoint_bytes(
+        "f32",
+        &[
+            ("netdata_u64", SECTION_U64, [9, 1, 1, 1], u64_data(&[1; 9])),
+            (
+                "netdata_float",
+                SECTION_F64,
+                [1, 1, 1, 1],
+                1.0f64.to_le_bytes().to_vec(),
+            ),
+        ],
+    );
+    let filename = write_file("dtype_mismatch", &bytes);
+    assert_invalid_checkpoint(try_load_checkpoint::<f64>(&filename));
+
+    // Same sections with a consistent dtype get past the header checks
+    let bytes = checkpoint_bytes(
+        "f32",
+        &[
+            ("netdata_u64", SECTION_U64, [9, 1, 1, 1], u64_data(&[1; 9])),
+            (
+                "netdata_float",
+                SECTION_F32,
+                [1, 1, 1, 1],
+                1.0f32.to_le_bytes().to_vec(),
+            ),
+        ],
+    );
+    let filename = write_file("dtype_match", &bytes);
+    match try_load_checkpoint::<f64>(&filename) {
+        Err(Error::InvalidCheckpoint { reason, .. }) => {
+            assert!(reason.contains("netdata has"), "{}", reason)
+        }
+        Err(e) => panic!("expected netdata size error, found {}", e),
+        Ok(_) => panic!("expected netdata size error, checkpoint loaded"),
+    }
+}
+
+#[test]
+fn train_checkpoint_keeps_optimizer_kind() {
+    let neural_network = small_network("checkpoint", "train_state", 3, 2, 4, 2).neural_network;
+    let filename = test_dir("checkpoint", "train_state").join("train.ck");
+    let filename = filename.to_str().unwrap();
+
+    let param_size = neural_network.network_params.elements() as u64;
+    let param_dims = arrayfire::Dim4::new(&[param_size, 1, 1, 1]);
+    let train_state = train_state_type {
+        epoch: 3,
+        seed: 7,
+        alpha: 0.1,
+        loss_val: 1.0,
+        first_loss: 2.0,
+        mean_loss: 1.5,
+        min_loss: 1.0,
+        loss_counter: 1,
+        global_alpha_max: 0.5,
+        cur_alpha_max: 0.5,
+        crossval_mean_loss: 1.2,
+        optimizer_kind: optimizer_kind_type::NESTEROV,
+        optimizer_state: vec![arrayfire::constant::<f64>(0.5, param_dims)],
+        scheduler_state: vec![1.0, 2.0],
+        grad: arrayfire::constant::<f64>(0.25, param_dims),
+        alpha_max_vec: vec![0.5],
+        loss_vec: vec![2.0, 1.0],
+        crossval_vec: vec![1.2],
+        alpha_history_vec: vec![0.1, 0.1],
+        idx_history_vec: vec![0, 1],
+    };
+
+    try_save_train_checkpoint(filename, &neural_network, &train_state).unwrap();
+    let (loaded, loaded_state) = try_load_train_checkpoint::<f64>(filename).unwrap();
+
+    assert_same_network(&neural_network, &loaded);
+    assert!(loaded_state.optimizer_kind == optimizer_kind_type::NESTEROV);
+    assert_eq!(loaded_state.optimizer_state.len(), 1);
+    assert_eq!(
+        to_host(&loaded_state.optimizer_state[0]),
+        to_host(&train_state.optimizer_state[0])
+    );
+    assert_eq!(loaded_state.scheduler_state, train_state.scheduler_state);
+}
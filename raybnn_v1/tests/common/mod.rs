@@ -0,0 +1,54 @@
+// Fixtures shared by the integration tests. Every test crate uses only some of them
+#![allow(dead_code)]
+
+use raybnn::interface::automatic_f64::arch_search_type;
+use raybnn::interface::builder_f64::network_builder_type;
+
+pub const BACK_END: arrayfire::Backend = arrayfire::Backend::CPU;
+pub const DEVICE: i32 = 0;
+
+// Directory raybnn_<suite>_<name> in the temporary directory, one per test
+pub fn test_dir(suite: &str, name: &str) -> std::path::PathBuf {
+    let dir_path = std::env::temp_dir().join(format!("raybnn_{}_{}", suite, name));
+    std::fs::create_dir_all(&dir_path).unwrap();
+    dir_path
+}
+
+/*
+Small network on the CPU backend with 20 active and at most 40 neurons. The arrayfire seed
+is 42, so every test of a suite starts from the same network
+
+Inputs
+suite:               Name of the test file, the first part of the directory
+name:                Name of the test, the directory of the network file
+input_size:          Number of input neurons
+output_size:         Number of output neurons
+batch_size:          Batch size
+proc_num:            Number of processing steps
+
+Outputs:
+arch_search:         Network and its metadata
+
+*/
+pub fn small_network(
+    suite: &str,
+    name: &str,
+    input_size: u64,
+    output_size: u64,
+    batch_size: u64,
+    proc_num: u64,
+) -> arch_search_type {
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+    arrayfire::set_seed(42);
+
+    let dir_path = test_dir(suite, name);
+
+    network_builder_type::new(input_size, output_size, dir_path.to_str().unwrap())
+        .active_size(20)
+        .max_neuron_size(40)
+        .batch_size(batch_size)
+        .proc_num(proc_num)
+        .build()
+        .unwrap()
+}
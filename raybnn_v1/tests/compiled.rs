@@ -0,0 +1,81 @@
+#![allow(non_snake_case)]
+extern crate arrayfire;
+extern crate raybnn;
+
+mod common;
+
+use common::small_network;
+
+use raybnn::interface::autotest_f64::test_network;
+
+use raybnn::neural::compiled::compile_network;
+
+use raybnn::Error;
+
+const TOL: f64 = 1.0e-10;
+
+#[test]
+fn compiled_matches_test_network() {
+    let batch_size = 4;
+    let traj_size = 2;
+    let arch_search = small_network("compiled", "matches", 3, 2, batch_size, 3);
+
+    let X = arrayfire::randu::<f64>(arrayfire::Dim4::new(&[3, batch_size, traj_size, 1]));
+    let mut X_cpu = vec![0.0f64; X.elements()];
+    X.host(&mut X_cpu);
+
+    let mut validationdata_X = nohash_hasher::IntMap::default();
+    validationdata_X.insert(0, X_cpu);
+    let mut Yhat_out = nohash_hasher::IntMap::default();
+    test_network(&validationdata_X, &arch_search, &mut Yhat_out);
+
+    let model = compile_network(&arch_search.neural_network).unwrap();
+    let Yhat = model.predict_batch(&X);
+    let mut Yhat_cpu = vec![0.0f64; Yhat.elements()];
+    Yhat.host(&mut Yhat_cpu);
+
+    let expected = &Yhat_out[&0];
+    assert_eq!(Yhat_cpu.len(), expected.len());
+    for (found, expected) in Yhat_cpu.iter().zip(expected.iter()) {
+        assert!(
+            (found - expected).abs() < TOL,
+            "found {}, expected {}",
+            found,
+            expected
+        );
+    }
+}
+
+#[test]
+fn zero_input_size_is_rejected() {
+    let mut arch_search = small_network("compiled", "zero_input", 3, 2, 4, 2);
+    arch_search.neural_network.netdata.input_size = 0;
+
+    assert!(matches!(
+        compile_network(&arch_search.neural_network),
+        Err(Error::InvalidNetwork(_))
+    ));
+}
+
+#[test]
+fn wrong_input_dims_are_shape_mismatch() {
+    let arch_search = small_network("compiled", "wrong_dims", 3, 2, 4, 2);
+    let model = compile_network(&arch_search.neural_network).unwrap();
+
+    let X = arrayfire::constant::<f64>(0.0, arrayfire::Dim4::new(&[5, 4, 1, 1]));
+    match model.try_predict_batch(&X) {
+        Err(Error::ShapeMismatch {
+            expected, found, ..
+        }) => {
+            assert_eq!(expected, 3);
+            assert_eq!(found, 5);
+        }
+        Err(e) => panic!("expected ShapeMismatch, found {}", e),
+        Ok(_) => panic!("expected ShapeMismatch, prediction succeeded"),
+    }
+
+    assert!(matches!(
+        model.try_predict(&[0.0; 4]),
+        Err(Error::ShapeMismatch { .. })
+    ));
+}
@@ -0,0 +1,268 @@
+#![allow(non_snake_case)]
+extern crate arrayfire;
+extern crate raybnn;
+
+use nohash_hasher;
+
+use raybnn::export::dataloader::fold_split_type;
+use raybnn::export::dataloader::try_kfold_idx;
+
+use raybnn::interface::automatic_f64::arch_search_type;
+use raybnn::interface::builder_f64::network_builder_type;
+
+use raybnn::interface::autotrain_f64::lr_strategy2_type;
+use raybnn::interface::autotrain_f64::lr_strategy_type;
+use raybnn::interface::autotrain_f64::stop_strategy_type;
+use raybnn::interface::autotrain_f64::train_network_options_type;
+
+use raybnn::interface::crossval_f64::cross_validate_network;
+use raybnn::interface::crossval_f64::crossval_options_type;
+use raybnn::interface::crossval_f64::try_cross_validate_network;
+
+use raybnn::neural::normalization::create_norm_options;
+use raybnn::neural::normalization::norm_kind_type;
+
+use raybnn::optimal::loss_f64::MSE_grad;
+use raybnn::optimal::loss_f64::MSE;
+use raybnn::optimal::optimizer::create_optimizer_options;
+use raybnn::optimal::optimizer::optimizer_kind_type;
+use raybnn::optimal::regularization::create_regularization_options;
+use raybnn::optimal::scheduler::lr_schedule_type;
+
+use raybnn::Error;
+
+const BACK_END: arrayfire::Backend = arrayfire::Backend::CPU;
+const DEVICE: i32 = 0;
+
+const INPUT_SIZE: u64 = 3;
+const OUTPUT_SIZE: u64 = 2;
+const BATCH_SIZE: u64 = 4;
+
+// Every sample appears in exactly one fold
+fn assert_partition(fold_idx: &[Vec<u64>], sample_num: u64) {
+    let mut all: Vec<u64> = fold_idx.iter().flatten().cloned().collect();
+    all.sort();
+    assert_eq!(all, (0..sample_num).collect::<Vec<u64>>());
+}
+
+// Number of samples of label in the fold
+fn label_count(fold: &[u64], labels: &[u64], label: u64) -> u64 {
+    fold.iter()
+        .filter(|idx| labels[**idx as usize] == label)
+        .count() as u64
+}
+
+#[test]
+fn stratified_folds_partition_and_balance_labels() {
+    //Unbalanced labels, 30 of label 0, 18 of label 1 and 12 of label 2
+    let labels: Vec<u64> = (0..60u64)
+        .map(|i| (i >= 30) as u64 + (i >= 48) as u64)
+        .collect();
+    let fold_num = 4;
+
+    let fold_idx = try_kfold_idx(&labels, fold_num, &fold_split_type::STRATIFIED, 3).unwrap();
+    assert_eq!(fold_idx.len(), fold_num as usize);
+    assert_partition(&fold_idx, labels.len() as u64);
+
+    for label in 0..3 {
+        let counts: Vec<u64> = fold_idx
+            .iter()
+            .map(|fold| label_count(fold, &labels, label))
+            .collect();
+        let min = *counts.iter().min().unwrap();
+        let max = *counts.iter().max().unwrap();
+        assert!(max - min <= 1, "label {} counts {:?}", label, counts);
+    }
+
+    let same_seed = try_kfold_idx(&labels, fold_num, &fold_split_type::STRATIFIED, 3).unwrap();
+    assert_eq!(fold_idx, same_seed);
+}
+
+#[test]
+fn group_folds_partition_and_keep_groups_together() {
+    //10 groups of 1 to 10 samples
+    let groups: Vec<u64> = (0..10u64).flat_map(|g| vec![g; (g + 1) as usize]).collect();
+    let labels = vec![0; groups.len()];
+    let split = fold_split_type::GROUP {
+        groups: groups.clone(),
+    };
+
+    let fold_idx = try_kfold_idx(&labels, 3, &split, 0).unwrap();
+    assert_partition(&fold_idx, labels.len() as u64);
+
+    for group in 0..10 {
+        let folds: Vec<usize> = (0..fold_idx.len())
+            .filter(|f| {
+                fold_idx[*f]
+                    .iter()
+                    .any(|idx| groups[*idx as usize] == group)
+            })
+            .collect();
+        assert_eq!(folds.len(), 1, "group {} is in folds {:?}", group, folds);
+    }
+
+    //55 samples, the largest group first into the smallest fold gives 19, 18 and 18
+    let mut sizes: Vec<usize> = fold_idx.iter().map(|fold| fold.len()).collect();
+    sizes.sort();
+    assert_eq!(sizes, vec![18, 18, 19]);
+}
+
+#[test]
+fn kfold_rejects_invalid_folds() {
+    let labels = vec![0; 10];
+
+    assert!(matches!(
+        try_kfold_idx(&labels, 1, &fold_split_type::CONTIGUOUS, 0),
+        Err(Error::InvalidDataset(_))
+    ));
+    assert!(matches!(
+        try_kfold_idx(&labels, 11, &fold_split_type::SHUFFLED, 0),
+        Err(Error::InvalidDataset(_))
+    ));
+
+    //Fewer groups than folds
+    let split = fold_split_type::GROUP {
+        groups: vec![0, 0, 0, 0, 0, 1, 1, 1, 1, 1],
+    };
+    assert!(matches!(
+        try_kfold_idx(&labels, 3, &split, 0),
+        Err(Error::InvalidDataset(_))
+    ));
+
+    //One group per sample is required
+    let split = fold_split_type::GROUP {
+        groups: vec![0, 1, 2],
+    };
+    assert!(matches!(
+        try_kfold_idx(&labels, 3, &split, 0),
+        Err(Error::InvalidDataset(_))
+    ));
+}
+
+// Regression dataset of batch_num batches
+fn dataset(
+    batch_num: u64,
+) -> (
+    nohash_hasher::IntMap<u64, Vec<f64>>,
+    nohash_hasher::IntMap<u64, Vec<f64>>,
+) {
+    let mut X = nohash_hasher::IntMap::default();
+    let mut Y = nohash_hasher::IntMap::default();
+
+    for b in 0..batch_num {
+        let mut X_cpu = Vec::new();
+        let mut Y_cpu = Vec::new();
+        for j in 0..BATCH_SIZE {
+            let t = ((b * BATCH_SIZE + j) as f64) * 0.37;
+            X_cpu.extend_from_slice(&[t.sin(), t.cos(), (0.5 * t).sin()]);
+            Y_cpu.extend_from_slice(&[(t.sin() + t.cos()) * 0.5, (0.5 * t).sin() * t.cos()]);
+        }
+        X.insert(b, X_cpu);
+        Y.insert(b, Y_cpu);
+    }
+
+    (X, Y)
+}
+
+fn create_network(fold: u64) -> arch_search_type {
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+
+    let dir_path = std::env::temp_dir().join(format!("raybnn_crossval_{}", fold));
+    std::fs::create_dir_all(&dir_path).unwrap();
+
+    network_builder_type::new(INPUT_SIZE, OUTPUT_SIZE, dir_path.to_str().unwrap())
+        .active_size(20)
+        .max_neuron_size(40)
+        .batch_size(BATCH_SIZE)
+        .seed(fold)
+        .build()
+        .unwrap()
+}
+
+fn train_options() -> train_network_options_type {
+    train_network_options_type {
+        stop_strategy: stop_strategy_type::NONE,
+        lr_strategy: lr_strategy_type::NONE,
+        lr_strategy2: lr_strategy2_type::MAX_ALPHA,
+
+        max_epoch: 3,
+        stop_epoch: 3,
+        stop_train_loss: 0.0,
+
+        exit_counter_threshold: 1000,
+        shuffle_counter_threshold: 1000,
+        optimizer: create_optimizer_options(optimizer_kind_type::ADAM),
+        lr_schedule: lr_schedule_type::CONSTANT,
+        regularization: create_regularization_options(),
+        normalization: create_norm_options(norm_kind_type::NONE),
+    }
+}
+
+fn crossval_options(fold_num: u64) -> crossval_options_type {
+    crossval_options_type {
+        fold_num: fold_num,
+        split: fold_split_type::SHUFFLED,
+        seed: 5,
+    }
+}
+
+#[test]
+fn cross_validation_rejects_small_folds() {
+    let (data_X, data_Y) = dataset(6);
+
+    let two_folds = try_cross_validate_network(
+        &data_X,
+        &data_Y,
+        create_network,
+        MSE,
+        MSE_grad,
+        &train_options(),
+        &[],
+        &crossval_options(2),
+    );
+    assert!(matches!(two_folds, Err(Error::InvalidDataset(_))));
+
+    //3 batches of 4 samples give folds of 2 samples, fewer than batch_size
+    let (data_X, data_Y) = dataset(3);
+    let undersized = try_cross_validate_network(
+        &data_X,
+        &data_Y,
+        create_network,
+        MSE,
+        MSE_grad,
+        &train_options(),
+        &[],
+        &crossval_options(6),
+    );
+    assert!(matches!(undersized, Err(Error::InvalidDataset(_))));
+}
+
+#[test]
+fn cross_validation_runs_every_fold() {
+    let fold_num = 3;
+    let (data_X, data_Y) = dataset(2 * fold_num);
+
+    let result = cross_validate_network(
+        &data_X,
+        &data_Y,
+        create_network,
+        MSE,
+        MSE_grad,
+        &train_options(),
+        &[],
+        &crossval_options(fold_num),
+    );
+
+    assert_eq!(result.folds.len(), fold_num as usize);
+    for (fold, fold_result) in result.folds.iter().enumerate() {
+        assert_eq!(fold_result.fold, fold as u64);
+        assert_eq!(fold_result.train_num, 2 * BATCH_SIZE);
+        assert_eq!(fold_result.valid_num, 2 * BATCH_SIZE);
+        assert_eq!(fold_result.test_num, 2 * BATCH_SIZE);
+        assert!(!fold_result.loss_vec.is_empty());
+        assert_eq!(fold_result.eval_metric_out.len(), 2);
+    }
+    assert!(result.mean_eval_metric.is_finite());
+    assert!(result.mean_metrics.is_empty());
+}
@@ -0,0 +1,72 @@
+extern crate arrayfire;
+extern crate raybnn;
+
+use raybnn::interface::automatic::arch_search_type;
+use raybnn::interface::automatic::create_start_archtecture;
+use raybnn::optimal::genetic::search_genetic;
+use raybnn::precision::float_trait::raybnn_float_trait;
+
+use raybnn::Error;
+
+const BACK_END: arrayfire::Backend = arrayfire::Backend::CPU;
+const DEVICE: i32 = 0;
+
+fn arch_search<T: raybnn_float_trait>(crossval: &[f64]) -> arch_search_type<T> {
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+
+    let mut arch_search = create_start_archtecture::<T>(3, 3, 2, 2, 200, 4, 1, "/tmp/");
+    arch_search.evolution_info.crossval_vec = crossval.iter().map(|c| T::from_f64(*c)).collect();
+    arch_search
+}
+
+fn empty_population_is_rejected<T: raybnn_float_trait>() {
+    let empty = arch_search::<T>(&[]);
+    assert!(matches!(
+        search_genetic(&empty, 4),
+        Err(Error::InvalidNetwork(_))
+    ));
+
+    let evaluated = arch_search::<T>(&[0.3, 0.1, 0.2]);
+    assert!(matches!(
+        search_genetic(&evaluated, 0),
+        Err(Error::InvalidNetwork(_))
+    ));
+}
+
+fn parents_are_from_the_population<T: raybnn_float_trait>() {
+    //The population is the two models with the lowest crossval, indices 1 and 3
+    let evaluated = arch_search::<T>(&[0.5, 0.1, 0.4, 0.2, 0.9]);
+
+    for _ in 0..20 {
+        let (parent_a, parent_b) = search_genetic(&evaluated, 2).unwrap();
+
+        assert!([1, 3].contains(&parent_a), "parent_a {}", parent_a);
+        assert!([1, 3].contains(&parent_b), "parent_b {}", parent_b);
+        assert_ne!(parent_a, parent_b);
+    }
+
+    //A single model is both parents
+    let single = arch_search::<T>(&[0.7]);
+    assert_eq!(search_genetic(&single, 4).unwrap(), (0, 0));
+}
+
+#[test]
+fn empty_population_is_rejected_f32() {
+    empty_population_is_rejected::<f32>();
+}
+
+#[test]
+fn empty_population_is_rejected_f64() {
+    empty_population_is_rejected::<f64>();
+}
+
+#[test]
+fn parents_are_from_the_population_f32() {
+    parents_are_from_the_population::<f32>();
+}
+
+#[test]
+fn parents_are_from_the_population_f64() {
+    parents_are_from_the_population::<f64>();
+}
@@ -0,0 +1,256 @@
+#![allow(non_snake_case)]
+extern crate arrayfire;
+extern crate raybnn;
+
+use raybnn::neural::network_f64::create_nullnetdata;
+
+use raybnn::physics::geometry::boundary_region_type;
+use raybnn::physics::geometry::cylinder_type;
+use raybnn::physics::geometry::ellipsoid_type;
+use raybnn::physics::geometry::geometry_cell_collision;
+use raybnn::physics::geometry::geometry_trait;
+use raybnn::physics::geometry::non_overlap_idx;
+use raybnn::physics::geometry::project_to_surface;
+use raybnn::physics::geometry::sample_inside;
+use raybnn::physics::geometry::sdf_geometry_type;
+use raybnn::physics::geometry::slab_type;
+use raybnn::physics::geometry::surface_neurons;
+use raybnn::physics::geometry::torus_type;
+
+use raybnn::physics::initial_f64::select_non_overlap;
+
+const BACK_END: arrayfire::Backend = arrayfire::Backend::CPU;
+const DEVICE: i32 = 0;
+
+const TOL: f64 = 1.0e-4;
+
+fn setup(seed: u64) {
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+    arrayfire::set_seed(seed);
+}
+
+fn to_host<T: arrayfire::HasAfEnum + Default + Clone>(arr: &arrayfire::Array<T>) -> Vec<T> {
+    let mut arr_cpu = vec![T::default(); arr.elements()];
+    arr.host(&mut arr_cpu);
+    arr_cpu
+}
+
+// Positions from rows of coordinates
+fn positions(rows: &[[f64; 3]]) -> arrayfire::Array<f64> {
+    let mut pos_cpu = vec![0.0; rows.len() * 3];
+    for (i, row) in rows.iter().enumerate() {
+        for d in 0..3 {
+            pos_cpu[d * rows.len() + i] = row[d];
+        }
+    }
+    arrayfire::Array::new(
+        &pos_cpu,
+        arrayfire::Dim4::new(&[rows.len() as u64, 3, 1, 1]),
+    )
+}
+
+// Uniform random positions in the cube [-half_width, half_width]^3
+fn random_pos(pos_num: u64, half_width: f64) -> arrayfire::Array<f64> {
+    let pos = arrayfire::randu::<f64>(arrayfire::Dim4::new(&[pos_num, 3, 1, 1]));
+    (pos * (2.0 * half_width)) - half_width
+}
+
+fn sphere(radius: f64) -> ellipsoid_type<f64> {
+    ellipsoid_type {
+        center: vec![0.0; 3],
+        radii: vec![radius; 3],
+    }
+}
+
+// Signed distances at the rows and the bounding box
+fn probe(geometry: &dyn geometry_trait<f64>, rows: &[[f64; 3]]) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+    let (lo, hi) = geometry.bounding_box();
+    (to_host(&geometry.sdf(&positions(rows))), lo, hi)
+}
+
+#[test]
+fn sdf_signs_and_bounding_boxes() {
+    setup(0);
+
+    let ellipsoid = ellipsoid_type {
+        center: vec![0.0; 3],
+        radii: vec![1.0, 2.0, 3.0],
+    };
+    let (dist, lo, hi) = probe(&ellipsoid, &[[0.0, 0.0, 0.0], [0.0, 0.0, 4.0]]);
+    assert!((dist[0] + 1.0).abs() < TOL);
+    assert!(dist[1] > 0.0);
+    assert_eq!(lo, vec![-1.0, -2.0, -3.0]);
+    assert_eq!(hi, vec![1.0, 2.0, 3.0]);
+
+    let cylinder = cylinder_type {
+        center: vec![0.0; 3],
+        radius: 1.0,
+        half_length: 2.0,
+        axis: 2,
+    };
+    let (dist, lo, hi) = probe(
+        &cylinder,
+        &[[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 0.0, 3.0]],
+    );
+    assert!((dist[0] + 1.0).abs() < TOL);
+    assert!((dist[1] - 1.0).abs() < TOL);
+    assert!((dist[2] - 1.0).abs() < TOL);
+    assert_eq!(lo, vec![-1.0, -1.0, -2.0]);
+    assert_eq!(hi, vec![1.0, 1.0, 2.0]);
+
+    let torus = torus_type {
+        center: vec![0.0; 3],
+        major_rad: 2.0,
+        minor_rad: 0.5,
+    };
+    let (dist, lo, hi) = probe(&torus, &[[2.0, 0.0, 0.0], [0.0, 0.0, 0.0]]);
+    assert!((dist[0] + 0.5).abs() < TOL);
+    assert!((dist[1] - 1.5).abs() < TOL);
+    assert_eq!(lo, vec![-2.5, -2.5, -0.5]);
+    assert_eq!(hi, vec![2.5, 2.5, 0.5]);
+
+    //Layers [0, 0.4] and [0.6, 1] along z
+    let slab = slab_type {
+        lo: vec![0.0; 3],
+        hi: vec![1.0; 3],
+        axis: 2,
+        layer_num: 2,
+        gap: 0.2,
+    };
+    let (dist, lo, hi) = probe(
+        &slab,
+        &[
+            [0.5, 0.5, 0.2],
+            [0.5, 0.5, 0.5],
+            [0.5, 0.5, 0.8],
+            [2.0, 0.5, 0.2],
+        ],
+    );
+    assert!((dist[0] + 0.2).abs() < TOL);
+    assert!((dist[1] - 0.1).abs() < TOL);
+    assert!((dist[2] + 0.2).abs() < TOL);
+    assert!(dist[3] > 0.0);
+    assert_eq!(lo, vec![0.0; 3]);
+    assert_eq!(hi, vec![1.0; 3]);
+
+    let closure = sdf_geometry_type {
+        sdf: Box::new(|pos: &arrayfire::Array<f64>| arrayfire::col(pos, 0) - 1.0),
+        lo: vec![-1.0; 3],
+        hi: vec![1.0; 3],
+    };
+    let (dist, lo, hi) = probe(&closure, &[[0.0, 0.0, 0.0], [3.0, 0.0, 0.0]]);
+    assert!((dist[0] + 1.0).abs() < TOL);
+    assert!((dist[1] - 2.0).abs() < TOL);
+    assert_eq!(lo, vec![-1.0; 3]);
+    assert_eq!(hi, vec![1.0; 3]);
+}
+
+#[test]
+fn sample_inside_respects_margin() {
+    setup(1);
+
+    let geometry = sphere(2.0);
+    let margin = 0.3;
+
+    let pos = sample_inside(&geometry, margin, 500);
+    assert_eq!(pos.dims()[0], 500);
+    assert_eq!(pos.dims()[1], 3);
+
+    let dist = to_host(&geometry.sdf(&pos));
+    assert!(dist.iter().all(|&d| d <= -margin));
+}
+
+#[test]
+fn non_overlap_idx_agrees_with_select_non_overlap() {
+    setup(2);
+
+    let neuron_rad = 0.2;
+    let pos = random_pos(300, 4.0);
+    let pos_num = pos.dims()[0] as u32;
+
+    let mut keep = to_host(&non_overlap_idx(&pos, neuron_rad));
+    keep.sort();
+
+    //select_non_overlap returns the collided rows
+    let collided = to_host(&select_non_overlap(&pos, neuron_rad));
+    let expected: Vec<u32> = (0..pos_num).filter(|i| !collided.contains(i)).collect();
+
+    assert!(!collided.is_empty());
+    assert_eq!(keep, expected);
+}
+
+#[test]
+fn project_to_surface_reaches_boundary() {
+    setup(3);
+
+    let geometry = ellipsoid_type {
+        center: vec![0.5, 0.0, -0.5],
+        radii: vec![1.0, 2.0, 1.5],
+    };
+
+    let pos = project_to_surface(&geometry, &random_pos(200, 4.0));
+    assert_eq!(pos.dims()[0], 200);
+
+    let dist = to_host(&geometry.sdf(&pos));
+    assert!(dist.iter().all(|d| d.abs() < TOL));
+}
+
+#[test]
+fn surface_neurons_respects_region() {
+    setup(4);
+
+    let geometry = sphere(1.0);
+
+    let half_space = boundary_region_type::HALF_SPACE {
+        normal: vec![0.0, 0.0, 1.0],
+        offset: 0.2,
+    };
+    let pos = surface_neurons(&geometry, &half_space, 20, 0.0);
+    assert_eq!(pos.dims()[0], 20);
+
+    let z = to_host(&arrayfire::col(&pos, 2));
+    assert!(z.iter().all(|&z| z >= 0.2));
+    let dist = to_host(&geometry.sdf(&pos));
+    assert!(dist.iter().all(|d| d.abs() < TOL));
+
+    let cube = boundary_region_type::BOX {
+        lo: vec![0.0, 0.0, -1.0],
+        hi: vec![1.0, 1.0, 1.0],
+    };
+    let pos = surface_neurons(&geometry, &cube, 10, 0.0);
+    assert_eq!(pos.dims()[0], 10);
+    let pos_cpu = to_host(&pos);
+    assert!(pos_cpu[..20].iter().all(|&x| x >= 0.0));
+
+    let pos = surface_neurons(&geometry, &boundary_region_type::ALL, 0, 0.0);
+    assert_eq!(pos.dims()[0], 0);
+    assert_eq!(pos.dims()[1], 3);
+}
+
+#[test]
+fn geometry_cell_collision_handles_empty_parts() {
+    setup(5);
+
+    let geometry = sphere(3.0);
+
+    let mut netdata = create_nullnetdata();
+    netdata.active_size = 50;
+    netdata.neuron_rad = 0.1;
+
+    let temp_dims = arrayfire::Dim4::new(&[4, 1, 1, 1]);
+    let mut glia_pos = arrayfire::constant::<f64>(0.0, temp_dims);
+    let mut neuron_pos = arrayfire::constant::<f64>(0.0, temp_dims);
+
+    //No neurons
+    netdata.nratio = 0.0;
+    geometry_cell_collision(&netdata, &geometry, &mut glia_pos, &mut neuron_pos);
+    assert_eq!(neuron_pos.dims()[0], 0);
+    assert!(glia_pos.dims()[0] > 0);
+
+    //No glial cells
+    netdata.nratio = 1.0;
+    geometry_cell_collision(&netdata, &geometry, &mut glia_pos, &mut neuron_pos);
+    assert!(neuron_pos.dims()[0] > 0);
+    assert_eq!(glia_pos.dims()[0], 0);
+}
@@ -0,0 +1,212 @@
+#![allow(non_snake_case)]
+extern crate arrayfire;
+extern crate raybnn;
+
+mod common;
+
+use common::small_network;
+
+use raybnn::neural::normalization::create_norm_options;
+use raybnn::neural::normalization::create_norm_state;
+use raybnn::neural::normalization::norm_kind_type;
+
+use raybnn::optimal::gradcheck_f64::create_gradcheck_options;
+use raybnn::optimal::gradcheck_f64::gradcheck_result_type;
+use raybnn::optimal::gradcheck_f64::gradient_check;
+use raybnn::optimal::gradcheck_f64::try_gradient_check;
+
+use raybnn::optimal::loss::cross_entropy_loss_type;
+use raybnn::optimal::loss::loss_metric;
+use raybnn::optimal::loss::loss_metric_grad;
+use raybnn::optimal::loss_f64::MSE_grad;
+use raybnn::optimal::loss_f64::MSE;
+
+fn one_hot(output_size: u64, batch_size: u64) -> arrayfire::Array<f64> {
+    let mut Y_cpu = vec![0.0f64; (output_size * batch_size) as usize];
+    for j in 0..batch_size {
+        Y_cpu[(j * output_size + (j % output_size)) as usize] = 1.0;
+    }
+
+    arrayfire::Array::new(
+        &Y_cpu,
+        arrayfire::Dim4::new(&[output_size, batch_size, 1, 1]),
+    )
+}
+
+fn assert_passed(result: &gradcheck_result_type) {
+    assert!(result.param_idx.len() > 0);
+    assert!(
+        result.passed,
+        "gradient check failed at {:?}, max_rel_error {} at index {}",
+        result.failed_idx, result.max_rel_error, result.worst_idx
+    );
+}
+
+#[test]
+fn gradcheck_mse() {
+    let arch_search = small_network("gradcheck", "mse", 3, 2, 4, 2);
+
+    let X = arrayfire::randu::<f64>(arrayfire::Dim4::new(&[3, 4, 1, 1]));
+    let Y = arrayfire::randu::<f64>(arrayfire::Dim4::new(&[2, 4, 1, 1]));
+
+    let result = gradient_check(
+        &arch_search.neural_network,
+        &X,
+        &Y,
+        MSE,
+        MSE_grad,
+        &create_gradcheck_options(),
+    );
+
+    assert_passed(&result);
+}
+
+#[test]
+fn gradcheck_cross_entropy_deep() {
+    let arch_search = small_network("gradcheck", "cross_entropy", 4, 3, 6, 4);
+
+    let X = arrayfire::randn::<f64>(arrayfire::Dim4::new(&[4, 6, 1, 1]));
+    let Y = one_hot(3, 6);
+
+    let loss = cross_entropy_loss_type {
+        label_smoothing: 0.1,
+        class_weight: None,
+    };
+
+    let mut options = create_gradcheck_options();
+    options.sample_size = 30;
+
+    let result = gradient_check(
+        &arch_search.neural_network,
+        &X,
+        &Y,
+        loss_metric(&loss),
+        loss_metric_grad(&loss),
+        &options,
+    );
+
+    assert_passed(&result);
+}
+
+#[test]
+fn gradcheck_batch_norm() {
+    let mut arch_search = small_network("gradcheck", "batch_norm", 3, 2, 8, 3);
+
+    let mut norm = create_norm_state(create_norm_options(norm_kind_type::BATCH_NORM));
+    norm.training = true;
+    arch_search.neural_network.norm = norm;
+
+    let X = arrayfire::randu::<f64>(arrayfire::Dim4::new(&[3, 8, 1, 1]));
+    let Y = arrayfire::randu::<f64>(arrayfire::Dim4::new(&[2, 8, 1, 1]));
+
+    let result = gradient_check(
+        &arch_search.neural_network,
+        &X,
+        &Y,
+        MSE,
+        MSE_grad,
+        &create_gradcheck_options(),
+    );
+
+    assert_passed(&result);
+}
+
+// Layer norm statistics mix every neuron, including neurons that are not on a path to the output
+#[test]
+fn gradcheck_layer_norm() {
+    let mut arch_search = small_network("gradcheck", "layer_norm", 3, 2, 5, 3);
+
+    arch_search.neural_network.norm =
+        create_norm_state(create_norm_options(norm_kind_type::LAYER_NORM));
+
+    let X = arrayfire::randn::<f64>(arrayfire::Dim4::new(&[3, 5, 1, 1]));
+    let Y = arrayfire::randu::<f64>(arrayfire::Dim4::new(&[2, 5, 1, 1]));
+
+    let result = gradient_check(
+        &arch_search.neural_network,
+        &X,
+        &Y,
+        MSE,
+        MSE_grad,
+        &create_gradcheck_options(),
+    );
+
+    assert_passed(&result);
+}
+
+// L1 and L2 penalties of the weights and of the UAF parameters
+#[test]
+fn gradcheck_regularization() {
+    let arch_search = small_network("gradcheck", "regularization", 3, 2, 4, 2);
+
+    let X = arrayfire::randu::<f64>(arrayfire::Dim4::new(&[3, 4, 1, 1]));
+    let Y = arrayfire::randu::<f64>(arrayfire::Dim4::new(&[2, 4, 1, 1]));
+
+    let mut options = create_gradcheck_options();
+    options.regularization.weight.l1 = 0.01;
+    options.regularization.weight.l2 = 0.1;
+    options.regularization.uaf.l1 = 0.02;
+    options.regularization.uaf.l2 = 0.05;
+
+    let result = gradient_check(&arch_search.neural_network, &X, &Y, MSE, MSE_grad, &options);
+
+    assert_passed(&result);
+
+    // The penalty must change the checked gradient
+    let plain = gradient_check(
+        &arch_search.neural_network,
+        &X,
+        &Y,
+        MSE,
+        MSE_grad,
+        &create_gradcheck_options(),
+    );
+
+    assert!(plain
+        .analytic
+        .iter()
+        .zip(result.analytic.iter())
+        .any(|(a, b)| (a - b).abs() > 1.0e-6));
+}
+
+// The harness must notice a wrong gradient
+#[test]
+fn gradcheck_detects_wrong_gradient() {
+    let arch_search = small_network("gradcheck", "wrong_gradient", 3, 2, 4, 2);
+
+    let X = arrayfire::randu::<f64>(arrayfire::Dim4::new(&[3, 4, 1, 1]));
+    let Y = arrayfire::randu::<f64>(arrayfire::Dim4::new(&[2, 4, 1, 1]));
+
+    let wrong_grad =
+        |yhat: &arrayfire::Array<f64>, y: &arrayfire::Array<f64>| MSE_grad(yhat, y) * 2.0f64;
+
+    let result = gradient_check(
+        &arch_search.neural_network,
+        &X,
+        &Y,
+        MSE,
+        wrong_grad,
+        &create_gradcheck_options(),
+    );
+
+    assert!(!result.passed);
+}
+
+#[test]
+fn gradcheck_rejects_wrong_dims() {
+    let arch_search = small_network("gradcheck", "wrong_dims", 3, 2, 4, 2);
+
+    let X = arrayfire::randu::<f64>(arrayfire::Dim4::new(&[5, 4, 1, 1]));
+    let Y = arrayfire::randu::<f64>(arrayfire::Dim4::new(&[2, 4, 1, 1]));
+
+    let result = try_gradient_check(
+        &arch_search.neural_network,
+        &X,
+        &Y,
+        MSE,
+        MSE_grad,
+        &create_gradcheck_options(),
+    );
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,214 @@
+#![allow(non_snake_case)]
+extern crate arrayfire;
+extern crate raybnn;
+
+use raybnn::optimal::loss::cross_entropy_loss_type;
+use raybnn::optimal::loss::focal_loss_type;
+use raybnn::optimal::loss::hinge_loss_type;
+use raybnn::optimal::loss::huber_loss_type;
+use raybnn::optimal::loss::kl_divergence_loss_type;
+use raybnn::optimal::loss::loss_trait;
+use raybnn::optimal::loss::mae_loss_type;
+use raybnn::optimal::loss::mse_loss_type;
+use raybnn::optimal::loss::poisson_nll_loss_type;
+use raybnn::optimal::loss::quantile_loss_type;
+use raybnn::optimal::loss::sigmoid_cross_entropy_loss_type;
+
+const BACK_END: arrayfire::Backend = arrayfire::Backend::CPU;
+const DEVICE: i32 = 0;
+
+const OUTPUT_SIZE: u64 = 3;
+const BATCH_SIZE: u64 = 4;
+
+const STEP: f64 = 1.0e-6;
+const TOL: f64 = 1.0e-6;
+
+fn to_array(values: &[f64]) -> arrayfire::Array<f64> {
+    arrayfire::Array::new(
+        values,
+        arrayfire::Dim4::new(&[OUTPUT_SIZE, BATCH_SIZE, 1, 1]),
+    )
+}
+
+fn to_host(arr: &arrayfire::Array<f64>) -> Vec<f64> {
+    let mut out = vec![0.0f64; arr.elements()];
+    arr.host(&mut out);
+    out
+}
+
+fn elements() -> usize {
+    (OUTPUT_SIZE * BATCH_SIZE) as usize
+}
+
+// Predictions that stay away from the kinks of hinge, Huber, MAE and quantile losses
+fn yhat_values() -> Vec<f64> {
+    (0..elements())
+        .map(|j| 1.5 * (1.3 * (j as f64) + 0.4).sin())
+        .collect()
+}
+
+fn regression_target() -> Vec<f64> {
+    (0..elements()).map(|j| (0.7 * (j as f64)).cos()).collect()
+}
+
+fn one_hot_target() -> Vec<f64> {
+    (0..elements())
+        .map(|j| {
+            let row = (j as u64) % OUTPUT_SIZE;
+            let col = (j as u64) / OUTPUT_SIZE;
+            if row == (col % OUTPUT_SIZE) {
+                1.0
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+// Every column is a probability distribution
+fn distribution_target() -> Vec<f64> {
+    let mut y = vec![0.0f64; elements()];
+    for col in 0..(BATCH_SIZE as usize) {
+        let start = col * (OUTPUT_SIZE as usize);
+        let weights: Vec<f64> = (0..(OUTPUT_SIZE as usize))
+            .map(|row| 1.0 + (((start + row) as f64) * 0.9).sin().abs())
+            .collect();
+        let total: f64 = weights.iter().sum();
+        for row in 0..(OUTPUT_SIZE as usize) {
+            y[start + row] = weights[row] / total;
+        }
+    }
+    y
+}
+
+fn class_weight() -> Option<arrayfire::Array<f64>> {
+    Some(arrayfire::Array::new(
+        &[0.5, 1.0, 2.0],
+        arrayfire::Dim4::new(&[OUTPUT_SIZE, 1, 1, 1]),
+    ))
+}
+
+// Compares grad() with a central difference of loss() at every element of yhat
+fn check_grad(loss: &dyn loss_trait<f64>, yhat: &[f64], y: &[f64]) {
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+
+    let y_arr = to_array(y);
+    let grad = to_host(&loss.grad(&to_array(yhat), &y_arr));
+
+    for j in 0..yhat.len() {
+        let mut plus = yhat.to_vec();
+        plus[j] += STEP;
+        let mut minus = yhat.to_vec();
+        minus[j] -= STEP;
+
+        let numeric = (loss.loss(&to_array(&plus), &y_arr) - loss.loss(&to_array(&minus), &y_arr))
+            / (2.0 * STEP);
+
+        assert!(
+            (grad[j] - numeric).abs() <= TOL * (1.0 + numeric.abs()),
+            "element {}: analytic {} numeric {}",
+            j,
+            grad[j],
+            numeric
+        );
+    }
+}
+
+#[test]
+fn cross_entropy_grad() {
+    let loss = cross_entropy_loss_type {
+        label_smoothing: 0.1,
+        class_weight: class_weight(),
+    };
+    check_grad(&loss, &yhat_values(), &one_hot_target());
+}
+
+#[test]
+fn sigmoid_cross_entropy_grad() {
+    let loss = sigmoid_cross_entropy_loss_type {
+        pos_weight: 2.0,
+        class_weight: class_weight(),
+    };
+    check_grad(&loss, &yhat_values(), &one_hot_target());
+}
+
+#[test]
+fn focal_grad() {
+    let loss = focal_loss_type {
+        gamma: 2.0,
+        alpha: 0.25,
+        class_weight: class_weight(),
+    };
+    check_grad(&loss, &yhat_values(), &one_hot_target());
+}
+
+#[test]
+fn hinge_grad() {
+    let loss = hinge_loss_type {
+        class_weight: class_weight(),
+    };
+    check_grad(&loss, &yhat_values(), &one_hot_target());
+}
+
+#[test]
+fn kl_divergence_grad() {
+    let loss = kl_divergence_loss_type {};
+    check_grad(&loss, &yhat_values(), &distribution_target());
+}
+
+#[test]
+fn poisson_nll_grad() {
+    let counts: Vec<f64> = (0..elements()).map(|j| (j % 4) as f64).collect();
+
+    let loss = poisson_nll_loss_type { log_input: true };
+    check_grad(&loss, &yhat_values(), &counts);
+
+    let rates: Vec<f64> = yhat_values().iter().map(|v| v.abs() + 0.5).collect();
+    let loss = poisson_nll_loss_type { log_input: false };
+    check_grad(&loss, &rates, &counts);
+}
+
+// Rates at or below zero are clamped inside the log, so the loss stays finite
+#[test]
+fn poisson_nll_clamps_rate() {
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+
+    let loss = poisson_nll_loss_type { log_input: false };
+    let mut rates = yhat_values();
+    rates[0] = 0.0;
+    rates[1] = -0.5;
+    let counts = vec![1.0f64; elements()];
+
+    let value = loss.loss(&to_array(&rates), &to_array(&counts));
+    assert!(value.is_finite());
+
+    let grad = to_host(&loss.grad(&to_array(&rates), &to_array(&counts)));
+    assert!(grad.iter().all(|g| g.is_finite()));
+    assert_eq!(grad[1], 1.0 / (elements() as f64));
+}
+
+#[test]
+fn huber_grad() {
+    let loss = huber_loss_type { delta: 0.5 };
+    check_grad(&loss, &yhat_values(), &regression_target());
+}
+
+#[test]
+fn quantile_grad() {
+    let loss = quantile_loss_type { quantile: 0.3 };
+    check_grad(&loss, &yhat_values(), &regression_target());
+}
+
+#[test]
+fn mse_grad() {
+    let loss = mse_loss_type {};
+    check_grad(&loss, &yhat_values(), &regression_target());
+}
+
+#[test]
+fn mae_grad() {
+    let loss = mae_loss_type {};
+    check_grad(&loss, &yhat_values(), &regression_target());
+}
@@ -0,0 +1,145 @@
+#![allow(non_snake_case)]
+extern crate arrayfire;
+extern crate raybnn;
+
+use raybnn::optimal::loss::MAE;
+use raybnn::optimal::loss::RMSE;
+
+use raybnn::optimal::metrics::accuracy;
+use raybnn::optimal::metrics::averaging_type;
+use raybnn::optimal::metrics::class_confusion_matrix;
+use raybnn::optimal::metrics::precision;
+use raybnn::optimal::metrics::recall;
+use raybnn::optimal::metrics::F1_score;
+use raybnn::optimal::metrics::R2;
+
+const BACK_END: arrayfire::Backend = arrayfire::Backend::CPU;
+const DEVICE: i32 = 0;
+
+const TOL: f64 = 1.0e-12;
+
+const LABEL_NUM: u64 = 3;
+
+// True and predicted classes of six samples. Class 0 has three samples, class 1 two and class 2 one
+const TRUTH: [usize; 6] = [0, 0, 0, 1, 1, 2];
+const PRED: [usize; 6] = [0, 0, 1, 1, 2, 2];
+
+fn assert_close(value: f64, expected: f64) {
+    assert!(
+        (value - expected).abs() <= TOL,
+        "value {} expected {}",
+        value,
+        expected
+    );
+}
+
+fn to_array(values: &[f64], rows: u64) -> arrayfire::Array<f64> {
+    arrayfire::Array::new(
+        values,
+        arrayfire::Dim4::new(&[rows, (values.len() as u64) / rows, 1, 1]),
+    )
+}
+
+// Logits with the largest value at the predicted class and one-hot targets
+fn multiclass() -> (arrayfire::Array<f64>, arrayfire::Array<f64>) {
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+
+    let size = LABEL_NUM as usize;
+    let mut yhat = vec![0.0f64; TRUTH.len() * size];
+    let mut y = vec![0.0f64; TRUTH.len() * size];
+    for s in 0..TRUTH.len() {
+        yhat[s * size + PRED[s]] = 2.0;
+        y[s * size + TRUTH[s]] = 1.0;
+    }
+
+    (to_array(&yhat, LABEL_NUM), to_array(&y, LABEL_NUM))
+}
+
+// Single output row. TP = 2, FP = 1, FN = 2 and TN = 1
+fn binary() -> (arrayfire::Array<f64>, arrayfire::Array<f64>) {
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+
+    let yhat = [2.0, -1.0, 3.0, -2.0, 0.5, -0.5];
+    let y = [1.0, 0.0, 0.0, 1.0, 1.0, 1.0];
+
+    (to_array(&yhat, 1), to_array(&y, 1))
+}
+
+#[test]
+fn confusion_matrix_rows_are_true_classes() {
+    let (yhat, y) = multiclass();
+
+    let confusion = class_confusion_matrix(&yhat, &y);
+    assert_eq!(confusion.dims()[0], LABEL_NUM);
+    assert_eq!(confusion.dims()[1], LABEL_NUM);
+
+    let mut confusion_cpu = vec![0u32; confusion.elements()];
+    confusion.host(&mut confusion_cpu);
+
+    // Column major, confusion[true + pred*LABEL_NUM]
+    assert_eq!(confusion_cpu, vec![2, 0, 0, 1, 1, 0, 0, 1, 1]);
+}
+
+#[test]
+fn multiclass_accuracy() {
+    let (yhat, y) = multiclass();
+    assert_close(accuracy(&yhat, &y), 4.0 / 6.0);
+}
+
+#[test]
+fn multiclass_precision() {
+    let (yhat, y) = multiclass();
+
+    // Per class precision 1, 1/2, 1/2
+    assert_close(precision(&yhat, &y, averaging_type::MACRO), 2.0 / 3.0);
+    assert_close(precision(&yhat, &y, averaging_type::MICRO), 4.0 / 6.0);
+    assert_close(precision(&yhat, &y, averaging_type::WEIGHTED), 4.5 / 6.0);
+}
+
+#[test]
+fn multiclass_recall() {
+    let (yhat, y) = multiclass();
+
+    // Per class recall 2/3, 1/2, 1
+    assert_close(recall(&yhat, &y, averaging_type::MACRO), 13.0 / 18.0);
+    assert_close(recall(&yhat, &y, averaging_type::MICRO), 4.0 / 6.0);
+    assert_close(recall(&yhat, &y, averaging_type::WEIGHTED), 4.0 / 6.0);
+}
+
+#[test]
+fn multiclass_F1() {
+    let (yhat, y) = multiclass();
+
+    // Per class F1 4/5, 1/2, 2/3
+    assert_close(F1_score(&yhat, &y, averaging_type::MACRO), 59.0 / 90.0);
+    assert_close(F1_score(&yhat, &y, averaging_type::MICRO), 4.0 / 6.0);
+    assert_close(F1_score(&yhat, &y, averaging_type::WEIGHTED), 61.0 / 90.0);
+}
+
+#[test]
+fn binary_metrics_use_positive_class() {
+    let (yhat, y) = binary();
+
+    assert_close(accuracy(&yhat, &y), 3.0 / 6.0);
+    assert_close(precision(&yhat, &y, averaging_type::MACRO), 2.0 / 3.0);
+    assert_close(recall(&yhat, &y, averaging_type::MACRO), 2.0 / 4.0);
+    assert_close(F1_score(&yhat, &y, averaging_type::MACRO), 4.0 / 7.0);
+}
+
+#[test]
+fn regression_metrics() {
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+
+    let yhat = to_array(&[1.0, 2.0, 3.0, 4.0], 1);
+    let y = to_array(&[1.5, 2.0, 2.0, 5.0], 1);
+
+    // Residuals 0.5, 0, -1, 1 and Var(y) = 1.921875
+    assert_close(MAE(&yhat, &y), 0.625);
+    assert_close(RMSE(&yhat, &y), 0.75);
+    assert_close(R2(&yhat, &y), 29.0 / 41.0);
+
+    assert_close(R2(&y, &y), 1.0);
+}
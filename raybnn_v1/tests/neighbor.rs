@@ -0,0 +1,91 @@
+#![allow(non_snake_case)]
+extern crate arrayfire;
+extern crate raybnn;
+
+use raybnn::neural::network_f64::create_nullnetdata;
+use raybnn::neural::network_f64::network_metadata_type;
+
+use raybnn::physics::dynamic_f64::run;
+use raybnn::physics::neighbor::neighbor_search_type;
+
+const BACK_END: arrayfire::Backend = arrayfire::Backend::CPU;
+const DEVICE: i32 = 0;
+
+const TOL: f64 = 1.0e-9;
+
+fn to_host<T: arrayfire::HasAfEnum + Default + Clone>(arr: &arrayfire::Array<T>) -> Vec<T> {
+    let mut arr_cpu = vec![T::default(); arr.elements()];
+    arr.host(&mut arr_cpu);
+    arr_cpu
+}
+
+// Scattered positions in the cube [-half_width, half_width]^3, fixed by offset
+fn cell_pos(cell_num: u64, half_width: f64, offset: f64) -> arrayfire::Array<f64> {
+    let pos_cpu: Vec<f64> = (0..cell_num * 3)
+        .map(|k| {
+            let x = ((k as f64) + offset) * 0.618033988749895;
+            (2.0 * x.fract() - 1.0) * half_width
+        })
+        .collect();
+
+    arrayfire::Array::new(&pos_cpu, arrayfire::Dim4::new(&[cell_num, 3, 1, 1]))
+}
+
+// Simulation settings of network_builder_type with a short run
+fn netdata(neighbor_search: neighbor_search_type) -> network_metadata_type {
+    let mut netdata = create_nullnetdata();
+    netdata.space_dims = 3;
+    netdata.step_num = 20;
+    netdata.time_step = 0.1;
+    netdata.neuron_rad = 0.1;
+    netdata.con_rad = 0.8;
+    netdata.center_const = 0.005;
+    netdata.spring_const = 0.01;
+    netdata.repel_const = 0.01;
+    netdata.neighbor_search = neighbor_search;
+    netdata
+}
+
+#[test]
+fn cell_list_matches_dense() {
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+
+    let neuron_pos = cell_pos(40, 2.0, 0.3);
+    let glia_pos = cell_pos(60, 2.0, 0.7);
+
+    let mut dense_glia_pos = glia_pos.copy();
+    let mut dense_neuron_pos = neuron_pos.copy();
+    run(
+        &netdata(neighbor_search_type::DENSE),
+        &mut dense_glia_pos,
+        &mut dense_neuron_pos,
+    );
+
+    let mut cell_list_glia_pos = glia_pos.copy();
+    let mut cell_list_neuron_pos = neuron_pos.copy();
+    run(
+        &netdata(neighbor_search_type::CELL_LIST),
+        &mut cell_list_glia_pos,
+        &mut cell_list_neuron_pos,
+    );
+
+    //The cells moved
+    let start = to_host(&neuron_pos);
+    let dense = to_host(&dense_neuron_pos);
+    assert!(start
+        .iter()
+        .zip(dense.iter())
+        .any(|(a, b)| (a - b).abs() > TOL));
+
+    let pairs = [
+        (dense, to_host(&cell_list_neuron_pos)),
+        (to_host(&dense_glia_pos), to_host(&cell_list_glia_pos)),
+    ];
+    for (dense, cell_list) in pairs.iter() {
+        assert_eq!(dense.len(), cell_list.len());
+        for (a, b) in dense.iter().zip(cell_list.iter()) {
+            assert!((a - b).abs() < TOL, "dense {} cell list {}", a, b);
+        }
+    }
+}
@@ -0,0 +1,246 @@
+#![allow(non_snake_case)]
+extern crate arrayfire;
+extern crate raybnn;
+
+use raybnn::optimal::optimizer::create_optimizer;
+use raybnn::optimal::optimizer::create_optimizer_options;
+use raybnn::optimal::optimizer::create_uniform_param_groups;
+use raybnn::optimal::optimizer::optimizer_kind_type;
+use raybnn::optimal::optimizer::optimizer_options_type;
+use raybnn::optimal::optimizer::optimizer_trait;
+use raybnn::optimal::optimizer::param_group_type;
+
+use raybnn::Error;
+
+const BACK_END: arrayfire::Backend = arrayfire::Backend::CPU;
+const DEVICE: i32 = 0;
+
+const TOL: f64 = 1.0e-12;
+
+const PARAMS: [f64; 4] = [0.5, -1.0, 2.0, 0.0];
+const DIRECTION0: [f64; 4] = [0.1, -0.2, 0.3, 1.0];
+const DIRECTION1: [f64; 4] = [-0.3, 0.4, 0.1, 2.0];
+
+fn to_array(values: &[f64]) -> arrayfire::Array<f64> {
+    arrayfire::Array::new(
+        values,
+        arrayfire::Dim4::new(&[values.len() as u64, 1, 1, 1]),
+    )
+}
+
+fn to_host(arr: &arrayfire::Array<f64>) -> Vec<f64> {
+    let mut out = vec![0.0f64; arr.elements()];
+    arr.host(&mut out);
+    out
+}
+
+fn optimizer(
+    kind: optimizer_kind_type,
+    weight_decay: f64,
+) -> (optimizer_options_type<f64>, Box<dyn optimizer_trait<f64>>) {
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+
+    let options = create_optimizer_options::<f64>(kind);
+    let group = param_group_type {
+        lr_scale: 1.0,
+        weight_decay: weight_decay,
+    };
+    let groups = create_uniform_param_groups(PARAMS.len() as u64, group);
+
+    (options, create_optimizer(&options, groups))
+}
+
+// Descent directions returned by the optimizer for DIRECTION0 and then DIRECTION1
+fn directions(optimizer: &mut Box<dyn optimizer_trait<f64>>) -> (Vec<f64>, Vec<f64>) {
+    let params = to_array(&PARAMS);
+
+    let mut direction = to_array(&DIRECTION0);
+    optimizer.direction(&params, &mut direction);
+    let first = to_host(&direction);
+
+    let mut direction = to_array(&DIRECTION1);
+    optimizer.direction(&params, &mut direction);
+    let second = to_host(&direction);
+
+    (first, second)
+}
+
+fn assert_close(found: &[f64], expected: &[f64]) {
+    assert_eq!(found.len(), expected.len());
+    for (f, e) in found.iter().zip(expected.iter()) {
+        assert!(
+            (f - e).abs() < TOL,
+            "found {:?}, expected {:?}",
+            found,
+            expected
+        );
+    }
+}
+
+#[test]
+fn sgd_step() {
+    let (_, mut sgd) = optimizer(optimizer_kind_type::SGD, 0.1);
+
+    let mut params = to_array(&PARAMS);
+    let mut direction = to_array(&DIRECTION0);
+    sgd.step(0.5, &mut params, &mut direction);
+
+    // p + alpha (d - wd p)
+    let expected: Vec<f64> = (0..4)
+        .map(|k| PARAMS[k] + 0.5 * (DIRECTION0[k] - 0.1 * PARAMS[k]))
+        .collect();
+    assert_close(&to_host(&params), &expected);
+}
+
+#[test]
+fn nesterov_direction() {
+    let (options, mut nesterov) = optimizer(optimizer_kind_type::NESTEROV, 0.0);
+    let mu = options.beta0;
+    let (first, second) = directions(&mut nesterov);
+
+    let expected0: Vec<f64> = DIRECTION0.iter().map(|d| (1.0 + mu) * d).collect();
+    let expected1: Vec<f64> = (0..4)
+        .map(|k| {
+            let v = mu * DIRECTION0[k] + DIRECTION1[k];
+            DIRECTION1[k] + mu * v
+        })
+        .collect();
+    assert_close(&first, &expected0);
+    assert_close(&second, &expected1);
+}
+
+#[test]
+fn adam_direction_is_bias_corrected() {
+    let (options, mut adam) = optimizer(optimizer_kind_type::ADAM, 0.0);
+    let (b0, b1, eps) = (options.beta0, options.beta1, options.epsilon);
+    let (first, second) = directions(&mut adam);
+
+    // The first step is d / (|d| + eps)
+    let expected0: Vec<f64> = DIRECTION0.iter().map(|d| d / (d.abs() + eps)).collect();
+    let expected1: Vec<f64> = (0..4)
+        .map(|k| {
+            let (d0, d1) = (DIRECTION0[k], DIRECTION1[k]);
+            let m = b0 * (1.0 - b0) * d0 + (1.0 - b0) * d1;
+            let v = b1 * (1.0 - b1) * d0 * d0 + (1.0 - b1) * d1 * d1;
+            (m / (1.0 - b0 * b0)) / ((v / (1.0 - b1 * b1)).sqrt() + eps)
+        })
+        .collect();
+    assert_close(&first, &expected0);
+    assert_close(&second, &expected1);
+}
+
+#[test]
+fn adamw_decay_is_decoupled() {
+    let (options, mut adamw) = optimizer(optimizer_kind_type::ADAMW, 0.1);
+    let eps = options.epsilon;
+
+    let mut params = to_array(&PARAMS);
+    let mut direction = to_array(&DIRECTION0);
+    adamw.step(0.5, &mut params, &mut direction);
+
+    // p - alpha wd p + alpha d / (|d| + eps)
+    let expected: Vec<f64> = (0..4)
+        .map(|k| {
+            let d = DIRECTION0[k];
+            PARAMS[k] - 0.5 * 0.1 * PARAMS[k] + 0.5 * d / (d.abs() + eps)
+        })
+        .collect();
+    assert_close(&to_host(&params), &expected);
+}
+
+#[test]
+fn rmsprop_direction() {
+    let (options, mut rmsprop) = optimizer(optimizer_kind_type::RMSPROP, 0.0);
+    let (rho, eps) = (options.beta1, options.epsilon);
+    let (first, second) = directions(&mut rmsprop);
+
+    let expected0: Vec<f64> = DIRECTION0
+        .iter()
+        .map(|d| d / (((1.0 - rho) * d * d).sqrt() + eps))
+        .collect();
+    let expected1: Vec<f64> = (0..4)
+        .map(|k| {
+            let (d0, d1) = (DIRECTION0[k], DIRECTION1[k]);
+            let sq = rho * (1.0 - rho) * d0 * d0 + (1.0 - rho) * d1 * d1;
+            d1 / (sq.sqrt() + eps)
+        })
+        .collect();
+    assert_close(&first, &expected0);
+    assert_close(&second, &expected1);
+}
+
+#[test]
+fn adagrad_direction() {
+    let (options, mut adagrad) = optimizer(optimizer_kind_type::ADAGRAD, 0.0);
+    let eps = options.epsilon;
+    let (first, second) = directions(&mut adagrad);
+
+    let expected0: Vec<f64> = DIRECTION0.iter().map(|d| d / (d.abs() + eps)).collect();
+    let expected1: Vec<f64> = (0..4)
+        .map(|k| {
+            let (d0, d1) = (DIRECTION0[k], DIRECTION1[k]);
+            d1 / ((d0 * d0 + d1 * d1).sqrt() + eps)
+        })
+        .collect();
+    assert_close(&first, &expected0);
+    assert_close(&second, &expected1);
+}
+
+#[test]
+fn lion_direction_is_sign() {
+    let (options, mut lion) = optimizer(optimizer_kind_type::LION, 0.0);
+    let (b0, b1) = (options.beta0, options.beta1);
+    let (first, second) = directions(&mut lion);
+
+    let sign = |x: f64| {
+        if x > 0.0 {
+            1.0
+        } else if x < 0.0 {
+            -1.0
+        } else {
+            0.0
+        }
+    };
+    let expected0: Vec<f64> = DIRECTION0.iter().map(|d| sign(*d)).collect();
+    let expected1: Vec<f64> = (0..4)
+        .map(|k| {
+            let m = (1.0 - b1) * DIRECTION0[k];
+            sign(b0 * m + (1.0 - b0) * DIRECTION1[k])
+        })
+        .collect();
+    assert_close(&first, &expected0);
+    assert_close(&second, &expected1);
+}
+
+#[test]
+fn adam_state_round_trip() {
+    let (_, mut adam) = optimizer(optimizer_kind_type::ADAM, 0.0);
+    let params = to_array(&PARAMS);
+    let mut direction = to_array(&DIRECTION0);
+    adam.direction(&params, &mut direction);
+
+    let (_, mut resumed) = optimizer(optimizer_kind_type::ADAM, 0.0);
+    resumed.set_state(adam.state()).unwrap();
+
+    let mut expected = to_array(&DIRECTION1);
+    adam.direction(&params, &mut expected);
+    let mut found = to_array(&DIRECTION1);
+    resumed.direction(&params, &mut found);
+    assert_close(&to_host(&found), &to_host(&expected));
+}
+
+#[test]
+fn state_mismatch_is_invalid_train_state() {
+    let (_, mut nesterov) = optimizer(optimizer_kind_type::NESTEROV, 0.0);
+    let (_, adam) = optimizer(optimizer_kind_type::ADAM, 0.0);
+
+    assert!(matches!(
+        nesterov.set_state(adam.state()),
+        Err(Error::InvalidTrainState(_))
+    ));
+    assert!(matches!(
+        nesterov.set_state(vec![to_array(&[0.0; 3])]),
+        Err(Error::InvalidTrainState(_))
+    ));
+}
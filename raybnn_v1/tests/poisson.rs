@@ -0,0 +1,124 @@
+#![allow(non_snake_case)]
+extern crate arrayfire;
+extern crate raybnn;
+
+use raybnn::interface::builder_f64::network_builder_type;
+
+use raybnn::physics::poisson::cell_placement_type;
+use raybnn::physics::poisson::try_poisson_disk_sphere;
+
+const BACK_END: arrayfire::Backend = arrayfire::Backend::CPU;
+const DEVICE: i32 = 0;
+
+const SPHERE_RAD: f64 = 1.0;
+const NEURON_RAD: f64 = 0.05;
+
+fn to_host(arr: &arrayfire::Array<f64>) -> Vec<f64> {
+    let mut out = vec![0.0f64; arr.elements()];
+    arr.host(&mut out);
+    out
+}
+
+// Positions of every cell, pos[i][d]
+fn positions(pos: &arrayfire::Array<f64>) -> Vec<Vec<f64>> {
+    let pos_num = pos.dims()[0] as usize;
+    let space_dims = pos.dims()[1] as usize;
+    let pos_cpu = to_host(pos);
+
+    (0..pos_num)
+        .map(|i| (0..space_dims).map(|d| pos_cpu[d * pos_num + i]).collect())
+        .collect()
+}
+
+fn sample(space_dims: u64, pos_num: u64, seed: u64) -> arrayfire::Array<f64> {
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+
+    try_poisson_disk_sphere(space_dims, SPHERE_RAD, NEURON_RAD, pos_num, seed).unwrap()
+}
+
+#[test]
+fn places_exact_count_inside_sphere() {
+    for space_dims in [2, 3, 4] {
+        let pos = sample(space_dims, 100, 7);
+        assert_eq!(pos.dims()[0], 100);
+        assert_eq!(pos.dims()[1], space_dims);
+
+        for p in positions(&pos) {
+            let rad = p.iter().map(|x| x * x).sum::<f64>().sqrt();
+            assert!(rad + NEURON_RAD <= SPHERE_RAD + 1.0e-12);
+        }
+    }
+}
+
+#[test]
+fn cells_keep_minimum_distance() {
+    let min_dist = 2.0 * NEURON_RAD * (1.1f64).sqrt();
+
+    let pos = positions(&sample(3, 300, 11));
+    for i in 0..pos.len() {
+        for j in (i + 1)..pos.len() {
+            let dist = (0..3)
+                .map(|d| (pos[i][d] - pos[j][d]).powi(2))
+                .sum::<f64>()
+                .sqrt();
+            assert!(
+                dist >= min_dist - 1.0e-12,
+                "cells {} and {} are {} apart",
+                i,
+                j,
+                dist
+            );
+        }
+    }
+}
+
+#[test]
+fn same_seed_same_positions() {
+    let first = to_host(&sample(3, 200, 1234));
+    let second = to_host(&sample(3, 200, 1234));
+    assert_eq!(first, second);
+
+    let other = to_host(&sample(3, 200, 4321));
+    assert_ne!(first, other);
+}
+
+#[test]
+fn too_many_cells_is_an_error() {
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+
+    let result = try_poisson_disk_sphere(3, SPHERE_RAD, NEURON_RAD, 100000, 3);
+    assert!(result.is_err());
+}
+
+#[test]
+fn builder_places_cells_with_poisson_disk() {
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+
+    let dir_path = std::env::temp_dir().join("raybnn_poisson_builder");
+    std::fs::create_dir_all(&dir_path).unwrap();
+
+    let build = || {
+        network_builder_type::new(3, 2, dir_path.to_str().unwrap())
+            .active_size(30)
+            .max_neuron_size(60)
+            .cell_placement(cell_placement_type::POISSON_DISK)
+            .seed(5)
+            .build()
+            .unwrap()
+    };
+
+    let first = build();
+    let second = build();
+
+    assert_eq!(
+        to_host(&first.neural_network.glia_pos),
+        to_host(&second.neural_network.glia_pos)
+    );
+    assert_eq!(
+        to_host(&first.neural_network.neuron_pos),
+        to_host(&second.neural_network.neuron_pos)
+    );
+}
@@ -0,0 +1,79 @@
+extern crate arrayfire;
+extern crate raybnn;
+
+use half::f16;
+
+use raybnn::interface::automatic::arch_search_type;
+use raybnn::interface::autotrain::loss_status_type;
+use raybnn::interface::autotrain::train_network;
+use raybnn::interface::autotrain::train_network_options_type;
+use raybnn::interface::builder::network_builder_type;
+use raybnn::neural::network::state_space_forward_batch;
+use raybnn::optimal::evolution::try_evolve_network;
+use raybnn::optimal::loss::MSE_grad;
+use raybnn::optimal::loss::MSE;
+use raybnn::precision::float_trait::raybnn_float_trait;
+use raybnn::precision::reduce;
+
+// Trains on data at T. Only instantiated, arrayfire is not called
+fn train<T: raybnn_float_trait>(
+    data: &nohash_hasher::IntMap<u64, Vec<T>>,
+    train_options: train_network_options_type<T>,
+    arch_search: &mut arch_search_type<T>,
+) {
+    let mut loss_status = loss_status_type::LOSS_PLATEAU;
+
+    train_network(
+        data,
+        data,
+        data,
+        data,
+        MSE,
+        MSE_grad,
+        train_options,
+        &mut Vec::new(),
+        &mut Vec::new(),
+        &mut Vec::new(),
+        arch_search,
+        &mut loss_status,
+    );
+}
+
+// Addresses of the generic entry points at T, which makes the compiler instantiate them
+fn entry_points<T: raybnn_float_trait>() -> Vec<*const ()> {
+    vec![
+        network_builder_type::<T>::build as *const (),
+        state_space_forward_batch::<T> as *const (),
+        train::<T> as *const (),
+        try_evolve_network::<T> as *const (),
+        reduce::sum::<T> as *const (),
+        reduce::product::<T> as *const (),
+        reduce::sum_all::<T> as *const (),
+    ]
+}
+
+#[test]
+fn f16_instantiates_the_generic_modules() {
+    assert_eq!(<f16 as raybnn_float_trait>::DTYPE_NAME, "f16");
+    assert_eq!(
+        <f16 as raybnn_float_trait>::from_f64(0.5),
+        f16::from_f32(0.5)
+    );
+
+    assert!(entry_points::<f16>().iter().all(|address| !address.is_null()));
+}
+
+#[test]
+fn f16_derives_the_same_settings_as_f32() {
+    let netdata16 = network_builder_type::<f16>::new(30, 10, "/tmp/")
+        .proc_num(3)
+        .netdata()
+        .unwrap();
+    let netdata32 = network_builder_type::<f32>::new(30, 10, "/tmp/")
+        .proc_num(3)
+        .netdata()
+        .unwrap();
+
+    assert_eq!(netdata16.active_size, netdata32.active_size);
+    assert_eq!(netdata16.neuron_size, netdata32.neuron_size);
+}
@@ -0,0 +1,174 @@
+#![allow(non_snake_case)]
+extern crate arrayfire;
+extern crate raybnn;
+
+use raybnn::neural::network_f64::create_nullnetdata;
+
+use raybnn::physics::raytrace_f64::line_sphere_intersect_batchV2;
+use raybnn::physics::raytrace_f64::raytrace_accel_type;
+use raybnn::physics::raytrace_f64::raytrace_option_type;
+use raybnn::physics::raytrace_f64::RT2_directly_connected;
+
+use raybnn::physics::raytrace_grid::create_raytrace_grid;
+use raybnn::physics::raytrace_grid::line_sphere_intersect_grid;
+
+const BACK_END: arrayfire::Backend = arrayfire::Backend::CPU;
+const DEVICE: i32 = 0;
+
+// Uniform random positions in the cube [-half_width, half_width]^3
+fn random_pos(pos_num: u64, half_width: f64) -> arrayfire::Array<f64> {
+    let pos = arrayfire::randu::<f64>(arrayfire::Dim4::new(&[pos_num, 3, 1, 1]));
+    (pos * (2.0 * half_width)) - half_width
+}
+
+fn to_host<T: arrayfire::HasAfEnum + Default + Clone>(arr: &arrayfire::Array<T>) -> Vec<T> {
+    let mut arr_cpu = vec![T::default(); arr.elements()];
+    arr.host(&mut arr_cpu);
+    arr_cpu
+}
+
+fn run_RT2(
+    accel: raytrace_accel_type,
+    glia_pos: &arrayfire::Array<f64>,
+    input_pos: &arrayfire::Array<f64>,
+    input_idx: &arrayfire::Array<i32>,
+    hidden_pos: &arrayfire::Array<f64>,
+    hidden_idx: &arrayfire::Array<i32>,
+) -> (Vec<i32>, Vec<i32>) {
+    let mut netdata = create_nullnetdata();
+    netdata.neuron_size = input_idx.dims()[0] + hidden_idx.dims()[0];
+    netdata.neuron_rad = 0.3;
+    netdata.con_rad = 3.0;
+
+    let raytrace_options = raytrace_option_type {
+        max_rounds: 1,
+        input_connection_num: 1000000,
+        ray_neuron_intersect: true,
+        ray_glia_intersect: true,
+        accel: accel,
+    };
+
+    let single_dims = arrayfire::Dim4::new(&[1, 1, 1, 1]);
+    let mut WRowIdxCOO = arrayfire::constant::<i32>(0, single_dims);
+    let mut WColIdx = arrayfire::constant::<i32>(0, single_dims);
+
+    RT2_directly_connected(
+        &raytrace_options,
+        &netdata,
+        glia_pos,
+        input_pos,
+        input_idx,
+        hidden_pos,
+        hidden_idx,
+        &mut WRowIdxCOO,
+        &mut WColIdx,
+    );
+
+    (to_host(&WRowIdxCOO), to_host(&WColIdx))
+}
+
+#[test]
+fn grid_RT2_matches_brute_force() {
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+    arrayfire::set_seed(7);
+
+    let input_size = 12u64;
+    let hidden_size = 80u64;
+
+    let glia_pos = random_pos(40, 5.0);
+    let input_pos = random_pos(input_size, 5.0);
+    let hidden_pos = random_pos(hidden_size, 5.0);
+
+    let input_idx_cpu: Vec<i32> = (0..input_size as i32).collect();
+    let hidden_idx_cpu: Vec<i32> = (input_size as i32..(input_size + hidden_size) as i32).collect();
+    let input_idx =
+        arrayfire::Array::new(&input_idx_cpu, arrayfire::Dim4::new(&[input_size, 1, 1, 1]));
+    let hidden_idx = arrayfire::Array::new(
+        &hidden_idx_cpu,
+        arrayfire::Dim4::new(&[hidden_size, 1, 1, 1]),
+    );
+
+    let brute_force = run_RT2(
+        raytrace_accel_type::BRUTE_FORCE,
+        &glia_pos,
+        &input_pos,
+        &input_idx,
+        &hidden_pos,
+        &hidden_idx,
+    );
+    let grid = run_RT2(
+        raytrace_accel_type::UNIFORM_GRID,
+        &glia_pos,
+        &input_pos,
+        &input_idx,
+        &hidden_pos,
+        &hidden_idx,
+    );
+
+    assert!(brute_force.0.len() > 1);
+    assert_eq!(brute_force, grid);
+}
+
+#[test]
+fn grid_intersect_matches_brute_force() {
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+    arrayfire::set_seed(11);
+
+    let ray_num = 300u64;
+    let neuron_rad = 0.4f64;
+
+    let circle_center = random_pos(200, 4.0);
+    let circle_radius =
+        arrayfire::constant::<f64>(neuron_rad, arrayfire::Dim4::new(&[200, 1, 1, 1]));
+
+    let start_line = random_pos(ray_num, 4.0);
+    let dir_line = random_pos(ray_num, 2.0);
+
+    let idx_cpu: Vec<i32> = (0..ray_num as i32).collect();
+    let idx = arrayfire::Array::new(&idx_cpu, arrayfire::Dim4::new(&[ray_num, 1, 1, 1]));
+
+    for threshold in [0u32, 2u32] {
+        let mut start_bf = start_line.clone();
+        let mut dir_bf = dir_line.clone();
+        let mut input_bf = idx.clone();
+        let mut hidden_bf = idx.clone();
+
+        line_sphere_intersect_batchV2(
+            7,
+            threshold,
+            &circle_center,
+            &circle_radius,
+            &mut start_bf,
+            &mut dir_bf,
+            &mut input_bf,
+            &mut hidden_bf,
+        );
+
+        // Cells close to the sphere diameter, so spheres overlap neighbouring cells
+        let grid = create_raytrace_grid(&circle_center, 0.25);
+
+        let mut start_grid = start_line.clone();
+        let mut dir_grid = dir_line.clone();
+        let mut input_grid = idx.clone();
+        let mut hidden_grid = idx.clone();
+
+        line_sphere_intersect_grid(
+            &grid,
+            neuron_rad,
+            threshold,
+            &mut start_grid,
+            &mut dir_grid,
+            &mut input_grid,
+            &mut hidden_grid,
+        );
+
+        assert!(input_bf.elements() > 0);
+        assert!(input_bf.elements() < ray_num as usize);
+        assert_eq!(to_host(&input_bf), to_host(&input_grid));
+        assert_eq!(to_host(&hidden_bf), to_host(&hidden_grid));
+        assert_eq!(to_host(&start_bf), to_host(&start_grid));
+        assert_eq!(to_host(&dir_bf), to_host(&dir_grid));
+    }
+}
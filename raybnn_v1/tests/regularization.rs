@@ -0,0 +1,187 @@
+#![allow(non_snake_case)]
+extern crate arrayfire;
+extern crate raybnn;
+
+use raybnn::optimal::regularization::add_regularization_grad;
+use raybnn::optimal::regularization::apply_max_norm;
+use raybnn::optimal::regularization::check_regularization_options;
+use raybnn::optimal::regularization::create_drop_mask;
+use raybnn::optimal::regularization::create_regularization_options;
+use raybnn::optimal::regularization::regularization_options_type;
+use raybnn::optimal::regularization::regularization_penalty;
+
+use raybnn::Error;
+
+const BACK_END: arrayfire::Backend = arrayfire::Backend::CPU;
+const DEVICE: i32 = 0;
+
+const TOL: f64 = 1.0e-9;
+
+// W is network_params[0..4], A..E are network_params[5..9], H is network_params[4]
+const PARAMS: [f64; 10] = [0.5, -1.0, 2.0, 0.0, 3.0, 0.25, -0.5, 1.5, -2.0, 0.75];
+
+fn seqs(first: i32, last: i32) -> [arrayfire::Seq<i32>; 1] {
+    [arrayfire::Seq::new(first, last, 1)]
+}
+
+fn to_array<T: arrayfire::HasAfEnum>(values: &[T]) -> arrayfire::Array<T> {
+    arrayfire::Array::new(
+        values,
+        arrayfire::Dim4::new(&[values.len() as u64, 1, 1, 1]),
+    )
+}
+
+fn to_host<T: arrayfire::HasAfEnum + Default + Clone>(arr: &arrayfire::Array<T>) -> Vec<T> {
+    let mut out = vec![T::default(); arr.elements()];
+    arr.host(&mut out);
+    out
+}
+
+fn options() -> regularization_options_type<f64> {
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+
+    let mut options = create_regularization_options::<f64>();
+    options.weight.l1 = 0.1;
+    options.weight.l2 = 0.2;
+    options.uaf.l2 = 0.3;
+    options
+}
+
+fn penalty(params: &arrayfire::Array<f64>, options: &regularization_options_type<f64>) -> f64 {
+    regularization_penalty(
+        params,
+        options,
+        &seqs(0, 3),
+        &seqs(5, 5),
+        &seqs(6, 6),
+        &seqs(7, 7),
+        &seqs(8, 8),
+        &seqs(9, 9),
+    )
+}
+
+fn assert_invalid(options: &regularization_options_type<f64>) {
+    match check_regularization_options(options) {
+        Err(Error::InvalidTrainState(_)) => (),
+        Err(e) => panic!("expected InvalidTrainState, found {}", e),
+        Ok(()) => panic!("expected InvalidTrainState, options accepted"),
+    }
+}
+
+#[test]
+fn drop_probability_must_be_below_one() {
+    let mut options = create_regularization_options::<f64>();
+    check_regularization_options(&options).unwrap();
+
+    options.dropout = 0.5;
+    options.dropconnect = 0.99;
+    check_regularization_options(&options).unwrap();
+
+    for p in [1.0, 1.5, -0.1, f64::NAN] {
+        let mut dropout = create_regularization_options::<f64>();
+        dropout.dropout = p;
+        assert_invalid(&dropout);
+
+        let mut dropconnect = create_regularization_options::<f64>();
+        dropconnect.dropconnect = p;
+        assert_invalid(&dropconnect);
+    }
+
+    let mut negative = create_regularization_options::<f64>();
+    negative.weight.l2 = -1.0;
+    assert_invalid(&negative);
+}
+
+#[test]
+fn penalty_known_answer() {
+    let options = options();
+    let params = to_array(&PARAMS);
+
+    // W: 0.1 * 3.5 + 0.5 * 0.2 * 5.25, UAF: 0.5 * 0.3 * 7.125. H is not penalized
+    let expected = 0.1 * 3.5 + 0.1 * 5.25 + 0.15 * 7.125;
+    assert!((penalty(&params, &options) - expected).abs() < TOL);
+}
+
+#[test]
+fn penalty_grad_matches_finite_difference() {
+    let options = options();
+    let params = to_array(&PARAMS);
+
+    let mut grad = arrayfire::constant::<f64>(0.0, params.dims());
+    add_regularization_grad(
+        &params,
+        &options,
+        &seqs(0, 3),
+        &seqs(5, 5),
+        &seqs(6, 6),
+        &seqs(7, 7),
+        &seqs(8, 8),
+        &seqs(9, 9),
+        &mut grad,
+    );
+    let grad = to_host(&grad);
+
+    // Central differences, skipping W[3] = 0 where the L1 penalty has no derivative
+    let h = 1.0e-6;
+    for k in [0, 1, 2, 4, 5, 6, 7, 8, 9] {
+        let mut plus = PARAMS.to_vec();
+        plus[k] = plus[k] + h;
+        let mut minus = PARAMS.to_vec();
+        minus[k] = minus[k] - h;
+
+        let numeric = (penalty(&to_array(&plus), &options) - penalty(&to_array(&minus), &options))
+            / (2.0 * h);
+        assert!(
+            (grad[k] - numeric).abs() < 1.0e-6,
+            "index {}: analytic {}, numeric {}",
+            k,
+            grad[k],
+            numeric
+        );
+    }
+}
+
+#[test]
+fn max_norm_limits_incoming_weights() {
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+
+    // Rows 0, 0, 1, 1: the norm of row 0 is 5 and of row 1 is 0.5
+    let mut params = to_array(&[3.0f64, 4.0, 0.3, 0.4]);
+    let WRowIdxCOO = to_array(&[0i32, 0, 1, 1]);
+
+    apply_max_norm(1.0, &WRowIdxCOO, &seqs(0, 3), &mut params);
+
+    let W = to_host(&params);
+    let expected = [0.6, 0.8, 0.3, 0.4];
+    for k in 0..4 {
+        assert!((W[k] - expected[k]).abs() < TOL, "{:?}", W);
+    }
+}
+
+#[test]
+fn drop_mask_is_scaled_and_seeded() {
+    let mut options = create_regularization_options::<f64>();
+    options.dropconnect = 0.25;
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+
+    let WColIdx = to_array(&(0..1000).map(|k| k % 50).collect::<Vec<i32>>());
+
+    let first = to_host(&create_drop_mask(&options, &WColIdx, 7).unwrap());
+    let second = to_host(&create_drop_mask(&options, &WColIdx, 7).unwrap());
+    let other = to_host(&create_drop_mask(&options, &WColIdx, 8).unwrap());
+
+    assert_eq!(first, second);
+    assert_ne!(first, other);
+
+    let keep = 1.0 / 0.75;
+    assert!(first
+        .iter()
+        .all(|v| (*v == 0.0) || ((v - keep).abs() < TOL)));
+    let dropped = first.iter().filter(|v| **v == 0.0).count();
+    assert!((150..350).contains(&dropped), "dropped {}", dropped);
+
+    assert!(create_drop_mask(&create_regularization_options::<f64>(), &WColIdx, 7).is_none());
+}
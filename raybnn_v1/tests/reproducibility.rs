@@ -0,0 +1,287 @@
+#![allow(non_snake_case)]
+#![allow(non_camel_case_types)]
+extern crate arrayfire;
+extern crate raybnn;
+
+use nohash_hasher;
+
+use raybnn::interface::automatic_f64::arch_search_type;
+use raybnn::interface::builder_f64::network_builder_type;
+
+use raybnn::interface::autotrain_f64::loss_status_type;
+use raybnn::interface::autotrain_f64::lr_strategy2_type;
+use raybnn::interface::autotrain_f64::lr_strategy_type;
+use raybnn::interface::autotrain_f64::resume_train_network;
+use raybnn::interface::autotrain_f64::stop_strategy_type;
+use raybnn::interface::autotrain_f64::train_network;
+use raybnn::interface::autotrain_f64::train_network_checkpoint;
+use raybnn::interface::autotrain_f64::train_network_options_type;
+
+use raybnn::neural::normalization::create_norm_options;
+use raybnn::neural::normalization::norm_kind_type;
+
+use raybnn::optimal::loss_f64::MSE_grad;
+use raybnn::optimal::loss_f64::MSE;
+use raybnn::optimal::optimizer::create_optimizer_options;
+use raybnn::optimal::optimizer::optimizer_kind_type;
+use raybnn::optimal::regularization::create_regularization_options;
+use raybnn::optimal::scheduler::lr_schedule_type;
+
+use raybnn::export::rng::scope_raybnn_rng;
+
+use raybnn::physics::topology::small_world_strategy_type;
+
+const BACK_END: arrayfire::Backend = arrayfire::Backend::CPU;
+const DEVICE: i32 = 0;
+
+const INPUT_SIZE: u64 = 3;
+const OUTPUT_SIZE: u64 = 2;
+const BATCH_SIZE: u64 = 8;
+const BATCH_NUM: u64 = 2;
+
+// Everything a run produces that depends on random numbers
+struct run_result_type {
+    neuron_pos: Vec<f64>,
+    glia_pos: Vec<f64>,
+    WColIdx: Vec<i32>,
+    network_params: Vec<f64>,
+    loss_vec: Vec<f64>,
+    crossval_vec: Vec<f64>,
+}
+
+fn to_host<T: arrayfire::HasAfEnum + Default + Clone>(arr: &arrayfire::Array<T>) -> Vec<T> {
+    let mut out = vec![T::default(); arr.elements()];
+    arr.host(&mut out);
+    out
+}
+
+// Fixed regression dataset, split into batches
+fn dataset() -> (
+    nohash_hasher::IntMap<u64, Vec<f64>>,
+    nohash_hasher::IntMap<u64, Vec<f64>>,
+) {
+    let mut X = nohash_hasher::IntMap::default();
+    let mut Y = nohash_hasher::IntMap::default();
+
+    for b in 0..BATCH_NUM {
+        let mut X_cpu = Vec::new();
+        let mut Y_cpu = Vec::new();
+        for j in 0..BATCH_SIZE {
+            let t = ((b * BATCH_SIZE + j) as f64) * 0.37;
+            X_cpu.extend_from_slice(&[t.sin(), t.cos(), (0.5 * t).sin()]);
+            Y_cpu.extend_from_slice(&[(t.sin() + t.cos()) * 0.5, (0.5 * t).sin() * t.cos()]);
+        }
+        X.insert(b, X_cpu);
+        Y.insert(b, Y_cpu);
+    }
+
+    (X, Y)
+}
+
+fn run(name: &str, seed: u64) -> run_result_type {
+    // Tests run in parallel, the scope keeps the random numbers of this run together
+    let _scope = scope_raybnn_rng(seed);
+
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+
+    let dir_path = std::env::temp_dir().join(format!("raybnn_reproducibility_{}", name));
+    std::fs::create_dir_all(&dir_path).unwrap();
+
+    // Small world rewiring draws host random numbers in parallel
+    let connection_strategy = small_world_strategy_type {
+        k: 4,
+        rewire_prob: 0.3,
+    };
+
+    let mut arch_search =
+        network_builder_type::new(INPUT_SIZE, OUTPUT_SIZE, dir_path.to_str().unwrap())
+            .active_size(30)
+            .max_neuron_size(60)
+            .batch_size(BATCH_SIZE)
+            .proc_num(3)
+            .seed(seed)
+            .build_with_strategy(&connection_strategy)
+            .unwrap();
+
+    let (traindata_X, traindata_Y) = dataset();
+
+    let train_options = train_network_options_type {
+        stop_strategy: stop_strategy_type::STOP_AT_EPOCH,
+        lr_strategy: lr_strategy_type::SHUFFLE_CONNECTIONS,
+        lr_strategy2: lr_strategy2_type::MAX_ALPHA,
+
+        max_epoch: 20,
+        stop_epoch: 20,
+        stop_train_loss: 0.0,
+
+        exit_counter_threshold: 4,
+        shuffle_counter_threshold: 2,
+        optimizer: create_optimizer_options(optimizer_kind_type::ADAM),
+        lr_schedule: lr_schedule_type::CONSTANT,
+        regularization: create_regularization_options(),
+        normalization: create_norm_options(norm_kind_type::NONE),
+    };
+
+    let mut alpha_max_vec = Vec::new();
+    let mut loss_vec = Vec::new();
+    let mut crossval_vec = Vec::new();
+    let mut loss_status = loss_status_type::LOSS_PLATEAU;
+
+    train_network(
+        &traindata_X,
+        &traindata_Y,
+        &traindata_X,
+        &traindata_Y,
+        MSE,
+        MSE_grad,
+        train_options,
+        &mut alpha_max_vec,
+        &mut loss_vec,
+        &mut crossval_vec,
+        &mut arch_search,
+        &mut loss_status,
+    );
+
+    let neural_network = &arch_search.neural_network;
+    run_result_type {
+        neuron_pos: to_host(&neural_network.neuron_pos),
+        glia_pos: to_host(&neural_network.glia_pos),
+        WColIdx: to_host(&neural_network.WColIdx),
+        network_params: to_host(&neural_network.network_params),
+        loss_vec: loss_vec,
+        crossval_vec: crossval_vec,
+    }
+}
+
+#[test]
+fn identical_seeds_reproduce_network_and_training() {
+    let first = run("first", 1234);
+    let second = run("second", 1234);
+
+    assert!(!first.loss_vec.is_empty());
+
+    assert_eq!(first.neuron_pos, second.neuron_pos);
+    assert_eq!(first.glia_pos, second.glia_pos);
+    assert_eq!(first.WColIdx, second.WColIdx);
+    assert_eq!(first.network_params, second.network_params);
+    assert_eq!(first.loss_vec, second.loss_vec);
+    assert_eq!(first.crossval_vec, second.crossval_vec);
+
+    let other = run("other", 4321);
+    assert_ne!(first.neuron_pos, other.neuron_pos);
+}
+
+// Trains with dropout for max_epoch epochs and a checkpoint every 4 epochs
+fn train_with_checkpoint(
+    arch_search: &mut arch_search_type,
+    checkpoint_file: &str,
+    max_epoch: u64,
+    resume: bool,
+) -> Vec<f64> {
+    let (traindata_X, traindata_Y) = dataset();
+
+    let mut regularization = create_regularization_options();
+    regularization.dropout = 0.2;
+
+    let train_options = train_network_options_type {
+        stop_strategy: stop_strategy_type::NONE,
+        lr_strategy: lr_strategy_type::SHUFFLE_CONNECTIONS,
+        lr_strategy2: lr_strategy2_type::MAX_ALPHA,
+
+        max_epoch: max_epoch,
+        stop_epoch: max_epoch,
+        stop_train_loss: 0.0,
+
+        exit_counter_threshold: 1000,
+        shuffle_counter_threshold: 1000,
+        optimizer: create_optimizer_options(optimizer_kind_type::ADAM),
+        lr_schedule: lr_schedule_type::CONSTANT,
+        regularization: regularization,
+        normalization: create_norm_options(norm_kind_type::NONE),
+    };
+
+    let mut alpha_max_vec = Vec::new();
+    let mut loss_vec = Vec::new();
+    let mut crossval_vec = Vec::new();
+    let mut loss_status = loss_status_type::LOSS_PLATEAU;
+
+    if resume {
+        resume_train_network(
+            checkpoint_file,
+            4,
+            &traindata_X,
+            &traindata_Y,
+            &traindata_X,
+            &traindata_Y,
+            MSE,
+            MSE_grad,
+            train_options,
+            &mut alpha_max_vec,
+            &mut loss_vec,
+            &mut crossval_vec,
+            arch_search,
+            &mut loss_status,
+        )
+        .unwrap();
+    } else {
+        train_network_checkpoint(
+            &traindata_X,
+            &traindata_Y,
+            &traindata_X,
+            &traindata_Y,
+            MSE,
+            MSE_grad,
+            train_options,
+            &mut alpha_max_vec,
+            &mut loss_vec,
+            &mut crossval_vec,
+            arch_search,
+            &mut loss_status,
+            checkpoint_file,
+            4,
+            None,
+        );
+    }
+
+    loss_vec
+}
+
+// Training that stops after the checkpoint of epoch 4 and resumes matches training without a break
+#[test]
+fn resume_matches_uninterrupted_training() {
+    let _scope = scope_raybnn_rng(99);
+
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+
+    let dir_path = std::env::temp_dir().join("raybnn_reproducibility_resume");
+    std::fs::create_dir_all(&dir_path).unwrap();
+    let build = || {
+        network_builder_type::new(INPUT_SIZE, OUTPUT_SIZE, dir_path.to_str().unwrap())
+            .active_size(30)
+            .max_neuron_size(60)
+            .batch_size(BATCH_SIZE)
+            .proc_num(3)
+            .seed(99)
+            .build()
+            .unwrap()
+    };
+
+    let mut full = build();
+    let full_file = dir_path.join("full.ck");
+    let full_loss = train_with_checkpoint(&mut full, full_file.to_str().unwrap(), 8, false);
+
+    let mut resumed = build();
+    let resumed_file = dir_path.join("resumed.ck");
+    let resumed_file = resumed_file.to_str().unwrap();
+    train_with_checkpoint(&mut resumed, resumed_file, 5, false);
+    let resumed_loss = train_with_checkpoint(&mut resumed, resumed_file, 8, true);
+
+    assert_eq!(full_loss.len(), 8);
+    assert_eq!(full_loss, resumed_loss);
+    assert_eq!(
+        to_host(&full.neural_network.network_params),
+        to_host(&resumed.neural_network.network_params)
+    );
+}
@@ -0,0 +1,245 @@
+#![allow(non_snake_case)]
+#![allow(non_camel_case_types)]
+extern crate raybnn;
+
+#[allow(deprecated)]
+use raybnn::optimal::control_f64::cosine_annealing;
+use raybnn::optimal::control_f64::create_nullcontrol;
+#[allow(deprecated)]
+use raybnn::optimal::control_f64::plateau;
+use raybnn::optimal::scheduler::create_lr_scheduler;
+use raybnn::optimal::scheduler::lr_schedule_type;
+use raybnn::optimal::scheduler::lr_scheduler_trait;
+
+use raybnn::Error;
+
+const TOL: f64 = 1.0e-12;
+
+// Factors of the first epoch_num epochs with a constant loss
+fn factors(schedule: &lr_schedule_type<f64>, epoch_num: u64, loss: f64) -> Vec<f64> {
+    let mut scheduler = create_lr_scheduler(schedule);
+    (0..epoch_num)
+        .map(|epoch| scheduler.factor(epoch, loss))
+        .collect()
+}
+
+fn assert_close(found: &[f64], expected: &[f64]) {
+    assert_eq!(found.len(), expected.len());
+    for (f, e) in found.iter().zip(expected.iter()) {
+        assert!(
+            (f - e).abs() < TOL,
+            "found {:?}, expected {:?}",
+            found,
+            expected
+        );
+    }
+}
+
+#[test]
+fn constant_is_one() {
+    assert_close(
+        &factors(&lr_schedule_type::CONSTANT, 3, 1.0),
+        &[1.0, 1.0, 1.0],
+    );
+}
+
+#[test]
+fn warmup_rises_linearly() {
+    let schedule = lr_schedule_type::WARMUP { warmup_epoch: 4 };
+
+    assert_close(
+        &factors(&schedule, 6, 1.0),
+        &[0.25, 0.5, 0.75, 1.0, 1.0, 1.0],
+    );
+}
+
+#[test]
+fn step_decay_and_exponential() {
+    let step = lr_schedule_type::STEP_DECAY {
+        step_epoch: 2,
+        gamma: 0.5,
+    };
+    assert_close(&factors(&step, 5, 1.0), &[1.0, 1.0, 0.5, 0.5, 0.25]);
+
+    let exponential = lr_schedule_type::EXPONENTIAL { gamma: 0.5 };
+    assert_close(&factors(&exponential, 4, 1.0), &[1.0, 0.5, 0.25, 0.125]);
+}
+
+#[test]
+fn one_cycle_peaks_and_ends_low() {
+    let schedule = lr_schedule_type::ONE_CYCLE {
+        total_epoch: 10,
+        pct_start: 0.3,
+        div_factor: 10.0,
+        final_div_factor: 100.0,
+    };
+    let out = factors(&schedule, 11, 1.0);
+
+    assert!((out[0] - 0.1).abs() < TOL);
+    assert!((out[3] - 1.0).abs() < TOL);
+    assert!((out[10] - 0.001).abs() < TOL);
+    for epoch in 3..10 {
+        assert!(out[epoch + 1] <= out[epoch]);
+    }
+}
+
+#[test]
+fn cosine_restarts_restarts_with_longer_period() {
+    let schedule = lr_schedule_type::COSINE_RESTARTS {
+        period_epoch: 2,
+        period_mult: 2,
+        min_factor: 0.0,
+    };
+
+    // Periods are epochs 0..2 and 2..6
+    assert_close(
+        &factors(&schedule, 7, 1.0),
+        &[
+            1.0,
+            0.5,
+            1.0,
+            0.8535533905932737,
+            0.5,
+            0.14644660940672624,
+            1.0,
+        ],
+    );
+}
+
+#[test]
+fn cosine_annealing_waits_for_plateau() {
+    let schedule = lr_schedule_type::COSINE_ANNEALING { period_epoch: 3 };
+    let mut scheduler = create_lr_scheduler(&schedule);
+
+    // A falling loss keeps the factor at one
+    for epoch in 0..5 {
+        let loss = 0.5f64.powi(epoch as i32);
+        assert_eq!(scheduler.factor(epoch, loss), 1.0);
+    }
+
+    // Three epochs without improvement start the annealing
+    assert_eq!(scheduler.factor(5, 1.0), 1.0);
+    assert_eq!(scheduler.factor(6, 1.0), 1.0);
+    let expected = 4.0 * (2.0 * std::f64::consts::PI * 7.0 / 3.0).cos().abs();
+    assert!((scheduler.factor(7, 1.0) - expected).abs() < TOL);
+}
+
+#[test]
+fn plateau_reduces_after_patience() {
+    let schedule = lr_schedule_type::REDUCE_ON_PLATEAU {
+        patience: 2,
+        factor: 0.5,
+        threshold: 0.01,
+        min_factor: 0.2,
+    };
+
+    // First epoch sets the best loss, the factor falls after three more without improvement
+    assert_close(
+        &factors(&schedule, 10, 1.0),
+        &[1.0, 1.0, 1.0, 0.5, 0.5, 0.5, 0.25, 0.25, 0.25, 0.2],
+    );
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_cosine_annealing_follows_the_schedule() {
+    let mut control_state = create_nullcontrol();
+    control_state.window_epoch = 3;
+    control_state.max_alpha = 1.0;
+    control_state.mean_loss = 1.0;
+
+    let mut alpha0 = 0.0;
+    let mut alpha1 = 0.0;
+    let mut alphas = Vec::new();
+    for _ in 0..4 {
+        cosine_annealing(&mut control_state, &mut alpha0, &mut alpha1);
+        assert_eq!(alpha0, alpha1);
+        alphas.push(alpha0);
+    }
+
+    // Epochs 1 to 4 of COSINE_ANNEALING with period_epoch 3 and a constant loss
+    let expected = 4.0 * (2.0 * std::f64::consts::PI * 4.0 / 3.0).cos().abs();
+    assert_close(&alphas, &[1.0, 1.0, 1.0, expected]);
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_plateau_reduces_after_window() {
+    let mut control_state = create_nullcontrol();
+    control_state.window_epoch = 2;
+    control_state.decrease_alpha = 0.5;
+    control_state.mean_loss = 1.0;
+
+    let mut alpha0 = 1.0;
+    let mut alpha1 = 1.0;
+    let mut alphas = Vec::new();
+    for _ in 0..7 {
+        plateau(1.0, &mut control_state, &mut alpha0, &mut alpha1);
+        alphas.push(alpha0);
+    }
+
+    assert_close(&alphas, &[1.0, 1.0, 1.0, 0.5, 0.5, 0.5, 0.25]);
+    assert_eq!(alpha0, alpha1);
+}
+
+#[test]
+fn compose_multiplies_factors() {
+    let schedule = lr_schedule_type::COMPOSE(vec![
+        lr_schedule_type::WARMUP { warmup_epoch: 2 },
+        lr_schedule_type::EXPONENTIAL { gamma: 0.5 },
+    ]);
+
+    assert_close(&factors(&schedule, 3, 1.0), &[0.5, 0.5, 0.25]);
+}
+
+#[test]
+fn state_round_trip_resumes_exactly() {
+    let schedule = lr_schedule_type::COMPOSE(vec![
+        lr_schedule_type::REDUCE_ON_PLATEAU {
+            patience: 1,
+            factor: 0.5,
+            threshold: 0.0,
+            min_factor: 0.0,
+        },
+        lr_schedule_type::COSINE_ANNEALING { period_epoch: 2 },
+    ]);
+    let losses = [3.0, 2.0, 2.0, 2.0, 2.5, 1.0, 1.0, 1.0, 1.0, 1.0];
+
+    let mut full = create_lr_scheduler(&schedule);
+    let expected: Vec<f64> = losses
+        .iter()
+        .enumerate()
+        .map(|(epoch, loss)| full.factor(epoch as u64, *loss))
+        .collect();
+
+    let mut first = create_lr_scheduler(&schedule);
+    for epoch in 0..4 {
+        first.factor(epoch, losses[epoch as usize]);
+    }
+    let state = first.state();
+    assert_eq!(state.len(), 5);
+
+    let mut resumed = create_lr_scheduler(&schedule);
+    assert_eq!(resumed.set_state(&state).unwrap(), 5);
+    let found: Vec<f64> = (4..losses.len())
+        .map(|epoch| resumed.factor(epoch as u64, losses[epoch]))
+        .collect();
+
+    assert_close(&found, &expected[4..]);
+}
+
+#[test]
+fn short_state_is_rejected() {
+    let schedule: lr_schedule_type<f64> = lr_schedule_type::REDUCE_ON_PLATEAU {
+        patience: 1,
+        factor: 0.5,
+        threshold: 0.0,
+        min_factor: 0.0,
+    };
+    let mut scheduler = create_lr_scheduler(&schedule);
+
+    match scheduler.set_state(&[1.0]) {
+        Err(Error::InvalidTrainState(_)) => (),
+        _ => panic!("expected InvalidTrainState"),
+    }
+}
@@ -0,0 +1,185 @@
+#![allow(non_snake_case)]
+extern crate arrayfire;
+extern crate raybnn;
+
+use raybnn::neural::network_f64::create_nullnetdata;
+use raybnn::neural::network_f64::network_metadata_type;
+
+use raybnn::physics::topology::connection_stage_type;
+use raybnn::physics::topology::connection_strategy_trait;
+use raybnn::physics::topology::knn_strategy_type;
+use raybnn::physics::topology::merge_connections;
+use raybnn::physics::topology::small_world_strategy_type;
+use raybnn::physics::topology::waxman_strategy_type;
+
+const BACK_END: arrayfire::Backend = arrayfire::Backend::CPU;
+const DEVICE: i32 = 0;
+
+const NEURON_NUM: u64 = 30;
+
+fn to_host<T: arrayfire::HasAfEnum + Default + Clone>(arr: &arrayfire::Array<T>) -> Vec<T> {
+    let mut arr_cpu = vec![T::default(); arr.elements()];
+    arr.host(&mut arr_cpu);
+    arr_cpu
+}
+
+// Scattered positions in the unit cube without equal distances, column major
+fn neuron_pos_cpu() -> Vec<f64> {
+    let mut pos_cpu = vec![0.0; (NEURON_NUM * 3) as usize];
+    for i in 0..NEURON_NUM as usize {
+        for d in 0..3 {
+            let x = ((i * 3 + d + 1) as f64) * 0.618033988749895 + (d as f64) * 0.1414;
+            pos_cpu[d * (NEURON_NUM as usize) + i] = x.fract();
+        }
+    }
+    pos_cpu
+}
+
+fn neurons() -> (
+    network_metadata_type,
+    arrayfire::Array<f64>,
+    arrayfire::Array<i32>,
+) {
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+
+    let mut netdata = create_nullnetdata();
+    netdata.neuron_size = NEURON_NUM;
+
+    let neuron_pos = arrayfire::Array::new(
+        &neuron_pos_cpu(),
+        arrayfire::Dim4::new(&[NEURON_NUM, 3, 1, 1]),
+    );
+
+    let idx_cpu: Vec<i32> = (0..NEURON_NUM as i32).collect();
+    let neuron_idx = arrayfire::Array::new(&idx_cpu, arrayfire::Dim4::new(&[NEURON_NUM, 1, 1, 1]));
+
+    (netdata, neuron_pos, neuron_idx)
+}
+
+// Connects the neurons to themselves with strategy, starting without connections
+fn connect(strategy: &dyn connection_strategy_trait<f64>) -> (Vec<i32>, Vec<i32>) {
+    let (netdata, neuron_pos, neuron_idx) = neurons();
+
+    let single_dims = arrayfire::Dim4::new(&[1, 1, 1, 1]);
+    let mut WRowIdxCOO = arrayfire::constant::<i32>(0, single_dims);
+    let mut WColIdx = arrayfire::constant::<i32>(0, single_dims);
+
+    strategy.connect(
+        connection_stage_type::HIDDEN,
+        0,
+        &netdata,
+        &neuron_pos,
+        &neuron_pos,
+        &neuron_idx,
+        &neuron_pos,
+        &neuron_idx,
+        &mut WRowIdxCOO,
+        &mut WColIdx,
+    );
+
+    (to_host(&WRowIdxCOO), to_host(&WColIdx))
+}
+
+#[test]
+fn knn_connects_k_nearest_without_self_connections() {
+    let k = 4;
+    let (WRowIdxCOO, WColIdx) = connect(&knn_strategy_type { k: k });
+
+    assert_eq!(WColIdx.len() as u64, k * NEURON_NUM);
+    assert!(WRowIdxCOO
+        .iter()
+        .zip(WColIdx.iter())
+        .all(|(row, col)| row != col));
+
+    let pos_cpu = neuron_pos_cpu();
+    let sq_dist = |i: usize, j: usize| {
+        (0..3)
+            .map(|d| {
+                let diff =
+                    pos_cpu[d * (NEURON_NUM as usize) + i] - pos_cpu[d * (NEURON_NUM as usize) + j];
+                diff * diff
+            })
+            .sum::<f64>()
+    };
+
+    for col in 0..NEURON_NUM as i32 {
+        let mut targets: Vec<i32> = WRowIdxCOO
+            .iter()
+            .zip(WColIdx.iter())
+            .filter(|(_, c)| **c == col)
+            .map(|(row, _)| *row)
+            .collect();
+        targets.sort();
+
+        //Brute force nearest neighbors
+        let mut expected: Vec<i32> = (0..NEURON_NUM as i32).filter(|j| *j != col).collect();
+        expected.sort_by(|a, b| {
+            sq_dist(col as usize, *a as usize)
+                .partial_cmp(&sq_dist(col as usize, *b as usize))
+                .unwrap()
+        });
+        expected.truncate(k as usize);
+        expected.sort();
+
+        assert_eq!(targets, expected);
+    }
+}
+
+#[test]
+fn small_world_without_rewiring_is_knn() {
+    let knn = connect(&knn_strategy_type { k: 3 });
+    let small_world = connect(&small_world_strategy_type {
+        k: 3,
+        rewire_prob: 0.0,
+    });
+
+    assert_eq!(knn, small_world);
+}
+
+#[test]
+fn waxman_extremes() {
+    let (WRowIdxCOO, WColIdx) = connect(&waxman_strategy_type {
+        alpha: 0.0,
+        beta: 1.0,
+    });
+    assert!(WRowIdxCOO.is_empty());
+    assert!(WColIdx.is_empty());
+
+    //Probability 1 for every pair
+    let (WRowIdxCOO, WColIdx) = connect(&waxman_strategy_type {
+        alpha: 1.0,
+        beta: f64::INFINITY,
+    });
+    assert_eq!(WColIdx.len() as u64, NEURON_NUM * (NEURON_NUM - 1));
+    assert!(WRowIdxCOO
+        .iter()
+        .zip(WColIdx.iter())
+        .all(|(row, col)| row != col));
+}
+
+#[test]
+fn merge_connections_sorts_and_deduplicates() {
+    arrayfire::set_backend(BACK_END);
+    arrayfire::set_device(DEVICE);
+
+    let neuron_size = 10;
+
+    let old_dims = arrayfire::Dim4::new(&[2, 1, 1, 1]);
+    let mut WRowIdxCOO = arrayfire::Array::new(&[4, 1], old_dims);
+    let mut WColIdx = arrayfire::Array::new(&[2, 7], old_dims);
+
+    let new_row = [3, 1, 4, 0, 3];
+    let new_col = [5, 7, 2, 9, 5];
+    merge_connections(
+        neuron_size,
+        &new_row,
+        &new_col,
+        &mut WRowIdxCOO,
+        &mut WColIdx,
+    );
+
+    //Global weight indexes 9, 17, 35 and 42
+    assert_eq!(to_host(&WRowIdxCOO), vec![0, 1, 3, 4]);
+    assert_eq!(to_host(&WColIdx), vec![9, 7, 5, 2]);
+}